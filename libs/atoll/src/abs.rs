@@ -1,10 +1,16 @@
 //! Generate abstract views of layout cells.
-use crate::grid::{AtollLayer, LayerSlice, LayerStack, PdkLayer, RoutingGrid, RoutingState};
+use crate::grid::{
+    AtollLayer, InvalidGridOffset, LayerSlice, LayerStack, PdkLayer, RoutingGrid, RoutingState,
+};
+use crate::route::{GridSegment, Path, Router};
+use crate::straps::StrapSegment;
 use crate::{AssignedGridPoints, NetId, Orientation, PointState};
 use grid::Grid;
+use indexmap::IndexSet;
 use num::integer::{div_ceil, div_floor};
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
+use std::fmt::{Display, Formatter};
 use substrate::arcstr::ArcStr;
 use substrate::block::Block;
 use substrate::geometry::bbox::Bbox;
@@ -110,8 +116,16 @@ pub struct Abstract {
     ///
     /// The order of net IDs matches that provided by [`layout::Cell::ports`].
     ports: Vec<NetId>,
+    /// Straps on port nets that parent tiles may connect to, in grid coordinates.
+    straps: Vec<StrapSegment>,
     /// The routing grid used to produce this abstract view.
     pub(crate) grid: RoutingGrid<PdkLayer>,
+    /// The number of primitive devices drawn in the tile, including those in nested tiles.
+    pub(crate) device_count: usize,
+    /// The number of distinct nets in the tile.
+    pub(crate) net_count: usize,
+    /// The names of the signals from which nets originate, where known.
+    pub(crate) net_names: BTreeMap<NetId, ArcStr>,
 }
 
 impl Abstract {
@@ -136,6 +150,7 @@ impl Abstract {
             self.lcm_bounds.width(),
             self.lcm_bounds.height(),
         );
+        state.grid = self.grid.clone();
         for (i, layer) in self.layers.iter().enumerate() {
             match layer {
                 LayerAbstract::Available => {}
@@ -149,19 +164,96 @@ impl Abstract {
                 }
             }
         }
+        state.straps = self.straps.clone();
         state
     }
 
     /// Populates an [`Abstract`] based on the provided [`RoutingState`].
+    ///
+    /// Only straps on port nets are exposed to parent tiles.
     pub fn from_routing_state(&mut self, state: RoutingState<PdkLayer>) {
         for port in self.ports.iter_mut() {
             *port = state.roots[port];
         }
+        self.straps = state
+            .straps
+            .iter()
+            .map(|strap| StrapSegment {
+                net: state.roots.get(&strap.net).copied().unwrap_or(strap.net),
+                ..*strap
+            })
+            .filter(|strap| self.ports.contains(&strap.net))
+            .collect();
         for (layer, states) in self.layers.iter_mut().zip(state.layers) {
             *layer = LayerAbstract::Detailed { states }
         }
     }
 
+    /// Shifts the tracks on the given layer by `offset` physical units.
+    ///
+    /// The grid points of every layer whose grid is formed by the shifted tracks move with them.
+    /// A moved point that lands on a point of the unshifted grid takes on that point's state.
+    /// A point that lands halfway between two points of the unshifted grid keeps their state
+    /// only if both have the same state, and is blocked otherwise.
+    /// Straps that no longer lie on grid points are dropped.
+    pub(crate) fn set_grid_offset(
+        &mut self,
+        layer: usize,
+        offset: i64,
+    ) -> Result<(), InvalidGridOffset> {
+        let delta = offset - self.grid.offset(layer);
+        self.grid.set_offset(layer, offset)?;
+        if delta == 0 {
+            return Ok(());
+        }
+        let l = self.grid.stack.layer(layer);
+        let shift = l.num_tracks(div_floor(delta, l.period()) * l.period());
+        let half = delta.rem_euclid(l.period()) != 0;
+
+        for (i, layer_abs) in self.layers.iter_mut().enumerate() {
+            let vert = self.grid.stack.layer(i).dir().track_dir() == Dir::Vert;
+            let gdl = self.grid.grid_defining_layer(i);
+            // The layers whose tracks index the x and y coordinates of layer `i`.
+            let (xl, yl) = if vert { (i, gdl) } else { (gdl, i) };
+            if xl != layer && yl != layer {
+                continue;
+            }
+            let along_x = xl == layer;
+
+            if let LayerAbstract::Detailed { states } = layer_abs {
+                *states = shift_states(states, along_x, shift, half);
+            }
+
+            // Strap tracks index x on vertical layers and y on horizontal layers.
+            let along_track = along_x == vert;
+            let moved = |idx: usize| usize::try_from(idx as i64 - shift).ok();
+            self.straps.retain_mut(|strap| {
+                if strap.layer != i {
+                    return true;
+                }
+                if half {
+                    return false;
+                }
+                if along_track {
+                    match moved(strap.track) {
+                        Some(track) => strap.track = track,
+                        None => return false,
+                    }
+                } else {
+                    match (moved(strap.start), moved(strap.stop)) {
+                        (Some(start), Some(stop)) => {
+                            strap.start = start;
+                            strap.stop = stop;
+                        }
+                        _ => return false,
+                    }
+                }
+                true
+            });
+        }
+        Ok(())
+    }
+
     /// Converts a grid point to a physical point in the coordinates of the cell.
     ///
     /// See [coordinate systems](Abstract#coordinates) for more information.
@@ -178,11 +270,17 @@ impl Abstract {
     }
 
     fn xofs(&self, layer: usize) -> i64 {
-        self.lcm_bounds.left() * self.slice().lcm_unit_width() / self.grid.xpitch(layer)
+        self.grid.xtracks(
+            layer,
+            self.lcm_bounds.left() * self.slice().lcm_unit_width(),
+        )
     }
 
     fn yofs(&self, layer: usize) -> i64 {
-        self.lcm_bounds.bot() * self.slice().lcm_unit_height() / self.grid.ypitch(layer)
+        self.grid.ytracks(
+            layer,
+            self.lcm_bounds.bot() * self.slice().lcm_unit_height(),
+        )
     }
 
     /// Converts a grid point to a track point in the coordinates of the cell.
@@ -217,6 +315,37 @@ impl Abstract {
         }
     }
 
+    /// Returns every grid point occupied by `path`, in track coordinates.
+    ///
+    /// Points are yielded in path order. Points shared by adjacent segments are yielded once.
+    pub fn occupied_coords(&self, path: &[GridSegment]) -> impl Iterator<Item = TrackCoord> + '_ {
+        fn step(from: usize, to: usize, i: usize) -> usize {
+            if to >= from {
+                from + i.min(to - from)
+            } else {
+                from - i.min(from - to)
+            }
+        }
+
+        let mut coords = IndexSet::new();
+        for (a, b) in path {
+            if a.layer == b.layer {
+                let steps = std::cmp::max(a.x.abs_diff(b.x), a.y.abs_diff(b.y));
+                coords.extend((0..=steps).map(|i| GridCoord {
+                    layer: a.layer,
+                    x: step(a.x, b.x, i),
+                    y: step(a.y, b.y, i),
+                }));
+            } else {
+                coords.insert(*a);
+                coords.insert(*b);
+            }
+        }
+        coords
+            .into_iter()
+            .map(move |coord| self.grid_to_track(coord))
+    }
+
     pub(crate) fn slice(&self) -> LayerSlice<'_, PdkLayer> {
         self.grid.slice()
     }
@@ -227,6 +356,47 @@ impl Abstract {
         self.lcm_bounds.lower_left() * self.slice().lcm_units()
     }
 
+    /// Returns the straps exposed by this abstract, in grid coordinates.
+    ///
+    /// Parent tiles can connect to these straps with vias
+    /// rather than drawing straps of their own on the same layers.
+    pub fn straps(&self) -> &[StrapSegment] {
+        &self.straps
+    }
+
+    /// Returns each net in this abstract along with the name of its originating signal, if known.
+    ///
+    /// Includes port nets, strapped nets, and every net routed on a detailed layer,
+    /// sorted by [`NetId`]. Useful for annotating visualizations of the abstract.
+    pub fn net_manifest(&self) -> Vec<(NetId, Option<ArcStr>)> {
+        let mut nets: BTreeSet<NetId> = self.ports.iter().copied().collect();
+        nets.extend(self.straps.iter().map(|strap| strap.net));
+        for layer in self.layers.iter() {
+            if let LayerAbstract::Detailed { states } = layer {
+                nets.extend(states.iter().filter_map(|pt| match pt {
+                    PointState::Routed { net, .. } => Some(*net),
+                    _ => None,
+                }));
+            }
+        }
+        nets.into_iter()
+            .map(|net| (net, self.net_names.get(&net).cloned()))
+            .collect()
+    }
+
+    /// Returns a report of routing congestion on each layer of this abstract.
+    pub fn congestion(&self) -> CongestionReport {
+        let state = self.routing_state();
+        CongestionReport {
+            layers: state
+                .layers
+                .iter()
+                .enumerate()
+                .map(|(layer, states)| LayerCongestion::from_states(layer, states))
+                .collect(),
+        }
+    }
+
     pub(crate) fn block_available_on_layer(&mut self, layer: usize) {
         match &mut self.layers[layer] {
             abs @ LayerAbstract::Available => *abs = LayerAbstract::Blocked,
@@ -243,7 +413,71 @@ impl Abstract {
         }
     }
 
+    /// Returns the grid points occupied by this abstract when placed at `loc` (in LCM units)
+    /// with the given orientation.
+    ///
+    /// Points are returned as `(layer, bounds)` pairs, where `bounds` is a horizontal run
+    /// of points in track coordinates. Points on the boundary of the abstract are shared
+    /// with neighboring tiles and are not considered occupied.
+    pub(crate) fn occupied_track_runs(
+        &self,
+        loc: Point,
+        orientation: Orientation,
+    ) -> Vec<(usize, Rect)> {
+        let state = self.routing_state();
+        let slice = self.slice();
+        let mut runs = Vec::new();
+        for layer in 0..=self.top_layer {
+            let states = state.layer(layer);
+            let (nx, ny) = states.size();
+            let xofs = self.grid.xtracks(
+                layer,
+                (self.lcm_bounds.left() + loc.x) * slice.lcm_unit_width(),
+            );
+            let yofs = self.grid.ytracks(
+                layer,
+                (self.lcm_bounds.bot() + loc.y) * slice.lcm_unit_height(),
+            );
+
+            let mut occupied = Grid::init(nx, ny, false);
+            for x in 1..nx {
+                for y in 1..ny {
+                    if states[(x, y)] != PointState::Available {
+                        let (x, y) = match orientation {
+                            Orientation::R0 => (x, y),
+                            Orientation::R180 => (nx - x, ny - y),
+                            Orientation::ReflectVert => (x, ny - y),
+                            Orientation::ReflectHoriz => (nx - x, y),
+                        };
+                        occupied[(x, y)] = true;
+                    }
+                }
+            }
+
+            for y in 0..ny {
+                let mut start = None;
+                for x in 0..=nx {
+                    match (start, x < nx && occupied[(x, y)]) {
+                        (None, true) => start = Some(x),
+                        (Some(x0), false) => {
+                            let y = y as i64 + yofs;
+                            runs.push((
+                                layer,
+                                Rect::from_sides(x0 as i64 + xofs, y, x as i64 - 1 + xofs, y),
+                            ));
+                            start = None;
+                        }
+                        _ => {}
+                    }
+                }
+            }
+        }
+        runs
+    }
+
     /// Generates an abstract view of a layout cell.
+    ///
+    /// The cell is counted as a single primitive device.
     pub fn generate<PDK: Pdk, T: ExportsNestedData + ExportsLayoutData>(
         ctx: &PdkContext<PDK>,
         layout: &layout::Cell<T>,
@@ -275,17 +509,19 @@ impl Abstract {
         let grid = RoutingGrid::new((*stack).clone(), 0..top + 1);
         let mut state = RoutingState::new((*stack).clone(), top, nx, ny);
         let mut ports = Vec::new();
-        for (i, (_name, geom)) in cell.ports().enumerate() {
+        let mut net_names = BTreeMap::new();
+        for (i, (name, geom)) in cell.ports().enumerate() {
             let net = NetId(i);
             ports.push(net);
+            net_names.insert(net, name.to_string().into());
             for shape in geom.shapes() {
                 if let Some(layer) = stack.layer_idx(shape.layer().drawing()) {
                     let rect = shape.bbox_rect();
                     if let Some(rect) = grid.shrink_to_grid(rect, layer) {
                         for x in rect.left()..=rect.right() {
                             for y in rect.bot()..=rect.top() {
-                                let xofs = xmin * slice.lcm_unit_width() / grid.xpitch(layer);
-                                let yofs = ymin * slice.lcm_unit_height() / grid.ypitch(layer);
+                                let xofs = grid.xtracks(layer, xmin * slice.lcm_unit_width());
+                                let yofs = grid.ytracks(layer, ymin * slice.lcm_unit_height());
                                 let gx = usize::try_from(x.checked_sub(xofs).unwrap()).unwrap();
                                 let gy = usize::try_from(y.checked_sub(yofs).unwrap()).unwrap();
                                 if let Some(pt) = state.layer_mut(layer).get_mut(gx, gy) {
@@ -311,12 +547,155 @@ impl Abstract {
             top_layer: top,
             lcm_bounds,
             grid: RoutingGrid::new((*stack).clone(), 0..top + 1),
+            net_count: ports.len(),
             ports,
             layers,
+            straps: Vec::new(),
+            device_count: 1,
+            net_names,
+        }
+    }
+}
+
+/// An error indicating that abutting instances do not connect a net across their shared edge.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub struct AbutmentMismatch {
+    /// The net that is expected to connect by abutment.
+    pub net: NetId,
+    /// The parent grid point on the shared edge at which the instances do not align.
+    pub coord: GridCoord,
+}
+
+impl Display for AbutmentMismatch {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "net {:?} does not align across abutting instances at {:?}",
+            self.net, self.coord
+        )
+    }
+}
+
+impl std::error::Error for AbutmentMismatch {}
+
+/// How [`InstanceAbstract::merge`] handles grid points assigned to two different nets.
+#[derive(Debug, Copy, Clone, Default, Eq, PartialEq, Hash, Serialize, Deserialize)]
+pub enum AssignmentConflicts {
+    /// Return an [`AssignmentConflict`] error.
+    #[default]
+    Error,
+    /// Log a warning and keep the later assignment.
+    Warn,
+}
+
+/// An error indicating that a grid point was assigned to two different nets.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub struct AssignmentConflict {
+    /// The conflicting parent grid point.
+    pub coord: GridCoord,
+    /// The net assigned to the point first.
+    pub first: NetId,
+    /// The net assigned to the point second.
+    pub second: NetId,
+}
+
+impl Display for AssignmentConflict {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "grid point {:?} is assigned to both net {:?} and net {:?}",
+            self.coord, self.first, self.second
+        )
+    }
+}
+
+impl std::error::Error for AssignmentConflict {}
+
+/// An error encountered while merging instance abstracts.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub enum MergeError {
+    /// Abutting instances do not align on a net that connects by abutment.
+    Abutment(AbutmentMismatch),
+    /// A grid point was assigned to two different nets.
+    AssignmentConflict(AssignmentConflict),
+}
+
+impl Display for MergeError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Abutment(err) => err.fmt(f),
+            Self::AssignmentConflict(err) => err.fmt(f),
+        }
+    }
+}
+
+impl std::error::Error for MergeError {}
+
+/// An error indicating that a moved instance would overlap another instance.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub struct InstanceOverlap {
+    /// The index of the moved instance.
+    pub index: usize,
+    /// The index of the instance it would overlap.
+    pub other: usize,
+}
+
+impl Display for InstanceOverlap {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "instance {} would overlap instance {}",
+            self.index, self.other
+        )
+    }
+}
+
+impl std::error::Error for InstanceOverlap {}
+
+/// An error encountered while moving an instance of an [`IncrementalRouting`].
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub enum MoveError {
+    /// The moved instance would overlap another instance.
+    Overlap(InstanceOverlap),
+    /// The instances could not be merged after the move.
+    Merge(MergeError),
+}
+
+impl Display for MoveError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Overlap(err) => err.fmt(f),
+            Self::Merge(err) => err.fmt(f),
         }
     }
 }
 
+impl std::error::Error for MoveError {}
+
+impl From<InstanceOverlap> for MoveError {
+    fn from(value: InstanceOverlap) -> Self {
+        Self::Overlap(value)
+    }
+}
+
+impl From<MergeError> for MoveError {
+    fn from(value: MergeError) -> Self {
+        Self::Merge(value)
+    }
+}
+
+impl From<AbutmentMismatch> for MergeError {
+    fn from(value: AbutmentMismatch) -> Self {
+        Self::Abutment(value)
+    }
+}
+
+impl From<AssignmentConflict> for MergeError {
+    fn from(value: AssignmentConflict) -> Self {
+        Self::AssignmentConflict(value)
+    }
+}
+
 /// An abstract of an instance.
 #[derive(Debug, Clone, Eq, PartialEq, Hash, Serialize, Deserialize)]
 pub struct InstanceAbstract {
@@ -327,7 +706,10 @@ pub struct InstanceAbstract {
 }
 
 impl InstanceAbstract {
-    pub(crate) fn new(
+    /// Creates an instance of the given abstract placed at `loc` (in LCM units).
+    ///
+    /// The instance's ports are mapped, in order, to the given parent nets.
+    pub fn new(
         mut abs: Abstract,
         loc: Point,
         orientation: Orientation,
@@ -353,13 +735,88 @@ impl InstanceAbstract {
         self.abs.lcm_bounds
     }
 
+    /// Merges placed instances into a parent abstract and routes connections
+    /// between their exposed pins on the parent's layers.
+    ///
+    /// Each group in `to_connect` lists parent nets to be connected,
+    /// with the first net of each group becoming the group's root.
+    /// Returns the parent abstract, the routed paths in parent grid coordinates,
+    /// and the root nets of the groups that could not be fully connected.
+    ///
+    /// Returns an error if the instances cannot be merged.
+    pub fn route(
+        abstracts: Vec<Self>,
+        top_layer: usize,
+        ports: Vec<NetId>,
+        to_connect: Vec<Vec<NetId>>,
+        router: &dyn Router,
+    ) -> Result<(Abstract, Vec<Path>, Vec<NetId>), MergeError> {
+        let roots = Self::parent_roots(&abstracts, &ports, &to_connect);
+        let mut abs = Self::merge(
+            abstracts,
+            top_layer,
+            None,
+            ports,
+            Vec::new(),
+            AssignmentConflicts::Error,
+            &roots,
+            &HashMap::new(),
+        )?;
+        let mut state = abs.routing_state();
+        state.roots = roots.clone();
+        let (paths, unrouted) = router.try_route(&mut state, to_connect);
+        for (net, root) in roots {
+            state.relabel_net(net, root);
+        }
+        abs.from_routing_state(state);
+        Ok((abs, paths, unrouted))
+    }
+
+    /// Maps each parent net to the root of its group in `to_connect`.
+    ///
+    /// Nets not in any group are their own roots.
+    fn parent_roots(
+        abstracts: &[Self],
+        ports: &[NetId],
+        to_connect: &[Vec<NetId>],
+    ) -> HashMap<NetId, NetId> {
+        let mut roots: HashMap<_, _> = abstracts
+            .iter()
+            .flat_map(|inst| inst.parent_net_ids.iter().copied())
+            .chain(ports.iter().copied())
+            .map(|net| (net, net))
+            .collect();
+        for group in to_connect.iter() {
+            if let Some(&root) = group.first() {
+                for &net in group {
+                    roots.insert(net, root);
+                }
+            }
+        }
+        roots
+    }
+
+    /// Merges placed instances into a parent abstract.
+    ///
+    /// `abutment_nets` maps each parent net that connects by abutment to the root of its group.
+    /// Where two instances abut, the points of these nets on the tracks crossing the
+    /// shared edge must match on both sides; otherwise, an [`AbutmentMismatch`] is returned.
+    ///
+    /// Grid points assigned to two nets that are not connected, including points of
+    /// instance pins overwritten by an assignment, are handled according to `conflicts`.
+    /// `net_roots` maps each parent net to the root of the nets it is connected to;
+    /// nets not in the map are their own roots.
+    #[allow(clippy::too_many_arguments)]
     pub(crate) fn merge(
         abstracts: Vec<Self>,
         mut top_layer: usize,
         physical_bbox: Option<Rect>,
         ports: Vec<NetId>,
         assigned_grid_points: Vec<AssignedGridPoints>,
-    ) -> Abstract {
+        conflicts: AssignmentConflicts,
+        net_roots: &HashMap<NetId, NetId>,
+        abutment_nets: &HashMap<NetId, NetId>,
+    ) -> Result<Abstract, MergeError> {
         assert!(!abstracts.is_empty());
 
         for abs in &abstracts {
@@ -383,91 +840,77 @@ impl InstanceAbstract {
             new_bounds.height(),
         );
 
+        // The track extents `(left, bot, width, height)` of each instance on each layer,
+        // and the points of each instance that belong to nets connecting by abutment.
+        let mut extents = Vec::with_capacity(abstracts.len());
+        let mut abutment_points = Vec::with_capacity(abstracts.len());
+
         for inst in &abstracts {
-            let net_translation: HashMap<_, _> = inst
-                .abs
-                .ports
-                .iter()
-                .copied()
-                .zip(inst.parent_net_ids.iter().copied())
-                .collect();
-            for i in 0..=inst.abs.top_layer {
-                let layer = grid.stack.layer(i);
-                let parallel_pitch = layer.pitch();
-                let perp_pitch = grid.stack.layer(grid.grid_defining_layer(i)).pitch();
-
-                let (xpitch, ypitch) = match layer.dir().track_dir() {
-                    Dir::Horiz => (perp_pitch, parallel_pitch),
-                    Dir::Vert => (parallel_pitch, perp_pitch),
-                };
+            let inst_extents = inst.track_extents(&grid, new_physical_bounds);
+            state
+                .straps
+                .extend(inst.parent_straps(&grid, &inst_extents));
+            abutment_points.push(inst.stamp(&mut state, &inst_extents, abutment_nets));
+            extents.push(inst_extents);
+        }
 
-                let left_offset =
-                    (inst.physical_bounds().left() - new_physical_bounds.left()) / xpitch;
-                let bot_offset =
-                    (inst.physical_bounds().bot() - new_physical_bounds.bot()) / ypitch;
-                let track_width = inst.physical_bounds().width() / xpitch;
-                let track_height = inst.physical_bounds().height() / ypitch;
-
-                for x in left_offset + 1..left_offset + track_width {
-                    for y in bot_offset + 1..bot_offset + track_height {
-                        let point_state = &mut state.layer_mut(i)[(x as usize, y as usize)];
-                        match &inst.abs.layers[i] {
-                            LayerAbstract::Available => {}
-                            LayerAbstract::Blocked => {
-                                assert_eq!(point_state, &PointState::Available);
-                                *point_state = PointState::Blocked { has_via: false };
-                            }
-                            LayerAbstract::Detailed { states } => {
-                                let new_state = states[match inst.orientation {
-                                    Orientation::R0 => {
-                                        ((x - left_offset) as usize, (y - bot_offset) as usize)
-                                    }
-                                    Orientation::R180 => (
-                                        (left_offset + track_width - x) as usize,
-                                        (bot_offset + track_height - y) as usize,
-                                    ),
-                                    Orientation::ReflectVert => (
-                                        (x - left_offset) as usize,
-                                        (bot_offset + track_height - y) as usize,
-                                    ),
-                                    Orientation::ReflectHoriz => (
-                                        (left_offset + track_width - x) as usize,
-                                        (y - bot_offset) as usize,
-                                    ),
-                                }];
-
-                                // TODO: decide semantics for conflicting net labels
-                                match new_state {
-                                    PointState::Available => {}
-                                    PointState::Blocked { has_via } => {
-                                        *point_state = PointState::Blocked { has_via };
-                                    }
-                                    PointState::Routed { net, has_via } => {
-                                        if let Some(translation) = net_translation.get(&net) {
-                                            *point_state = PointState::Routed {
-                                                net: *translation,
-                                                has_via,
-                                            };
-                                        } else {
-                                            *point_state = PointState::Blocked { has_via };
-                                        }
-                                    }
-                                    PointState::Reserved { net } => {
-                                        if let Some(translation) = net_translation.get(&net) {
-                                            *point_state =
-                                                PointState::Reserved { net: *translation };
-                                        } else {
-                                            *point_state = PointState::Blocked { has_via: false };
-                                        }
-                                    }
-                                }
+        if !abutment_nets.is_empty() {
+            for (a, b) in
+                (0..abstracts.len()).flat_map(|a| (0..abstracts.len()).map(move |b| (a, b)))
+            {
+                let (a_bounds, b_bounds) = (
+                    abstracts[a].physical_bounds(),
+                    abstracts[b].physical_bounds(),
+                );
+                let top = std::cmp::min(abstracts[a].abs.top_layer, abstracts[b].abs.top_layer);
+                for layer in 0..=top {
+                    let (al, ab, aw, ah) = extents[a][layer];
+                    let (bl, bb, bw, bh) = extents[b][layer];
+                    // Points just inside the shared edge of each instance, paired by
+                    // the track crossing the edge.
+                    let pairs: Vec<_> = match grid.stack.layer(layer).dir().track_dir() {
+                        Dir::Horiz if a_bounds.right() == b_bounds.left() => {
+                            let x = al + aw;
+                            (std::cmp::max(ab, bb) + 1..std::cmp::min(ab + ah, bb + bh))
+                                .map(|y| (x, y, (x - 1, y), (x + 1, y)))
+                                .collect()
+                        }
+                        Dir::Vert if a_bounds.top() == b_bounds.bot() => {
+                            let y = ab + ah;
+                            (std::cmp::max(al, bl) + 1..std::cmp::min(al + aw, bl + bw))
+                                .map(|x| (x, y, (x, y - 1), (x, y + 1)))
+                                .collect()
+                        }
+                        _ => Vec::new(),
+                    };
+                    let point = |inst: usize, (x, y): (i64, i64)| {
+                        abutment_points[inst].get(&GridCoord {
+                            layer,
+                            x: x as usize,
+                            y: y as usize,
+                        })
+                    };
+                    for (x, y, a_point, b_point) in pairs {
+                        let (a_net, b_net) = (point(a, a_point), point(b, b_point));
+                        if a_net != b_net {
+                            return Err(AbutmentMismatch {
+                                net: *a_net.or(b_net).unwrap(),
+                                coord: GridCoord {
+                                    layer,
+                                    x: x as usize,
+                                    y: y as usize,
+                                },
                             }
+                            .into());
                         }
                     }
                 }
             }
         }
 
+        let root = |net: NetId| net_roots.get(&net).copied().unwrap_or(net);
+        // The net most recently assigned to each grid point.
+        let mut assigned = HashMap::new();
         for AssignedGridPoints {
             net,
             layer,
@@ -475,33 +918,48 @@ impl InstanceAbstract {
             only_if_available,
         } in assigned_grid_points
         {
-            let pdk_layer = grid.stack.layer(layer);
-            let defining_layer = grid.stack.layer(grid.grid_defining_layer(layer));
-            let parallel_pitch = pdk_layer.pitch();
-            let perp_pitch = defining_layer.pitch();
-
-            let (xpitch, ypitch) = match pdk_layer.dir().track_dir() {
-                Dir::Horiz => (perp_pitch, parallel_pitch),
-                Dir::Vert => (parallel_pitch, perp_pitch),
-            };
-
             let lcm_xpitch = grid.slice().lcm_unit_width();
             let lcm_ypitch = grid.slice().lcm_unit_height();
 
-            let left = bounds.left() - new_bounds.left() * lcm_xpitch / xpitch;
+            let left = bounds.left() - grid.xtracks(layer, new_bounds.left() * lcm_xpitch);
 
-            let bot = bounds.bot() - new_bounds.bot() * lcm_ypitch / ypitch;
+            let bot = bounds.bot() - grid.ytracks(layer, new_bounds.bot() * lcm_ypitch);
 
             for i in left..=left + bounds.width() {
                 for j in bot..=bot + bounds.height() {
-                    if state.in_bounds(GridCoord {
+                    let coord = GridCoord {
                         x: i as usize,
                         y: j as usize,
                         layer,
-                    }) {
+                    };
+                    if state.in_bounds(coord) {
                         let coords = (i as usize, j as usize);
                         let curr_state = state.layer(layer)[coords];
                         if !only_if_available || matches!(curr_state, PointState::Available) {
+                            if let Some(second) = net {
+                                // Points not yet assigned may hold the pin of an instance.
+                                let first = assigned.insert(coord, second).or(match curr_state {
+                                    PointState::Routed { net, .. } => Some(net),
+                                    _ => None,
+                                });
+                                if let Some(first) =
+                                    first.filter(|&first| root(first) != root(second))
+                                {
+                                    let conflict = AssignmentConflict {
+                                        coord,
+                                        first,
+                                        second,
+                                    };
+                                    match conflicts {
+                                        AssignmentConflicts::Error => {
+                                            return Err(conflict.into());
+                                        }
+                                        AssignmentConflicts::Warn => {
+                                            tracing::warn!("{conflict}");
+                                        }
+                                    }
+                                }
+                            }
                             state.layer_mut(layer)[(i as usize, j as usize)] =
                                 if let Some(net) = net {
                                     PointState::Routed {
@@ -517,7 +975,14 @@ impl InstanceAbstract {
             }
         }
 
-        Abstract {
+        let device_count = abstracts.iter().map(|inst| inst.abs.device_count).sum();
+        let net_count = ports
+            .iter()
+            .chain(abstracts.iter().flat_map(|inst| &inst.parent_net_ids))
+            .collect::<IndexSet<_>>()
+            .len();
+
+        Ok(Abstract {
             top_layer,
             lcm_bounds: new_bounds,
             layers: state
@@ -526,8 +991,403 @@ impl InstanceAbstract {
                 .map(|states| LayerAbstract::Detailed { states })
                 .collect(),
             ports,
+            straps: state.straps,
             grid,
+            device_count,
+            net_count,
+            net_names: BTreeMap::new(),
+        })
+    }
+}
+
+impl InstanceAbstract {
+    /// Maps the ports of this instance's abstract to parent nets.
+    fn net_translation(&self) -> HashMap<NetId, NetId> {
+        self.abs
+            .ports
+            .iter()
+            .copied()
+            .zip(self.parent_net_ids.iter().copied())
+            .collect()
+    }
+
+    /// Returns the track extents `(left, bot, width, height)` of this instance on each
+    /// of its layers, relative to the lower left of `parent_bounds`.
+    fn track_extents(
+        &self,
+        grid: &RoutingGrid<PdkLayer>,
+        parent_bounds: Rect,
+    ) -> Vec<(i64, i64, i64, i64)> {
+        let bounds = self.physical_bounds();
+        (0..=self.abs.top_layer)
+            .map(|i| {
+                (
+                    grid.xtracks(i, bounds.left() - parent_bounds.left()),
+                    grid.ytracks(i, bounds.bot() - parent_bounds.bot()),
+                    grid.xtracks(i, bounds.width()),
+                    grid.ytracks(i, bounds.height()),
+                )
+            })
+            .collect()
+    }
+
+    /// Converts a grid point of this instance to parent grid coordinates.
+    fn to_parent(
+        &self,
+        (left, bot, width, height): (i64, i64, i64, i64),
+        x: usize,
+        y: usize,
+    ) -> (usize, usize) {
+        let (x, y) = (x as i64, y as i64);
+        let (x, y) = match self.orientation {
+            Orientation::R0 => (left + x, bot + y),
+            Orientation::R180 => (left + width - x, bot + height - y),
+            Orientation::ReflectVert => (left + x, bot + height - y),
+            Orientation::ReflectHoriz => (left + width - x, bot + y),
+        };
+        (x as usize, y as usize)
+    }
+
+    /// Returns the straps of this instance on parent nets, in parent grid coordinates.
+    fn parent_straps(
+        &self,
+        grid: &RoutingGrid<PdkLayer>,
+        extents: &[(i64, i64, i64, i64)],
+    ) -> Vec<StrapSegment> {
+        let net_translation = self.net_translation();
+        let mut straps = Vec::new();
+        for (i, &extents) in extents.iter().enumerate() {
+            for strap in self.abs.straps.iter().filter(|strap| strap.layer == i) {
+                if let Some(net) = net_translation.get(&strap.net) {
+                    let (track, start, stop) = match grid.stack.layer(i).dir().track_dir() {
+                        Dir::Horiz => {
+                            let (start, track) = self.to_parent(extents, strap.start, strap.track);
+                            let (stop, _) = self.to_parent(extents, strap.stop, strap.track);
+                            (track, start, stop)
+                        }
+                        Dir::Vert => {
+                            let (track, start) = self.to_parent(extents, strap.track, strap.start);
+                            let (_, stop) = self.to_parent(extents, strap.track, strap.stop);
+                            (track, start, stop)
+                        }
+                    };
+                    straps.push(StrapSegment {
+                        net: *net,
+                        layer: i,
+                        track,
+                        start: std::cmp::min(start, stop),
+                        stop: std::cmp::max(start, stop),
+                    });
+                }
+            }
+        }
+        straps
+    }
+
+    /// Copies the grid points of this instance into `state`, translating port nets to
+    /// parent nets and blocking points on internal nets.
+    ///
+    /// Returns the points on nets that connect by abutment, along with the root of each net.
+    fn stamp(
+        &self,
+        state: &mut RoutingState<PdkLayer>,
+        extents: &[(i64, i64, i64, i64)],
+        abutment_nets: &HashMap<NetId, NetId>,
+    ) -> HashMap<GridCoord, NetId> {
+        let net_translation = self.net_translation();
+        let mut abutment_points = HashMap::new();
+        for (i, &(left_offset, bot_offset, track_width, track_height)) in extents.iter().enumerate()
+        {
+            for x in left_offset + 1..left_offset + track_width {
+                for y in bot_offset + 1..bot_offset + track_height {
+                    let point_state = &mut state.layer_mut(i)[(x as usize, y as usize)];
+                    match &self.abs.layers[i] {
+                        LayerAbstract::Available => {}
+                        LayerAbstract::Blocked => {
+                            assert_eq!(point_state, &PointState::Available);
+                            *point_state = PointState::Blocked { has_via: false };
+                        }
+                        LayerAbstract::Detailed { states } => {
+                            let new_state = states[match self.orientation {
+                                Orientation::R0 => {
+                                    ((x - left_offset) as usize, (y - bot_offset) as usize)
+                                }
+                                Orientation::R180 => (
+                                    (left_offset + track_width - x) as usize,
+                                    (bot_offset + track_height - y) as usize,
+                                ),
+                                Orientation::ReflectVert => (
+                                    (x - left_offset) as usize,
+                                    (bot_offset + track_height - y) as usize,
+                                ),
+                                Orientation::ReflectHoriz => (
+                                    (left_offset + track_width - x) as usize,
+                                    (y - bot_offset) as usize,
+                                ),
+                            }];
+
+                            // TODO: decide semantics for conflicting net labels
+                            match new_state {
+                                PointState::Available => {}
+                                PointState::Blocked { has_via } => {
+                                    *point_state = PointState::Blocked { has_via };
+                                }
+                                PointState::Routed { net, has_via } => {
+                                    if let Some(translation) = net_translation.get(&net) {
+                                        *point_state = PointState::Routed {
+                                            net: *translation,
+                                            has_via,
+                                        };
+                                        if let Some(root) = abutment_nets.get(translation) {
+                                            abutment_points.insert(
+                                                GridCoord {
+                                                    layer: i,
+                                                    x: x as usize,
+                                                    y: y as usize,
+                                                },
+                                                *root,
+                                            );
+                                        }
+                                    } else {
+                                        *point_state = PointState::Blocked { has_via };
+                                    }
+                                }
+                                PointState::Reserved { net } => {
+                                    if let Some(translation) = net_translation.get(&net) {
+                                        *point_state = PointState::Reserved { net: *translation };
+                                    } else {
+                                        *point_state = PointState::Blocked { has_via: false };
+                                    }
+                                }
+                            }
+                        }
+                    }
+                }
+            }
         }
+        abutment_points
+    }
+}
+
+/// Placed instances routed together, supporting incremental updates
+/// when a single instance moves.
+///
+/// Each group of nets is routed separately, so that the paths of groups
+/// unaffected by a move are kept as-is.
+#[derive(Debug, Clone)]
+pub struct IncrementalRouting {
+    instances: Vec<InstanceAbstract>,
+    ports: Vec<NetId>,
+    to_connect: Vec<Vec<NetId>>,
+    /// The merged abstract of the instances, before routing.
+    merged: Abstract,
+    /// The track extents of each instance on each of its layers.
+    extents: Vec<Vec<(i64, i64, i64, i64)>>,
+    /// The merged routing state, before routing.
+    unrouted: RoutingState<PdkLayer>,
+    /// The merged routing state after routing all groups.
+    routed: RoutingState<PdkLayer>,
+    /// The paths routed for each group of `to_connect`.
+    paths: Vec<Vec<Path>>,
+    /// The root nets of each group of `to_connect` that could not be fully connected.
+    unrouted_roots: Vec<Vec<NetId>>,
+}
+
+impl IncrementalRouting {
+    /// Merges placed instances and routes each group in `to_connect`, in order.
+    ///
+    /// See [`InstanceAbstract::route`] for the meaning of each argument.
+    ///
+    /// Returns an error if the instances cannot be merged.
+    pub fn new(
+        abstracts: Vec<InstanceAbstract>,
+        top_layer: usize,
+        ports: Vec<NetId>,
+        to_connect: Vec<Vec<NetId>>,
+        router: &dyn Router,
+    ) -> Result<Self, MergeError> {
+        let roots = InstanceAbstract::parent_roots(&abstracts, &ports, &to_connect);
+        let merged = InstanceAbstract::merge(
+            abstracts.clone(),
+            top_layer,
+            None,
+            ports.clone(),
+            Vec::new(),
+            AssignmentConflicts::Error,
+            &roots,
+            &HashMap::new(),
+        )?;
+        let extents = abstracts
+            .iter()
+            .map(|inst| inst.track_extents(&merged.grid, merged.physical_bounds()))
+            .collect();
+        let mut unrouted = merged.routing_state();
+        unrouted.roots = roots;
+        let mut routed = unrouted.clone();
+        let (paths, unrouted_roots) = to_connect
+            .iter()
+            .map(|group| router.try_route(&mut routed, vec![group.clone()]))
+            .unzip();
+
+        Ok(Self {
+            instances: abstracts,
+            ports,
+            to_connect,
+            merged,
+            extents,
+            unrouted,
+            routed,
+            paths,
+            unrouted_roots,
+        })
+    }
+
+    /// Returns the routed parent abstract.
+    pub fn abs(&self) -> Abstract {
+        let mut state = self.routed.clone();
+        for (net, root) in self.routed.roots.iter() {
+            state.relabel_net(*net, *root);
+        }
+        let mut abs = self.merged.clone();
+        abs.from_routing_state(state);
+        abs
+    }
+
+    /// Returns the paths routed for each group of nets, in parent grid coordinates.
+    pub fn paths(&self) -> &[Vec<Path>] {
+        &self.paths
+    }
+
+    /// Returns the root nets of the groups that could not be fully connected.
+    pub fn unrouted_roots(&self) -> Vec<NetId> {
+        self.unrouted_roots.iter().flatten().copied().collect()
+    }
+
+    /// Moves the instance at `index` by `offset` (in LCM units).
+    ///
+    /// Only the regions of the merged abstract covered by the instance before and after
+    /// the move are updated, and only groups with points in those regions or on the
+    /// instance's nets are re-routed. If the instance moves outside the current bounds,
+    /// everything is merged and routed again.
+    ///
+    /// Returns an error, leaving the routing unchanged, if the instance would overlap
+    /// another instance or the instances cannot be merged again.
+    pub fn move_instance(
+        &mut self,
+        index: usize,
+        offset: Point,
+        router: &dyn Router,
+    ) -> Result<(), MoveError> {
+        let mut moved = self.instances[index].clone();
+        moved.abs.lcm_bounds.translate_mut(offset);
+
+        for (other, inst) in self.instances.iter().enumerate() {
+            if other == index {
+                continue;
+            }
+            if let Some(overlap) = inst.lcm_bounds().intersection(moved.lcm_bounds()) {
+                if overlap.width() > 0 && overlap.height() > 0 {
+                    return Err(InstanceOverlap { index, other }.into());
+                }
+            }
+        }
+
+        let bounds = self.merged.physical_bounds();
+        if bounds.union(moved.physical_bounds()) != bounds {
+            let mut instances = self.instances.clone();
+            instances[index] = moved;
+            *self = Self::new(
+                instances,
+                self.merged.top_layer,
+                self.ports.clone(),
+                self.to_connect.clone(),
+                router,
+            )?;
+            return Ok(());
+        }
+
+        let old = std::mem::take(&mut self.extents[index]);
+        let new = moved.track_extents(&self.merged.grid, bounds);
+        let roots = self.routed.roots.clone();
+        let root = |net: NetId| roots.get(&net).copied().unwrap_or(net);
+
+        // Find the groups with points near the instance, before or after the move.
+        let mut impacted: HashSet<_> = moved.parent_net_ids.iter().map(|&net| root(net)).collect();
+        for extents in [&old, &new] {
+            for (layer, &(left, bot, width, height)) in extents.iter().enumerate() {
+                for x in left..=left + width {
+                    for y in bot..=bot + height {
+                        let coord = GridCoord {
+                            layer,
+                            x: x as usize,
+                            y: y as usize,
+                        };
+                        if !self.routed.in_bounds(coord) {
+                            continue;
+                        }
+                        if let PointState::Routed { net, .. } | PointState::Reserved { net } =
+                            self.routed[coord]
+                        {
+                            impacted.insert(root(net));
+                        }
+                    }
+                }
+            }
+        }
+
+        // Rip up the impacted groups, restoring the points they used to their unrouted state.
+        for layer in 0..self.routed.layers.len() {
+            let (nx, ny) = self.routed.layer(layer).size();
+            for x in 0..nx {
+                for y in 0..ny {
+                    let coord = GridCoord { layer, x, y };
+                    if let PointState::Routed { net, .. } | PointState::Reserved { net } =
+                        self.routed[coord]
+                    {
+                        if impacted.contains(&root(net)) {
+                            self.routed[coord] = self.unrouted[coord];
+                        }
+                    }
+                }
+            }
+        }
+
+        for state in [&mut self.unrouted, &mut self.routed] {
+            for (layer, &(left, bot, width, height)) in old.iter().enumerate() {
+                for x in left + 1..left + width {
+                    for y in bot + 1..bot + height {
+                        state.layer_mut(layer)[(x as usize, y as usize)] = PointState::Available;
+                    }
+                }
+            }
+            moved.stamp(state, &new, &HashMap::new());
+        }
+        self.instances[index] = moved;
+        self.extents[index] = new;
+
+        let straps: Vec<_> = self
+            .instances
+            .iter()
+            .zip(self.extents.iter())
+            .flat_map(|(inst, extents)| inst.parent_straps(&self.merged.grid, extents))
+            .collect();
+        self.unrouted.straps = straps.clone();
+        self.routed.straps = straps;
+
+        for ((group, paths), unrouted_roots) in self
+            .to_connect
+            .iter()
+            .zip(self.paths.iter_mut())
+            .zip(self.unrouted_roots.iter_mut())
+        {
+            if group
+                .first()
+                .is_some_and(|&net| impacted.contains(&root(net)))
+            {
+                (*paths, *unrouted_roots) = router.try_route(&mut self.routed, vec![group.clone()]);
+            }
+        }
+        Ok(())
     }
 }
 
@@ -547,6 +1407,159 @@ pub enum LayerAbstract {
     },
 }
 
+/// The side length, in grid points, of the square windows used to locate peak congestion.
+pub const CONGESTION_WINDOW: usize = 8;
+
+/// A summary of routing congestion in an [`Abstract`].
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CongestionReport {
+    /// The congestion of each layer, indexed by layer.
+    pub layers: Vec<LayerCongestion>,
+}
+
+/// Routing congestion on a single layer.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LayerCongestion {
+    /// The layer index.
+    pub layer: usize,
+    /// The number of grid points that are routed or reserved.
+    pub used: usize,
+    /// The total number of grid points on the layer.
+    pub total: usize,
+    /// The most congested window on the layer, if the layer has any grid points.
+    pub peak: Option<CongestionRegion>,
+}
+
+/// A rectangular region of a routing layer and its congestion.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CongestionRegion {
+    /// The layer index.
+    pub layer: usize,
+    /// The grid points contained in the region, in grid coordinates (inclusive).
+    pub bounds: Rect,
+    /// The number of grid points in the region that are routed or reserved.
+    pub used: usize,
+    /// The total number of grid points in the region.
+    pub total: usize,
+}
+
+impl CongestionReport {
+    /// Returns the most congested region across all layers.
+    pub fn peak(&self) -> Option<&CongestionRegion> {
+        self.layers
+            .iter()
+            .filter_map(|layer| layer.peak.as_ref())
+            .fold(None, |acc: Option<&CongestionRegion>, region| match acc {
+                Some(acc) if acc.utilization() >= region.utilization() => Some(acc),
+                _ => Some(region),
+            })
+    }
+}
+
+impl LayerCongestion {
+    fn from_states(layer: usize, states: &Grid<PointState>) -> Self {
+        let (nx, ny) = states.size();
+        let count_used = |xs: std::ops::Range<usize>, ys: std::ops::Range<usize>| {
+            xs.flat_map(|x| ys.clone().map(move |y| (x, y)))
+                .filter(|&pt| {
+                    matches!(
+                        states[pt],
+                        PointState::Routed { .. } | PointState::Reserved { .. }
+                    )
+                })
+                .count()
+        };
+
+        let mut peak: Option<CongestionRegion> = None;
+        for x0 in (0..nx).step_by(CONGESTION_WINDOW) {
+            for y0 in (0..ny).step_by(CONGESTION_WINDOW) {
+                let x1 = std::cmp::min(x0 + CONGESTION_WINDOW, nx);
+                let y1 = std::cmp::min(y0 + CONGESTION_WINDOW, ny);
+                let region = CongestionRegion {
+                    layer,
+                    bounds: Rect::from_sides(x0 as i64, y0 as i64, x1 as i64 - 1, y1 as i64 - 1),
+                    used: count_used(x0..x1, y0..y1),
+                    total: (x1 - x0) * (y1 - y0),
+                };
+                if peak
+                    .as_ref()
+                    .map(|peak| region.utilization() > peak.utilization())
+                    .unwrap_or(true)
+                {
+                    peak = Some(region);
+                }
+            }
+        }
+
+        Self {
+            layer,
+            used: count_used(0..nx, 0..ny),
+            total: nx * ny,
+            peak,
+        }
+    }
+
+    /// The fraction of grid points on the layer that are routed or reserved.
+    ///
+    /// Returns 0 if the layer has no grid points.
+    pub fn utilization(&self) -> f64 {
+        utilization(self.used, self.total)
+    }
+}
+
+impl CongestionRegion {
+    /// The fraction of grid points in the region that are routed or reserved.
+    pub fn utilization(&self) -> f64 {
+        utilization(self.used, self.total)
+    }
+}
+
+/// Moves the point states of a layer along one axis after its tracks are shifted by
+/// `shift` tracks, plus half a track if `half` is set.
+///
+/// Points that do not land on points of the unshifted grid, or that would take on
+/// differing states, are blocked.
+fn shift_states(
+    states: &Grid<PointState>,
+    along_x: bool,
+    shift: i64,
+    half: bool,
+) -> Grid<PointState> {
+    let (nx, ny) = states.size();
+    let n = if along_x { nx } else { ny } as i64;
+    let mut shifted = Grid::init(nx, ny, PointState::Blocked { has_via: false });
+    for x in 0..nx {
+        for y in 0..ny {
+            let idx = if along_x { x } else { y } as i64 + shift;
+            let sources = if half { idx..=idx + 1 } else { idx..=idx };
+            if !(0..n).contains(sources.start()) || !(0..n).contains(sources.end()) {
+                continue;
+            }
+            let mut sources = sources.map(|src| {
+                let src = src as usize;
+                if along_x {
+                    states[(src, y)]
+                } else {
+                    states[(x, src)]
+                }
+            });
+            let first = sources.next().unwrap();
+            if sources.all(|state| state == first) {
+                shifted[(x, y)] = first;
+            }
+        }
+    }
+    shifted
+}
+
+fn utilization(used: usize, total: usize) -> f64 {
+    if total == 0 {
+        0.
+    } else {
+        used as f64 / total as f64
+    }
+}
+
 fn top_layer(cell: &RawCell, stack: &LayerStack<PdkLayer>) -> Option<usize> {
     let mut state = HashMap::new();
     top_layer_inner(cell, &mut state, stack)
@@ -675,3 +1688,876 @@ impl<PDK: Pdk> Draw<PDK> for DebugAbstract {
         recv.draw(&self)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::abs::*;
+    use crate::grid::{AbstractLayer, ExplicitTracks, TrackOffset};
+    use crate::route::GreedyRouter;
+    use crate::straps::{GreedyStrapper, LayerStrappingParams, Strapper, StrappingParams};
+    use crate::RoutingDir;
+    use std::collections::HashSet;
+    use substrate::geometry::contains::Contains;
+    use substrate::pdk::layers::LayerId;
+
+    fn layer_stack() -> LayerStack<PdkLayer> {
+        LayerStack {
+            layers: [RoutingDir::Horiz, RoutingDir::Vert, RoutingDir::Horiz]
+                .into_iter()
+                .map(|dir| PdkLayer {
+                    id: LayerId::default(),
+                    inner: AbstractLayer {
+                        dir,
+                        line: 100,
+                        space: 100,
+                        offset: TrackOffset::None,
+                        endcap: 20,
+                        via_spacing: 1,
+                        strap_via_spacing: 1,
+                        explicit_tracks: None,
+                    },
+                })
+                .collect(),
+            offset_x: 0,
+            offset_y: 0,
+        }
+    }
+
+    /// A 4x4 child abstract exposing `pin` as a column of two points on layer 1.
+    fn pin_column_child(stack: &LayerStack<PdkLayer>, pin: NetId) -> Abstract {
+        let mut state = RoutingState::new(stack.clone(), 1, 4, 4);
+        for y in 1..=2 {
+            state.layer_mut(1)[(1, y)] = PointState::Routed {
+                net: pin,
+                has_via: false,
+            };
+        }
+        state.roots = HashMap::from([(pin, pin)]);
+        let mut abs = Abstract {
+            top_layer: 1,
+            lcm_bounds: Rect::from_sides(0, 0, 4, 4),
+            layers: vec![LayerAbstract::Available; 2],
+            ports: vec![pin],
+            straps: Vec::new(),
+            grid: RoutingGrid::new(stack.clone(), 0..2),
+            device_count: 0,
+            net_count: 0,
+            net_names: BTreeMap::new(),
+        };
+        abs.from_routing_state(state);
+        abs
+    }
+
+    #[test]
+    fn layer_congestion_counts_routed_and_reserved() {
+        let mut states = Grid::init(10, 4, PointState::Available);
+        states[(0, 0)] = PointState::Routed {
+            net: NetId(0),
+            has_via: false,
+        };
+        states[(1, 0)] = PointState::Reserved { net: NetId(1) };
+        states[(2, 0)] = PointState::Blocked { has_via: false };
+        states[(9, 3)] = PointState::Routed {
+            net: NetId(0),
+            has_via: true,
+        };
+
+        let congestion = LayerCongestion::from_states(1, &states);
+        assert_eq!(congestion.used, 3);
+        assert_eq!(congestion.total, 40);
+        assert_eq!(congestion.utilization(), 3. / 40.);
+
+        let peak = congestion.peak.unwrap();
+        assert_eq!(peak.layer, 1);
+        assert_eq!(peak.bounds, Rect::from_sides(8, 0, 9, 3));
+        assert_eq!(peak.used, 1);
+        assert_eq!(peak.total, 8);
+    }
+
+    #[test]
+    fn grid_offset_moves_abstract_states() {
+        let net = NetId(0);
+        let routed = PointState::Routed {
+            net,
+            has_via: false,
+        };
+        let blocked = PointState::Blocked { has_via: false };
+        let mut layers = vec![Grid::init(6, 4, PointState::Available); 3];
+        layers[0][(3, 1)] = blocked;
+        layers[1][(3, 2)] = routed;
+        let mut abs = Abstract {
+            top_layer: 2,
+            lcm_bounds: Rect::from_sides(0, 0, 3, 2),
+            layers: layers
+                .into_iter()
+                .map(|states| LayerAbstract::Detailed { states })
+                .collect(),
+            ports: vec![net],
+            straps: vec![StrapSegment {
+                net,
+                layer: 1,
+                track: 3,
+                start: 1,
+                stop: 2,
+            }],
+            grid: RoutingGrid::new(layer_stack(), 0..3),
+            device_count: 0,
+            net_count: 1,
+            net_names: BTreeMap::new(),
+        };
+        let state = |abs: &Abstract, layer, x, y| abs.routing_state()[GridCoord { layer, x, y }];
+
+        // A whole pitch moves points on layer 1 and on the layers it forms grids for.
+        abs.set_grid_offset(1, 200).unwrap();
+        assert_eq!(abs.grid.offset(1), 200);
+        assert_eq!(state(&abs, 1, 2, 2), routed);
+        assert_eq!(state(&abs, 1, 3, 2), PointState::Available);
+        assert_eq!(state(&abs, 0, 2, 1), blocked);
+        assert_eq!(abs.straps[0].track, 2);
+
+        // Half a pitch lands between points; only points whose neighbors agree keep their state.
+        abs.set_grid_offset(1, 300).unwrap();
+        assert_eq!(state(&abs, 1, 1, 2), blocked);
+        assert_eq!(state(&abs, 1, 2, 2), blocked);
+        assert_eq!(state(&abs, 1, 3, 2), PointState::Available);
+        assert_eq!(state(&abs, 1, 5, 2), blocked);
+        assert!(abs.straps.is_empty());
+    }
+
+    #[test]
+    fn path_occupied_coords() {
+        let abs = Abstract {
+            top_layer: 2,
+            lcm_bounds: Rect::from_sides(2, 1, 10, 9),
+            layers: vec![LayerAbstract::Available; 3],
+            ports: Vec::new(),
+            straps: Vec::new(),
+            grid: RoutingGrid::new(layer_stack(), 0..3),
+            device_count: 0,
+            net_count: 0,
+            net_names: BTreeMap::new(),
+        };
+        let coord = |layer, x, y| GridCoord { layer, x, y };
+        // An L-shaped path: right along layer 2, via down, then down along layer 1.
+        let path = vec![
+            (coord(2, 1, 3), coord(2, 4, 3)),
+            (coord(2, 4, 3), coord(1, 4, 3)),
+            (coord(1, 4, 3), coord(1, 4, 1)),
+        ];
+
+        let track = |layer, x: i64, y: i64| TrackCoord {
+            layer,
+            x: x + 2,
+            y: y + 1,
+        };
+        assert_eq!(
+            abs.occupied_coords(&path).collect::<Vec<_>>(),
+            vec![
+                track(2, 1, 3),
+                track(2, 2, 3),
+                track(2, 3, 3),
+                track(2, 4, 3),
+                track(1, 4, 3),
+                track(1, 4, 2),
+                track(1, 4, 1),
+            ]
+        );
+    }
+
+    #[test]
+    fn parent_vias_up_to_child_strap() {
+        let stack = layer_stack();
+        let vdd = NetId(0);
+        let child_strap = StrapSegment {
+            net: vdd,
+            layer: 1,
+            track: 3,
+            start: 1,
+            stop: 6,
+        };
+
+        let mut state = RoutingState::new(stack.clone(), 1, 8, 8);
+        for y in child_strap.start..=child_strap.stop {
+            state.layer_mut(1)[(child_strap.track, y)] = PointState::Routed {
+                net: vdd,
+                has_via: false,
+            };
+        }
+        state.roots = HashMap::from([(vdd, vdd), (NetId(1), NetId(1))]);
+        state.straps.push(child_strap);
+        // Straps on internal nets are not exposed.
+        state.straps.push(StrapSegment {
+            net: NetId(1),
+            track: 5,
+            ..child_strap
+        });
+
+        let mut child = Abstract {
+            top_layer: 1,
+            lcm_bounds: Rect::from_sides(0, 0, 8, 8),
+            layers: vec![LayerAbstract::Available; 2],
+            ports: vec![vdd],
+            straps: Vec::new(),
+            grid: RoutingGrid::new(stack, 0..2),
+            device_count: 0,
+            net_count: 0,
+            net_names: BTreeMap::new(),
+        };
+        child.from_routing_state(state);
+        assert_eq!(child.straps(), &[child_strap]);
+
+        let parent_vdd = NetId(5);
+        let inst = InstanceAbstract::new(child, Point::zero(), Orientation::R0, vec![parent_vdd]);
+        let parent = InstanceAbstract::merge(
+            vec![inst],
+            2,
+            None,
+            vec![parent_vdd],
+            Vec::new(),
+            AssignmentConflicts::Error,
+            &HashMap::new(),
+            &HashMap::new(),
+        )
+        .unwrap();
+        let inherited = StrapSegment {
+            net: parent_vdd,
+            ..child_strap
+        };
+        assert_eq!(parent.straps(), &[inherited]);
+
+        let mut state = parent.routing_state();
+        state.roots = HashMap::from([(parent_vdd, parent_vdd)]);
+        let paths = GreedyStrapper.strap(
+            &mut state,
+            vec![(
+                parent_vdd,
+                StrappingParams::new(2, vec![LayerStrappingParams::ViaDown { min_period: 1 }]),
+            )],
+        );
+
+        // The child strap is not redrawn.
+        assert!(paths
+            .iter()
+            .flatten()
+            .all(|(a, b)| a.layer != 1 || b.layer != 1));
+        for y in child_strap.start..=child_strap.stop {
+            let via = (
+                GridCoord { layer: 2, x: 3, y },
+                GridCoord { layer: 1, x: 3, y },
+            );
+            assert!(paths.contains(&vec![via]));
+        }
+        assert_eq!(state.straps()[0], inherited);
+        assert!(state.straps()[1..]
+            .iter()
+            .all(|strap| strap.layer == 2 && strap.net == parent_vdd));
+    }
+
+    #[test]
+    fn route_between_placed_instances() {
+        let stack = layer_stack();
+        let pin = NetId(0);
+
+        let child = || pin_column_child(&stack, pin);
+
+        let (a, b) = (NetId(10), NetId(11));
+        let left = InstanceAbstract::new(child(), Point::zero(), Orientation::R0, vec![a]);
+        let right = InstanceAbstract::new(child(), Point::new(4, 0), Orientation::R0, vec![b]);
+
+        let (parent, paths, unrouted) = InstanceAbstract::route(
+            vec![left, right],
+            2,
+            vec![a],
+            vec![vec![a, b]],
+            &GreedyRouter::new(),
+        )
+        .unwrap();
+
+        assert!(unrouted.is_empty());
+        assert!(!paths.is_empty());
+        assert!(paths
+            .iter()
+            .flatten()
+            .any(|(s, e)| s.layer == 2 || e.layer == 2));
+        assert_eq!(parent.ports, vec![a]);
+
+        // Both pins now belong to the same parent net.
+        let state = parent.routing_state();
+        for x in [1, 5] {
+            for y in 1..=2 {
+                assert!(matches!(
+                    state.layer(1)[(x, y)],
+                    PointState::Routed { net, .. } if net == a
+                ));
+            }
+        }
+    }
+
+    #[test]
+    fn route_between_placed_instances_reports_unrouted_groups() {
+        let stack = layer_stack();
+        let pin = NetId(0);
+
+        // Pins surrounded by blockages on every layer available to the parent.
+        let child = || {
+            let mut abs = pin_column_child(&stack, pin);
+            let mut state = abs.routing_state();
+            state.roots = HashMap::from([(pin, pin)]);
+            for layer in 0..=1 {
+                let (nx, ny) = state.layer(layer).size();
+                for x in 0..nx {
+                    for y in 0..ny {
+                        if state.layer(layer)[(x, y)] == PointState::Available {
+                            state.layer_mut(layer)[(x, y)] = PointState::Blocked { has_via: false };
+                        }
+                    }
+                }
+            }
+            abs.from_routing_state(state);
+            abs
+        };
+
+        let (a, b) = (NetId(10), NetId(11));
+        let left = InstanceAbstract::new(child(), Point::zero(), Orientation::R0, vec![a]);
+        let right = InstanceAbstract::new(child(), Point::new(4, 0), Orientation::R0, vec![b]);
+
+        let (_, paths, unrouted) = InstanceAbstract::route(
+            vec![left, right],
+            1,
+            vec![a],
+            vec![vec![a, b]],
+            &GreedyRouter::new(),
+        )
+        .unwrap();
+
+        assert!(paths.is_empty());
+        assert_eq!(unrouted.len(), 1);
+    }
+
+    #[test]
+    fn greedy_router_is_deterministic() {
+        let stack = layer_stack();
+        let pin = NetId(0);
+
+        let child = || pin_column_child(&stack, pin);
+
+        let nets: Vec<_> = (10..14).map(NetId).collect();
+        let route = |to_connect: Vec<Vec<NetId>>| {
+            let instances = nets
+                .iter()
+                .enumerate()
+                .map(|(i, &net)| {
+                    InstanceAbstract::new(
+                        child(),
+                        Point::new(4 * i as i64, 0),
+                        Orientation::R0,
+                        vec![net],
+                    )
+                })
+                .collect();
+            let (_, paths, _) = InstanceAbstract::route(
+                instances,
+                2,
+                vec![nets[0], nets[2]],
+                to_connect,
+                &GreedyRouter::new(),
+            )
+            .unwrap();
+            format!("{paths:?}")
+        };
+
+        let expected = route(vec![vec![nets[0], nets[1]], vec![nets[2], nets[3]]]);
+        assert_eq!(
+            route(vec![vec![nets[0], nets[1]], vec![nets[2], nets[3]]]),
+            expected
+        );
+        assert_eq!(
+            route(vec![vec![nets[2], nets[3]], vec![nets[0], nets[1]]]),
+            expected
+        );
+    }
+
+    #[test]
+    fn incremental_move_reroutes_affected_nets() {
+        let stack = layer_stack();
+        let pin = NetId(0);
+
+        let child = || pin_column_child(&stack, pin);
+
+        let nets: Vec<_> = (10..14).map(NetId).collect();
+        let instances = [0, 4, 16, 24]
+            .into_iter()
+            .zip(nets.iter())
+            .map(|(x, &net)| {
+                InstanceAbstract::new(child(), Point::new(x, 0), Orientation::R0, vec![net])
+            })
+            .collect();
+        let router = GreedyRouter::new();
+        let mut routing = IncrementalRouting::new(
+            instances,
+            2,
+            vec![nets[0], nets[2]],
+            vec![vec![nets[0], nets[1]], vec![nets[2], nets[3]]],
+            &router,
+        )
+        .unwrap();
+        let before = routing.paths().to_vec();
+        let bounds = routing.abs().lcm_bounds;
+
+        // Instances may not overlap.
+        assert_eq!(
+            routing.move_instance(3, Point::new(-6, 0), &router),
+            Err(MoveError::Overlap(InstanceOverlap { index: 3, other: 2 }))
+        );
+        assert_eq!(routing.paths(), before);
+
+        routing
+            .move_instance(3, Point::new(-4, 0), &router)
+            .unwrap();
+        assert!(routing.unrouted_roots().is_empty());
+
+        let after = routing.paths();
+        assert_eq!(after[0], before[0]);
+        assert_ne!(after[1], before[1]);
+        assert!(!after[1].is_empty());
+
+        let abs = routing.abs();
+        assert_eq!(abs.lcm_bounds, bounds);
+        let state = abs.routing_state();
+        for y in 1..=2 {
+            // The moved pin is connected to the rest of its group.
+            assert!(matches!(
+                state.layer(1)[(21, y)],
+                PointState::Routed { net, .. } if net == nets[2]
+            ));
+            // The previous location of the pin is no longer occupied.
+            assert_eq!(state.layer(1)[(25, y)], PointState::Available);
+        }
+    }
+
+    #[test]
+    fn assignment_conflict() {
+        let stack = layer_stack();
+        let (a, b) = (NetId(1), NetId(2));
+        let merge = |conflicts, net_roots: &HashMap<NetId, NetId>| {
+            let parent = Abstract {
+                top_layer: 1,
+                lcm_bounds: Rect::from_sides(0, 0, 8, 8),
+                layers: vec![LayerAbstract::Available; 2],
+                ports: Vec::new(),
+                straps: Vec::new(),
+                grid: RoutingGrid::new(stack.clone(), 0..2),
+                device_count: 0,
+                net_count: 0,
+                net_names: BTreeMap::new(),
+            };
+            let inst = InstanceAbstract::new(parent, Point::zero(), Orientation::R0, Vec::new());
+            let assigned = [
+                (a, Rect::from_sides(1, 2, 3, 2)),
+                (b, Rect::from_sides(3, 2, 5, 2)),
+            ]
+            .into_iter()
+            .map(|(net, bounds)| AssignedGridPoints {
+                net: Some(net),
+                layer: 0,
+                bounds,
+                only_if_available: false,
+            })
+            .collect();
+            InstanceAbstract::merge(
+                vec![inst],
+                1,
+                None,
+                Vec::new(),
+                assigned,
+                conflicts,
+                net_roots,
+                &HashMap::new(),
+            )
+        };
+
+        let coord = GridCoord {
+            layer: 0,
+            x: 3,
+            y: 2,
+        };
+        let err = merge(AssignmentConflicts::Error, &HashMap::new()).unwrap_err();
+        assert_eq!(
+            err,
+            MergeError::AssignmentConflict(AssignmentConflict {
+                coord,
+                first: a,
+                second: b,
+            })
+        );
+        assert_eq!(
+            err.to_string(),
+            format!("grid point {coord:?} is assigned to both net {a:?} and net {b:?}")
+        );
+
+        // The later assignment takes precedence when conflicts only warn.
+        let state = merge(AssignmentConflicts::Warn, &HashMap::new())
+            .unwrap()
+            .routing_state();
+        assert_eq!(
+            state[coord],
+            PointState::Routed {
+                net: b,
+                has_via: false
+            }
+        );
+
+        // Connected nets may share grid points.
+        assert!(merge(AssignmentConflicts::Error, &HashMap::from([(b, a)])).is_ok());
+    }
+
+    #[test]
+    fn assignment_overwrites_instance_pin() {
+        let stack = layer_stack();
+        let pin = NetId(0);
+        let (a, b) = (NetId(1), NetId(2));
+
+        let mut state = RoutingState::new(stack.clone(), 1, 4, 4);
+        state.layer_mut(0)[(2, 2)] = PointState::Routed {
+            net: pin,
+            has_via: false,
+        };
+        state.roots = HashMap::from([(pin, pin)]);
+        let mut child = Abstract {
+            top_layer: 1,
+            lcm_bounds: Rect::from_sides(0, 0, 4, 4),
+            layers: vec![LayerAbstract::Available; 2],
+            ports: vec![pin],
+            straps: Vec::new(),
+            grid: RoutingGrid::new(stack.clone(), 0..2),
+            device_count: 0,
+            net_count: 0,
+            net_names: BTreeMap::new(),
+        };
+        child.from_routing_state(state);
+
+        let merge = |net| {
+            let inst =
+                InstanceAbstract::new(child.clone(), Point::zero(), Orientation::R0, vec![a]);
+            InstanceAbstract::merge(
+                vec![inst],
+                1,
+                None,
+                vec![a],
+                vec![AssignedGridPoints {
+                    net: Some(net),
+                    layer: 0,
+                    bounds: Rect::from_sides(1, 2, 3, 2),
+                    only_if_available: false,
+                }],
+                AssignmentConflicts::Error,
+                &HashMap::new(),
+                &HashMap::new(),
+            )
+        };
+
+        assert_eq!(
+            merge(b).unwrap_err(),
+            MergeError::AssignmentConflict(AssignmentConflict {
+                coord: GridCoord {
+                    layer: 0,
+                    x: 2,
+                    y: 2,
+                },
+                first: a,
+                second: b,
+            })
+        );
+        assert!(merge(a).is_ok());
+    }
+
+    #[test]
+    fn abutment_mismatch() {
+        let stack = layer_stack();
+        let pin = NetId(0);
+
+        let child = |rail: usize| {
+            let mut state = RoutingState::new(stack.clone(), 1, 4, 4);
+            for x in 1..=3 {
+                state.layer_mut(0)[(x, rail)] = PointState::Routed {
+                    net: pin,
+                    has_via: false,
+                };
+            }
+            state.roots = HashMap::from([(pin, pin)]);
+            let mut abs = Abstract {
+                top_layer: 1,
+                lcm_bounds: Rect::from_sides(0, 0, 4, 4),
+                layers: vec![LayerAbstract::Available; 2],
+                ports: vec![pin],
+                straps: Vec::new(),
+                grid: RoutingGrid::new(stack.clone(), 0..2),
+                device_count: 0,
+                net_count: 0,
+                net_names: BTreeMap::new(),
+            };
+            abs.from_routing_state(state);
+            abs
+        };
+
+        let (a, b) = (NetId(10), NetId(11));
+        let abutment_nets = HashMap::from([(a, a), (b, a)]);
+        let merge = |right_rail: usize| {
+            let left = InstanceAbstract::new(child(2), Point::zero(), Orientation::R0, vec![a]);
+            let right = InstanceAbstract::new(
+                child(right_rail),
+                Point::new(4, 0),
+                Orientation::R0,
+                vec![b],
+            );
+            InstanceAbstract::merge(
+                vec![left, right],
+                1,
+                None,
+                vec![a],
+                Vec::new(),
+                AssignmentConflicts::Error,
+                &HashMap::new(),
+                &abutment_nets,
+            )
+        };
+
+        assert!(merge(2).is_ok());
+        assert_eq!(
+            merge(1).unwrap_err(),
+            MergeError::Abutment(AbutmentMismatch {
+                net: a,
+                coord: GridCoord {
+                    layer: 0,
+                    x: 4,
+                    y: 1
+                },
+            })
+        );
+    }
+
+    #[test]
+    fn bus_bits_route_on_adjacent_tracks() {
+        let mut state = RoutingState::new(layer_stack(), 2, 20, 20);
+        let (nx, ny) = state.layer(0).size();
+        for x in 0..nx {
+            for y in 0..ny {
+                state.layer_mut(0)[(x, y)] = PointState::Blocked { has_via: false };
+            }
+        }
+
+        let bits: Vec<_> = (0..4).map(|i| (NetId(2 * i), NetId(2 * i + 1))).collect();
+        for (i, &(a, b)) in bits.iter().enumerate() {
+            state.layer_mut(1)[(2 + i, 2)] = PointState::Routed {
+                net: a,
+                has_via: false,
+            };
+            state.layer_mut(1)[(12 + i, 15)] = PointState::Routed {
+                net: b,
+                has_via: false,
+            };
+            state.roots.insert(a, a);
+            state.roots.insert(b, a);
+        }
+        state.buses = vec![(bits.iter().map(|(a, _)| *a).collect(), 2)];
+
+        let (_, unrouted) = GreedyRouter::new().try_route(
+            &mut state,
+            bits.iter().rev().map(|&(a, b)| vec![a, b]).collect(),
+        );
+        assert!(unrouted.is_empty());
+
+        let (nx, ny) = state.layer(2).size();
+        let tracks: Vec<_> = bits
+            .iter()
+            .map(|&(a, _)| {
+                let tracks: HashSet<_> = (0..nx)
+                    .flat_map(|x| (0..ny).map(move |y| (x, y)))
+                    .filter(|&(x, y)| {
+                        matches!(
+                            state.layer(2)[(x, y)],
+                            PointState::Routed { net, .. } if net == a
+                        )
+                    })
+                    .map(|(_, y)| y)
+                    .collect();
+                assert_eq!(tracks.len(), 1);
+                tracks.into_iter().next().unwrap()
+            })
+            .collect();
+        assert!(
+            tracks.windows(2).all(|w| w[1] == w[0] + 1)
+                || tracks.windows(2).all(|w| w[1] + 1 == w[0])
+        );
+    }
+
+    #[test]
+    fn net_keep_clear_region() {
+        let mut state = RoutingState::new(layer_stack(), 2, 20, 20);
+        let (a0, a1, b0, b1) = (NetId(0), NetId(1), NetId(2), NetId(3));
+        for (net, x, y) in [(a0, 3, 10), (a1, 17, 10), (b0, 3, 8), (b1, 17, 8)] {
+            state.layer_mut(1)[(x, y)] = PointState::Routed {
+                net,
+                has_via: false,
+            };
+        }
+        state.roots = HashMap::from([(a0, a0), (a1, a0), (b0, b0), (b1, b0)]);
+        let region = Rect::from_sides(1_000, 1_000, 3_000, 3_000);
+        state.keep_clear = vec![(a0, region)];
+
+        let (_, unrouted) =
+            GreedyRouter::new().try_route(&mut state, vec![vec![a0, a1], vec![b0, b1]]);
+        assert!(unrouted.is_empty());
+
+        let routed_in_region = |root: NetId| {
+            (0..=2).any(|layer| {
+                let (nx, ny) = state.layer(layer).size();
+                (0..nx)
+                    .flat_map(|x| (0..ny).map(move |y| (x, y)))
+                    .any(|(x, y)| {
+                        let coord = GridCoord { layer, x, y };
+                        let on_net = matches!(
+                            state[coord],
+                            PointState::Routed { net, .. } if state.roots[&net] == root
+                        );
+                        on_net && region.encloses(&state.grid_to_rel_physical(coord))
+                    })
+            })
+        };
+        assert!(!routed_in_region(a0));
+        assert!(routed_in_region(b0));
+    }
+
+    #[test]
+    fn route_on_explicit_tracks() {
+        let mut stack = layer_stack();
+        stack.layers[1].inner.explicit_tracks = Some(ExplicitTracks::new([0, 200, 600], 800));
+        let mut state = RoutingState::new(stack, 2, 2, 4);
+        assert_eq!(state.layer(1).size(), (6, 4));
+
+        let (nx, ny) = state.layer(0).size();
+        for x in 0..nx {
+            for y in 0..ny {
+                state.layer_mut(0)[(x, y)] = PointState::Blocked { has_via: false };
+            }
+        }
+        let (a, b) = (NetId(0), NetId(1));
+        state.layer_mut(1)[(0, 0)] = PointState::Routed {
+            net: a,
+            has_via: false,
+        };
+        state.layer_mut(1)[(5, 3)] = PointState::Routed {
+            net: b,
+            has_via: false,
+        };
+        state.roots = HashMap::from([(a, a), (b, a)]);
+
+        let (paths, unrouted) = GreedyRouter::new().try_route(&mut state, vec![vec![a, b]]);
+        assert!(unrouted.is_empty());
+        assert!(!paths.is_empty());
+
+        // Every routed point lies on one of the explicit tracks.
+        for coord in paths.iter().flatten().flat_map(|&(s, e)| [s, e]) {
+            let x = state.grid_to_rel_physical(coord).x;
+            assert!(
+                [0, 200, 600].contains(&x.rem_euclid(800)),
+                "{coord:?} is not on an explicit track"
+            );
+        }
+        assert!(state.is_routed_for_net(
+            GridCoord {
+                layer: 1,
+                x: 5,
+                y: 3
+            },
+            a
+        ));
+    }
+
+    #[test]
+    fn block_from_abstract_footprint() {
+        let stack = layer_stack();
+
+        let mut state = RoutingState::new(stack.clone(), 1, 4, 4);
+        state.layer_mut(0)[(1, 1)] = PointState::Blocked { has_via: false };
+        state.layer_mut(1)[(2, 1)] = PointState::Routed {
+            net: NetId(0),
+            has_via: false,
+        };
+        state.layer_mut(1)[(2, 2)] = PointState::Routed {
+            net: NetId(0),
+            has_via: false,
+        };
+        state.layer_mut(1)[(3, 2)] = PointState::Reserved { net: NetId(0) };
+        state.roots = HashMap::from([(NetId(0), NetId(0))]);
+        let mut child = Abstract {
+            top_layer: 1,
+            lcm_bounds: Rect::from_sides(0, 0, 4, 4),
+            layers: vec![LayerAbstract::Available; 2],
+            ports: vec![NetId(0)],
+            straps: Vec::new(),
+            grid: RoutingGrid::new(stack.clone(), 0..2),
+            device_count: 0,
+            net_count: 0,
+            net_names: BTreeMap::new(),
+        };
+        child.from_routing_state(state);
+
+        let runs = child.occupied_track_runs(Point::new(2, 1), Orientation::R180);
+        assert_eq!(
+            runs,
+            vec![
+                (0, Rect::from_sides(5, 4, 5, 4)),
+                (1, Rect::from_sides(3, 3, 4, 3)),
+                (1, Rect::from_sides(4, 4, 4, 4)),
+            ]
+        );
+
+        let parent = Abstract {
+            top_layer: 1,
+            lcm_bounds: Rect::from_sides(0, 0, 8, 8),
+            layers: vec![LayerAbstract::Available; 2],
+            ports: Vec::new(),
+            straps: Vec::new(),
+            grid: RoutingGrid::new(stack, 0..2),
+            device_count: 0,
+            net_count: 0,
+            net_names: BTreeMap::new(),
+        };
+        let inst = InstanceAbstract::new(parent, Point::zero(), Orientation::R0, Vec::new());
+        let assigned = runs
+            .into_iter()
+            .map(|(layer, bounds)| AssignedGridPoints {
+                net: None,
+                layer,
+                bounds,
+                only_if_available: true,
+            })
+            .collect();
+        let parent = InstanceAbstract::merge(
+            vec![inst],
+            1,
+            None,
+            Vec::new(),
+            assigned,
+            AssignmentConflicts::Error,
+            &HashMap::new(),
+            &HashMap::new(),
+        )
+        .unwrap();
+        let state = parent.routing_state();
+
+        let blocked: Vec<_> = (0..=1)
+            .flat_map(|layer| {
+                let states = state.layer(layer);
+                let (nx, ny) = states.size();
+                (0..nx)
+                    .flat_map(move |x| (0..ny).map(move |y| (x, y)))
+                    .filter(|&(x, y)| states[(x, y)] == PointState::Blocked { has_via: false })
+                    .map(move |(x, y)| (layer, x, y))
+                    .collect::<Vec<_>>()
+            })
+            .collect();
+        assert_eq!(blocked, vec![(0, 5, 4), (1, 3, 3), (1, 4, 3), (1, 4, 4)]);
+    }
+}
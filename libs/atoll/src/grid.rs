@@ -1,16 +1,19 @@
-//! Uniform routing grids and layer stacks.
+//! Routing grids and layer stacks.
 use crate::{NetId, PointState, RoutingDir};
 use grid::Grid;
 use num::integer::{div_ceil, div_floor};
 use serde::{Deserialize, Serialize};
 use std::any::Any;
 use std::cmp::Ordering;
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
+use std::fmt::{Display, Formatter};
 
-use crate::abs::GridCoord;
+use crate::abs::{GridCoord, TrackCoord};
 use crate::route::RoutingNode;
+use crate::straps::StrapSegment;
 use std::ops::{Index, IndexMut, Range};
 use substrate::context::{ContextBuilder, Installation};
+use substrate::geometry::contains::Contains;
 use substrate::geometry::corner::Corner;
 use substrate::geometry::dims::Dims;
 use substrate::geometry::dir::Dir;
@@ -32,6 +35,164 @@ pub enum TrackOffset {
     HalfPitch,
 }
 
+/// An explicit list of track centers that repeats with a fixed period.
+///
+/// Used for layers whose tracks are not uniformly spaced,
+/// such as layers with wide power tracks interspersed among signal tracks.
+///
+/// Track `i` is centered at `centers[i mod n] + floor(i / n) * period`,
+/// where `n` is the number of centers.
+#[derive(Debug, Clone, Eq, PartialEq, Hash, Serialize, Deserialize)]
+pub struct ExplicitTracks {
+    centers: Vec<i64>,
+    period: i64,
+}
+
+impl ExplicitTracks {
+    /// Creates a new set of explicit tracks.
+    ///
+    /// `centers` are the track centers within a single period,
+    /// relative to the start of the period.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `centers` is empty, is not strictly increasing,
+    /// or contains coordinates outside of `0..period`.
+    pub fn new(centers: impl Into<Vec<i64>>, period: i64) -> Self {
+        let centers = centers.into();
+        assert!(!centers.is_empty(), "explicit track list must not be empty");
+        assert!(
+            centers.windows(2).all(|w| w[0] < w[1]),
+            "explicit track centers must be strictly increasing"
+        );
+        assert!(
+            centers[0] >= 0 && centers[centers.len() - 1] < period,
+            "explicit track centers must lie within a single period"
+        );
+        Self { centers, period }
+    }
+
+    /// The track centers within a single period.
+    pub fn centers(&self) -> &[i64] {
+        &self.centers
+    }
+
+    /// The length of one repetition of the track pattern.
+    pub fn period(&self) -> i64 {
+        self.period
+    }
+
+    /// The number of tracks in one period.
+    pub fn len(&self) -> usize {
+        self.centers.len()
+    }
+
+    /// Whether or not the track list is empty.
+    ///
+    /// Always returns `false`, since [`ExplicitTracks::new`] rejects empty track lists.
+    pub fn is_empty(&self) -> bool {
+        self.centers.is_empty()
+    }
+
+    /// The center of track `idx`, relative to the start of period 0.
+    pub fn center(&self, idx: i64) -> i64 {
+        let n = self.centers.len() as i64;
+        self.centers[idx.rem_euclid(n) as usize] + idx.div_euclid(n) * self.period
+    }
+
+    /// The index of the last track centered at or below `coord`.
+    fn last_at_or_below(&self, coord: i64) -> i64 {
+        let n = self.centers.len() as i64;
+        let period = div_floor(coord, self.period);
+        let local = coord - period * self.period;
+        period * n + self.centers.partition_point(|&c| c <= local) as i64 - 1
+    }
+}
+
+/// The set of tracks on a single ATOLL layer.
+#[derive(Debug, Clone, Eq, PartialEq, Hash, Serialize, Deserialize)]
+pub enum LayerTracks {
+    /// Uniformly spaced tracks.
+    Uniform(UniformTracks),
+    /// Explicitly listed, periodically repeating tracks.
+    Explicit {
+        /// The width of each track.
+        line: i64,
+        /// The track centers.
+        tracks: ExplicitTracks,
+        /// An offset that translates all tracks.
+        offset: i64,
+    },
+}
+
+impl LayerTracks {
+    /// Creates the set of tracks for the given layer, translated by `offset`.
+    pub fn new(layer: &impl AtollLayer, offset: i64) -> Self {
+        match layer.explicit_tracks() {
+            Some(tracks) => Self::Explicit {
+                line: layer.line(),
+                tracks: tracks.clone(),
+                offset,
+            },
+            None => Self::Uniform(UniformTracks::with_offset(
+                layer.line(),
+                layer.space(),
+                offset,
+            )),
+        }
+    }
+
+    /// Gets the coordinates of the `i`-th track.
+    pub fn get(&self, idx: i64) -> Span {
+        match self {
+            Self::Uniform(tracks) => tracks.get(idx),
+            Self::Explicit {
+                line,
+                tracks,
+                offset,
+            } => Span::from_center_span(offset + tracks.center(idx), *line),
+        }
+    }
+
+    /// Converts a geometric coordinate to the index of the nearest track.
+    ///
+    /// Rounding behaves as in [`UniformTracks::to_track_idx`].
+    pub fn to_track_idx(&self, coord: i64, mode: RoundingMode) -> i64 {
+        match self {
+            Self::Uniform(tracks) => tracks.to_track_idx(coord, mode),
+            Self::Explicit {
+                line,
+                tracks,
+                offset,
+            } => {
+                let coord = coord - offset;
+                match mode {
+                    RoundingMode::Down => tracks.last_at_or_below(coord + line / 2),
+                    RoundingMode::Up => tracks.last_at_or_below(coord - line / 2 - 1) + 1,
+                    RoundingMode::Nearest => {
+                        let lo = tracks.last_at_or_below(coord);
+                        if tracks.center(lo + 1) - coord <= coord - tracks.center(lo) {
+                            lo + 1
+                        } else {
+                            lo
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+impl Tracks for LayerTracks {
+    fn try_track(&self, idx: i64) -> Option<Span> {
+        Some(self.get(idx))
+    }
+
+    fn try_range(&self) -> (Option<i64>, Option<i64>) {
+        (None, None)
+    }
+}
+
 /// An ATOLL-compatible routing layer.
 pub trait AtollLayer {
     /// The preferred routing direction.
@@ -54,6 +215,12 @@ pub trait AtollLayer {
     fn strap_via_spacing(&self) -> usize {
         1
     }
+    /// Explicit track centers for layers with nonuniform track pitch.
+    ///
+    /// If [`None`], tracks are spaced uniformly by [`AtollLayer::pitch`].
+    fn explicit_tracks(&self) -> Option<&ExplicitTracks> {
+        None
+    }
 
     /// The line + space of this layer.
     ///
@@ -68,6 +235,24 @@ pub trait AtollLayer {
             TrackOffset::HalfPitch => self.pitch() / 2,
         }
     }
+    /// The length after which the track pattern of this layer repeats.
+    ///
+    /// Equal to [`AtollLayer::pitch`] for uniformly spaced tracks.
+    fn period(&self) -> i64 {
+        self.explicit_tracks()
+            .map(|tracks| tracks.period())
+            .unwrap_or_else(|| self.pitch())
+    }
+    /// The number of tracks in a length of `len` physical units.
+    ///
+    /// `len` should be a multiple of [`AtollLayer::period`].
+    fn num_tracks(&self, len: i64) -> i64 {
+        let per_period = self
+            .explicit_tracks()
+            .map(|tracks| tracks.len() as i64)
+            .unwrap_or(1);
+        len * per_period / self.period()
+    }
 }
 
 /// An abstract layer with no relation to a physical layer in any process.
@@ -89,6 +274,11 @@ pub struct AbstractLayer {
     pub via_spacing: usize,
     /// The minimum spacing between adjacent vias on the same power strap.
     pub strap_via_spacing: usize,
+    /// Explicit track centers, for layers without a uniform track pitch.
+    ///
+    /// If present, `line` is still used as the track width,
+    /// while `space` is the minimum space between adjacent tracks.
+    pub explicit_tracks: Option<ExplicitTracks>,
 }
 
 /// An ATOLL-layer associated with a layer provided by a PDK.
@@ -108,8 +298,8 @@ impl AsRef<LayerId> for PdkLayer {
 
 impl AbstractLayer {
     /// The (infinite) set of tracks on this layer.
-    pub fn tracks(&self) -> UniformTracks {
-        UniformTracks::with_offset(self.line, self.space, self.physical_offset())
+    pub fn tracks(&self) -> LayerTracks {
+        LayerTracks::new(self, self.physical_offset())
     }
 }
 
@@ -164,6 +354,10 @@ impl AtollLayer for AbstractLayer {
     fn strap_via_spacing(&self) -> usize {
         self.strap_via_spacing
     }
+
+    fn explicit_tracks(&self) -> Option<&ExplicitTracks> {
+        self.explicit_tracks.as_ref()
+    }
 }
 
 impl AtollLayer for PdkLayer {
@@ -194,6 +388,10 @@ impl AtollLayer for PdkLayer {
     fn strap_via_spacing(&self) -> usize {
         self.inner.strap_via_spacing()
     }
+
+    fn explicit_tracks(&self) -> Option<&ExplicitTracks> {
+        self.inner.explicit_tracks()
+    }
 }
 
 impl<L> LayerStack<L> {
@@ -233,13 +431,50 @@ impl<L> LayerStack<L> {
 
 impl<L: AtollLayer> LayerStack<L> {
     /// The set of tracks on the given layer index.
-    pub fn tracks(&self, layer: usize) -> UniformTracks {
+    pub fn tracks(&self, layer: usize) -> LayerTracks {
+        LayerTracks::new(&self.layers[layer], self.track_offset(layer))
+    }
+
+    /// Checks that the tracks on the given layer may be shifted by `offset` physical units.
+    pub(crate) fn check_offset(&self, layer: usize, offset: i64) -> Result<(), InvalidGridOffset> {
+        let l = &self.layers[layer];
+        // Comparing against twice the offset avoids dividing by a half pitch of zero.
+        let (pitch, aligned) = match l.explicit_tracks() {
+            Some(tracks) => (tracks.period(), offset % tracks.period() == 0),
+            None => (l.pitch(), (2 * offset) % l.pitch() == 0),
+        };
+        if !aligned {
+            return Err(InvalidGridOffset {
+                layer,
+                offset,
+                pitch,
+            });
+        }
+        Ok(())
+    }
+
+    /// The physical coordinate of the center of track 0 on the given layer.
+    fn track_offset(&self, layer: usize) -> i64 {
         let layer = &self.layers[layer];
         let ofs = match layer.dir().track_dir() {
             Dir::Vert => self.offset_x,
             Dir::Horiz => self.offset_y,
         };
-        UniformTracks::with_offset(layer.line(), layer.space(), layer.physical_offset() + ofs)
+        layer.physical_offset() + ofs
+    }
+
+    /// The minimum legal `(width, height)`, in physical units, of a tile whose topmost
+    /// layer is `top_layer`.
+    ///
+    /// Tile dimensions must be multiples of these values, which are the LCMs of the
+    /// periods of the layers up to and including `top_layer`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `top_layer` is out of bounds.
+    pub fn min_tile_dims(&self, top_layer: usize) -> (i64, i64) {
+        let slice = self.slice(0..top_layer + 1);
+        (slice.lcm_unit_width(), slice.lcm_unit_height())
     }
 
     /// Returns whether or not the layer stack is valid.
@@ -280,7 +515,7 @@ impl<'a, L: AtollLayer> LayerSlice<'a, L> {
         (self.start..self.end)
             .map(|l| self.layer(l))
             .filter(|&l| l.dir().track_dir() == !dir)
-            .map(|l| l.pitch())
+            .map(|l| l.period())
             .fold(1, num::integer::lcm)
     }
 
@@ -305,7 +540,7 @@ impl<'a, L: AtollLayer> LayerSlice<'a, L> {
     }
 
     /// The set of tracks on the given layer.
-    pub fn tracks(&self, layer: usize) -> UniformTracks {
+    pub fn tracks(&self, layer: usize) -> LayerTracks {
         assert!(
             self.range().contains(&layer),
             "layer {layer} out of bounds for layer slice"
@@ -373,8 +608,37 @@ pub struct RoutingGrid<L> {
     start: usize,
     /// The end layer (not inclusive)
     end: usize,
+    /// Per-layer track offsets, in physical units, applied on top of the layer stack's offsets.
+    offsets: BTreeMap<usize, i64>,
 }
 
+/// The error returned when a grid offset would misalign a layer's tracks.
+///
+/// Offsets must be a multiple of half the layer's pitch, so that every track
+/// center still lies on a track or space center of the original grid.
+/// Layers with explicit tracks may only be offset by whole periods.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub struct InvalidGridOffset {
+    /// The layer index.
+    pub layer: usize,
+    /// The requested offset.
+    pub offset: i64,
+    /// The pitch of the layer, or the track period for layers with explicit tracks.
+    pub pitch: i64,
+}
+
+impl Display for InvalidGridOffset {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "grid offset {} on layer {} does not align with the layer's tracks (pitch {})",
+            self.offset, self.layer, self.pitch
+        )
+    }
+}
+
+impl std::error::Error for InvalidGridOffset {}
+
 impl<L> RoutingGrid<L> {
     /// Creates a new routing grid with the given properties.
     pub fn new(stack: LayerStack<L>, layers: Range<usize>) -> Self {
@@ -382,6 +646,7 @@ impl<L> RoutingGrid<L> {
             stack,
             start: layers.start,
             end: layers.end,
+            offsets: BTreeMap::new(),
         }
     }
 
@@ -408,17 +673,36 @@ impl<L: AtollLayer> RoutingGrid<L> {
         }
     }
 
+    /// Shifts the tracks on the given layer by `offset` physical units.
+    ///
+    /// Returns an error if `offset` is not a multiple of half the layer's pitch,
+    /// or of the track period for layers with explicit tracks.
+    pub fn set_offset(&mut self, layer: usize, offset: i64) -> Result<(), InvalidGridOffset> {
+        self.stack.check_offset(layer, offset)?;
+        self.offsets.insert(layer, offset);
+        Ok(())
+    }
+
+    /// The offset applied to the tracks on the given layer.
+    pub fn offset(&self, layer: usize) -> i64 {
+        self.offsets.get(&layer).copied().unwrap_or_default()
+    }
+
     /// Calculates the span of a particular track on the given layer.
     pub fn track_span(&self, layer: usize, track: i64) -> Span {
-        let slice = self.stack.slice(self.start..self.end);
-        let tracks = slice.tracks(layer);
-
-        tracks.track(track)
+        assert!(
+            self.layers().contains(&layer),
+            "layer {layer} out of bounds for routing grid"
+        );
+        self.tracks(layer).track(track)
     }
 
     /// The tracks on the given layer.
-    pub fn tracks(&self, layer: usize) -> UniformTracks {
-        self.stack.tracks(layer)
+    pub fn tracks(&self, layer: usize) -> LayerTracks {
+        LayerTracks::new(
+            self.stack.layer(layer),
+            self.stack.track_offset(layer) + self.offset(layer),
+        )
     }
 
     /// Returns the track grid for the given layer.
@@ -426,9 +710,9 @@ impl<L: AtollLayer> RoutingGrid<L> {
     /// Returns a tuple containing the vertical going tracks followed by the horizontal going tracks.
     /// In other words, the first element of the tuple is indexed by an x-coordinate,
     /// and the second element of the tuple is indexed by a y-coordinate.
-    pub fn track_grid(&self, layer: usize) -> (UniformTracks, UniformTracks) {
+    pub fn track_grid(&self, layer: usize) -> (LayerTracks, LayerTracks) {
         let tracks = self.tracks(layer);
-        let adj_tracks = self.stack.tracks(self.grid_defining_layer(layer));
+        let adj_tracks = self.tracks(self.grid_defining_layer(layer));
 
         match self.stack.layer(layer).dir().track_dir() {
             Dir::Horiz => (adj_tracks, tracks),
@@ -444,7 +728,7 @@ impl<L: AtollLayer> RoutingGrid<L> {
 
         // note that the grid defining layer may be outside the slice,
         // e.g. if the slice contains layers 2 through 5, the grid defining layer of 2 is 1.
-        let adj_tracks = self.stack.tracks(self.grid_defining_layer(layer));
+        let adj_tracks = self.tracks(self.grid_defining_layer(layer));
 
         // This allows `start` to be larger than `end`.
         let (start, end) = sorted2(start, end);
@@ -464,8 +748,8 @@ impl<L: AtollLayer> RoutingGrid<L> {
     /// Returns the physical coordinates of the grid point defined by the given `track` and `cross_track`
     /// on layer `layer`, where `track` is the track on `layer` and `cross_track` is a perpendicular track.
     pub fn track_point(&self, layer: usize, track: i64, cross_track: i64) -> Point {
-        let tracks = self.stack.tracks(layer);
-        let cross_tracks = self.stack.tracks(self.grid_defining_layer(layer));
+        let tracks = self.tracks(layer);
+        let cross_tracks = self.tracks(self.grid_defining_layer(layer));
 
         let track = tracks.track(track).center();
         let cross = cross_tracks.track(cross_track).center();
@@ -473,11 +757,38 @@ impl<L: AtollLayer> RoutingGrid<L> {
         Point::from_dir_coords(self.stack.layer(layer).dir().track_dir(), cross, track)
     }
 
+    /// Returns the rectangle drawn for a segment between two points on the same layer.
+    ///
+    /// The segment is `width` times as wide as the layer's line width.
+    pub(crate) fn segment_rect(&self, a: TrackCoord, b: TrackCoord, width: usize) -> Rect {
+        // todo: handle multiple routing directions
+        assert!(a.layer == b.layer && (a.x == b.x || a.y == b.y));
+        let layer = self.stack.layer(a.layer);
+        let (start_track, start_cross_track, end_track, end_cross_track) =
+            if layer.dir().track_dir() == Dir::Vert {
+                (a.x, a.y, b.x, b.y)
+            } else {
+                (a.y, a.x, b.y, b.x)
+            };
+        let start = self.track_point(a.layer, start_track, start_cross_track);
+        let end = self.track_point(b.layer, end_track, end_cross_track);
+        Rect::from_point(start)
+            .union(Rect::from_point(end))
+            .expand_dir(
+                if a.x == b.x { Dir::Horiz } else { Dir::Vert },
+                width as i64 * layer.line() / 2,
+            )
+            .expand_dir(
+                if a.y == b.y { Dir::Horiz } else { Dir::Vert },
+                layer.endcap(),
+            )
+    }
+
     /// Returns the physical coordinates of the grid point defined by the given `x_track` and `y_track`
     /// on layer `layer`.
     pub fn xy_track_point(&self, layer: usize, x_track: i64, y_track: i64) -> Point {
-        let tracks = self.stack.tracks(layer);
-        let gdl_tracks = self.stack.tracks(self.grid_defining_layer(layer));
+        let tracks = self.tracks(layer);
+        let gdl_tracks = self.tracks(self.grid_defining_layer(layer));
 
         match self.stack.layer(layer).dir().track_dir() {
             Dir::Horiz => Point::new(
@@ -500,8 +811,8 @@ impl<L: AtollLayer> RoutingGrid<L> {
         round_y: RoundingMode,
     ) -> Point {
         let gdl = self.grid_defining_layer(layer);
-        let trk = self.stack.tracks(layer);
-        let gdtrk = self.stack.tracks(gdl);
+        let trk = self.tracks(layer);
+        let gdtrk = self.tracks(gdl);
         match self.stack.layer(layer).dir().track_dir() {
             Dir::Vert => Point::new(
                 trk.to_track_idx(p.x, round_x),
@@ -553,28 +864,38 @@ impl<L: AtollLayer> RoutingGrid<L> {
         Rect::new(ll, ur)
     }
 
-    /// The number of PDK units in one horizontal grid coordinate on this layer.
-    pub(crate) fn xpitch(&self, layer: usize) -> i64 {
+    /// The number of horizontal grid coordinates in `len` PDK units on this layer.
+    ///
+    /// `len` should be a multiple of the horizontal LCM unit.
+    pub(crate) fn xtracks(&self, layer: usize, len: i64) -> i64 {
         match self.stack.layer(layer).dir().track_dir() {
-            Dir::Vert => self.stack.layer(layer).pitch(),
-            Dir::Horiz => self.stack.layer(self.grid_defining_layer(layer)).pitch(),
+            Dir::Vert => self.stack.layer(layer).num_tracks(len),
+            Dir::Horiz => self
+                .stack
+                .layer(self.grid_defining_layer(layer))
+                .num_tracks(len),
         }
     }
 
-    /// The number of PDK units in one vertical grid coordinate on this layer.
-    pub(crate) fn ypitch(&self, layer: usize) -> i64 {
+    /// The number of vertical grid coordinates in `len` PDK units on this layer.
+    ///
+    /// `len` should be a multiple of the vertical LCM unit.
+    pub(crate) fn ytracks(&self, layer: usize, len: i64) -> i64 {
         match self.stack.layer(layer).dir().track_dir() {
-            Dir::Horiz => self.stack.layer(layer).pitch(),
-            Dir::Vert => self.stack.layer(self.grid_defining_layer(layer)).pitch(),
+            Dir::Horiz => self.stack.layer(layer).num_tracks(len),
+            Dir::Vert => self
+                .stack
+                .layer(self.grid_defining_layer(layer))
+                .num_tracks(len),
         }
     }
 
-    /// The number of PDK units in one grid coordinate in the given direction on this layer.
+    /// The number of grid coordinates in `len` PDK units in the given direction on this layer.
     #[allow(dead_code)]
-    pub(crate) fn dir_pitch(&self, layer: usize, dir: Dir) -> i64 {
+    pub(crate) fn dir_tracks(&self, layer: usize, dir: Dir, len: i64) -> i64 {
         match dir {
-            Dir::Horiz => self.xpitch(layer),
-            Dir::Vert => self.ypitch(layer),
+            Dir::Horiz => self.xtracks(layer, len),
+            Dir::Vert => self.ytracks(layer, len),
         }
     }
 }
@@ -608,13 +929,13 @@ impl<L> DebugRoutingGrid<L> {
         let slice = self.grid.stack.slice(self.grid.start..self.grid.end);
         let layer = slice.layer(layer);
         let lcm = slice.lcm_unit(!layer.dir().track_dir());
-        let pitch = layer.pitch();
-        let (units, rem) = (lcm / pitch, lcm % pitch);
+        let period = layer.period();
         assert_eq!(
-            rem, 0,
-            "expected lcm ({lcm}) to be an integer multiple of the layer pitch ({pitch})"
+            lcm % period,
+            0,
+            "expected lcm ({lcm}) to be an integer multiple of the layer period ({period})"
         );
-        units * self.ndir(!layer.dir().track_dir())
+        layer.num_tracks(lcm) * self.ndir(!layer.dir().track_dir())
     }
 }
 
@@ -642,12 +963,74 @@ fn sorted2<T: PartialOrd>(a: T, b: T) -> (T, T) {
     }
 }
 
+/// Returns `true` if the given point lies inside or on the boundary of the polygon.
+fn polygon_contains(poly: &[Point], p: Point) -> bool {
+    let mut inside = false;
+    for (i, &a) in poly.iter().enumerate() {
+        let b = poly[(i + 1) % poly.len()];
+        let (dx, dy) = (b.x - a.x, b.y - a.y);
+        let on_line = dx * (p.y - a.y) == dy * (p.x - a.x);
+        let (xmin, xmax) = sorted2(a.x, b.x);
+        let (ymin, ymax) = sorted2(a.y, b.y);
+        if on_line && (xmin..=xmax).contains(&p.x) && (ymin..=ymax).contains(&p.y) {
+            return true;
+        }
+        if (a.y > p.y) != (b.y > p.y) {
+            let lhs = (p.x - a.x) * dy;
+            let rhs = (p.y - a.y) * dx;
+            if (dy > 0 && lhs < rhs) || (dy < 0 && lhs > rhs) {
+                inside = !inside;
+            }
+        }
+    }
+    inside
+}
+
+/// Rasterizes a polygon given in grid coordinates.
+///
+/// Returns one zero-height rectangle for each horizontal run of grid points
+/// that lie inside or on the boundary of the polygon.
+pub(crate) fn rasterize_polygon(poly: &[Point]) -> Vec<Rect> {
+    let mut runs = Vec::new();
+    if poly.is_empty() {
+        return runs;
+    }
+    let xmin = poly.iter().map(|p| p.x).min().unwrap();
+    let xmax = poly.iter().map(|p| p.x).max().unwrap();
+    let ymin = poly.iter().map(|p| p.y).min().unwrap();
+    let ymax = poly.iter().map(|p| p.y).max().unwrap();
+    for y in ymin..=ymax {
+        let mut start = None;
+        for x in xmin..=xmax + 1 {
+            let covered = x <= xmax && polygon_contains(poly, Point::new(x, y));
+            match (covered, start) {
+                (true, None) => start = Some(x),
+                (false, Some(x0)) => {
+                    runs.push(Rect::from_sides(x0, y, x - 1, y));
+                    start = None;
+                }
+                _ => {}
+            }
+        }
+    }
+    runs
+}
+
 /// A fixed-size routing grid.
 #[derive(Clone, Debug)]
 pub struct RoutingState<L> {
     pub(crate) grid: RoutingGrid<L>,
     pub(crate) layers: Vec<Grid<PointState>>,
     pub(crate) roots: HashMap<NetId, NetId>,
+    pub(crate) straps: Vec<StrapSegment>,
+    /// Width multipliers of nets, keyed by root net.
+    pub(crate) net_widths: HashMap<NetId, usize>,
+    /// Buses whose bits should be routed on adjacent tracks, as root nets in bit order
+    /// along with the layer on which the bits should run in parallel.
+    pub(crate) buses: Vec<(Vec<NetId>, usize)>,
+    /// Regions that a root net may not be routed through, relative to the lower-left
+    /// corner of the grid.
+    pub(crate) keep_clear: Vec<(NetId, Rect)>,
 }
 
 impl<L> Index<GridCoord> for RoutingState<L> {
@@ -678,6 +1061,11 @@ impl<L> RoutingState<L> {
     pub fn layer_mut(&mut self, layer: usize) -> &mut Grid<PointState> {
         &mut self.layers[layer]
     }
+
+    /// Returns the straps drawn so far, including those inherited from child tiles.
+    pub fn straps(&self) -> &[StrapSegment] {
+        &self.straps
+    }
 }
 
 pub(crate) struct InterlayerTransition {
@@ -697,10 +1085,10 @@ impl<L: AtollLayer + Clone> RoutingState<L> {
         for i in 0..=top {
             let layer = stack.layer(i);
             let dim = slice.lcm_unit(!layer.dir().track_dir());
-            let num_tracks = dim / layer.pitch();
+            let num_tracks = layer.num_tracks(dim);
             let perp_layer = stack.layer(grid.grid_defining_layer(i));
             let perp_dim = slice.lcm_unit(!perp_layer.dir().track_dir());
-            let perp_tracks = perp_dim / perp_layer.pitch();
+            let perp_tracks = perp_layer.num_tracks(perp_dim);
             let grid = if layer.dir().track_dir() == Dir::Vert {
                 Grid::init(
                     (nx * num_tracks) as usize,
@@ -720,7 +1108,106 @@ impl<L: AtollLayer + Clone> RoutingState<L> {
             grid,
             layers,
             roots: HashMap::new(),
+            straps: Vec::new(),
+            net_widths: HashMap::new(),
+            buses: Vec::new(),
+            keep_clear: Vec::new(),
+        }
+    }
+
+    /// Returns the index of the bus containing the given net and the net's bit index within it.
+    pub(crate) fn bus_bit(&self, net: NetId) -> Option<(usize, usize)> {
+        let root = self.roots.get(&net)?;
+        self.buses.iter().enumerate().find_map(|(bus, (nets, _))| {
+            nets.iter()
+                .position(|bit| self.roots.get(bit) == Some(root))
+                .map(|bit| (bus, bit))
+        })
+    }
+
+    /// Returns the width multiplier of the given net.
+    pub(crate) fn net_width(&self, net: NetId) -> usize {
+        self.roots
+            .get(&net)
+            .and_then(|root| self.net_widths.get(root))
+            .copied()
+            .unwrap_or(1)
+    }
+
+    /// Reserves the tracks adjacent to a segment drawn `width` times wider than
+    /// the layer's line width, so that no other net is routed close enough to short to it.
+    pub(crate) fn reserve_for_width(
+        &mut self,
+        src: GridCoord,
+        dst: GridCoord,
+        net: NetId,
+        width: usize,
+    ) {
+        if width <= 1 || src.layer != dst.layer {
+            return;
         }
+        for x in std::cmp::min(src.x, dst.x)..=std::cmp::max(src.x, dst.x) {
+            for y in std::cmp::min(src.y, dst.y)..=std::cmp::max(src.y, dst.y) {
+                let coord = GridCoord {
+                    layer: src.layer,
+                    x,
+                    y,
+                };
+                for neighbor in self.width_neighbors(coord, width) {
+                    if let PointState::Available = self[neighbor] {
+                        self[neighbor] = PointState::Reserved { net };
+                    }
+                }
+            }
+        }
+    }
+
+    /// Returns the in-bounds points on the tracks adjacent to `coord` that a segment
+    /// drawn `width` times wider than the layer's line width would cover.
+    fn width_neighbors(&self, coord: GridCoord, width: usize) -> Vec<GridCoord> {
+        if width <= 1 {
+            return Vec::new();
+        }
+        let layer = self.grid.stack.layer(coord.layer);
+        let extra = (width as i64 - 1) * layer.line();
+        let num_tracks = div_ceil(extra, 2 * layer.pitch()) as usize;
+        let vert = layer.dir().track_dir() == Dir::Vert;
+        let (nx, ny) = self.layer(coord.layer).size();
+        let GridCoord { x, y, .. } = coord;
+
+        let mut out = Vec::new();
+        for k in 1..=num_tracks {
+            let neighbors = if vert {
+                [x.checked_sub(k).map(|x| (x, y)), Some((x + k, y))]
+            } else {
+                [y.checked_sub(k).map(|y| (x, y)), Some((x, y + k))]
+            };
+            for (x, y) in neighbors.into_iter().flatten() {
+                if x < nx && y < ny {
+                    out.push(GridCoord {
+                        layer: coord.layer,
+                        x,
+                        y,
+                    });
+                }
+            }
+        }
+        out
+    }
+
+    /// Returns `true` if the tracks adjacent to `coord` are clear enough for
+    /// `net` to be drawn at its configured width.
+    pub(crate) fn has_clearance_for_net(&self, coord: GridCoord, net: NetId) -> bool {
+        self.width_neighbors(coord, self.net_width(net))
+            .into_iter()
+            .all(|neighbor| match self[neighbor] {
+                PointState::Routed { net: grid_net, .. }
+                | PointState::Reserved { net: grid_net } => {
+                    self.roots[&grid_net] == self.roots[&net]
+                }
+                PointState::Available => !self.is_kept_clear(neighbor, net),
+                PointState::Blocked { .. } => false,
+            })
     }
 
     /// Finds a single grid coordinate belonging to the given net.
@@ -786,11 +1273,23 @@ impl<L: AtollLayer + Clone> RoutingState<L> {
         }
     }
 
+    /// Returns `true` if `coord` lies in a region that `net` must keep clear of.
+    pub(crate) fn is_kept_clear(&self, coord: GridCoord, net: NetId) -> bool {
+        if self.keep_clear.is_empty() {
+            return false;
+        }
+        let root = self.roots[&net];
+        let point = self.grid_to_rel_physical(coord);
+        self.keep_clear
+            .iter()
+            .any(|(region_net, rect)| *region_net == root && rect.encloses(&point))
+    }
+
     #[inline]
     pub(crate) fn is_available_for_net(&self, coord: GridCoord, net: NetId) -> bool {
         match self[coord] {
             PointState::Routed { net: grid_net, .. } => self.roots[&grid_net] == self.roots[&net],
-            PointState::Available => true,
+            PointState::Available => !self.is_kept_clear(coord, net),
             PointState::Blocked { .. } => false,
             PointState::Reserved { .. } => false,
         }
@@ -809,14 +1308,13 @@ impl<L: AtollLayer + Clone> RoutingState<L> {
     pub(crate) fn is_available_or_reserved_for_net(&self, coord: GridCoord, net: NetId) -> bool {
         match self[coord] {
             PointState::Routed { .. } => false,
-            PointState::Available => true,
+            PointState::Available => !self.is_kept_clear(coord, net),
             PointState::Blocked { .. } => false,
             PointState::Reserved { net: grid_net } => self.roots[&net] == self.roots[&grid_net],
         }
     }
 
     #[inline]
-    #[allow(dead_code)]
     pub(crate) fn is_available(&self, coord: GridCoord) -> bool {
         match self[coord] {
             PointState::Routed { .. } => false,
@@ -880,7 +1378,7 @@ impl<L: AtollLayer + Clone> RoutingState<L> {
                 y: coord.y - jump,
                 ..coord
             };
-            if self.is_available_for_net(next, net) {
+            if self.is_available_for_net(next, net) && self.has_clearance_for_net(next, net) {
                 out.push((
                     RoutingNode {
                         coord: next,
@@ -895,7 +1393,7 @@ impl<L: AtollLayer + Clone> RoutingState<L> {
                 y: coord.y + jump,
                 ..coord
             };
-            if self.is_available_for_net(next, net) {
+            if self.is_available_for_net(next, net) && self.has_clearance_for_net(next, net) {
                 out.push((
                     RoutingNode {
                         coord: next,
@@ -919,7 +1417,7 @@ impl<L: AtollLayer + Clone> RoutingState<L> {
                 x: coord.x - jump,
                 ..coord
             };
-            if self.is_available_for_net(next, net) {
+            if self.is_available_for_net(next, net) && self.has_clearance_for_net(next, net) {
                 out.push((
                     RoutingNode {
                         coord: next,
@@ -934,7 +1432,7 @@ impl<L: AtollLayer + Clone> RoutingState<L> {
                 x: coord.x + jump,
                 ..coord
             };
-            if self.is_available_for_net(next, net) {
+            if self.is_available_for_net(next, net) && self.has_clearance_for_net(next, net) {
                 out.push((
                     RoutingNode {
                         coord: next,
@@ -1198,6 +1696,7 @@ mod tests {
                     endcap: 20,
                     via_spacing: 1,
                     strap_via_spacing: 1,
+                    explicit_tracks: None,
                 },
                 AbstractLayer {
                     dir: RoutingDir::Vert,
@@ -1207,6 +1706,7 @@ mod tests {
                     endcap: 20,
                     via_spacing: 1,
                     strap_via_spacing: 1,
+                    explicit_tracks: None,
                 },
                 AbstractLayer {
                     dir: RoutingDir::Horiz,
@@ -1216,6 +1716,7 @@ mod tests {
                     endcap: 40,
                     via_spacing: 1,
                     strap_via_spacing: 1,
+                    explicit_tracks: None,
                 },
                 AbstractLayer {
                     dir: RoutingDir::Vert,
@@ -1225,6 +1726,7 @@ mod tests {
                     endcap: 50,
                     via_spacing: 1,
                     strap_via_spacing: 1,
+                    explicit_tracks: None,
                 },
             ],
             offset_x: 0,
@@ -1240,4 +1742,247 @@ mod tests {
         assert_eq!(slice.lcm_unit(Dir::Vert), 600);
         assert!(layers.is_valid());
     }
+
+    #[test]
+    fn min_tile_dims() {
+        let layers = layer_stack();
+        // Vertical tracks (pitches 320 and 600) constrain widths,
+        // horizontal tracks (pitches 300 and 600) constrain heights.
+        assert_eq!(layers.min_tile_dims(1), (320, 300));
+        assert_eq!(layers.min_tile_dims(2), (320, 600));
+        assert_eq!(layers.min_tile_dims(3), (4_800, 600));
+    }
+
+    #[test]
+    fn grid_offset_shifts_tracks() {
+        let mut grid = RoutingGrid::new(layer_stack(), 0..4);
+        assert_eq!(grid.xy_track_point(1, 2, 3), Point::new(640, 900));
+        assert_eq!(grid.xy_track_point(2, 2, 1), Point::new(640, 600));
+
+        grid.set_offset(1, 160).unwrap();
+        assert_eq!(grid.offset(1), 160);
+        assert_eq!(grid.track_span(1, 2), Span::new(740, 860));
+        assert_eq!(grid.xy_track_point(1, 2, 3), Point::new(800, 900));
+        // Layer 1 defines the cross tracks of layer 2.
+        assert_eq!(grid.xy_track_point(2, 2, 1), Point::new(800, 600));
+        // Other layers are unaffected.
+        assert_eq!(grid.xy_track_point(3, 1, 1), Point::new(600, 600));
+
+        assert_eq!(
+            grid.set_offset(1, 100),
+            Err(InvalidGridOffset {
+                layer: 1,
+                offset: 100,
+                pitch: 320,
+            })
+        );
+        assert_eq!(grid.offset(1), 160);
+    }
+
+    #[test]
+    fn grid_offset_with_unit_pitch() {
+        let mut stack = layer_stack();
+        stack.layers[1].line = 1;
+        stack.layers[1].space = 0;
+        let mut grid = RoutingGrid::new(stack, 0..4);
+        grid.set_offset(1, 3).unwrap();
+        assert_eq!(grid.offset(1), 3);
+        assert_eq!(grid.set_offset(1, 0), Ok(()));
+    }
+
+    #[test]
+    fn explicit_track_points() {
+        let mut stack = layer_stack();
+        stack.layers[1].explicit_tracks = Some(ExplicitTracks::new([0, 320, 800], 1_280));
+        assert_eq!(stack.all().lcm_unit(Dir::Horiz), 19_200);
+
+        let mut grid = RoutingGrid::new(stack.clone(), 0..4);
+        assert_eq!(grid.track_span(1, 0), Span::new(-60, 60));
+        assert_eq!(grid.track_span(1, 2), Span::new(740, 860));
+        assert_eq!(grid.track_span(1, 3), Span::new(1_220, 1_340));
+        assert_eq!(grid.track_span(1, -1), Span::new(-540, -420));
+        assert_eq!(grid.xy_track_point(1, 2, 3), Point::new(800, 900));
+        // Layer 1 defines the cross tracks of layer 2.
+        assert_eq!(grid.xy_track_point(2, 4, 1), Point::new(1_600, 600));
+
+        let tracks = grid.tracks(1);
+        assert_eq!(tracks.to_track_idx(500, RoundingMode::Down), 1);
+        assert_eq!(tracks.to_track_idx(500, RoundingMode::Up), 2);
+        assert_eq!(tracks.to_track_idx(500, RoundingMode::Nearest), 1);
+        assert_eq!(tracks.to_track_idx(600, RoundingMode::Nearest), 2);
+        assert_eq!(tracks.to_track_idx(-100, RoundingMode::Down), -1);
+        assert_eq!(tracks.to_track_idx(-100, RoundingMode::Up), 0);
+        assert_eq!(
+            grid.point_to_grid(
+                Point::new(1_300, 880),
+                1,
+                RoundingMode::Nearest,
+                RoundingMode::Nearest
+            ),
+            Point::new(3, 3)
+        );
+
+        let state = RoutingState::new(stack, 3, 1, 1);
+        assert_eq!(state.layer(1).size(), (45, 2));
+
+        assert_eq!(
+            grid.set_offset(1, 640),
+            Err(InvalidGridOffset {
+                layer: 1,
+                offset: 640,
+                pitch: 1_280,
+            })
+        );
+        grid.set_offset(1, 1_280).unwrap();
+        assert_eq!(grid.track_span(1, 0), Span::new(1_220, 1_340));
+    }
+
+    #[test]
+    fn wide_segment_rect() {
+        let grid = RoutingGrid::new(layer_stack(), 0..4);
+        let a = TrackCoord {
+            layer: 1,
+            x: 2,
+            y: 1,
+        };
+        let b = TrackCoord { y: 3, ..a };
+
+        let rect = grid.segment_rect(a, b, 1);
+        assert_eq!(rect, Rect::from_sides(580, 280, 700, 920));
+
+        let rect = grid.segment_rect(a, b, 2);
+        assert_eq!(rect.width(), 240);
+        assert_eq!(rect, Rect::from_sides(520, 280, 760, 920));
+    }
+
+    #[test]
+    fn wide_net_reserves_adjacent_tracks() {
+        let net = NetId(0);
+        let mut state = RoutingState::new(layer_stack(), 3, 2, 4);
+        state.roots = HashMap::from([(net, net)]);
+        state.net_widths = HashMap::from([(net, 2)]);
+        assert_eq!(state.net_width(net), 2);
+
+        let blocked = GridCoord {
+            layer: 1,
+            x: 6,
+            y: 4,
+        };
+        state[blocked] = PointState::Blocked { has_via: false };
+        for y in 2..=6 {
+            state[GridCoord { layer: 1, x: 5, y }] = PointState::Routed {
+                net,
+                has_via: false,
+            };
+        }
+        state.reserve_for_width(
+            GridCoord {
+                layer: 1,
+                x: 5,
+                y: 2,
+            },
+            GridCoord {
+                layer: 1,
+                x: 5,
+                y: 6,
+            },
+            net,
+            state.net_width(net),
+        );
+
+        for y in 2..=6 {
+            assert_eq!(
+                state[GridCoord { layer: 1, x: 4, y }],
+                PointState::Reserved { net }
+            );
+            let right = GridCoord { layer: 1, x: 6, y };
+            if right != blocked {
+                assert_eq!(state[right], PointState::Reserved { net });
+            }
+            assert_eq!(
+                state[GridCoord { layer: 1, x: 3, y }],
+                PointState::Available
+            );
+        }
+        assert_eq!(state[blocked], PointState::Blocked { has_via: false });
+        assert_eq!(
+            state[GridCoord {
+                layer: 1,
+                x: 4,
+                y: 7
+            }],
+            PointState::Available
+        );
+    }
+
+    #[test]
+    fn wide_net_successors_require_clearance() {
+        let (net, other) = (NetId(0), NetId(1));
+        let mut state = RoutingState::new(layer_stack(), 3, 2, 4);
+        state.roots = HashMap::from([(net, net), (other, other)]);
+        state.net_widths = HashMap::from([(net, 2)]);
+
+        let start = GridCoord {
+            layer: 1,
+            x: 5,
+            y: 3,
+        };
+        let up = GridCoord { y: 4, ..start };
+        let down = GridCoord { y: 2, ..start };
+        state[GridCoord {
+            layer: 1,
+            x: 6,
+            y: 4,
+        }] = PointState::Routed {
+            net: other,
+            has_via: false,
+        };
+        state[GridCoord {
+            layer: 1,
+            x: 4,
+            y: 2,
+        }] = PointState::Reserved { net };
+
+        assert!(!state.has_clearance_for_net(up, net));
+        assert!(state.has_clearance_for_net(down, net));
+        assert!(state.has_clearance_for_net(up, other));
+
+        let node = RoutingNode {
+            coord: start,
+            has_via: false,
+        };
+        let mut out = Vec::new();
+        state.successors_vert(node, net, &mut out);
+        let next = out.iter().map(|(n, _)| n.coord).collect::<Vec<_>>();
+        assert_eq!(next, vec![down]);
+
+        out.clear();
+        state.successors_vert(node, other, &mut out);
+        assert_eq!(out.len(), 2);
+    }
+
+    #[test]
+    fn rasterize_l_shaped_polygon() {
+        let poly = [
+            Point::new(0, 0),
+            Point::new(4, 0),
+            Point::new(4, 2),
+            Point::new(2, 2),
+            Point::new(2, 4),
+            Point::new(0, 4),
+        ];
+        let runs = rasterize_polygon(&poly);
+        assert_eq!(
+            runs,
+            vec![
+                Rect::from_sides(0, 0, 4, 0),
+                Rect::from_sides(0, 1, 4, 1),
+                Rect::from_sides(0, 2, 4, 2),
+                Rect::from_sides(0, 3, 2, 3),
+                Rect::from_sides(0, 4, 2, 4),
+            ]
+        );
+        assert!(!polygon_contains(&poly, Point::new(3, 3)));
+        assert!(polygon_contains(&poly, Point::new(1, 3)));
+    }
 }
@@ -62,6 +62,9 @@
 //! Atoll can be configured to insert power straps on tracks
 //! available after routing.
 //!
+//! Straps on port nets are exposed in the tile's [`Abstract`](abs::Abstract),
+//! so parent tiles strapping the same net can via up to them.
+//!
 #![warn(missing_docs)]
 
 pub mod abs;
@@ -69,14 +72,15 @@ pub mod grid;
 pub mod route;
 pub mod straps;
 
-use crate::abs::{Abstract, InstanceAbstract, TrackCoord};
-use crate::grid::{AtollLayer, LayerStack, PdkLayer};
-use crate::route::{Path, Router, ViaMaker};
+use crate::abs::{Abstract, AssignmentConflicts, InstanceAbstract, TrackCoord};
+use crate::grid::{AtollLayer, InvalidGridOffset, LayerStack, PdkLayer, RoutingGrid, RoutingState};
+use crate::route::{Path, RouteResult, Router, ViaMaker};
 use ena::unify::UnifyKey;
 use serde::{Deserialize, Serialize};
 use std::any::Any;
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 use std::ops::Deref;
+use std::panic::Location;
 
 use cache::mem::TypeCache;
 use indexmap::{IndexMap, IndexSet};
@@ -91,8 +95,9 @@ use substrate::geometry::transform::{
 };
 use substrate::io::layout::Builder;
 use substrate::io::schematic::{Bundle, Connect, HardwareType, IsBundle, Node, TerminalView};
-use substrate::io::Flatten;
+use substrate::io::{Flatten, HasNameTree, NameBuf};
 use substrate::layout::element::Shape;
+use substrate::layout::tracks::RoundingMode;
 
 use crate::straps::{Strapper, StrappingParams};
 use substrate::geometry::align::AlignMode;
@@ -423,6 +428,26 @@ impl RawInstance {
     pub fn physical_bounds(&self) -> Rect {
         self.abs.physical_bounds().translate(self.physical_loc())
     }
+
+    /// Returns the area, device count, and net count of this instance.
+    pub fn metrics(&self) -> TileMetrics {
+        TileMetrics {
+            area: self.physical_bounds().area(),
+            device_count: self.abs.device_count,
+            net_count: self.abs.net_count,
+        }
+    }
+}
+
+/// Size and complexity metrics of a generated tile.
+#[derive(Debug, Copy, Clone, Default, Hash, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TileMetrics {
+    /// The area of the tile's physical bounds.
+    pub area: i64,
+    /// The number of primitive devices drawn in the tile, including those in nested tiles.
+    pub device_count: usize,
+    /// The number of distinct nets in the tile.
+    pub net_count: usize,
 }
 
 impl<T: ExportsNestedData + ExportsLayoutData> Instance<T> {
@@ -509,6 +534,11 @@ impl<T: ExportsNestedData + ExportsLayoutData> Instance<T> {
         self.raw.physical_bounds()
     }
 
+    /// Returns the area, device count, and net count of this instance.
+    pub fn metrics(&self) -> TileMetrics {
+        self.raw.metrics()
+    }
+
     /// Returns a reference to the underlying [`RawInstance`].
     pub fn raw(&self) -> &RawInstance {
         &self.raw
@@ -533,6 +563,16 @@ pub(crate) struct AssignedGridPoints {
     pub(crate) only_if_available: bool,
 }
 
+/// A standalone via added using [`TileBuilder::add_via`].
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct ViaPlacement {
+    pub(crate) net: NetId,
+    /// The via's location on the lower layer, in track coordinates.
+    pub(crate) lower: TrackCoord,
+    /// The via's location on the upper layer, in track coordinates.
+    pub(crate) upper: TrackCoord,
+}
+
 /// A builder for ATOLL tiles.
 pub struct TileBuilder<'a, PDK: Pdk + Schema + ?Sized> {
     nodes: IndexMap<Node, NodeInfo>,
@@ -553,9 +593,31 @@ pub struct TileBuilder<'a, PDK: Pdk + Schema + ?Sized> {
     router: Option<Arc<dyn Router>>,
     strapper: Option<Arc<dyn Strapper>>,
     via_maker: Option<Arc<dyn ViaMaker<PDK>>>,
+    via_makers: HashMap<usize, Arc<dyn ViaMaker<PDK>>>,
     straps: Vec<(NetId, StrappingParams)>,
+    grid_offsets: BTreeMap<usize, i64>,
+    net_widths: HashMap<NetId, usize>,
+    buses: Vec<(Vec<NetId>, usize)>,
+    keep_clear: Vec<(NetId, Rect)>,
+    vias: Vec<ViaPlacement>,
+    pre_route_hooks: Vec<PreRouteHook>,
+    abutment_nodes: Vec<NodeKey>,
+    assignment_conflicts: AssignmentConflicts,
+    connectivity_assertions: Vec<ConnectivityAssertion>,
+    net_names: BTreeMap<NetId, ArcStr>,
 }
 
+/// A pair of nodes that must be connected, added using [`TileBuilder::assert_connected`].
+#[derive(Debug, Clone, Copy)]
+struct ConnectivityAssertion {
+    a: NodeKey,
+    b: NodeKey,
+    location: &'static Location<'static>,
+}
+
+/// A function that modifies the routing state before routing.
+type PreRouteHook = Arc<dyn Fn(&mut RoutingState<PdkLayer>) + Send + Sync>;
+
 /// Fields required for building an abstract.
 struct TileAbstractBuilder {
     nodes: IndexMap<Node, NodeInfo>,
@@ -568,9 +630,19 @@ struct TileAbstractBuilder {
     router: Option<Arc<dyn Router>>,
     strapper: Option<Arc<dyn Strapper>>,
     straps: Vec<(NetId, StrappingParams)>,
+    grid_offsets: BTreeMap<usize, i64>,
     layers_to_block: IndexSet<usize>,
     layer_bbox: Option<Rect>,
     port_ids: Vec<NetId>,
+    net_widths: HashMap<NetId, usize>,
+    buses: Vec<(Vec<NetId>, usize)>,
+    keep_clear: Vec<(NetId, Rect)>,
+    vias: Vec<ViaPlacement>,
+    pre_route_hooks: Vec<PreRouteHook>,
+    abutment_nodes: Vec<NodeKey>,
+    assignment_conflicts: AssignmentConflicts,
+    connectivity_assertions: Vec<ConnectivityAssertion>,
+    net_names: BTreeMap<NetId, ArcStr>,
 }
 
 /// Remaining fields of [`TileBuilder`] not contained in [`TileAbstractBuilder`].
@@ -585,6 +657,8 @@ struct TileBuilderUnused<'a, PDK: Pdk + Schema + ?Sized> {
     #[allow(dead_code)]
     next_net_id: usize,
     via_maker: Option<Arc<dyn ViaMaker<PDK>>>,
+    via_makers: HashMap<usize, Arc<dyn ViaMaker<PDK>>>,
+    vias: Vec<ViaPlacement>,
 }
 
 /// A drawn ATOLL instance.
@@ -595,8 +669,106 @@ pub struct DrawnInstance<T: ExportsNestedData + ExportsLayoutData> {
     pub layout: layout::Instance<T>,
 }
 
+/// A tile frozen into a black-box macro with a fixed abstract.
+///
+/// Created by [`freeze_tile`] and instantiated using [`TileBuilder::instantiate_frozen`],
+/// which does not re-run the tile's generator.
+#[derive(Debug, Clone)]
+pub struct FrozenTile {
+    abs: Abstract,
+    layout: Arc<layout::element::RawCell>,
+    cell: ArcStr,
+    ports: Vec<ArcStr>,
+}
+
+impl FrozenTile {
+    /// The abstract of the frozen tile.
+    pub fn abs(&self) -> &Abstract {
+        &self.abs
+    }
+
+    /// The layout geometry of the frozen tile.
+    pub fn layout(&self) -> &Arc<layout::element::RawCell> {
+        &self.layout
+    }
+
+    /// The name of the black-box schematic cell.
+    pub fn cell_name(&self) -> &ArcStr {
+        &self.cell
+    }
+
+    /// The names of the tile's ports, in the order of its flattened IO.
+    pub fn ports(&self) -> &[ArcStr] {
+        &self.ports
+    }
+}
+
+/// An instance of a [`FrozenTile`].
+pub struct FrozenInstance {
+    io: Vec<Node>,
+    ports: Vec<ArcStr>,
+    layout: Arc<layout::element::RawCell>,
+    raw: RawInstance,
+}
+
+impl FrozenInstance {
+    /// Translates this instance by the given XY-coordinates in LCM units.
+    pub fn translate_mut(&mut self, p: Point) {
+        self.raw.translate_mut(p);
+    }
+
+    /// Translates this instance by the given XY-coordinates in LCM units.
+    pub fn translate(mut self, p: Point) -> Self {
+        self.translate_mut(p);
+        self
+    }
+
+    /// Orients this instance in the given orientation.
+    pub fn orient_mut(&mut self, orientation: Orientation) {
+        self.raw.orient_mut(orientation);
+    }
+
+    /// Orients this instance in the given orientation.
+    pub fn orient(mut self, orientation: Orientation) -> Self {
+        self.orient_mut(orientation);
+        self
+    }
+
+    /// The ports of this instance, in the order given by [`FrozenTile::ports`].
+    pub fn io(&self) -> &[Node] {
+        &self.io
+    }
+
+    /// Returns the LCM bounds of this instance.
+    pub fn lcm_bounds(&self) -> Rect {
+        self.raw.lcm_bounds()
+    }
+
+    /// Returns the underlying raw instance.
+    pub fn raw(&self) -> &RawInstance {
+        &self.raw
+    }
+
+    /// Returns a mutable reference to the underlying raw instance.
+    pub fn raw_mut(&mut self) -> &mut RawInstance {
+        &mut self.raw
+    }
+}
+
 impl TileAbstractBuilder {
-    fn finalize_abstract(self) -> (Abstract, Vec<Path>) {
+    fn finalize_abstract(self) -> (Abstract, Vec<(Path, usize)>) {
+        let (abs, paths, result) = self.try_finalize_abstract();
+        if let Some(group_root) = result.unrouted.first() {
+            panic!("cannot connect all nodes in group {:?}", group_root);
+        }
+        (abs, paths)
+    }
+
+    /// Finalizes the abstract, also returning the root nets of any groups that
+    /// could not be routed and any nets that could not be strapped.
+    ///
+    /// Each path is returned along with the width multiplier of its net.
+    fn try_finalize_abstract(self) -> (Abstract, Vec<(Path, usize)>, RouteResult) {
         let TileAbstractBuilder {
             nodes,
             mut connections,
@@ -609,16 +781,87 @@ impl TileAbstractBuilder {
             skip_all_nets,
             strapper,
             straps,
+            grid_offsets,
             layer_bbox,
             port_ids,
+            net_widths,
+            buses,
+            keep_clear,
+            vias,
+            pre_route_hooks,
+            abutment_nodes,
+            assignment_conflicts,
+            connectivity_assertions,
+            net_names,
         } = self;
-        let mut abs = InstanceAbstract::merge(abs, top_layer, layer_bbox, port_ids, assigned_nets);
+        let top_layer = vias
+            .iter()
+            .map(|via| via.upper.layer)
+            .fold(top_layer, std::cmp::max);
+
+        for assertion in connectivity_assertions {
+            if !connections.unioned(assertion.a, assertion.b) {
+                panic!(
+                    "nodes asserted to be connected at {} are not connected",
+                    assertion.location
+                );
+            }
+        }
+
+        // Maps each net that connects by abutment to the first net in its group.
+        let abutment_roots: IndexSet<NodeKey> = abutment_nodes
+            .iter()
+            .map(|key| connections.find(*key))
+            .collect();
+        let mut abutment_groups = HashMap::new();
+        let mut abutment_nets = HashMap::new();
+        for info in nodes.values() {
+            let root = connections.find(info.key);
+            if abutment_roots.contains(&root) {
+                let group = *abutment_groups.entry(root).or_insert(info.net);
+                abutment_nets.insert(info.net, group);
+            }
+        }
+
+        // Maps each net to the first net it is connected to.
+        let mut connection_groups = HashMap::new();
+        let net_roots = nodes
+            .values()
+            .map(|info| {
+                let root = connections.find(info.key);
+                (info.net, *connection_groups.entry(root).or_insert(info.net))
+            })
+            .collect();
+
+        let mut abs = InstanceAbstract::merge(
+            abs,
+            top_layer,
+            layer_bbox,
+            port_ids,
+            assigned_nets,
+            assignment_conflicts,
+            &net_roots,
+            &abutment_nets,
+        )
+        .unwrap_or_else(|err| panic!("{err}"));
 
         for layer in layers_to_block {
             abs.block_available_on_layer(layer);
         }
+        for (layer, offset) in grid_offsets {
+            abs.set_grid_offset(layer, offset)
+                .expect("grid offsets are validated when set");
+        }
 
         let mut routing_state = abs.routing_state();
+        for via in vias {
+            for coord in [via.lower, via.upper] {
+                routing_state[abs.track_to_grid(coord)] = PointState::Routed {
+                    net: via.net,
+                    has_via: true,
+                };
+            }
+        }
 
         let mut roots = HashMap::new();
 
@@ -630,6 +873,7 @@ impl TileAbstractBuilder {
                 .or_insert(IndexSet::new())
                 .insert(info.net);
         }
+        abs.net_count = to_connect_raw.len();
 
         // Raw nets with skipped nets filtered out.
         let mut to_connect = to_connect_raw.clone();
@@ -643,6 +887,29 @@ impl TileAbstractBuilder {
                 seq.swap_remove(net);
             }
         }
+        for (net, width) in net_widths {
+            if let Some(root) = roots.get(&net) {
+                let entry = routing_state.net_widths.entry(*root).or_insert(1);
+                *entry = std::cmp::max(*entry, width);
+            }
+        }
+        routing_state.buses = buses
+            .into_iter()
+            .map(|(nets, layer)| {
+                let nets = nets
+                    .into_iter()
+                    .filter_map(|net| roots.get(&net).copied())
+                    .collect();
+                (nets, layer)
+            })
+            .collect();
+        let origin = abs.physical_bounds().lower_left();
+        routing_state.keep_clear = keep_clear
+            .into_iter()
+            .filter_map(|(net, rect)| {
+                Some((*roots.get(&net)?, rect.translate(Point::zero() - origin)))
+            })
+            .collect();
         routing_state.roots = roots;
 
         let to_connect: Vec<_> = to_connect
@@ -652,21 +919,58 @@ impl TileAbstractBuilder {
             .map(Vec::from_iter)
             .collect();
 
+        for hook in pre_route_hooks {
+            hook(&mut routing_state);
+        }
+
         let mut paths = Vec::new();
+        let mut unrouted = Vec::new();
 
         if let Some(router) = router {
-            paths.extend(router.route(&mut routing_state, to_connect));
+            let (routed, failed) = router.try_route(&mut routing_state, to_connect);
+            paths.extend(routed);
+            unrouted.extend(failed);
         }
+        let mut unstrapped = Vec::new();
         if let Some(strapper) = strapper {
             paths.extend(strapper.strap(&mut routing_state, straps));
+        } else if !straps.is_empty() {
+            unstrapped.extend(straps.into_iter().map(|(net, _)| net));
+            tracing::warn!(
+                nets = ?unstrapped,
+                "strapping was configured but no strapper was set; straps will not be drawn"
+            );
         }
         for (_, nets) in to_connect_raw {
             for net in nets {
                 routing_state.relabel_net(net, routing_state.roots[&net]);
             }
         }
+        // Nets are numbered in the order they were created, so IO names take precedence.
+        for (net, name) in net_names {
+            if let Some(root) = routing_state.roots.get(&net) {
+                abs.net_names.entry(*root).or_insert(name);
+            }
+        }
+        let paths = paths
+            .into_iter()
+            .map(|path| {
+                let width = match path.first().map(|(a, _)| routing_state[*a]) {
+                    Some(PointState::Routed { net, .. }) => routing_state.net_width(net),
+                    _ => 1,
+                };
+                (path, width)
+            })
+            .collect();
         abs.from_routing_state(routing_state);
-        (abs, paths)
+        (
+            abs,
+            paths,
+            RouteResult {
+                unrouted,
+                unstrapped,
+            },
+        )
     }
 }
 
@@ -694,7 +998,18 @@ impl<'a, PDK: Pdk + Schema> TileBuilder<'a, PDK> {
             router,
             strapper,
             via_maker,
+            via_makers,
             straps,
+            grid_offsets,
+            net_widths,
+            buses,
+            keep_clear,
+            vias,
+            pre_route_hooks,
+            abutment_nodes,
+            assignment_conflicts,
+            connectivity_assertions,
+            net_names,
             layer_stack,
             layout,
             schematic,
@@ -712,18 +1027,38 @@ impl<'a, PDK: Pdk + Schema> TileBuilder<'a, PDK> {
                 router,
                 strapper,
                 straps,
+                grid_offsets,
                 layer_bbox,
                 port_ids,
+                net_widths,
+                buses,
+                keep_clear,
+                vias: vias.clone(),
+                pre_route_hooks,
+                abutment_nodes,
+                assignment_conflicts,
+                connectivity_assertions,
+                net_names,
             },
             TileBuilderUnused {
                 next_net_id,
                 via_maker,
+                via_makers,
+                vias,
                 layer_stack,
                 layout,
                 schematic,
             },
         )
     }
+    /// Records the names of the signals from which the given nodes' nets originate.
+    fn name_nodes(&mut self, nodes: Vec<Node>, names: Vec<NameBuf>) {
+        for (node, name) in nodes.into_iter().zip(names) {
+            self.net_names
+                .insert(self.nodes[&node].net, name.to_string().into());
+        }
+    }
+
     fn register_bundle<T: Flatten<Node>>(&mut self, bundle: &T) {
         let nodes: Vec<Node> = bundle.flatten_vec();
         let keys: Vec<NodeKey> = nodes.iter().map(|_| self.connections.new_key(())).collect();
@@ -739,8 +1074,9 @@ impl<'a, PDK: Pdk + Schema> TileBuilder<'a, PDK> {
         );
     }
 
-    fn new<T: io::schematic::IsBundle>(
-        schematic_io: &'a T,
+    fn new<B: Block>(
+        block: &B,
+        schematic_io: &'a Bundle<B::Io>,
         schematic: &'a mut schematic::CellBuilder<PDK>,
         layout: &'a mut layout::CellBuilder<PDK>,
     ) -> Self {
@@ -765,10 +1101,22 @@ impl<'a, PDK: Pdk + Schema> TileBuilder<'a, PDK> {
             router: None,
             strapper: None,
             via_maker: None,
+            via_makers: HashMap::new(),
             straps: Vec::new(),
+            grid_offsets: BTreeMap::new(),
+            net_widths: HashMap::new(),
+            buses: Vec::new(),
+            keep_clear: Vec::new(),
+            vias: Vec::new(),
+            pre_route_hooks: Vec::new(),
+            abutment_nodes: Vec::new(),
+            assignment_conflicts: AssignmentConflicts::default(),
+            connectivity_assertions: Vec::new(),
+            net_names: BTreeMap::new(),
         };
 
         builder.register_bundle(schematic_io);
+        builder.name_nodes(schematic_io.flatten_vec(), block.io().flat_names(None));
 
         builder
     }
@@ -835,8 +1183,12 @@ impl<'a, PDK: Pdk + Schema> TileBuilder<'a, PDK> {
                         schematic: &schematic_io,
                         layout: &mut layout_io,
                     };
-                    let mut cell =
-                        TileBuilder::new(&schematic_io, &mut schematic_cell, &mut layout_cell);
+                    let mut cell = TileBuilder::new(
+                        block,
+                        &schematic_io,
+                        &mut schematic_cell,
+                        &mut layout_cell,
+                    );
                     let _ = <B as Tile<PDK>>::tile(block, atoll_io, &mut cell);
 
                     cell.split_for_abstract(schematic_io.flatten_vec())
@@ -860,6 +1212,18 @@ impl<'a, PDK: Pdk + Schema> TileBuilder<'a, PDK> {
         }
     }
 
+    /// Generates an ATOLL instance from a block that implements [`Tile`]
+    /// and places it at the given location in LCM units.
+    pub fn generate_at<B: Clone + Tile<PDK>>(
+        &mut self,
+        block: B,
+        loc: Point,
+    ) -> Instance<TileWrapper<B>> {
+        let mut inst = self.generate(block);
+        inst.raw.loc = loc;
+        inst
+    }
+
     /// Generates an ATOLL instance from a block that implements [`Tile`]
     /// and connects its IO to the given bundle.
     pub fn generate_connected<B: Clone + Tile<PDK>, C: IsBundle>(
@@ -942,8 +1306,12 @@ impl<'a, PDK: Pdk + Schema> TileBuilder<'a, PDK> {
                         schematic: &schematic_io,
                         layout: &mut layout_io,
                     };
-                    let mut cell =
-                        TileBuilder::new(&schematic_io, &mut schematic_cell, &mut layout_cell);
+                    let mut cell = TileBuilder::new(
+                        block,
+                        &schematic_io,
+                        &mut schematic_cell,
+                        &mut layout_cell,
+                    );
                     let _ = <B as Tile<PDK>>::tile(block, atoll_io, &mut cell);
 
                     cell.split_for_abstract(schematic_io.flatten_vec())
@@ -1004,37 +1372,21 @@ impl<'a, PDK: Pdk + Schema> TileBuilder<'a, PDK> {
             .map(|node| self.nodes.get_mut(node).unwrap().net)
             .collect();
 
-        self.set_top_layer(instance.raw.abs.top_layer);
-
         let virtual_layers = self.layout.ctx.install_layers::<crate::VirtualLayers>();
-        let orig_bbox = instance.raw.abs.grid.slice().lcm_to_physical_rect(
-            instance.raw.abs.grid.slice().expand_to_lcm_units(
-                instance
-                    .layout
-                    .layer_bbox(virtual_layers.outline.id())
-                    .unwrap(),
-            ),
-        );
-        self.abs.push(InstanceAbstract::new(
-            instance.raw.abs,
-            instance.raw.loc,
-            instance.raw.orientation,
-            parent_net_ids,
-        ));
+        let outline = instance
+            .layout
+            .layer_bbox(virtual_layers.outline.id())
+            .unwrap();
+        let orientation = instance.raw.orientation;
+        let offset = self.place_abstract(instance.raw, parent_net_ids, outline);
 
         // todo: Use ATOLL virtual layer.
         let mut layout = instance.layout;
         layout.transform_mut(Transformation::from_offset_and_orientation(
             Point::zero(),
-            instance.raw.orientation,
-        ));
-        let new_bbox = orig_bbox.transform(Transformation::from_offset_and_orientation(
-            Point::zero(),
-            instance.raw.orientation,
+            orientation,
         ));
-        layout.translate_mut(
-            orig_bbox.corner(Corner::LowerLeft) - new_bbox.corner(Corner::LowerLeft) + physical_loc,
-        );
+        layout.translate_mut(offset + physical_loc);
         self.layout.draw(layout.clone())?;
 
         Ok(DrawnInstance {
@@ -1043,6 +1395,109 @@ impl<'a, PDK: Pdk + Schema> TileBuilder<'a, PDK> {
         })
     }
 
+    /// Instantiates a [`FrozenTile`] without re-running its generator.
+    ///
+    /// The schematic is a black-box instance of the frozen tile's cell.
+    /// Once drawn using [`TileBuilder::draw_frozen`], the internals of the tile
+    /// are treated as routing obstructions.
+    pub fn instantiate_frozen(&mut self, frozen: &FrozenTile) -> FrozenInstance
+    where
+        schematic::primitives::RawInstance: Schematic<PDK>,
+    {
+        let schematic = self
+            .schematic
+            .instantiate(schematic::primitives::RawInstance::new(
+                frozen.cell.clone(),
+                frozen.ports.clone(),
+            ));
+        self.register_bundle(schematic.io());
+
+        FrozenInstance {
+            io: schematic.io().flatten_vec(),
+            ports: frozen.ports.clone(),
+            layout: frozen.layout.clone(),
+            raw: RawInstance {
+                abs: frozen.abs.clone(),
+                loc: Default::default(),
+                orientation: Default::default(),
+            },
+        }
+    }
+
+    /// Draws an instance of a [`FrozenTile`] in layout.
+    ///
+    /// Returns the geometry of the instance's ports in this tile's coordinates,
+    /// in the order given by [`FrozenTile::ports`].
+    pub fn draw_frozen(
+        &mut self,
+        instance: FrozenInstance,
+    ) -> substrate::error::Result<Vec<io::layout::PortGeometry>> {
+        let physical_loc = instance.raw.physical_loc();
+
+        let parent_net_ids = instance
+            .io
+            .iter()
+            .map(|node| self.nodes[node].net)
+            .collect();
+
+        let virtual_layers = self.layout.ctx.install_layers::<crate::VirtualLayers>();
+        let outline = instance
+            .layout
+            .layer_bbox(virtual_layers.outline.id())
+            .expect("frozen tile must provide an outline on ATOLL virtual layer");
+        let orientation = instance.raw.orientation;
+        let offset = self.place_abstract(instance.raw, parent_net_ids, outline);
+
+        let mut layout = layout::element::RawInstance::new(
+            instance.layout,
+            Transformation::from_offset_and_orientation(Point::zero(), orientation),
+        );
+        layout.translate_mut(offset + physical_loc);
+        let cell = layout.cell();
+        let ports = instance
+            .ports
+            .iter()
+            .map(|name| {
+                cell.port_named(name)
+                    .cloned()
+                    .ok_or(layout::error::LayoutError::IoDefinition)
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        self.layout.draw(layout)?;
+        Ok(ports)
+    }
+
+    /// Adds the abstract of a placed instance to this tile.
+    ///
+    /// Returns the translation that realigns the instance's layout, oriented about the origin,
+    /// with its unoriented outline.
+    fn place_abstract(
+        &mut self,
+        raw: RawInstance,
+        parent_net_ids: Vec<NetId>,
+        outline: Rect,
+    ) -> Point {
+        self.set_top_layer(raw.abs.top_layer);
+
+        let orig_bbox = raw
+            .abs
+            .grid
+            .slice()
+            .lcm_to_physical_rect(raw.abs.grid.slice().expand_to_lcm_units(outline));
+        let new_bbox = orig_bbox.transform(Transformation::from_offset_and_orientation(
+            Point::zero(),
+            raw.orientation,
+        ));
+        self.abs.push(InstanceAbstract::new(
+            raw.abs,
+            raw.loc,
+            raw.orientation,
+            parent_net_ids,
+        ));
+
+        orig_bbox.corner(Corner::LowerLeft) - new_bbox.corner(Corner::LowerLeft)
+    }
+
     /// Connect all signals in the given data instances.
     pub fn connect<D1, D2>(&mut self, s1: D1, s2: D2)
     where
@@ -1067,9 +1522,11 @@ impl<'a, PDK: Pdk + Schema> TileBuilder<'a, PDK> {
         name: impl Into<ArcStr>,
         ty: TY,
     ) -> <TY as io::schematic::HardwareType>::Bundle {
-        let bundle = self.schematic.signal(name, ty);
+        let name = name.into();
+        let bundle = self.schematic.signal(name.clone(), ty.clone());
 
         self.register_bundle(&bundle);
+        self.name_nodes(bundle.flatten_vec(), ty.flat_names(Some(name.into())));
 
         bundle
     }
@@ -1086,6 +1543,13 @@ impl<'a, PDK: Pdk + Schema> TileBuilder<'a, PDK> {
         })
     }
 
+    /// Sets how grid points assigned to two different nets are handled.
+    ///
+    /// Defaults to [`AssignmentConflicts::Error`], which panics when the abstract is built.
+    pub fn set_assignment_conflicts(&mut self, conflicts: AssignmentConflicts) {
+        self.assignment_conflicts = conflicts;
+    }
+
     /// Assigns grid points to the provided node, but only if the grid point is currently marked available.
     ///
     /// If the provided node is `None`, blocks the grid point for routing.
@@ -1103,6 +1567,27 @@ impl<'a, PDK: Pdk + Schema> TileBuilder<'a, PDK> {
         })
     }
 
+    /// Blocks all grid points on the given layer covered by the provided polygon.
+    ///
+    /// The polygon vertices are specified in grid coordinates, as with [`TileBuilder::assign_grid_points`].
+    /// Grid points on the boundary of the polygon are blocked.
+    pub fn block_polygon(&mut self, layer: usize, poly: &[Point]) {
+        for bounds in grid::rasterize_polygon(poly) {
+            self.assign_grid_points(None, layer, bounds);
+        }
+    }
+
+    /// Blocks the grid points occupied by a tile's abstract, as if it were placed at `loc`
+    /// (in LCM units) with the given orientation.
+    ///
+    /// Useful for tiles that partially overlap, such as a guard ring around a block.
+    /// Only grid points that are still available are blocked.
+    pub fn block_from_abstract(&mut self, abs: &Abstract, loc: Point, orientation: Orientation) {
+        for (layer, bounds) in abs.occupied_track_runs(loc, orientation) {
+            self.assign_grid_points_if_available(None, layer, bounds);
+        }
+    }
+
     /// Blocks all remaining available grid points on the given layer.
     pub fn block_available_on_layer(&mut self, layer: usize) {
         self.layers_to_block.insert(layer);
@@ -1111,10 +1596,82 @@ impl<'a, PDK: Pdk + Schema> TileBuilder<'a, PDK> {
     /// Set up straps for the provided node.
     ///
     /// Order of calls to `set_strapping` may matter depending on the [`Strapper`] being used.
+    ///
+    /// Straps are only drawn if a strapper is set using [`TileBuilder::set_strapper`].
     pub fn set_strapping(&mut self, node: Node, params: StrappingParams) {
         self.straps.push((self.nodes[&node].net, params));
     }
 
+    /// Draws the routes of the given node `multiplier` times wider than the routing layer's line width.
+    ///
+    /// Tracks adjacent to the node's routes are reserved as needed to avoid shorts.
+    pub fn set_net_width(&mut self, node: Node, multiplier: usize) {
+        assert!(multiplier > 0, "net width multiplier must be positive");
+        self.net_widths.insert(self.nodes[&node].net, multiplier);
+    }
+
+    /// Routes the bits of the given bus on adjacent parallel tracks of `layer`, in bit order.
+    ///
+    /// This is a hint to the router; bits that cannot be placed next to the previous bit
+    /// are routed independently.
+    pub fn route_bus(&mut self, bundle: &impl Flatten<Node>, layer: usize) {
+        let nets = bundle
+            .flatten_vec()
+            .iter()
+            .map(|node| self.nodes[node].net)
+            .collect();
+        self.buses.push((nets, layer));
+    }
+
+    /// Prevents the router from routing the given node through `rect`.
+    ///
+    /// `rect` is given in the tile's layout coordinates. Other nets may still be routed
+    /// through the region.
+    pub fn keep_clear(&mut self, node: Node, rect: Rect) {
+        self.keep_clear.push((self.nodes[&node].net, rect));
+    }
+
+    /// Places a via on `net` between layer `coord.layer` and the layer above it,
+    /// without drawing any wires.
+    ///
+    /// `coord` is given in track coordinates on layer `coord.layer`.
+    /// The via is drawn by the via maker of the layer above,
+    /// and the grid points it occupies on both layers are assigned to `net`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the grid point at `coord` is not also a grid point of the layer above.
+    pub fn add_via(&mut self, net: Node, coord: TrackCoord) {
+        let mut grid = RoutingGrid::new((*self.layer_stack).clone(), 0..coord.layer + 2);
+        for (&layer, &offset) in &self.grid_offsets {
+            grid.set_offset(layer, offset)
+                .expect("grid offsets are validated when set");
+        }
+        let center = grid.xy_track_point(coord.layer, coord.x, coord.y);
+        let upper = grid.point_to_grid(
+            center,
+            coord.layer + 1,
+            RoundingMode::Nearest,
+            RoundingMode::Nearest,
+        );
+        let upper = TrackCoord {
+            layer: coord.layer + 1,
+            x: upper.x,
+            y: upper.y,
+        };
+        assert_eq!(
+            grid.xy_track_point(upper.layer, upper.x, upper.y),
+            center,
+            "via at {coord:?} is not aligned to a grid point on layer {}",
+            upper.layer
+        );
+        self.vias.push(ViaPlacement {
+            net: self.nodes[&net].net,
+            lower: coord,
+            upper,
+        });
+    }
+
     /// Gets the global context.
     pub fn ctx(&self) -> &PdkContext<PDK> {
         self.layout.ctx()
@@ -1133,6 +1690,39 @@ impl<'a, PDK: Pdk + Schema> TileBuilder<'a, PDK> {
         self.router = Some(Arc::new(router));
     }
 
+    /// Adds a hook that can modify the routing state after the abstract is built
+    /// but before the router runs.
+    ///
+    /// Hooks run in the order they were added.
+    pub fn add_pre_route_hook(
+        &mut self,
+        hook: impl Fn(&mut RoutingState<PdkLayer>) + Send + Sync + 'static,
+    ) {
+        self.pre_route_hooks.push(Arc::new(hook));
+    }
+
+    /// Verifies that the net containing `node` is drawn consistently across
+    /// the shared edges of abutting instances.
+    ///
+    /// Panics when the abstract is built if a track on the net reaches the edge
+    /// of one instance but not the corresponding track of its neighbor.
+    pub fn verify_abutment(&mut self, node: Node) {
+        self.abutment_nodes.push(self.nodes[&node].key);
+    }
+
+    /// Asserts that `a` and `b` are on the same net.
+    ///
+    /// Checked when the abstract is built, which panics if the nodes
+    /// were never connected.
+    #[track_caller]
+    pub fn assert_connected(&mut self, a: Node, b: Node) {
+        self.connectivity_assertions.push(ConnectivityAssertion {
+            a: self.nodes[&a].key,
+            b: self.nodes[&b].key,
+            location: Location::caller(),
+        });
+    }
+
     /// Skips routing a net.
     pub fn skip_routing(&mut self, node: Node) {
         self.skip_nets.insert(self.nodes[&node].net);
@@ -1149,9 +1739,30 @@ impl<'a, PDK: Pdk + Schema> TileBuilder<'a, PDK> {
     }
 
     /// Sets the via maker.
+    ///
+    /// Used for all layer transitions without a via maker set by [`TileBuilder::set_layer_via_maker`].
     pub fn set_via_maker<T: Any + ViaMaker<PDK>>(&mut self, via_maker: T) {
         self.via_maker = Some(Arc::new(via_maker));
     }
+
+    /// Sets the via maker used for vias from the given layer to the layer below.
+    pub fn set_layer_via_maker<T: Any + ViaMaker<PDK>>(&mut self, layer: usize, via_maker: T) {
+        self.via_makers.insert(layer, Arc::new(via_maker));
+    }
+
+    /// Shifts the tracks on the given layer by `offset` physical units.
+    ///
+    /// Applies to the tile's abstract and routing state as well as to its drawn routes,
+    /// e.g. to align the tile's grid with that of a parent. Grid points that fall halfway
+    /// between points of the unshifted grid are blocked unless both neighbors agree.
+    /// Vias added with [`TileBuilder::add_via`] after this call are placed on the shifted grid.
+    /// The offset must be a multiple of half the layer's pitch, so that track centers
+    /// continue to lie on track or space centers of the unshifted grid.
+    pub fn set_grid_offset(&mut self, layer: usize, offset: i64) -> Result<(), InvalidGridOffset> {
+        self.layer_stack.check_offset(layer, offset)?;
+        self.grid_offsets.insert(layer, offset);
+        Ok(())
+    }
 }
 
 /// A builder for an ATOLL tile's IOs.
@@ -1213,6 +1824,74 @@ impl<T: Block> Block for TileWrapper<T> {
     }
 }
 
+/// Attempts to route the given tile with the given top layer, without drawing it.
+///
+/// The given top layer overrides the one set by the tile itself.
+/// Runs the tile's generator and router but does not cache the resulting abstract.
+/// Tiles without a router always route successfully.
+/// Nets with strapping configured on a tile without a strapper are reported
+/// in [`RouteResult::unstrapped`].
+pub fn try_route<PDK: Pdk + Schema, B: Tile<PDK>>(
+    ctx: &PdkContext<PDK>,
+    block: B,
+    top_layer: usize,
+) -> substrate::error::Result<RouteResult> {
+    let (mut schematic_cell, schematic_io) = prepare_cell_builder(None, (**ctx).clone(), &block);
+    let mut layout_io = io::layout::HardwareType::builder(&block.io());
+    let mut layout_cell = layout::CellBuilder::new(ctx.clone());
+    let io = IoBuilder {
+        schematic: &schematic_io,
+        layout: &mut layout_io,
+    };
+    let mut cell = TileBuilder::new(&block, &schematic_io, &mut schematic_cell, &mut layout_cell);
+    <B as Tile<PDK>>::tile(&block, io, &mut cell)?;
+    cell.top_layer = top_layer;
+
+    let (_, _, result) = cell
+        .split_for_abstract(schematic_io.flatten_vec())
+        .0
+        .try_finalize_abstract();
+    Ok(result)
+}
+
+/// Freezes a generated tile instance into a black-box macro.
+///
+/// The frozen tile captures the instance's abstract, layout geometry, and port names,
+/// and can be instantiated in other tiles using [`TileBuilder::instantiate_frozen`].
+pub fn freeze_tile<T: ExportsNestedData + ExportsLayoutData>(instance: &Instance<T>) -> FrozenTile {
+    let block = instance.schematic.block();
+    FrozenTile {
+        abs: instance.raw.abs.clone(),
+        layout: instance.layout.raw_cell().raw().clone(),
+        cell: block.name(),
+        ports: block
+            .io()
+            .flat_names(None)
+            .into_iter()
+            .map(|name| name.to_string().into())
+            .collect(),
+    }
+}
+
+/// Draws a via from `coord` to the layer below using `maker`.
+///
+/// Via makers place vias on the unshifted `base` grid,
+/// so the via is translated to account for any offsets applied in `grid`.
+fn draw_via<PDK: Pdk>(
+    layout: &mut layout::CellBuilder<PDK>,
+    maker: &dyn ViaMaker<PDK>,
+    grid: &RoutingGrid<PdkLayer>,
+    base: &RoutingGrid<PdkLayer>,
+    coord: TrackCoord,
+) -> substrate::error::Result<()> {
+    let shift = grid.xy_track_point(coord.layer, coord.x, coord.y)
+        - base.xy_track_point(coord.layer, coord.x, coord.y);
+    for shape in maker.draw_via(layout.ctx().clone(), coord) {
+        layout.draw(shape.translate(shift))?;
+    }
+    Ok(())
+}
+
 impl<T: ExportsNestedData> ExportsNestedData for TileWrapper<T> {
     type NestedData = <T as ExportsNestedData>::NestedData;
 }
@@ -1236,7 +1915,7 @@ where
             schematic: io,
             layout: &mut layout_io,
         };
-        let mut cell = TileBuilder::new(io, cell, &mut layout_cell);
+        let mut cell = TileBuilder::new(self, io, cell, &mut layout_cell);
         let (schematic_data, _) = <T as Tile<PDK>>::tile(&self.block, atoll_io, &mut cell)?;
         Ok(schematic_data)
     }
@@ -1257,7 +1936,7 @@ where
             schematic: &schematic_io,
             layout: io,
         };
-        let mut cell = TileBuilder::new(&schematic_io, &mut schematic_cell, cell);
+        let mut cell = TileBuilder::new(self, &schematic_io, &mut schematic_cell, cell);
         let (_, layout_data) = <T as Tile<PDK>>::tile(&self.block, io, &mut cell)?;
 
         let ctx_clone = (**cell.ctx()).clone();
@@ -1266,7 +1945,11 @@ where
         let (
             cell,
             TileBuilderUnused {
-                layout, via_maker, ..
+                layout,
+                via_maker,
+                via_makers,
+                vias,
+                ..
             },
         ) = cell.split_for_abstract(schematic_io.flatten_vec());
         let abs_path = atoll_ctx
@@ -1278,53 +1961,34 @@ where
 
         let (abs, paths) = abs_path.get().clone();
 
-        for path in paths {
+        let grid = &abs.grid;
+        // The grid without offsets, on which via makers draw.
+        let base = RoutingGrid::new(grid.stack.clone(), grid.layers());
+
+        for (path, width) in paths {
             for (a, b) in path {
                 let (a, b) = (abs.grid_to_track(a), abs.grid_to_track(b));
                 if a.layer == b.layer {
-                    // todo: handle multiple routing directions
-                    assert!(a.x == b.x || a.y == b.y);
-                    let layer = abs.grid.stack.layer(a.layer);
-                    let (start_track, start_cross_track, end_track, end_cross_track) =
-                        if layer.dir().track_dir() == Dir::Vert {
-                            (a.x, a.y, b.x, b.y)
-                        } else {
-                            (a.y, a.x, b.y, b.x)
-                        };
-                    let start = abs
-                        .grid
-                        .track_point(a.layer, start_track, start_cross_track);
-                    let end = abs.grid.track_point(b.layer, end_track, end_cross_track);
-                    let track = Rect::from_point(start)
-                        .union(Rect::from_point(end))
-                        .expand_dir(
-                            if a.x == b.x { Dir::Horiz } else { Dir::Vert },
-                            abs.grid.stack.layer(a.layer).line() / 2,
-                        )
-                        .expand_dir(
-                            if a.y == b.y { Dir::Horiz } else { Dir::Vert },
-                            abs.grid.stack.layer(a.layer).endcap(),
-                        );
+                    let track = grid.segment_rect(a, b, width);
 
                     if track.width() > 0 && track.height() > 0 {
-                        layout.draw(Shape::new(abs.grid.stack.layer(a.layer).id, track))?;
+                        layout.draw(Shape::new(grid.stack.layer(a.layer).id, track))?;
                     }
                 } else if a.layer == b.layer + 1 || b.layer == a.layer + 1 {
                     let (a, b) = if b.layer > a.layer { (b, a) } else { (a, b) };
-                    let (in_track, out_track) = if abs.grid.stack.layer(a.layer).dir().track_dir()
+                    let (in_track, out_track) = if grid.stack.layer(a.layer).dir().track_dir()
                         == Dir::Horiz
                         && a.x == b.x
                     {
                         (
-                            abs.grid.track(b.layer, b.x, b.y, b.y),
-                            abs.grid.track_point(a.layer, a.y, a.x),
+                            grid.track(b.layer, b.x, b.y, b.y),
+                            grid.track_point(a.layer, a.y, a.x),
                         )
-                    } else if abs.grid.stack.layer(a.layer).dir().track_dir() == Dir::Vert
-                        && a.y == b.y
+                    } else if grid.stack.layer(a.layer).dir().track_dir() == Dir::Vert && a.y == b.y
                     {
                         (
-                            abs.grid.track(b.layer, b.y, b.x, b.x),
-                            abs.grid.track_point(a.layer, a.x, a.y),
+                            grid.track(b.layer, b.y, b.x, b.x),
+                            grid.track_point(a.layer, a.x, a.y),
                         )
                     } else {
                         panic!("cannot have a diagonal segment");
@@ -1335,24 +1999,21 @@ where
                         in_track.vspan().add_point(out_track.y),
                     );
                     if track.width() > 0 && track.height() > 0 {
-                        layout.draw(Shape::new(abs.grid.stack.layer(b.layer).id, track))?;
+                        layout.draw(Shape::new(grid.stack.layer(b.layer).id, track))?;
                     }
-                    if let Some(maker) = &via_maker {
-                        for shape in maker.draw_via(
-                            layout.ctx().clone(),
-                            TrackCoord {
-                                layer: a.layer,
-                                x: a.x,
-                                y: a.y,
-                            },
-                        ) {
-                            layout.draw(shape)?;
-                        }
+                    if let Some(maker) = via_makers.get(&a.layer).or(via_maker.as_ref()) {
+                        draw_via(layout, &**maker, grid, &base, a)?;
                     }
                 }
             }
         }
 
+        for via in vias {
+            if let Some(maker) = via_makers.get(&via.upper.layer).or(via_maker.as_ref()) {
+                draw_via(layout, &**maker, grid, &base, via.upper)?;
+            }
+        }
+
         Ok(layout_data)
     }
 }
@@ -1,7 +1,7 @@
 //! Routing interfaces and implementations.
 
 use crate::abs::{GridCoord, TrackCoord};
-use crate::grid::{PdkLayer, RoutingState};
+use crate::grid::{AtollLayer, PdkLayer, RoutingState};
 use crate::{NetId, PointState};
 use indexmap::{map::Entry, IndexMap};
 use num::Zero;
@@ -10,9 +10,10 @@ use rand::rngs::StdRng;
 use rand::SeedableRng;
 use rustc_hash::FxHasher;
 use std::cmp::Ordering;
-use std::collections::{BinaryHeap, HashSet};
+use std::collections::{BinaryHeap, HashMap, HashSet};
 use std::hash::{BuildHasherDefault, Hash};
 use substrate::context::PdkContext;
+use substrate::geometry::dir::Dir;
 use substrate::layout::element::Shape;
 use substrate::pdk::Pdk;
 
@@ -30,6 +31,35 @@ pub trait Router: Send + Sync {
         routing_state: &mut RoutingState<PdkLayer>,
         to_connect: Vec<Vec<NetId>>,
     ) -> Vec<Path>;
+
+    /// Returns routes that connect the given nets, along with the root nets of
+    /// the groups that could not be fully connected.
+    ///
+    /// The default implementation delegates to [`Router::route`] and reports no failures.
+    fn try_route(
+        &self,
+        routing_state: &mut RoutingState<PdkLayer>,
+        to_connect: Vec<Vec<NetId>>,
+    ) -> (Vec<Path>, Vec<NetId>) {
+        (self.route(routing_state, to_connect), Vec::new())
+    }
+}
+
+/// The result of attempting to route a tile.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct RouteResult {
+    /// The root nets of the groups that could not be fully connected.
+    pub unrouted: Vec<NetId>,
+    /// The nets that had strapping configured but were not strapped
+    /// because the tile has no strapper.
+    pub unstrapped: Vec<NetId>,
+}
+
+impl RouteResult {
+    /// Returns `true` if all nets were routed successfully.
+    pub fn is_success(&self) -> bool {
+        self.unrouted.is_empty()
+    }
 }
 
 /// A router that greedily routes net groups one at a time.
@@ -67,7 +97,7 @@ struct SmallestHolder<K> {
 
 impl<K: PartialEq> PartialEq for SmallestHolder<K> {
     fn eq(&self, other: &Self) -> bool {
-        self.cost == other.cost
+        self.cost == other.cost && self.index == other.index
     }
 }
 
@@ -81,7 +111,12 @@ impl<K: Ord> PartialOrd for SmallestHolder<K> {
 
 impl<K: Ord> Ord for SmallestHolder<K> {
     fn cmp(&self, other: &Self) -> Ordering {
-        other.cost.cmp(&self.cost)
+        // Ties are broken in favor of the node that was discovered first,
+        // which keeps the search independent of heap internals.
+        other
+            .cost
+            .cmp(&self.cost)
+            .then_with(|| other.index.cmp(&self.index))
     }
 }
 
@@ -200,11 +235,26 @@ where
 // END DIJKSTRA IMPL
 
 impl Router for GreedyRouter {
-    fn route(
+    fn route(&self, state: &mut RoutingState<PdkLayer>, to_connect: Vec<Vec<NetId>>) -> Vec<Path> {
+        let (paths, unrouted) = self.try_route(state, to_connect);
+        if let Some(group_root) = unrouted.first() {
+            panic!("cannot connect all nodes in group {:?}", group_root);
+        }
+        paths
+    }
+
+    fn try_route(
         &self,
         state: &mut RoutingState<PdkLayer>,
         mut to_connect: Vec<Vec<NetId>>,
-    ) -> Vec<Path> {
+    ) -> (Vec<Path>, Vec<NetId>) {
+        // Canonicalize the input so that the routed paths depend only on the
+        // contents of `to_connect` and the seed, not on the order of the groups.
+        for group in to_connect.iter_mut() {
+            group.sort();
+        }
+        to_connect.sort();
+
         let mut rng = StdRng::from_seed(self.seed);
         to_connect.shuffle(&mut rng);
         // remove nodes from the to connect list that are not on the grid
@@ -231,120 +281,238 @@ impl Router for GreedyRouter {
             }
         }
 
+        // Route wide nets first so that their adjacent tracks can still be reserved,
+        // followed by the bits of each bus in order.
+        to_connect.sort_by_key(|group| {
+            let net = group.first().copied();
+            (
+                std::cmp::Reverse(net.map_or(1, |net| state.net_width(net))),
+                net.and_then(|net| state.bus_bit(net))
+                    .map_or((1, 0, 0), |(bus, bit)| (0, bus, bit)),
+            )
+        });
+
         let mut paths = Vec::new();
+        let mut unrouted = Vec::new();
+        // The track used by the last routed bit of each bus, and the direction
+        // in which subsequent bits are placed.
+        let mut bus_tracks: HashMap<usize, (usize, Option<bool>)> = HashMap::new();
         for group in to_connect.iter() {
             if group.len() <= 1 {
                 // skip empty or one node groups
                 continue;
             }
-            let group_root = state.roots[&group[0]];
-
-            let mut remaining_nets: HashSet<_> = group[1..].iter().collect();
-
-            while !remaining_nets.is_empty() {
-                let start = state
-                    .find_all(group_root)
-                    .into_iter()
-                    .map(|coord| RoutingNode {
-                        coord,
-                        has_via: state.has_via(coord),
-                    })
-                    .collect::<Vec<_>>();
-                let path = dijkstra(
-                    start.iter(),
-                    |s, path| state.successors(*s, path, group_root).into_iter(),
-                    |node| {
-                        if let PointState::Routed { net, .. } = state[node.coord] {
-                            remaining_nets.contains(&net)
+            let bit = state.bus_bit(group[0]);
+
+            // Try to route each bus bit next to the previous one, falling back to
+            // unconstrained routing if no adjacent track works.
+            if let Some((bus, _)) = bit {
+                let layer = state.buses[bus].1;
+                if let Some(&(prev, dir)) = bus_tracks.get(&bus) {
+                    let candidates = match dir {
+                        Some(up) => vec![up],
+                        None => vec![true, false],
+                    };
+                    let mut routed = false;
+                    for up in candidates {
+                        let track = if up {
+                            prev.checked_add(1)
                         } else {
-                            false
+                            prev.checked_sub(1)
+                        };
+                        let Some(track) = track else {
+                            continue;
+                        };
+                        let mut trial = state.clone();
+                        let blocked = restrict_to_track(&mut trial, layer, track);
+                        let (group_paths, success) = route_group(&mut trial, group);
+                        if success && bus_track(&trial, &group_paths, layer) == Some(track) {
+                            for coord in blocked {
+                                trial[coord] = PointState::Available;
+                            }
+                            *state = trial;
+                            paths.extend(group_paths);
+                            bus_tracks.insert(bus, (track, Some(up)));
+                            routed = true;
+                            break;
                         }
-                    },
-                )
-                .unwrap_or_else(|| panic!("cannot connect all nodes in group {:?}", group_root))
-                .0;
-
-                let mut to_remove = HashSet::new();
-
-                let mut segment_path = Vec::new();
-                for nodes in path.windows(2) {
-                    if state.are_routed_for_same_net(nodes[0].coord, nodes[1].coord) {
+                    }
+                    if routed {
                         continue;
                     }
-                    segment_path.push((nodes[0].coord, nodes[1].coord));
                 }
+            }
 
-                for node in path.iter() {
-                    if let PointState::Routed { net, .. } = state[node.coord] {
-                        to_remove.insert(net);
+            let (group_paths, success) = route_group(state, group);
+            if !success {
+                unrouted.push(state.roots[&group[0]]);
+            }
+            if let Some((bus, _)) = bit {
+                match bus_track(state, &group_paths, state.buses[bus].1) {
+                    Some(track) => {
+                        bus_tracks.insert(bus, (track, None));
+                    }
+                    None => {
+                        bus_tracks.remove(&bus);
                     }
                 }
+            }
+            paths.extend(group_paths);
+        }
 
-                for nodes in path.windows(2) {
-                    match nodes[0].coord.layer.cmp(&nodes[1].coord.layer) {
-                        Ordering::Less => {
-                            let ilt = state.ilt_up(nodes[0].coord).unwrap();
-                            state[nodes[0].coord] = PointState::Routed {
-                                net: group_root,
-                                has_via: true,
-                            };
-                            state[nodes[1].coord] = PointState::Routed {
-                                net: group_root,
-                                has_via: true,
+        (paths, unrouted)
+    }
+}
+
+/// Routes a single group of nets, returning the paths drawn and whether all nets were connected.
+fn route_group(state: &mut RoutingState<PdkLayer>, group: &[NetId]) -> (Vec<Path>, bool) {
+    let group_root = state.roots[&group[0]];
+    let mut paths = Vec::new();
+
+    let mut remaining_nets: HashSet<_> = group[1..].iter().collect();
+
+    while !remaining_nets.is_empty() {
+        // Start from the lowest track index, then the lowest layer, so that
+        // equal-cost paths are always chosen in the same order.
+        let mut start = state
+            .find_all(group_root)
+            .into_iter()
+            .map(|coord| RoutingNode {
+                coord,
+                has_via: state.has_via(coord),
+            })
+            .collect::<Vec<_>>();
+        start.sort_by_key(|node| (node.coord.x, node.coord.y, node.coord.layer));
+        let Some((path, _)) = dijkstra(
+            start.iter(),
+            |s, path| state.successors(*s, path, group_root).into_iter(),
+            |node| {
+                if let PointState::Routed { net, .. } = state[node.coord] {
+                    remaining_nets.contains(&net)
+                } else {
+                    false
+                }
+            },
+        ) else {
+            return (paths, false);
+        };
+
+        let mut to_remove = HashSet::new();
+
+        let mut segment_path = Vec::new();
+        for nodes in path.windows(2) {
+            if state.are_routed_for_same_net(nodes[0].coord, nodes[1].coord) {
+                continue;
+            }
+            segment_path.push((nodes[0].coord, nodes[1].coord));
+        }
+
+        for node in path.iter() {
+            if let PointState::Routed { net, .. } = state[node.coord] {
+                to_remove.insert(net);
+            }
+        }
+
+        for nodes in path.windows(2) {
+            match nodes[0].coord.layer.cmp(&nodes[1].coord.layer) {
+                Ordering::Less => {
+                    let ilt = state.ilt_up(nodes[0].coord).unwrap();
+                    state[nodes[0].coord] = PointState::Routed {
+                        net: group_root,
+                        has_via: true,
+                    };
+                    state[nodes[1].coord] = PointState::Routed {
+                        net: group_root,
+                        has_via: true,
+                    };
+                    if let Some(requires) = ilt.requires {
+                        state[requires] = PointState::Reserved { net: group_root };
+                    }
+                }
+                Ordering::Greater => {
+                    let ilt = state.ilt_down(nodes[0].coord).unwrap();
+                    state[nodes[0].coord] = PointState::Routed {
+                        net: group_root,
+                        has_via: true,
+                    };
+                    state[nodes[1].coord] = PointState::Routed {
+                        net: group_root,
+                        has_via: true,
+                    };
+                    if let Some(requires) = ilt.requires {
+                        state[requires] = PointState::Reserved { net: group_root };
+                    }
+                }
+                Ordering::Equal => {
+                    for x in std::cmp::min(nodes[0].coord.x, nodes[1].coord.x)
+                        ..=std::cmp::max(nodes[0].coord.x, nodes[1].coord.x)
+                    {
+                        for y in std::cmp::min(nodes[0].coord.y, nodes[1].coord.y)
+                            ..=std::cmp::max(nodes[0].coord.y, nodes[1].coord.y)
+                        {
+                            let next = GridCoord {
+                                x,
+                                y,
+                                layer: nodes[0].coord.layer,
                             };
-                            if let Some(requires) = ilt.requires {
-                                state[requires] = PointState::Reserved { net: group_root };
+                            if let PointState::Routed { net, .. } = state[next] {
+                                to_remove.insert(net);
                             }
-                        }
-                        Ordering::Greater => {
-                            let ilt = state.ilt_down(nodes[0].coord).unwrap();
-                            state[nodes[0].coord] = PointState::Routed {
+                            state[next] = PointState::Routed {
                                 net: group_root,
-                                has_via: true,
+                                has_via: state.has_via(next),
                             };
-                            state[nodes[1].coord] = PointState::Routed {
-                                net: group_root,
-                                has_via: true,
-                            };
-                            if let Some(requires) = ilt.requires {
-                                state[requires] = PointState::Reserved { net: group_root };
-                            }
-                        }
-                        Ordering::Equal => {
-                            for x in std::cmp::min(nodes[0].coord.x, nodes[1].coord.x)
-                                ..=std::cmp::max(nodes[0].coord.x, nodes[1].coord.x)
-                            {
-                                for y in std::cmp::min(nodes[0].coord.y, nodes[1].coord.y)
-                                    ..=std::cmp::max(nodes[0].coord.y, nodes[1].coord.y)
-                                {
-                                    let next = GridCoord {
-                                        x,
-                                        y,
-                                        layer: nodes[0].coord.layer,
-                                    };
-                                    if let PointState::Routed { net, .. } = state[next] {
-                                        to_remove.insert(net);
-                                    }
-                                    state[next] = PointState::Routed {
-                                        net: group_root,
-                                        has_via: state.has_via(next),
-                                    };
-                                }
-                            }
                         }
                     }
                 }
-
-                for net in to_remove {
-                    state.relabel_net(net, group_root);
-                    remaining_nets.remove(&net);
-                }
-                paths.push(segment_path);
             }
         }
 
-        paths
+        let width = state.net_width(group_root);
+        for nodes in path.windows(2) {
+            state.reserve_for_width(nodes[0].coord, nodes[1].coord, group_root, width);
+        }
+
+        for net in to_remove {
+            state.relabel_net(net, group_root);
+            remaining_nets.remove(&net);
+        }
+        paths.push(segment_path);
+    }
+    (paths, true)
+}
+
+/// Returns the track occupied by the first segment of the given paths on `layer`, if any.
+fn bus_track(state: &RoutingState<PdkLayer>, paths: &[Path], layer: usize) -> Option<usize> {
+    let vert = state.grid.stack.layer(layer).dir().track_dir() == Dir::Vert;
+    paths
+        .iter()
+        .flatten()
+        .find(|(a, b)| a.layer == layer && b.layer == layer)
+        .map(|(a, _)| if vert { a.x } else { a.y })
+}
+
+/// Blocks all available grid points on `layer` that are not on the given track.
+///
+/// Returns the grid points that were blocked.
+fn restrict_to_track(
+    state: &mut RoutingState<PdkLayer>,
+    layer: usize,
+    track: usize,
+) -> Vec<GridCoord> {
+    let vert = state.grid.stack.layer(layer).dir().track_dir() == Dir::Vert;
+    let (nx, ny) = state.layer(layer).size();
+    let mut blocked = Vec::new();
+    for x in 0..nx {
+        for y in 0..ny {
+            let coord = GridCoord { layer, x, y };
+            if (if vert { x } else { y }) != track && state.is_available(coord) {
+                state[coord] = PointState::Blocked { has_via: false };
+                blocked.push(coord);
+            }
+        }
     }
+    blocked
 }
 
 /// An type capable of drawing vias.
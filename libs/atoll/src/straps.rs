@@ -6,6 +6,7 @@ use crate::grid::{AtollLayer, PdkLayer, RoutingState};
 use crate::route::Path;
 use crate::{NetId, PointState};
 use grid::Grid;
+use serde::{Deserialize, Serialize};
 use substrate::geometry::dir::Dir;
 use substrate::geometry::rect::Rect;
 
@@ -58,9 +59,29 @@ pub enum LayerStrappingParams {
     },
 }
 
+/// A strap segment drawn on a single track, in grid coordinates.
+///
+/// Straps drawn within a tile are exposed through its [`Abstract`](crate::abs::Abstract)
+/// so that parent tiles can continue them upward using vias instead of re-strapping.
+#[derive(Debug, Copy, Clone, Hash, Eq, PartialEq, Serialize, Deserialize)]
+pub struct StrapSegment {
+    /// The net of the strap.
+    pub net: NetId,
+    /// The layer of the strap.
+    pub layer: usize,
+    /// The grid track that the strap runs along.
+    pub track: usize,
+    /// The first grid coordinate along the track covered by the strap.
+    pub start: usize,
+    /// The last grid coordinate along the track covered by the strap.
+    pub stop: usize,
+}
+
 /// An ATOLL strapper.
 pub trait Strapper: Send + Sync {
     /// Returns paths that represent the straps and vias that were drawn.
+    ///
+    /// Straps inherited from child tiles are available via [`RoutingState::straps`].
     fn strap(
         &self,
         routing_state: &mut RoutingState<PdkLayer>,
@@ -79,6 +100,8 @@ struct Strap {
     start: usize,
     stop: usize,
     has_via: bool,
+    /// Whether the strap was drawn by a child tile.
+    inherited: bool,
 }
 
 struct GreedyStrapperState<'a> {
@@ -101,11 +124,36 @@ impl<'a> GreedyStrapperState<'a> {
 
             grid_to_strap.push(Grid::new(rows, cols));
         }
+        let mut tentative_straps = Vec::new();
+        for strap in routing_state.straps.iter() {
+            let track_dir = routing_state
+                .grid
+                .stack
+                .layer(strap.layer)
+                .dir()
+                .track_dir();
+            for track_coord in strap.start..=strap.stop {
+                let (x, y) = match track_dir {
+                    Dir::Horiz => (track_coord, strap.track),
+                    Dir::Vert => (strap.track, track_coord),
+                };
+                grid_to_strap[strap.layer][(x, y)] = Some(tentative_straps.len());
+            }
+            tentative_straps.push(Strap {
+                net: strap.net,
+                layer: strap.layer,
+                track: strap.track,
+                start: strap.start,
+                stop: strap.stop,
+                has_via: true,
+                inherited: true,
+            });
+        }
         Self {
             routing_state,
             to_strap,
             grid_to_strap,
-            tentative_straps: Vec::new(),
+            tentative_straps,
             paths: Vec::new(),
         }
     }
@@ -291,27 +339,14 @@ impl<'a> GreedyStrapperState<'a> {
                 let via_space = self.routing_state.grid.stack.layer(layer).via_spacing();
 
                 let (start_x, start_y, end_x, end_y) = if let Some(bbox) = &params.bounds {
-                    let pdk_layer = self.routing_state.grid.stack.layer(layer);
-                    let defining_layer = self
-                        .routing_state
-                        .grid
-                        .stack
-                        .layer(self.routing_state.grid.grid_defining_layer(layer));
-                    let parallel_pitch = pdk_layer.pitch();
-                    let perp_pitch = defining_layer.pitch();
-
-                    let (xpitch, ypitch) = match pdk_layer.dir().track_dir() {
-                        Dir::Horiz => (perp_pitch, parallel_pitch),
-                        Dir::Vert => (parallel_pitch, perp_pitch),
-                    };
+                    let grid = &self.routing_state.grid;
+                    let lcm_xpitch = grid.slice().lcm_unit_width();
+                    let lcm_ypitch = grid.slice().lcm_unit_height();
 
-                    let lcm_xpitch = self.routing_state.grid.slice().lcm_unit_width();
-                    let lcm_ypitch = self.routing_state.grid.slice().lcm_unit_height();
-
-                    let left = bbox.left() * lcm_xpitch / xpitch;
-                    let bot = bbox.bot() * lcm_ypitch / ypitch;
-                    let right = bbox.right() * lcm_xpitch / xpitch;
-                    let top = bbox.top() * lcm_ypitch / ypitch;
+                    let left = grid.xtracks(layer, bbox.left() * lcm_xpitch);
+                    let bot = grid.ytracks(layer, bbox.bot() * lcm_ypitch);
+                    let right = grid.xtracks(layer, bbox.right() * lcm_xpitch);
+                    let top = grid.ytracks(layer, bbox.top() * lcm_ypitch);
                     (
                         left as usize,
                         bot as usize,
@@ -369,6 +404,7 @@ impl<'a> GreedyStrapperState<'a> {
                                             start,
                                             stop,
                                             has_via: false,
+                                            inherited: false,
                                         },
                                         &params.layers[layer - params.start],
                                     );
@@ -389,6 +425,7 @@ impl<'a> GreedyStrapperState<'a> {
                                     start,
                                     stop,
                                     has_via: false,
+                                    inherited: false,
                                 },
                                 &params.layers[layer - params.start],
                             );
@@ -401,7 +438,7 @@ impl<'a> GreedyStrapperState<'a> {
 
     fn finalize_straps(&mut self) {
         for strap in &self.tentative_straps {
-            if !strap.has_via {
+            if !strap.has_via || strap.inherited {
                 continue;
             }
             let mut via_coords = Vec::new();
@@ -435,6 +472,13 @@ impl<'a> GreedyStrapperState<'a> {
                 strap.start,
             );
             let stop = std::cmp::min(*via_coords.last().unwrap() + 1, strap.stop);
+            self.routing_state.straps.push(StrapSegment {
+                net: strap.net,
+                layer: strap.layer,
+                track: strap.track,
+                start,
+                stop,
+            });
             for track_coord in start..=stop {
                 let track_dir = self
                     .routing_state
@@ -500,3 +544,176 @@ impl Strapper for GreedyStrapper {
         state.paths
     }
 }
+
+/// A strapper that applies a sequence of strappers in order.
+///
+/// Each strapper sees the occupancy and straps left by the strappers before it.
+#[derive(Default)]
+pub struct ChainStrapper {
+    strappers: Vec<Box<dyn Strapper>>,
+}
+
+impl ChainStrapper {
+    /// Creates a new [`ChainStrapper`] that applies the given strappers in order.
+    pub fn new(strappers: Vec<Box<dyn Strapper>>) -> Self {
+        Self { strappers }
+    }
+
+    /// Appends a strapper to the end of the chain.
+    pub fn push<T: Strapper + 'static>(&mut self, strapper: T) {
+        self.strappers.push(Box::new(strapper));
+    }
+}
+
+impl Strapper for ChainStrapper {
+    fn strap(
+        &self,
+        routing_state: &mut RoutingState<PdkLayer>,
+        to_strap: Vec<(NetId, StrappingParams)>,
+    ) -> Vec<Path> {
+        let mut paths = Vec::new();
+        for strapper in self.strappers.iter() {
+            paths.extend(strapper.strap(routing_state, to_strap.clone()));
+        }
+        paths
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::abs::GridCoord;
+    use crate::grid::{AbstractLayer, LayerStack, PdkLayer, RoutingState, TrackOffset};
+    use crate::route::Path;
+    use crate::straps::*;
+    use crate::RoutingDir;
+    use std::collections::HashMap;
+    use substrate::pdk::layers::LayerId;
+
+    fn layer_stack() -> LayerStack<PdkLayer> {
+        LayerStack {
+            layers: [RoutingDir::Horiz, RoutingDir::Vert]
+                .into_iter()
+                .map(|dir| PdkLayer {
+                    id: LayerId::default(),
+                    inner: AbstractLayer {
+                        dir,
+                        line: 100,
+                        space: 100,
+                        offset: TrackOffset::None,
+                        endcap: 20,
+                        via_spacing: 1,
+                        strap_via_spacing: 1,
+                        explicit_tracks: None,
+                    },
+                })
+                .collect(),
+            offset_x: 0,
+            offset_y: 0,
+        }
+    }
+
+    /// Draws a horizontal rail on layer 0 for each net to strap.
+    struct RailStrapper {
+        track: usize,
+        start: usize,
+        stop: usize,
+    }
+
+    impl Strapper for RailStrapper {
+        fn strap(
+            &self,
+            routing_state: &mut RoutingState<PdkLayer>,
+            to_strap: Vec<(NetId, StrappingParams)>,
+        ) -> Vec<Path> {
+            let mut paths = Vec::new();
+            for (net, _) in to_strap {
+                for x in self.start..=self.stop {
+                    routing_state.layer_mut(0)[(x, self.track)] = PointState::Routed {
+                        net,
+                        has_via: false,
+                    };
+                }
+                routing_state.straps.push(StrapSegment {
+                    net,
+                    layer: 0,
+                    track: self.track,
+                    start: self.start,
+                    stop: self.stop,
+                });
+                paths.push(vec![(
+                    GridCoord {
+                        layer: 0,
+                        x: self.start,
+                        y: self.track,
+                    },
+                    GridCoord {
+                        layer: 0,
+                        x: self.stop,
+                        y: self.track,
+                    },
+                )]);
+            }
+            paths
+        }
+    }
+
+    #[test]
+    fn chain_strapper_applies_strappers_in_order() {
+        let vdd = NetId(0);
+        let mut state = RoutingState::new(layer_stack(), 1, 8, 8);
+        state.roots = HashMap::from([(vdd, vdd)]);
+
+        let mut strapper = ChainStrapper::new(vec![Box::new(RailStrapper {
+            track: 2,
+            start: 1,
+            stop: 6,
+        })]);
+        strapper.push(GreedyStrapper);
+        let paths = strapper.strap(
+            &mut state,
+            vec![(
+                vdd,
+                StrappingParams::new(
+                    1,
+                    vec![LayerStrappingParams::OffsetPeriod {
+                        offset: 0,
+                        period: 3,
+                    }],
+                ),
+            )],
+        );
+
+        // The rail is drawn first and not redrawn by the grid strapper.
+        let rail = (
+            GridCoord {
+                layer: 0,
+                x: 1,
+                y: 2,
+            },
+            GridCoord {
+                layer: 0,
+                x: 6,
+                y: 2,
+            },
+        );
+        assert_eq!(paths[0], vec![rail]);
+        assert!(paths[1..]
+            .iter()
+            .flatten()
+            .all(|(a, b)| a.layer != 0 || b.layer != 0));
+
+        // The grid strapper vias down to the rail.
+        for x in [1, 4] {
+            let via = (
+                GridCoord { layer: 1, x, y: 2 },
+                GridCoord { layer: 0, x, y: 2 },
+            );
+            assert!(paths.contains(&vec![via]));
+        }
+        assert_eq!(state.straps()[0].layer, 0);
+        assert!(state.straps()[1..]
+            .iter()
+            .all(|strap| strap.layer == 1 && strap.net == vdd));
+        assert!(state.straps().len() > 1);
+    }
+}
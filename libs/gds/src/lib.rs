@@ -82,12 +82,13 @@ mod tests;
 #[doc(hidden)]
 mod write;
 
+use std::collections::{HashMap, HashSet};
 use std::convert::{TryFrom, TryInto};
 use std::error::Error;
 use std::fs::File;
 use std::io::{BufWriter, Cursor, Read, Seek, SeekFrom, Write};
 use std::path::Path;
-use std::sync::Arc;
+use std::sync::{Arc, Mutex};
 use std::{fmt, mem, str};
 
 use arcstr::ArcStr;
@@ -102,9 +103,10 @@ use serde::{Deserialize, Serialize};
 extern crate derive_builder;
 
 // Internal Modules
-use read::{GdsParser, GdsScanner, GdsStructScan};
+pub use read::{GdsParser, GdsReader, GdsStructStream};
+use read::{GdsScanner, GdsStructScan};
 pub use ser::{SerdeFile, SerializationFormat};
-use write::GdsWriter;
+pub use write::GdsWriter;
 
 /// An enumeration of GDS record types.
 ///
@@ -307,24 +309,39 @@ impl GdsFloat64 {
         }
     }
     /// Encodes `f64` to GDSII's eight bytes, stored as `u64`.
-    pub fn encode(mut val: f64) -> u64 {
+    ///
+    /// Values too small for the excess-64 exponent saturate to the smallest representable
+    /// magnitude. Non-finite values and values too large to represent return an error.
+    pub fn encode(val: f64) -> GdsResult<u64> {
         if val == 0.0 {
-            return 0;
-        };
-        let mut top: u8 = 0;
-        if val < 0.0 {
-            top = 0x80;
-            val = -val;
+            return Ok(0);
+        }
+        if !val.is_finite() {
+            return Err(format!("cannot encode non-finite value {val} as a GDSII float").into());
+        }
+        let sign: u64 = if val < 0.0 { 0x8000_0000_0000_0000 } else { 0 };
+        let mag = val.abs();
+        // Scale the magnitude by the given base-16 exponent into the range of a 56-bit mantissa
+        let scale = |exponent: i32| mag * 2f64.powi(56 - 4 * exponent);
+        // Normalize, correcting the initial estimate for any inexactness in `log2`
+        let mut exponent = ((0.25 * mag.log2()).floor() as i32 + 1).max(-64);
+        while scale(exponent) >= 2f64.powi(56) {
+            exponent += 1;
+        }
+        while scale(exponent) < 2f64.powi(52) && exponent > -64 {
+            exponent -= 1;
         }
-        let fexp: f64 = 0.25 * val.log2();
-        let mut exponent = fexp.ceil() as i32;
-        if fexp == fexp.ceil() {
+        let mut mantissa = scale(exponent).round();
+        if mantissa >= 2f64.powi(56) {
+            // Rounding carried out of the top hex digit
             exponent += 1;
+            mantissa = scale(exponent).round();
         }
-        let mantissa: u64 = (val * 16_f64.powi(14 - exponent)).round() as u64;
-        top += (64 + exponent) as u8;
-        let result: u64 = (top as u64).wrapping_shl(56) | (mantissa & 0x00FF_FFFF_FFFF_FFFF);
-        result
+        if exponent > 63 {
+            return Err(format!("value {val} exceeds the range of GDSII floats").into());
+        }
+        let mantissa = (mantissa as u64).max(1);
+        Ok(sign | (((64 + exponent) as u64) << 56) | mantissa)
     }
 }
 
@@ -357,6 +374,26 @@ pub struct GdsStrans {
     pub angle: Option<f64>,
 }
 
+impl GdsStrans {
+    /// Applies reflection, magnification, and rotation (in that order) to the point `(x, y)`.
+    fn apply(&self, x: i32, y: i32) -> (i32, i32) {
+        let mag = self.mag.unwrap_or(1.0);
+        let (x, y) = (x as f64 * mag, y as f64 * mag);
+        let y = if self.reflected { -y } else { y };
+        let (sin, cos) = match self.angle.unwrap_or(0.0).rem_euclid(360.0) {
+            a if a == 0.0 => (0.0, 1.0),
+            a if a == 90.0 => (1.0, 0.0),
+            a if a == 180.0 => (0.0, -1.0),
+            a if a == 270.0 => (-1.0, 0.0),
+            a => a.to_radians().sin_cos(),
+        };
+        (
+            (x * cos - y * sin).round() as i32,
+            (x * sin + y * cos).round() as i32,
+        )
+    }
+}
+
 /// GDS text presentation flags.
 ///
 /// Sets fonts, text justification, and the like.
@@ -561,6 +598,129 @@ pub struct GdsPath {
     #[builder(default, setter(strip_option))]
     pub properties: Vec<GdsProperty>,
 }
+impl GdsPath {
+    /// Number of segments used to approximate each semicircular end of a round-ended path.
+    pub const ROUND_END_SEGMENTS: usize = 16;
+
+    /// Expands the path's centerline into a polygon.
+    ///
+    /// The centerline is offset by half the path width on either side, with mitered joins.
+    /// Ends are treated according to `path_type`:
+    /// * 0 (or unset): flush with the end points.
+    /// * 1: rounded, approximated with [GdsPath::ROUND_END_SEGMENTS] segments per end.
+    /// * 2: square, extended by half the path width.
+    /// * 4: extended by `begin_extn` and `end_extn`.
+    ///
+    /// The resulting boundary is closed and wound counterclockwise.
+    /// Paths with fewer than two distinct points produce a boundary with no points.
+    /// Returns an error if the path's width is zero or unset.
+    pub fn to_boundary(&self) -> GdsResult<GdsBoundary> {
+        let width = match self.width {
+            Some(w) if w != 0 => w,
+            _ => {
+                return Err(GdsError::Str(format!(
+                    "cannot convert path on layer {}/{} with zero or missing width to a boundary",
+                    self.layer, self.datatype
+                )))
+            }
+        };
+        let mut pts: Vec<(f64, f64)> = Vec::with_capacity(self.xy.len());
+        for p in self.xy.iter() {
+            let p = (f64::from(p.x), f64::from(p.y));
+            if pts.last() != Some(&p) {
+                pts.push(p);
+            }
+        }
+        let mut boundary = GdsBoundary {
+            layer: self.layer,
+            datatype: self.datatype,
+            ..Default::default()
+        };
+        if pts.len() < 2 {
+            return Ok(boundary);
+        }
+
+        // Negative widths denote absolute widths, unaffected by magnification.
+        let hw = f64::from(width).abs() / 2.0;
+        let path_type = self.path_type.unwrap_or_default();
+        let (begin_extn, end_extn) = match path_type {
+            2 => (hw, hw),
+            4 => (
+                f64::from(self.begin_extn.unwrap_or_default()),
+                f64::from(self.end_extn.unwrap_or_default()),
+            ),
+            _ => (0.0, 0.0),
+        };
+
+        // Unit direction and left-hand normal of each segment.
+        let dirs: Vec<(f64, f64)> = pts
+            .windows(2)
+            .map(|w| {
+                let (dx, dy) = (w[1].0 - w[0].0, w[1].1 - w[0].1);
+                let len = dx.hypot(dy);
+                (dx / len, dy / len)
+            })
+            .collect();
+        let normal = |d: (f64, f64)| (-d.1, d.0);
+
+        let n = pts.len();
+        let (d0, dn) = (dirs[0], dirs[n - 2]);
+        pts[0] = (pts[0].0 - d0.0 * begin_extn, pts[0].1 - d0.1 * begin_extn);
+        pts[n - 1] = (
+            pts[n - 1].0 + dn.0 * end_extn,
+            pts[n - 1].1 + dn.1 * end_extn,
+        );
+
+        // Offsets from each vertex to the left side of the path.
+        let offsets: Vec<(f64, f64)> = (0..n)
+            .map(|i| {
+                let n1 = normal(dirs[i.saturating_sub(1)]);
+                let n2 = normal(dirs[std::cmp::min(i, n - 2)]);
+                let dot = n1.0 * n2.0 + n1.1 * n2.1;
+                if dot <= -1.0 + f64::EPSILON {
+                    // The path doubles back on itself; a miter is undefined.
+                    (n1.0 * hw, n1.1 * hw)
+                } else {
+                    let scale = hw / (1.0 + dot);
+                    ((n1.0 + n2.0) * scale, (n1.1 + n2.1) * scale)
+                }
+            })
+            .collect();
+
+        let arc = |center: (f64, f64), start: f64| {
+            (1..Self::ROUND_END_SEGMENTS).map(move |k| {
+                let theta =
+                    start + std::f64::consts::PI * k as f64 / Self::ROUND_END_SEGMENTS as f64;
+                (center.0 + hw * theta.cos(), center.1 + hw * theta.sin())
+            })
+        };
+
+        let mut poly = Vec::new();
+        // Right side, forward.
+        poly.extend((0..n).map(|i| (pts[i].0 - offsets[i].0, pts[i].1 - offsets[i].1)));
+        if path_type == 1 {
+            let angle = dn.1.atan2(dn.0);
+            poly.extend(arc(pts[n - 1], angle - std::f64::consts::FRAC_PI_2));
+        }
+        // Left side, backward.
+        poly.extend(
+            (0..n)
+                .rev()
+                .map(|i| (pts[i].0 + offsets[i].0, pts[i].1 + offsets[i].1)),
+        );
+        if path_type == 1 {
+            let angle = d0.1.atan2(d0.0);
+            poly.extend(arc(pts[0], angle + std::f64::consts::FRAC_PI_2));
+        }
+
+        boundary.xy = poly
+            .into_iter()
+            .map(|(x, y)| GdsPoint::new(x.round() as i32, y.round() as i32))
+            .collect();
+        boundary.xy.push(boundary.xy[0].clone());
+        Ok(boundary)
+    }
+}
 
 /// A GDS boundary element.
 ///
@@ -597,6 +757,271 @@ pub struct GdsBoundary {
     #[builder(default, setter(strip_option))]
     pub properties: Vec<GdsProperty>,
 }
+impl GdsBoundary {
+    /// Returns the winding order of the boundary's points.
+    ///
+    /// Computed from the sign of the polygon's area.
+    /// Degenerate (zero-area) boundaries are reported as [Winding::CounterClockwise].
+    pub fn winding(&self) -> Winding {
+        if self.signed_area2() < 0 {
+            Winding::Clockwise
+        } else {
+            Winding::CounterClockwise
+        }
+    }
+    /// Reverses the boundary's points if necessary to match winding order `w`.
+    ///
+    /// The first point is kept in place, so a closed boundary remains closed.
+    pub fn set_winding(&mut self, w: Winding) {
+        if self.winding() != w {
+            self.xy.reverse();
+        }
+    }
+
+    /// The maximum number of distinct vertices in a boundary.
+    ///
+    /// An XY record holds at most 8191 points, one of which repeats the first to close the polygon.
+    pub const MAX_VERTICES: usize = 8190;
+
+    /// Returns the number of distinct vertices, not counting a closing point.
+    pub fn num_vertices(&self) -> usize {
+        self.vertices().len()
+    }
+    /// Returns the boundary's points without the closing point, if present.
+    fn vertices(&self) -> &[GdsPoint] {
+        match self.xy.split_last() {
+            Some((last, rest)) if rest.first() == Some(last) => rest,
+            _ => &self.xy,
+        }
+    }
+    /// Splits the boundary into closed pieces with at most `max_verts` vertices each.
+    ///
+    /// Pieces are formed by repeatedly slicing the boundary in half along a vertical or
+    /// horizontal line through its median vertex, so their union covers the original boundary.
+    /// Coverage is preserved exactly for Manhattan boundaries; for other boundaries,
+    /// intersections with slicing lines are rounded to the nearest grid point.
+    /// Boundaries with at most `max_verts` vertices are returned unchanged.
+    ///
+    /// Pieces of concave boundaries may touch themselves along a slicing line.
+    /// Returns an error if some piece cannot be sliced into smaller pieces,
+    /// as can happen for self-intersecting boundaries.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `max_verts` is less than 4.
+    pub fn split(&self, max_verts: usize) -> GdsResult<Vec<GdsBoundary>> {
+        assert!(max_verts >= 4, "boundaries must allow at least 4 vertices");
+        if self.num_vertices() <= max_verts {
+            return Ok(vec![self.clone()]);
+        }
+
+        let mut pieces = Vec::new();
+        let mut stack = vec![self.vertices().to_vec()];
+        while let Some(pts) = stack.pop() {
+            if pts.len() <= max_verts {
+                pieces.push(pts);
+                continue;
+            }
+            let (lo, hi) = bisect_polygon(&pts);
+            if lo.len() >= pts.len() || hi.len() >= pts.len() {
+                return Err(GdsError::Str(format!(
+                    "cannot split boundary on layer {}, datatype {} into pieces with at most {} vertices",
+                    self.layer, self.datatype, max_verts
+                )));
+            }
+            stack.extend([lo, hi].into_iter().filter(|piece| piece.len() >= 3));
+        }
+
+        Ok(pieces
+            .into_iter()
+            .map(|mut xy| {
+                xy.push(xy[0].clone());
+                GdsBoundary {
+                    layer: self.layer,
+                    datatype: self.datatype,
+                    xy,
+                    elflags: self.elflags.clone(),
+                    plex: self.plex.clone(),
+                    properties: self.properties.clone(),
+                }
+            })
+            .collect())
+    }
+    /// Returns twice the signed area of the polygon, per the shoelace formula.
+    fn signed_area2(&self) -> i64 {
+        let n = self.xy.len();
+        (0..n)
+            .map(|i| {
+                let (a, b) = (&self.xy[i], &self.xy[(i + 1) % n]);
+                i64::from(a.x) * i64::from(b.y) - i64::from(b.x) * i64::from(a.y)
+            })
+            .sum()
+    }
+}
+
+/// Slices the (open) polygon `pts` in two along a line through its median vertex.
+///
+/// The line is perpendicular to the polygon's longer extent.
+fn bisect_polygon(pts: &[GdsPoint]) -> (Vec<GdsPoint>, Vec<GdsPoint>) {
+    let extent = |coord: fn(&GdsPoint) -> i32| {
+        let min = pts.iter().map(coord).min().unwrap_or_default();
+        let max = pts.iter().map(coord).max().unwrap_or_default();
+        (min, max)
+    };
+    let (xmin, xmax) = extent(|p| p.x);
+    let (ymin, ymax) = extent(|p| p.y);
+    let vertical = i64::from(xmax) - i64::from(xmin) >= i64::from(ymax) - i64::from(ymin);
+    let coord = |p: &GdsPoint| if vertical { p.x } else { p.y };
+    let (min, max) = if vertical { (xmin, xmax) } else { (ymin, ymax) };
+
+    let mut coords: Vec<i32> = pts.iter().map(coord).collect();
+    let mid = coords.len() / 2;
+    let mut cut = *coords.select_nth_unstable(mid).1;
+    if cut == min || cut == max {
+        cut = ((i64::from(min) + i64::from(max)) / 2) as i32;
+    }
+
+    (
+        clip_polygon(pts, vertical, cut, true),
+        clip_polygon(pts, vertical, cut, false),
+    )
+}
+
+/// Clips the (open) polygon `pts` to one side of the line `x = cut` (if `vertical`)
+/// or `y = cut` (otherwise), per the Sutherland-Hodgman algorithm.
+///
+/// Keeps the side with coordinates at most `cut` if `below`, and at least `cut` otherwise.
+fn clip_polygon(pts: &[GdsPoint], vertical: bool, cut: i32, below: bool) -> Vec<GdsPoint> {
+    let coords = |p: &GdsPoint| if vertical { (p.x, p.y) } else { (p.y, p.x) };
+    let point = |a: i32, b: i32| {
+        if vertical {
+            GdsPoint::new(a, b)
+        } else {
+            GdsPoint::new(b, a)
+        }
+    };
+    let inside = |p: &GdsPoint| {
+        let a = coords(p).0;
+        if below {
+            a <= cut
+        } else {
+            a >= cut
+        }
+    };
+    let intersect = |p: &GdsPoint, q: &GdsPoint| {
+        let ((pa, pb), (qa, qb)) = (coords(p), coords(q));
+        let t = (f64::from(cut) - f64::from(pa)) / (f64::from(qa) - f64::from(pa));
+        let b = f64::from(pb) + t * (f64::from(qb) - f64::from(pb));
+        point(cut, b.round() as i32)
+    };
+
+    let mut out: Vec<GdsPoint> = Vec::new();
+    for (i, q) in pts.iter().enumerate() {
+        let p = &pts[(i + pts.len() - 1) % pts.len()];
+        if inside(q) {
+            if !inside(p) {
+                out.push(intersect(p, q));
+            }
+            out.push(q.clone());
+        } else if inside(p) {
+            out.push(intersect(p, q));
+        }
+    }
+    out.dedup();
+    if out.len() > 1 && out.first() == out.last() {
+        out.pop();
+    }
+    out
+}
+
+/// The winding order of a polygon's points.
+#[derive(Debug, Clone, Copy, Deserialize, Serialize, PartialEq, Eq)]
+pub enum Winding {
+    /// Points are ordered clockwise.
+    Clockwise,
+    /// Points are ordered counterclockwise.
+    CounterClockwise,
+}
+
+/// A geometry problem found by [GdsLibrary::validate_geometry].
+///
+/// Each issue identifies the offending element by the name of its struct and its index
+/// in the struct's `elems`. A human-readable description is available via [Display](fmt::Display).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum GdsGeometryIssue {
+    /// A boundary whose first and last points differ.
+    UnclosedBoundary {
+        /// The name of the containing struct.
+        struct_name: ArcStr,
+        /// The index of the boundary in the struct's elements.
+        index: usize,
+    },
+    /// A boundary with fewer than four points, which cannot form a closed polygon.
+    TooFewPoints {
+        /// The name of the containing struct.
+        struct_name: ArcStr,
+        /// The index of the boundary in the struct's elements.
+        index: usize,
+        /// The number of points in the boundary.
+        points: usize,
+    },
+    /// A path with a negative width.
+    NegativePathWidth {
+        /// The name of the containing struct.
+        struct_name: ArcStr,
+        /// The index of the path in the struct's elements.
+        index: usize,
+        /// The width of the path.
+        width: i32,
+    },
+}
+
+impl GdsGeometryIssue {
+    /// The name of the struct containing the offending element.
+    pub fn struct_name(&self) -> &ArcStr {
+        match self {
+            Self::UnclosedBoundary { struct_name, .. }
+            | Self::TooFewPoints { struct_name, .. }
+            | Self::NegativePathWidth { struct_name, .. } => struct_name,
+        }
+    }
+
+    /// The index of the offending element in its struct's elements.
+    pub fn index(&self) -> usize {
+        match self {
+            Self::UnclosedBoundary { index, .. }
+            | Self::TooFewPoints { index, .. }
+            | Self::NegativePathWidth { index, .. } => *index,
+        }
+    }
+}
+
+impl fmt::Display for GdsGeometryIssue {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::UnclosedBoundary { struct_name, index } => write!(
+                f,
+                "boundary {index} in struct `{struct_name}` is not closed"
+            ),
+            Self::TooFewPoints {
+                struct_name,
+                index,
+                points,
+            } => write!(
+                f,
+                "boundary {index} in struct `{struct_name}` has only {points} points"
+            ),
+            Self::NegativePathWidth {
+                struct_name,
+                index,
+                width,
+            } => write!(
+                f,
+                "path {index} in struct `{struct_name}` has negative width {width}"
+            ),
+        }
+    }
+}
 
 /// A GDS struct reference (cell instance).
 ///
@@ -635,6 +1060,37 @@ pub struct GdsStructRef {
     pub properties: Vec<GdsProperty>,
 }
 
+impl GdsStructRef {
+    /// Returns the lower-left and upper-right corners of a child bounding box
+    /// after placement by this reference.
+    ///
+    /// Applies reflection, magnification, and rotation from `strans` (if any),
+    /// followed by translation to `xy`. Corners are rounded to the nearest database unit.
+    pub fn placed_bbox(&self, child_bbox: (GdsPoint, GdsPoint)) -> (GdsPoint, GdsPoint) {
+        let (ll, ur) = child_bbox;
+        let corners = [(ll.x, ll.y), (ur.x, ll.y), (ur.x, ur.y), (ll.x, ur.y)];
+        let placed: Vec<_> = corners
+            .into_iter()
+            .map(|(x, y)| {
+                let (x, y) = match &self.strans {
+                    Some(strans) => strans.apply(x, y),
+                    None => (x, y),
+                };
+                GdsPoint::new(x + self.xy.x, y + self.xy.y)
+            })
+            .collect();
+        placed
+            .iter()
+            .skip(1)
+            .fold((placed[0].clone(), placed[0].clone()), |(ll, ur), p| {
+                (
+                    GdsPoint::new(ll.x.min(p.x), ll.y.min(p.y)),
+                    GdsPoint::new(ur.x.max(p.x), ur.y.max(p.y)),
+                )
+            })
+    }
+}
+
 /// A GDS array reference.
 ///
 /// A two-dimensional array of struct (cell) instances.
@@ -671,6 +1127,96 @@ pub struct GdsArrayRef {
     #[builder(default, setter(strip_option))]
     pub properties: Vec<GdsProperty>,
 }
+impl GdsArrayRef {
+    /// Creates a new [GdsArrayRef] from an origin, per-column and per-row pitches, and counts.
+    ///
+    /// Computes the three control points required by the GDSII `XY` record:
+    /// the origin, the origin displaced by `cols` column pitches,
+    /// and the origin displaced by `rows` row pitches.
+    pub fn grid(
+        name: impl Into<ArcStr>,
+        origin: GdsPoint,
+        col_pitch: GdsPoint,
+        row_pitch: GdsPoint,
+        cols: i16,
+        rows: i16,
+    ) -> Self {
+        let (ncols, nrows) = (i32::from(cols), i32::from(rows));
+        Self {
+            name: name.into(),
+            xy: [
+                origin.clone(),
+                GdsPoint::new(
+                    origin.x + ncols * col_pitch.x,
+                    origin.y + ncols * col_pitch.y,
+                ),
+                GdsPoint::new(
+                    origin.x + nrows * row_pitch.x,
+                    origin.y + nrows * row_pitch.y,
+                ),
+            ],
+            cols,
+            rows,
+            ..Default::default()
+        }
+    }
+    /// Expands the array into one [GdsStructRef] per array element.
+    ///
+    /// References are produced in row-major order, starting from the origin.
+    pub fn expand(&self) -> Vec<GdsStructRef> {
+        let (cols, rows) = (i64::from(self.cols), i64::from(self.rows));
+        let mut refs = Vec::with_capacity((cols * rows).max(0) as usize);
+        for r in 0..rows {
+            for c in 0..cols {
+                refs.push(self.element(c, r));
+            }
+        }
+        refs
+    }
+    /// Returns a [GdsStructRef] to the array element in column `c` and row `r`.
+    fn element(&self, c: i64, r: i64) -> GdsStructRef {
+        let [origin, col_end, row_end] = &self.xy;
+        let (cols, rows) = (i64::from(self.cols), i64::from(self.rows));
+        let offset = |o: i32, ce: i32, re: i32| -> i32 {
+            let col_ofs = (i64::from(ce) - i64::from(o)) * c / cols;
+            let row_ofs = (i64::from(re) - i64::from(o)) * r / rows;
+            (i64::from(o) + col_ofs + row_ofs) as i32
+        };
+        GdsStructRef {
+            name: self.name.clone(),
+            xy: GdsPoint::new(
+                offset(origin.x, col_end.x, row_end.x),
+                offset(origin.y, col_end.y, row_end.y),
+            ),
+            strans: self.strans.clone(),
+            elflags: self.elflags.clone(),
+            plex: self.plex.clone(),
+            properties: self.properties.clone(),
+        }
+    }
+    /// Returns references to the (up to four) corner elements of the array.
+    ///
+    /// Element placements vary linearly with their column and row,
+    /// so the corner elements bound the placements of all others.
+    fn corners(&self) -> Vec<GdsStructRef> {
+        let (cols, rows) = (i64::from(self.cols), i64::from(self.rows));
+        if cols <= 0 || rows <= 0 {
+            return Vec::new();
+        }
+        let mut corners = Vec::with_capacity(4);
+        for r in [0, rows - 1] {
+            for c in [0, cols - 1] {
+                if !corners.contains(&(c, r)) {
+                    corners.push((c, r));
+                }
+            }
+        }
+        corners
+            .into_iter()
+            .map(|(c, r)| self.element(c, r))
+            .collect()
+    }
+}
 
 /// A GDS text element.
 ///
@@ -800,6 +1346,84 @@ pub enum GdsElement {
     GdsBox(GdsBox),
 }
 
+/// Returns the smallest bounding box containing both `a` (if any) and `b`.
+fn bbox_union(a: Option<(GdsPoint, GdsPoint)>, b: (GdsPoint, GdsPoint)) -> (GdsPoint, GdsPoint) {
+    match a {
+        Some((ll, ur)) => (
+            GdsPoint::new(ll.x.min(b.0.x), ll.y.min(b.0.y)),
+            GdsPoint::new(ur.x.max(b.1.x), ur.y.max(b.1.y)),
+        ),
+        None => b,
+    }
+}
+
+/// Returns the bounding box of struct `name` including all referenced geometry,
+/// memoizing results in `cache`.
+///
+/// Structs currently being visited are cached as [None] to detect cycles.
+fn hierarchical_bbox<'a>(
+    name: &'a str,
+    structs: &HashMap<&'a str, &'a GdsStruct>,
+    cache: &mut HashMap<&'a str, Option<Option<(GdsPoint, GdsPoint)>>>,
+) -> GdsResult<Option<(GdsPoint, GdsPoint)>> {
+    match cache.get(name) {
+        Some(Some(bbox)) => return Ok(bbox.clone()),
+        Some(None) => {
+            return Err(format!("struct hierarchy contains a cycle through `{name}`").into())
+        }
+        None => {}
+    }
+    cache.insert(name, None);
+
+    let strukt = structs[name];
+    let mut bbox = strukt.bbox(None);
+    for elem in strukt.elems.iter() {
+        let refs = match elem {
+            GdsElement::GdsStructRef(r) => vec![r.clone()],
+            GdsElement::GdsArrayRef(a) => a.corners(),
+            _ => continue,
+        };
+        let Some(child) = refs.first().map(|r| r.name.as_str()) else {
+            continue;
+        };
+        let Some(&child) = structs.get_key_value(child).map(|(k, _)| k) else {
+            return Err(format!("struct `{name}` references undefined struct `{child}`").into());
+        };
+        if let Some(child_bbox) = hierarchical_bbox(child, structs, cache)? {
+            for r in refs.iter() {
+                bbox = Some(bbox_union(bbox, r.placed_bbox(child_bbox.clone())));
+            }
+        }
+    }
+
+    cache.insert(name, Some(bbox.clone()));
+    Ok(bbox)
+}
+
+/// The key used to canonically order elements in [GdsLibrary::sort_elements].
+fn element_sort_key(elem: &GdsElement) -> (Option<(i16, i16)>, (i32, i32), String) {
+    use GdsElement::*;
+    let (layerspec, points): (Option<GdsLayerSpec>, &[GdsPoint]) = match elem {
+        GdsBoundary(b) => (Some(b.layerspec()), &b.xy),
+        GdsPath(p) => (Some(p.layerspec()), &p.xy),
+        GdsStructRef(s) => (None, std::slice::from_ref(&s.xy)),
+        GdsArrayRef(a) => (None, &a.xy),
+        GdsTextElem(t) => (Some(t.layerspec()), std::slice::from_ref(&t.xy)),
+        GdsNode(n) => (Some(n.layerspec()), &n.xy),
+        GdsBox(b) => (Some(b.layerspec()), &b.xy),
+    };
+    let min = points.iter().fold((i32::MAX, i32::MAX), |(x, y), p| {
+        (std::cmp::min(x, p.x), std::cmp::min(y, p.y))
+    });
+    (
+        layerspec.map(|spec| (spec.layer, spec.xtype)),
+        min,
+        // Elements contain floating point fields, so their debug representation
+        // is used as a deterministic tie-breaker.
+        format!("{elem:?}"),
+    )
+}
+
 /// GDS summary stats.
 ///
 /// Summary statistics for a [GdsLibrary] or [GdsStruct].  
@@ -871,6 +1495,40 @@ impl GdsStruct {
             ..Default::default()
         }
     }
+
+    /// Returns the lower-left and upper-right corners of the bounding box of this struct.
+    ///
+    /// Aggregates over boundaries, paths (including their width), nodes, text positions, and boxes.
+    /// Struct and array references are ignored, since resolving them requires the whole library;
+    /// use [GdsLibrary::bbox] to include referenced geometry.
+    /// If `layers` is provided, only elements on the given layers are considered.
+    /// Returns [`None`] if no elements are considered.
+    pub fn bbox(&self, layers: Option<&HashSet<GdsLayerSpec>>) -> Option<(GdsPoint, GdsPoint)> {
+        let included = |elem: &dyn HasLayer| layers.map_or(true, |l| l.contains(&elem.layerspec()));
+        let mut points = Vec::new();
+        for elem in &self.elems {
+            match elem {
+                GdsElement::GdsBoundary(b) if included(b) => points.extend_from_slice(&b.xy),
+                GdsElement::GdsPath(p) if included(p) => match p.to_boundary() {
+                    Ok(boundary) => points.extend(boundary.xy),
+                    // Zero-width paths cover only their centerline.
+                    Err(_) => points.extend_from_slice(&p.xy),
+                },
+                GdsElement::GdsTextElem(t) if included(t) => points.push(t.xy.clone()),
+                GdsElement::GdsNode(n) if included(n) => points.extend_from_slice(&n.xy),
+                GdsElement::GdsBox(b) if included(b) => points.extend_from_slice(&b.xy),
+                _ => {}
+            }
+        }
+        let first = points.first()?.clone();
+        Some(points.iter().fold((first.clone(), first), |(ll, ur), p| {
+            (
+                GdsPoint::new(ll.x.min(p.x), ll.y.min(p.y)),
+                GdsPoint::new(ur.x.max(p.x), ur.y.max(p.y)),
+            )
+        }))
+    }
+
     /// Counts and returns element statistics.
     fn stats(&self) -> GdsStats {
         let mut stats = GdsStats::default();
@@ -949,7 +1607,28 @@ pub struct GdsLibrary {
     pub format_type: Unsupported,
 }
 
+/// The versioned JSON representation of a [GdsLibrary].
+///
+/// Produced by [GdsLibrary::to_json_versioned].
+#[derive(Serialize)]
+struct VersionedJson<'a> {
+    schema_version: &'a str,
+    library: &'a GdsLibrary,
+}
+
+/// A [VersionedJson] whose library has not yet been deserialized.
+#[derive(Deserialize)]
+struct VersionedJsonRaw {
+    schema_version: String,
+    library: serde_json::Value,
+}
+
 impl GdsLibrary {
+    /// The version of the JSON schema written by [GdsLibrary::to_json_versioned].
+    ///
+    /// Incremented whenever the serialized shape of a [GdsLibrary] changes incompatibly.
+    pub const JSON_SCHEMA_VERSION: &'static str = "1";
+
     /// Creates a new and empty [GdsLibrary].
     pub fn new(name: impl Into<ArcStr>) -> Self {
         Self {
@@ -975,12 +1654,59 @@ impl GdsLibrary {
         GdsParser::open(fname)?.parse_lib()
     }
 
+    /// Streams the structs of the GDS file at path `fname`, parsing one struct at a time.
+    ///
+    /// Unlike [`GdsLibrary::load`], the file is read through a buffer and only the
+    /// current struct is held in memory. Errors are yielded as `Err` items.
+    pub fn stream_structs(fname: impl AsRef<Path>) -> impl Iterator<Item = GdsResult<GdsStruct>> {
+        GdsStructStream::open(fname)
+    }
+
+    /// Reads as much as possible of a possibly truncated or corrupt GDS file at path `fname`.
+    ///
+    /// Parsing stops at the first unrecoverable record. Returns a library containing
+    /// every struct read completely before that point, along with the errors encountered.
+    pub fn load_lossy(fname: impl AsRef<Path>) -> (GdsLibrary, Vec<GdsError>) {
+        match GdsParser::open(fname) {
+            Ok(mut parser) => parser.parse_lib_lossy(),
+            Err(e) => (GdsLibrary::default(), vec![e]),
+        }
+    }
+
     /// Reads a [GdsLibrary] from byte-vector `bytes`.
     pub fn from_bytes(bytes: Vec<u8>) -> GdsResult<GdsLibrary> {
         // Create the parser, and parse a Library
         GdsParser::from_bytes(bytes)?.parse_lib()
     }
 
+    /// Serializes to JSON, wrapped in an object alongside its schema version.
+    ///
+    /// The output has the shape `{"schema_version": ..., "library": ...}`,
+    /// where `schema_version` is [GdsLibrary::JSON_SCHEMA_VERSION].
+    pub fn to_json_versioned(&self) -> String {
+        serde_json::to_string(&VersionedJson {
+            schema_version: Self::JSON_SCHEMA_VERSION,
+            library: self,
+        })
+        .expect("GDS libraries are always serializable to JSON")
+    }
+
+    /// Reads a [GdsLibrary] from JSON written by [GdsLibrary::to_json_versioned].
+    ///
+    /// Returns an error if the schema version is not [GdsLibrary::JSON_SCHEMA_VERSION].
+    pub fn from_json_versioned(s: &str) -> GdsResult<GdsLibrary> {
+        let raw: VersionedJsonRaw =
+            serde_json::from_str(s).map_err(|e| GdsError::Boxed(Arc::new(e)))?;
+        if raw.schema_version != Self::JSON_SCHEMA_VERSION {
+            return Err(GdsError::Str(format!(
+                "unsupported GDS JSON schema version {:?} (expected {:?})",
+                raw.schema_version,
+                Self::JSON_SCHEMA_VERSION
+            )));
+        }
+        serde_json::from_value(raw.library).map_err(|e| GdsError::Boxed(Arc::new(e)))
+    }
+
     /// Runs a first-pass scan of GDSII data in `fname`.
     ///
     /// Returns a vector of [GdsStructScan]s including summary info per struct.
@@ -1000,6 +1726,303 @@ impl GdsLibrary {
         stats
     }
 
+    /// Extracts the struct named `top` and every struct it references into a new library.
+    ///
+    /// References are followed through both [GdsStructRef]s and [GdsArrayRef]s.
+    /// The library name, version, dates, and units are preserved.
+    /// Returns an error if `top` or any struct it references is not defined in this library.
+    pub fn subset(&self, top: &str) -> GdsResult<GdsLibrary> {
+        self.subset_with_options(top, &GdsFlattenOptions::default())
+    }
+
+    /// Extracts the struct named `top` and every struct it references into a new library,
+    /// post-processing the result according to `options`.
+    ///
+    /// See [GdsLibrary::subset].
+    pub fn subset_with_options(
+        &self,
+        top: &str,
+        options: &GdsFlattenOptions,
+    ) -> GdsResult<GdsLibrary> {
+        let structs: HashMap<&str, &GdsStruct> = self
+            .structs
+            .iter()
+            .map(|strukt| (strukt.name.as_str(), strukt))
+            .collect();
+        if !structs.contains_key(top) {
+            return Err(format!("struct `{top}` not found in library `{}`", self.name).into());
+        }
+
+        let mut reachable = HashSet::from([top]);
+        let mut stack = vec![top];
+        while let Some(name) = stack.pop() {
+            for elem in structs[name].elems.iter() {
+                let child = match elem {
+                    GdsElement::GdsStructRef(r) => r.name.as_str(),
+                    GdsElement::GdsArrayRef(r) => r.name.as_str(),
+                    _ => continue,
+                };
+                if !structs.contains_key(child) {
+                    return Err(
+                        format!("struct `{name}` references undefined struct `{child}`").into(),
+                    );
+                }
+                if reachable.insert(child) {
+                    stack.push(child);
+                }
+            }
+        }
+
+        let mut lib = GdsLibrary {
+            name: self.name.clone(),
+            version: self.version,
+            dates: self.dates.clone(),
+            units: self.units.clone(),
+            structs: self
+                .structs
+                .iter()
+                .filter(|strukt| reachable.contains(strukt.name.as_str()))
+                .cloned()
+                .collect(),
+            ..Default::default()
+        };
+        if options.expand_arrays {
+            lib.expand_arrays();
+        }
+        Ok(lib)
+    }
+
+    /// Returns the lower-left and upper-right corners of the bounding box of this library.
+    ///
+    /// Unions the bounding boxes of all top-level structs, i.e. those not referenced by
+    /// any other struct. Geometry placed by struct and array references is included,
+    /// transformed according to each reference's [GdsStrans].
+    /// Returns `Ok(None)` if the library contains no geometry, and an error if a reference
+    /// names an undefined struct or the hierarchy is cyclic.
+    pub fn bbox(&self) -> GdsResult<Option<(GdsPoint, GdsPoint)>> {
+        let structs: HashMap<&str, &GdsStruct> = self
+            .structs
+            .iter()
+            .map(|strukt| (strukt.name.as_str(), strukt))
+            .collect();
+        let referenced: HashSet<&str> = self
+            .structs
+            .iter()
+            .flat_map(|strukt| strukt.elems.iter())
+            .filter_map(|elem| match elem {
+                GdsElement::GdsStructRef(r) => Some(r.name.as_str()),
+                GdsElement::GdsArrayRef(r) => Some(r.name.as_str()),
+                _ => None,
+            })
+            .collect();
+
+        let mut cache = HashMap::new();
+        let mut bbox = None;
+        for strukt in self.structs.iter() {
+            if referenced.contains(strukt.name.as_str()) {
+                continue;
+            }
+            if let Some(b) = hierarchical_bbox(strukt.name.as_str(), &structs, &mut cache)? {
+                bbox = Some(bbox_union(bbox, b));
+            }
+        }
+        // Structs not reachable from any top-level struct may only be referenced by a cycle.
+        for strukt in self.structs.iter() {
+            if !cache.contains_key(strukt.name.as_str()) {
+                hierarchical_bbox(strukt.name.as_str(), &structs, &mut cache)?;
+            }
+        }
+        Ok(bbox)
+    }
+
+    /// Appends all structs of `other` to this library.
+    ///
+    /// Incoming structs whose names collide with structs already in this library are
+    /// renamed using `rename`, which is applied repeatedly until the name is unique.
+    /// References within `other` are updated to match, so they never bind to an
+    /// existing struct of the same original name. Non-colliding structs keep their names.
+    pub fn merge(&mut self, mut other: GdsLibrary, rename: impl Fn(&str) -> ArcStr) {
+        let existing: HashSet<ArcStr> = self.structs.iter().map(|s| s.name.clone()).collect();
+        let mut taken: HashSet<ArcStr> = existing
+            .iter()
+            .cloned()
+            .chain(other.structs.iter().map(|s| s.name.clone()))
+            .collect();
+        let mut renames = HashMap::new();
+        for strukt in other.structs.iter_mut() {
+            if !existing.contains(&strukt.name) {
+                continue;
+            }
+            let mut name = rename(&strukt.name);
+            while taken.contains(&name) {
+                name = rename(&name);
+            }
+            taken.insert(name.clone());
+            renames.insert(mem::replace(&mut strukt.name, name.clone()), name);
+        }
+
+        for elem in other.structs.iter_mut().flat_map(|s| s.elems.iter_mut()) {
+            let name = match elem {
+                GdsElement::GdsStructRef(r) => &mut r.name,
+                GdsElement::GdsArrayRef(r) => &mut r.name,
+                _ => continue,
+            };
+            if let Some(new) = renames.get(&*name) {
+                *name = new.clone();
+            }
+        }
+        self.structs.extend(other.structs);
+    }
+
+    /// Replaces every [GdsArrayRef] with one [GdsStructRef] per array element.
+    ///
+    /// See [GdsArrayRef::expand].
+    pub fn expand_arrays(&mut self) {
+        for strukt in self.structs.iter_mut() {
+            for elem in mem::take(&mut strukt.elems) {
+                match elem {
+                    GdsElement::GdsArrayRef(a) => strukt
+                        .elems
+                        .extend(a.expand().into_iter().map(GdsElement::from)),
+                    elem => strukt.elems.push(elem),
+                }
+            }
+        }
+    }
+
+    /// Splits every boundary with more than `max_verts` vertices into smaller boundaries.
+    ///
+    /// See [GdsBoundary::split]. Use [GdsBoundary::MAX_VERTICES] to produce valid GDSII.
+    /// If any boundary cannot be split, returns an error and leaves the library unchanged.
+    pub fn split_large_boundaries(&mut self, max_verts: usize) -> GdsResult<()> {
+        let mut splits = Vec::new();
+        for (i, strukt) in self.structs.iter().enumerate() {
+            for (j, elem) in strukt.elems.iter().enumerate() {
+                if let GdsElement::GdsBoundary(b) = elem {
+                    if b.num_vertices() > max_verts {
+                        splits.push(((i, j), b.split(max_verts)?));
+                    }
+                }
+            }
+        }
+        let mut splits = splits.into_iter().peekable();
+        for (i, strukt) in self.structs.iter_mut().enumerate() {
+            for (j, elem) in mem::take(&mut strukt.elems).into_iter().enumerate() {
+                match splits.next_if(|(idx, _)| *idx == (i, j)) {
+                    Some((_, pieces)) => strukt
+                        .elems
+                        .extend(pieces.into_iter().map(GdsElement::from)),
+                    None => strukt.elems.push(elem),
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Sorts the elements of each struct into a canonical order.
+    ///
+    /// Elements are ordered by layer and xtype, then by the minimum corner of their points.
+    /// References, which have no layer, are placed first.
+    /// Any remaining ties are broken by the element contents,
+    /// so equivalent libraries produce identical element orders.
+    pub fn sort_elements(&mut self) {
+        for strukt in self.structs.iter_mut() {
+            strukt.elems.sort_by_cached_key(element_sort_key);
+        }
+    }
+
+    /// Rewrites the layer and xtype of every element according to `map`.
+    ///
+    /// Elements whose [GdsLayerSpec] is absent from `map`, and references, which have
+    /// no layer, are left unchanged. Returns the number of elements modified.
+    pub fn remap_layers(&mut self, map: &HashMap<GdsLayerSpec, GdsLayerSpec>) -> usize {
+        fn remap(elem: &mut impl HasLayer, map: &HashMap<GdsLayerSpec, GdsLayerSpec>) -> usize {
+            match map.get(&elem.layerspec()) {
+                Some(&spec) => {
+                    elem.set_layerspec(spec);
+                    1
+                }
+                None => 0,
+            }
+        }
+        let mut count = 0;
+        for elem in self.structs.iter_mut().flat_map(|s| s.elems.iter_mut()) {
+            count += match elem {
+                GdsElement::GdsBoundary(b) => remap(b, map),
+                GdsElement::GdsPath(p) => remap(p, map),
+                GdsElement::GdsTextElem(t) => remap(t, map),
+                GdsElement::GdsNode(n) => remap(n, map),
+                GdsElement::GdsBox(b) => remap(b, map),
+                GdsElement::GdsStructRef(_) | GdsElement::GdsArrayRef(_) => 0,
+            };
+        }
+        count
+    }
+
+    /// Checks that the library can be represented in GDSII.
+    ///
+    /// Returns an error if any boundary has more than [GdsBoundary::MAX_VERTICES] vertices.
+    /// Such boundaries can be fixed using [GdsLibrary::split_large_boundaries].
+    pub fn validate(&self) -> GdsResult<()> {
+        for strukt in self.structs.iter() {
+            for elem in strukt.elems.iter() {
+                if let GdsElement::GdsBoundary(b) = elem {
+                    if b.num_vertices() > GdsBoundary::MAX_VERTICES {
+                        return Err(format!(
+                            "boundary in struct `{}` has {} vertices, exceeding the limit of {}",
+                            strukt.name,
+                            b.num_vertices(),
+                            GdsBoundary::MAX_VERTICES
+                        )
+                        .into());
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Checks the geometry of every element against the GDSII spec, without modifying the library.
+    ///
+    /// Flags boundaries that are not closed or have fewer than four points,
+    /// and paths with negative widths. Returns an empty vector if no issues are found.
+    pub fn validate_geometry(&self) -> Vec<GdsGeometryIssue> {
+        let mut issues = Vec::new();
+        for strukt in self.structs.iter() {
+            for (index, elem) in strukt.elems.iter().enumerate() {
+                let struct_name = || strukt.name.clone();
+                match elem {
+                    GdsElement::GdsBoundary(b) => {
+                        if b.xy.len() < 4 {
+                            issues.push(GdsGeometryIssue::TooFewPoints {
+                                struct_name: struct_name(),
+                                index,
+                                points: b.xy.len(),
+                            });
+                        }
+                        if b.xy.first() != b.xy.last() {
+                            issues.push(GdsGeometryIssue::UnclosedBoundary {
+                                struct_name: struct_name(),
+                                index,
+                            });
+                        }
+                    }
+                    GdsElement::GdsPath(GdsPath {
+                        width: Some(width), ..
+                    }) if *width < 0 => {
+                        issues.push(GdsGeometryIssue::NegativePathWidth {
+                            struct_name: struct_name(),
+                            index,
+                            width: *width,
+                        });
+                    }
+                    _ => {}
+                }
+            }
+        }
+        issues
+    }
+
     /// Saves to file at path `fname`.
     pub fn save(&self, fname: impl AsRef<Path>) -> GdsResult<()> {
         if let Some(prefix) = fname.as_ref().parent() {
@@ -1014,6 +2037,60 @@ impl GdsLibrary {
         let mut wr = GdsWriter::new(file);
         wr.write_lib(self)
     }
+
+    /// Saves to file at path `fname`, re-encoding only the structs marked dirty in `dirty`.
+    ///
+    /// Structs that are not dirty are written from the bytes cached in `dirty` by a previous
+    /// save, so the output is identical to that of [`GdsLibrary::save`] as long as every
+    /// modified struct has been marked dirty.
+    pub fn save_incremental(
+        &self,
+        fname: impl AsRef<Path>,
+        dirty: &StructDirtySet,
+    ) -> GdsResult<()> {
+        if let Some(prefix) = fname.as_ref().parent() {
+            std::fs::create_dir_all(prefix)?;
+        }
+        let mut wr = GdsWriter::open(fname)?;
+        wr.write_lib_incremental(self, dirty)
+    }
+}
+
+/// Options for extracting libraries with [`GdsLibrary::subset_with_options`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct GdsFlattenOptions {
+    /// Whether to replace each [GdsArrayRef] with individual [GdsStructRef]s.
+    pub expand_arrays: bool,
+}
+
+/// A set of modified struct names, along with cached encodings of each struct
+/// written by [`GdsLibrary::save_incremental`].
+#[derive(Debug, Default)]
+pub struct StructDirtySet {
+    dirty: HashSet<ArcStr>,
+    cache: Mutex<HashMap<ArcStr, Vec<u8>>>,
+}
+
+impl StructDirtySet {
+    /// Creates a new [`StructDirtySet`] with no dirty structs and an empty cache.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Marks the struct named `name` as modified since the last save.
+    pub fn mark_dirty(&mut self, name: impl Into<ArcStr>) {
+        self.dirty.insert(name.into());
+    }
+
+    /// Returns `true` if the struct named `name` is marked as modified.
+    pub fn is_dirty(&self, name: &str) -> bool {
+        self.dirty.contains(name)
+    }
+
+    /// Clears all dirty marks, keeping cached encodings.
+    pub fn clear(&mut self) {
+        self.dirty.clear();
+    }
 }
 
 // Enable [GdsLibrary] and [GdsStruct] serialization to file, in each of `utils` supported formats.
@@ -1029,7 +2106,7 @@ impl SerdeFile for GdsStruct {}
 ///
 /// `GdsLayerSpecs` generalize across these via the `xtype` field,
 /// which holds whichever is appropriate for the given element.
-#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+#[derive(Copy, Clone, Eq, PartialEq, Hash, Debug)]
 pub struct GdsLayerSpec {
     /// Layer ID number.
     pub layer: i16,
@@ -1041,6 +2118,8 @@ pub struct GdsLayerSpec {
 pub trait HasLayer {
     /// Returns a [GdsLayerSpec] including the two numbers `layer` and `xtype`.
     fn layerspec(&self) -> GdsLayerSpec;
+    /// Sets the `layer` and `xtype` numbers from a [GdsLayerSpec].
+    fn set_layerspec(&mut self, spec: GdsLayerSpec);
 }
 
 impl GdsLayerSpec {
@@ -1054,30 +2133,55 @@ impl HasLayer for GdsBoundary {
     fn layerspec(&self) -> GdsLayerSpec {
         GdsLayerSpec::new(self.layer, self.datatype)
     }
+
+    fn set_layerspec(&mut self, spec: GdsLayerSpec) {
+        self.layer = spec.layer;
+        self.datatype = spec.xtype;
+    }
 }
 
 impl HasLayer for GdsTextElem {
     fn layerspec(&self) -> GdsLayerSpec {
         GdsLayerSpec::new(self.layer, self.texttype)
     }
+
+    fn set_layerspec(&mut self, spec: GdsLayerSpec) {
+        self.layer = spec.layer;
+        self.texttype = spec.xtype;
+    }
 }
 
 impl HasLayer for GdsNode {
     fn layerspec(&self) -> GdsLayerSpec {
         GdsLayerSpec::new(self.layer, self.nodetype)
     }
+
+    fn set_layerspec(&mut self, spec: GdsLayerSpec) {
+        self.layer = spec.layer;
+        self.nodetype = spec.xtype;
+    }
 }
 
 impl HasLayer for GdsBox {
     fn layerspec(&self) -> GdsLayerSpec {
         GdsLayerSpec::new(self.layer, self.boxtype)
     }
+
+    fn set_layerspec(&mut self, spec: GdsLayerSpec) {
+        self.layer = spec.layer;
+        self.boxtype = spec.xtype;
+    }
 }
 
 impl HasLayer for GdsPath {
     fn layerspec(&self) -> GdsLayerSpec {
         GdsLayerSpec::new(self.layer, self.datatype)
     }
+
+    fn set_layerspec(&mut self, spec: GdsLayerSpec) {
+        self.layer = spec.layer;
+        self.datatype = spec.xtype;
+    }
 }
 
 /// An enumeration of each context in which a record can be parsed, primarily for error reporting.
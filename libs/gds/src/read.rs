@@ -1,11 +1,18 @@
 //! Utilies for reading and scanning.
 
+// Std-Lib Imports
+use std::io::BufReader;
+
 // Local imports
 use super::*;
 
 /// Size (in bytes) of the read/decode buffer array
 const READER_BUFSIZE: usize = 65537;
 
+/// A seekable source of GDS data.
+trait GdsSource: Read + Seek {}
+impl<T: Read + Seek> GdsSource for T {}
+
 /// A GDS reader.
 ///
 /// Helper for parsing and scanning GDS coming from files and similar sources.
@@ -13,16 +20,28 @@ pub struct GdsReader {
     /// Read/conversion buffer.
     buf: [u8; READER_BUFSIZE],
     /// File being read.
-    file: Cursor<Vec<u8>>, // FIXME: use &[u8], when we get around to piping around all the lifetimes.
+    file: Box<dyn GdsSource>, // FIXME: use &[u8], when we get around to piping around all the lifetimes.
 }
 impl GdsReader {
     /// Creates a [GdsReader], opening [File] at path `fname`.
+    ///
+    /// The entire file is read into memory.
     pub fn open(fname: impl AsRef<Path>) -> GdsResult<GdsReader> {
         let bytes = std::fs::read(fname)?;
         let cursor = Cursor::new(bytes);
         Ok(Self::new(cursor))
     }
 
+    /// Creates a [GdsReader] that reads the [File] at path `fname` through a buffer,
+    /// without loading the entire file into memory.
+    pub fn open_buffered(fname: impl AsRef<Path>) -> GdsResult<GdsReader> {
+        let file = BufReader::new(File::open(fname)?);
+        Ok(GdsReader {
+            file: Box::new(file),
+            buf: [0; READER_BUFSIZE],
+        })
+    }
+
     /// Creates a [GdsReader] of `bytes`.
     pub fn from_bytes(bytes: Vec<u8>) -> GdsReader {
         Self::new(Cursor::new(bytes))
@@ -31,7 +50,10 @@ impl GdsReader {
     /// Creates a [GdsReader] of `file`.
     pub fn new(file: Cursor<Vec<u8>>) -> GdsReader {
         let buf = [0; READER_BUFSIZE];
-        GdsReader { file, buf }
+        GdsReader {
+            file: Box::new(file),
+            buf,
+        }
     }
 
     /// Reads the next record-header from our file.
@@ -235,8 +257,9 @@ impl GdsReader {
     /// Gets the current file position.
     #[inline(always)]
     fn pos(&mut self) -> u64 {
-        // Note `unwrap` here never panics, as [Cursor.stream_position] *always* returns `Ok`.
-        self.file.stream_position().unwrap()
+        // In-memory sources always report their position; the position is only used for
+        // diagnostics, so failures of buffered file sources are reported as offset zero.
+        self.file.stream_position().unwrap_or_default()
     }
 }
 
@@ -415,6 +438,63 @@ impl GdsScanner {
     }
 }
 
+/// An iterator over the structs of a GDS file, parsing one struct at a time.
+///
+/// Created by [`GdsLibrary::stream_structs`]. Only the struct being parsed is held
+/// in memory. Errors are yielded as items, after which the iterator ends.
+pub struct GdsStructStream {
+    state: StreamState,
+}
+
+enum StreamState {
+    /// The library header has not yet been read.
+    Start(Box<GdsParser>),
+    /// Reading structs.
+    Structs(Box<GdsParser>),
+    /// Opening the file failed.
+    Failed(GdsError),
+    /// The library has ended or an error was yielded.
+    Done,
+}
+
+impl GdsStructStream {
+    /// Opens a stream over the structs of the GDS file at path `fname`.
+    pub(crate) fn open(fname: impl AsRef<Path>) -> Self {
+        let state = match GdsReader::open_buffered(fname).and_then(GdsParser::new) {
+            Ok(parser) => StreamState::Start(Box::new(parser)),
+            Err(e) => StreamState::Failed(e),
+        };
+        Self { state }
+    }
+}
+
+impl Iterator for GdsStructStream {
+    type Item = GdsResult<GdsStruct>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let mut parser = match mem::replace(&mut self.state, StreamState::Done) {
+            StreamState::Start(mut parser) => {
+                parser.ctx.push(GdsContext::Library);
+                if let Err(e) = parser.parse_lib_start() {
+                    return Some(Err(e));
+                }
+                parser
+            }
+            StreamState::Structs(parser) => parser,
+            StreamState::Failed(e) => return Some(Err(e)),
+            StreamState::Done => return None,
+        };
+        match parser.parse_next_struct() {
+            Ok(Some(strukt)) => {
+                self.state = StreamState::Structs(parser);
+                Some(Ok(strukt))
+            }
+            Ok(None) => None,
+            Err(e) => Some(Err(e)),
+        }
+    }
+}
+
 /// A GDS parser.
 ///
 /// A peekable iterator which loads GdsRecords from file, one at a time,
@@ -473,32 +553,106 @@ impl GdsParser {
         &self.nxt
     }
 
-    /// Parses a [GdsLibrary]. Generally the start-state when reading a GDS file.
-    pub fn parse_lib(&mut self) -> GdsResult<GdsLibrary> {
-        self.ctx.push(GdsContext::Library);
-        let mut lib = GdsLibraryBuilder::default();
-        let mut structs = Vec::<GdsStruct>::with_capacity(1024);
-        // Read the Header and its version data
-        lib = match self.next()? {
-            GdsRecord::Header { version: v } => lib.version(v),
+    /// Returns an iterator over the remaining raw records, read lazily.
+    ///
+    /// The iterator ends after yielding [GdsRecord::EndLib] or the first error.
+    pub fn records(&mut self) -> impl Iterator<Item = GdsResult<GdsRecord>> + '_ {
+        let mut done = false;
+        std::iter::from_fn(move || {
+            if done {
+                return None;
+            }
+            let record = self.next();
+            done = !matches!(record, Ok(ref r) if !matches!(r, GdsRecord::EndLib));
+            Some(record)
+        })
+    }
+
+    /// Reads the [Header](GdsRecord::Header) and [BgnLib](GdsRecord::BgnLib) records
+    /// that begin a library.
+    fn parse_lib_start(&mut self) -> GdsResult<(i16, GdsDateTimes)> {
+        let version = match self.next()? {
+            GdsRecord::Header { version: v } => v,
             _ => return self.fail("Invalid library: missing GDS HEADER record"),
         };
-        // Read the begin-lib
-        lib = match self.next()? {
-            GdsRecord::BgnLib { dates: d } => lib.dates(self.parse_datetimes(d)?),
+        let dates = match self.next()? {
+            GdsRecord::BgnLib { dates: d } => self.parse_datetimes(d)?,
             _ => return self.fail("Invalid library: missing GDS BGNLIB record"),
         };
+        Ok((version, dates))
+    }
+
+    /// Reads library records until the next struct has been parsed.
+    ///
+    /// Returns `None` upon reaching [EndLib](GdsRecord::EndLib).
+    fn parse_next_struct(&mut self) -> GdsResult<Option<GdsStruct>> {
+        loop {
+            let r = self.next()?;
+            match r {
+                GdsRecord::EndLib => return Ok(None),
+                GdsRecord::LibName(_) | GdsRecord::Units(_, _) => {}
+                GdsRecord::BgnStruct { dates } => return self.parse_struct(dates).map(Some),
+                GdsRecord::LibDirSize(_)
+                | GdsRecord::SrfName(_)
+                | GdsRecord::LibSecur(_)
+                | GdsRecord::RefLibs(_)
+                | GdsRecord::Fonts(_)
+                | GdsRecord::AttrTable(_)
+                | GdsRecord::Generations(_)
+                | GdsRecord::Format(_) => {
+                    return Err(GdsError::Unsupported(Some(r), Some(GdsContext::Library)))
+                }
+                _ => return self.invalid(r),
+            }
+        }
+    }
+
+    /// Parses a [GdsLibrary]. Generally the start-state when reading a GDS file.
+    pub fn parse_lib(&mut self) -> GdsResult<GdsLibrary> {
+        let mut lib = GdsLibrary::default();
+        self.parse_lib_into(&mut lib, true)?;
+        Ok(lib)
+    }
+
+    /// Parses as much of a [GdsLibrary] as possible, stopping at the first error.
+    ///
+    /// Returns the library read so far, containing every struct that was completely
+    /// parsed before the error, along with the errors encountered.
+    pub fn parse_lib_lossy(&mut self) -> (GdsLibrary, Vec<GdsError>) {
+        let mut lib = GdsLibrary::default();
+        let mut errors = Vec::new();
+        if let Err(e) = self.parse_lib_into(&mut lib, false) {
+            errors.push(e);
+        }
+        (lib, errors)
+    }
+
+    /// Parses library records into `lib`, adding each struct as soon as it is complete.
+    ///
+    /// Upon error, `lib` contains everything parsed before the error.
+    /// If `strict` is set, libraries missing a [LibName](GdsRecord::LibName)
+    /// or [Units](GdsRecord::Units) record are rejected.
+    fn parse_lib_into(&mut self, lib: &mut GdsLibrary, strict: bool) -> GdsResult<()> {
+        self.ctx.push(GdsContext::Library);
+        // Read the Header and begin-lib
+        (lib.version, lib.dates) = self.parse_lib_start()?;
+        let (mut has_name, mut has_units) = (false, false);
         // Iterate over all others
         loop {
             let r = self.next()?;
-            lib = match r {
+            match r {
                 GdsRecord::EndLib => break, // End-of-library
-                GdsRecord::LibName(d) => lib.name(d),
-                GdsRecord::Units(d0, d1) => lib.units(GdsUnits(d0, d1)),
+                GdsRecord::LibName(d) => {
+                    lib.name = d;
+                    has_name = true;
+                }
+                GdsRecord::Units(d0, d1) => {
+                    lib.units = GdsUnits(d0, d1);
+                    has_units = true;
+                }
                 GdsRecord::BgnStruct { dates } => {
                     let strukt = self.parse_struct(dates)?;
-                    structs.push(strukt);
-                    lib
+                    lib.structs.push(strukt);
                 }
                 // Spec-valid but unsupported records
                 GdsRecord::LibDirSize(_)
@@ -515,9 +669,13 @@ impl GdsParser {
                 _ => return self.invalid(r),
             };
         }
-        // Add the Vec of structs, and create the Library from its builder
-        lib = lib.structs(structs);
-        lib.build().map_err(|e| GdsError::Boxed(Arc::new(e)))
+        if strict && !has_name {
+            return self.fail("Invalid library: missing GDS LIBNAME record");
+        }
+        if strict && !has_units {
+            return self.fail("Invalid library: missing GDS UNITS record");
+        }
+        Ok(())
     }
 
     /// Parses a cell ([GdsStruct]).
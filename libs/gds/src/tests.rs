@@ -13,25 +13,66 @@ fn test_dates() -> GdsDateTimes {
 #[test]
 fn floats() -> GdsResult<()> {
     // Test conversions between normal-human and GDSII floating-point formats
-    let f = GdsFloat64::encode(0.0);
+    let f = GdsFloat64::encode(0.0)?;
     assert_eq!(f, 0);
     let d = GdsFloat64::decode(f);
     assert_eq!(d, 0.0);
-    let f = GdsFloat64::encode(1.0);
+    let f = GdsFloat64::encode(1.0)?;
     let d = GdsFloat64::decode(f);
     assert_eq!(d, 1.0);
-    let f = GdsFloat64::encode(1e-11);
+    let f = GdsFloat64::encode(1e-11)?;
     let d = GdsFloat64::decode(f);
     assert_eq!(d, 1e-11);
-    let f = GdsFloat64::encode(-0.69);
+    let f = GdsFloat64::encode(-0.69)?;
     let d = GdsFloat64::decode(f);
     assert_eq!(d, -0.69);
-    let f = GdsFloat64::encode(-33.33e-33);
+    let f = GdsFloat64::encode(-33.33e-33)?;
     let d = GdsFloat64::decode(f);
     assert_eq!(d, -33.33e-33);
     Ok(())
 }
 
+#[test]
+fn float_round_trip_sweep() -> GdsResult<()> {
+    // Sweep magnitudes across the representable range, in both signs
+    for decade in -78..75 {
+        for step in 0..16 {
+            let mag = 10f64.powi(decade) * (1.0 + step as f64 * 0.5625);
+            for val in [mag, -mag] {
+                let d = GdsFloat64::decode(GdsFloat64::encode(val)?);
+                let err = ((d - val) / val).abs();
+                assert!(err < 1e-15, "{val} decoded as {d} (relative error {err})");
+            }
+        }
+    }
+    // Exact powers of sixteen sit on the exponent boundary
+    for exp in -64..63 {
+        let val = 16f64.powi(exp);
+        assert_eq!(GdsFloat64::decode(GdsFloat64::encode(val)?), val);
+    }
+    Ok(())
+}
+
+#[test]
+fn float_encode_limits() -> GdsResult<()> {
+    // Values below the smallest exponent saturate rather than wrapping
+    assert_eq!(GdsFloat64::encode(1e-300)?, 0x0000_0000_0000_0001);
+    assert_eq!(
+        GdsFloat64::encode(-f64::MIN_POSITIVE)?,
+        0x8000_0000_0000_0001
+    );
+    assert!(GdsFloat64::decode(GdsFloat64::encode(1e-90)?) > 0.0);
+    // Values beyond the largest exponent, and non-finite values, are errors
+    assert!(GdsFloat64::encode(1e80).is_err());
+    assert!(GdsFloat64::encode(-1e300).is_err());
+    assert!(GdsFloat64::encode(f64::INFINITY).is_err());
+    assert!(GdsFloat64::encode(f64::NAN).is_err());
+    // The largest representable value still encodes
+    let max = 16f64.powi(63) * (1.0 - f64::EPSILON / 2.0);
+    assert_eq!(GdsFloat64::encode(max)?, 0x7FFF_FFFF_FFFF_FFF8);
+    Ok(())
+}
+
 #[test]
 fn stats() -> GdsResult<()> {
     // Test collecting statistics
@@ -157,6 +198,76 @@ fn it_arrays() -> GdsResult<()> {
     Ok(())
 }
 
+#[test]
+fn array_grid_expands() {
+    let arr = GdsArrayRef::grid(
+        "unit",
+        GdsPoint::new(100, 200),
+        GdsPoint::new(1_000, 0),
+        GdsPoint::new(0, 500),
+        3,
+        2,
+    );
+    assert_eq!(
+        arr.xy,
+        [
+            GdsPoint::new(100, 200),
+            GdsPoint::new(3_100, 200),
+            GdsPoint::new(100, 1_200),
+        ]
+    );
+    let refs = arr.expand();
+    assert_eq!(refs.len(), 6);
+    let locs: Vec<_> = refs.iter().map(|r| r.xy.clone()).collect();
+    assert_eq!(
+        locs,
+        GdsPoint::vec(&[
+            (100, 200),
+            (1_100, 200),
+            (2_100, 200),
+            (100, 700),
+            (1_100, 700),
+            (2_100, 700),
+        ])
+    );
+    assert!(refs.iter().all(|r| r.name == "unit"));
+}
+
+#[test]
+fn boundary_winding() {
+    let ccw = GdsBoundary {
+        xy: GdsPoint::vec(&[(0, 0), (10, 0), (10, 20), (0, 20), (0, 0)]),
+        ..GdsBoundary::default()
+    };
+    assert_eq!(ccw.winding(), Winding::CounterClockwise);
+
+    let cw = GdsBoundary {
+        xy: GdsPoint::vec(&[(0, 0), (0, 20), (10, 20), (10, 0), (0, 0)]),
+        ..GdsBoundary::default()
+    };
+    assert_eq!(cw.winding(), Winding::Clockwise);
+}
+
+#[test]
+fn boundary_set_winding() {
+    let mut b = GdsBoundary {
+        xy: GdsPoint::vec(&[(0, 0), (10, 0), (10, 20), (0, 20), (0, 0)]),
+        ..GdsBoundary::default()
+    };
+    b.set_winding(Winding::Clockwise);
+    assert_eq!(b.winding(), Winding::Clockwise);
+    assert_eq!(
+        b.xy,
+        GdsPoint::vec(&[(0, 0), (0, 20), (10, 20), (10, 0), (0, 0)])
+    );
+    assert_eq!(b.xy.first(), b.xy.last());
+
+    // Already clockwise; should be left unchanged.
+    let before = b.clone();
+    b.set_winding(Winding::Clockwise);
+    assert_eq!(b, before);
+}
+
 /// Test too-long record length (>16K) generates an error
 #[test]
 fn record_too_long() -> GdsResult<()> {
@@ -193,3 +304,964 @@ fn check(lib: &GdsLibrary, fname: impl AsRef<Path>) {
 fn resource(rname: &str) -> String {
     format!("{}/resources/{}", env!("CARGO_MANIFEST_DIR"), rname)
 }
+
+/// Returns the (xmin, ymin, xmax, ymax) extents of a set of points.
+fn extents(xy: &[GdsPoint]) -> (i32, i32, i32, i32) {
+    (
+        xy.iter().map(|p| p.x).min().unwrap(),
+        xy.iter().map(|p| p.y).min().unwrap(),
+        xy.iter().map(|p| p.x).max().unwrap(),
+        xy.iter().map(|p| p.y).max().unwrap(),
+    )
+}
+
+#[test]
+fn path_to_boundary() {
+    let path = |path_type: i16| GdsPath {
+        layer: 1,
+        datatype: 2,
+        xy: GdsPoint::vec(&[(0, 0), (100, 0)]),
+        width: Some(20),
+        path_type: Some(path_type),
+        ..GdsPath::default()
+    };
+
+    let flush = path(0).to_boundary().unwrap();
+    assert_eq!((flush.layer, flush.datatype), (1, 2));
+    assert_eq!(
+        flush.xy,
+        GdsPoint::vec(&[(0, -10), (100, -10), (100, 10), (0, 10), (0, -10)])
+    );
+    assert_eq!(flush.winding(), Winding::CounterClockwise);
+
+    let round = path(1).to_boundary().unwrap();
+    assert_eq!(extents(&round.xy), (-10, -10, 110, 10));
+    assert_eq!(round.xy.len(), 2 * (GdsPath::ROUND_END_SEGMENTS + 1) + 1);
+    assert_eq!(round.xy.first(), round.xy.last());
+    assert_eq!(round.winding(), Winding::CounterClockwise);
+
+    let square = path(2).to_boundary().unwrap();
+    assert_eq!(
+        square.xy,
+        GdsPoint::vec(&[(-10, -10), (110, -10), (110, 10), (-10, 10), (-10, -10)])
+    );
+}
+
+#[test]
+fn bent_path_to_boundary() {
+    let path = GdsPath {
+        xy: GdsPoint::vec(&[(0, 0), (100, 0), (100, 50)]),
+        width: Some(20),
+        path_type: Some(2),
+        ..GdsPath::default()
+    };
+    assert_eq!(
+        path.to_boundary().unwrap().xy,
+        GdsPoint::vec(&[
+            (-10, -10),
+            (110, -10),
+            (110, 60),
+            (90, 60),
+            (90, 10),
+            (-10, 10),
+            (-10, -10),
+        ])
+    );
+}
+
+#[test]
+fn l_path_to_boundary() {
+    let path = |path_type: i16| GdsPath {
+        layer: 3,
+        datatype: 4,
+        xy: GdsPoint::vec(&[(0, 0), (100, 0), (100, 50)]),
+        width: Some(20),
+        path_type: Some(path_type),
+        begin_extn: Some(5),
+        end_extn: Some(15),
+        ..GdsPath::default()
+    };
+    let closed = |b: &GdsBoundary| b.xy.first() == b.xy.last();
+
+    for path_type in [0, 1, 2, 4] {
+        let boundary = path(path_type).to_boundary().unwrap();
+        assert_eq!((boundary.layer, boundary.datatype), (3, 4));
+        assert!(closed(&boundary));
+        assert_eq!(boundary.winding(), Winding::CounterClockwise);
+        let expected = if path_type == 1 {
+            2 * (3 + GdsPath::ROUND_END_SEGMENTS - 1) + 1
+        } else {
+            7
+        };
+        assert_eq!(boundary.xy.len(), expected);
+    }
+
+    assert_eq!(
+        path(0).to_boundary().unwrap().xy,
+        GdsPoint::vec(&[
+            (0, -10),
+            (110, -10),
+            (110, 50),
+            (90, 50),
+            (90, 10),
+            (0, 10),
+            (0, -10),
+        ])
+    );
+    assert_eq!(
+        extents(&path(1).to_boundary().unwrap().xy),
+        (-10, -10, 110, 60)
+    );
+    assert_eq!(
+        path(4).to_boundary().unwrap().xy,
+        GdsPoint::vec(&[
+            (-5, -10),
+            (110, -10),
+            (110, 65),
+            (90, 65),
+            (90, 10),
+            (-5, 10),
+            (-5, -10),
+        ])
+    );
+
+    let mut unset = path(0);
+    unset.width = None;
+    assert!(matches!(unset.to_boundary(), Err(GdsError::Str(_))));
+    let mut zero = path(0);
+    zero.width = Some(0);
+    assert!(matches!(zero.to_boundary(), Err(GdsError::Str(_))));
+}
+
+#[test]
+fn subset() -> GdsResult<()> {
+    let sref = |name: &str| {
+        GdsElement::GdsStructRef(GdsStructRef {
+            name: name.into(),
+            xy: GdsPoint::new(0, 0),
+            strans: None,
+            elflags: None,
+            plex: None,
+            properties: Vec::new(),
+        })
+    };
+    let aref = |name: &str| {
+        GdsElement::GdsArrayRef(GdsArrayRef {
+            name: name.into(),
+            xy: [
+                GdsPoint::new(0, 0),
+                GdsPoint::new(0, 1_000),
+                GdsPoint::new(1_000, 0),
+            ],
+            cols: 2,
+            rows: 2,
+            strans: None,
+            elflags: None,
+            plex: None,
+            properties: Vec::new(),
+        })
+    };
+    let strukt = |name: &str, elems: Vec<GdsElement>| GdsStruct {
+        name: name.into(),
+        dates: test_dates(),
+        elems,
+    };
+
+    let mut lib = GdsLibrary::with_units("subset_lib", GdsUnits::new(1e-3, 1e-9));
+    lib.dates = test_dates();
+    lib.structs = vec![
+        strukt("leaf", Vec::new()),
+        strukt("unrelated", vec![sref("leaf")]),
+        strukt("mid", vec![aref("leaf")]),
+        strukt("top", vec![sref("mid"), sref("mid")]),
+        strukt("other_top", vec![sref("unrelated")]),
+    ];
+
+    let sub = lib.subset("top")?;
+    let names = sub
+        .structs
+        .iter()
+        .map(|strukt| strukt.name.as_str())
+        .collect::<Vec<_>>();
+    assert_eq!(names, vec!["leaf", "mid", "top"]);
+    assert_eq!(sub.name, lib.name);
+    assert_eq!(sub.units, lib.units);
+    assert_eq!(sub.dates, lib.dates);
+
+    assert!(lib.subset("missing").is_err());
+    lib.structs.push(strukt("dangling", vec![sref("missing")]));
+    assert!(lib.subset("dangling").is_err());
+    Ok(())
+}
+
+#[test]
+fn subset_expand_arrays() -> GdsResult<()> {
+    let strukt = |name: &str, elems: Vec<GdsElement>| GdsStruct {
+        name: name.into(),
+        dates: test_dates(),
+        elems,
+    };
+    let aref = GdsArrayRef::grid(
+        "leaf",
+        GdsPoint::new(0, 0),
+        GdsPoint::new(1_000, 0),
+        GdsPoint::new(0, 2_000),
+        3,
+        2,
+    );
+
+    let mut lib = GdsLibrary::with_units("expand_lib", GdsUnits::new(1e-3, 1e-9));
+    lib.dates = test_dates();
+    lib.structs = vec![
+        strukt("leaf", Vec::new()),
+        strukt("top", vec![aref.clone().into()]),
+    ];
+
+    let sub = lib.subset("top")?;
+    assert_eq!(sub.structs[1].elems, vec![aref.clone().into()]);
+
+    let sub = lib.subset_with_options(
+        "top",
+        &GdsFlattenOptions {
+            expand_arrays: true,
+        },
+    )?;
+    assert!(sub
+        .structs
+        .iter()
+        .flat_map(|strukt| strukt.elems.iter())
+        .all(|elem| !matches!(elem, GdsElement::GdsArrayRef(_))));
+    let expected: Vec<GdsElement> = aref.expand().into_iter().map(Into::into).collect();
+    assert_eq!(expected.len(), 6);
+    assert_eq!(sub.structs[1].elems, expected);
+    Ok(())
+}
+
+#[test]
+fn struct_bbox() {
+    let mut strukt = GdsStruct::new("bbox");
+    assert_eq!(strukt.bbox(None), None);
+
+    strukt.elems.push(GdsElement::GdsBoundary(GdsBoundary {
+        layer: 1,
+        datatype: 0,
+        xy: GdsPoint::vec(&[(0, 0), (100, 0), (100, 50), (0, 50), (0, 0)]),
+        ..Default::default()
+    }));
+    strukt.elems.push(GdsElement::GdsPath(GdsPath {
+        layer: 2,
+        datatype: 0,
+        xy: GdsPoint::vec(&[(200, 100), (300, 100)]),
+        width: Some(20),
+        ..Default::default()
+    }));
+    strukt.elems.push(GdsElement::GdsTextElem(GdsTextElem {
+        string: "label".into(),
+        layer: 2,
+        texttype: 0,
+        xy: GdsPoint::new(-10, 400),
+        ..Default::default()
+    }));
+
+    assert_eq!(
+        strukt.bbox(None),
+        Some((GdsPoint::new(-10, 0), GdsPoint::new(300, 400)))
+    );
+
+    let layer1 = HashSet::from([GdsLayerSpec::new(1, 0)]);
+    assert_eq!(
+        strukt.bbox(Some(&layer1)),
+        Some((GdsPoint::new(0, 0), GdsPoint::new(100, 50)))
+    );
+
+    let layer2 = HashSet::from([GdsLayerSpec::new(2, 0)]);
+    assert_eq!(
+        strukt.bbox(Some(&layer2)),
+        Some((GdsPoint::new(-10, 90), GdsPoint::new(300, 400)))
+    );
+
+    let layer3 = HashSet::from([GdsLayerSpec::new(3, 0)]);
+    assert_eq!(strukt.bbox(Some(&layer3)), None);
+}
+
+#[test]
+fn struct_ref_placed_bbox() {
+    let child = (GdsPoint::new(0, 0), GdsPoint::new(100, 50));
+
+    let unplaced = GdsStructRef {
+        name: "child".into(),
+        xy: GdsPoint::new(10, 20),
+        ..Default::default()
+    };
+    assert_eq!(
+        unplaced.placed_bbox(child.clone()),
+        (GdsPoint::new(10, 20), GdsPoint::new(110, 70))
+    );
+
+    let sref = GdsStructRef {
+        name: "child".into(),
+        xy: GdsPoint::new(1000, 2000),
+        strans: Some(GdsStrans {
+            mag: Some(2.0),
+            angle: Some(90.0),
+            ..Default::default()
+        }),
+        ..Default::default()
+    };
+    // Magnified to 200x100, then rotated to span x in [-100, 0] and y in [0, 200].
+    assert_eq!(
+        sref.placed_bbox(child.clone()),
+        (GdsPoint::new(900, 2000), GdsPoint::new(1000, 2200))
+    );
+
+    let reflected = GdsStructRef {
+        strans: Some(GdsStrans {
+            reflected: true,
+            mag: Some(2.0),
+            angle: Some(90.0),
+            ..Default::default()
+        }),
+        ..sref
+    };
+    // Reflection maps y to [-100, 0], which rotation moves to x in [0, 100].
+    assert_eq!(
+        reflected.placed_bbox(child),
+        (GdsPoint::new(1000, 2000), GdsPoint::new(1100, 2200))
+    );
+}
+
+#[test]
+fn library_bbox() {
+    let rect = |x0, y0, x1, y1| {
+        GdsElement::GdsBoundary(GdsBoundary {
+            layer: 1,
+            datatype: 0,
+            xy: GdsPoint::vec(&[(x0, y0), (x1, y0), (x1, y1), (x0, y1), (x0, y0)]),
+            ..Default::default()
+        })
+    };
+    let mut child = GdsStruct::new("child");
+    child.elems.push(rect(0, 0, 100, 50));
+
+    let rotated = GdsStructRef {
+        name: "child".into(),
+        xy: GdsPoint::new(1000, 2000),
+        strans: Some(GdsStrans {
+            mag: Some(2.0),
+            angle: Some(90.0),
+            ..Default::default()
+        }),
+        ..Default::default()
+    };
+    let reflected = GdsStructRef {
+        strans: Some(GdsStrans {
+            reflected: true,
+            mag: Some(2.0),
+            angle: Some(90.0),
+            ..Default::default()
+        }),
+        ..rotated.clone()
+    };
+    let mut top = GdsStruct::new("top");
+    top.elems.push(rect(500, 500, 510, 510));
+    top.elems.push(GdsElement::GdsNode(GdsNode {
+        layer: 2,
+        nodetype: 0,
+        xy: GdsPoint::vec(&[(505, 400)]),
+        ..Default::default()
+    }));
+    top.elems.push(rotated.into());
+
+    let mut lib = GdsLibrary::new("bbox_lib");
+    assert_eq!(lib.bbox().unwrap(), None);
+    lib.structs = vec![child, top];
+    // The child is only included as placed by the rotated reference.
+    assert_eq!(
+        lib.bbox().unwrap(),
+        Some((GdsPoint::new(500, 400), GdsPoint::new(1000, 2200)))
+    );
+
+    lib.structs[1].elems.push(reflected.into());
+    assert_eq!(
+        lib.bbox().unwrap(),
+        Some((GdsPoint::new(500, 400), GdsPoint::new(1100, 2200)))
+    );
+
+    lib.structs[1].elems.push(
+        GdsStructRef {
+            name: "missing".into(),
+            ..Default::default()
+        }
+        .into(),
+    );
+    assert!(lib.bbox().is_err());
+}
+
+#[test]
+fn library_bbox_of_arrays_and_cycles() {
+    let mut child = GdsStruct::new("child");
+    child.elems.push(GdsElement::GdsBoundary(GdsBoundary {
+        layer: 1,
+        datatype: 0,
+        xy: GdsPoint::vec(&[(0, 0), (100, 0), (100, 50), (0, 50), (0, 0)]),
+        ..Default::default()
+    }));
+
+    // Far too many elements to expand.
+    let mut top = GdsStruct::new("top");
+    top.elems.push(
+        GdsArrayRef::grid(
+            "child",
+            GdsPoint::new(0, 0),
+            GdsPoint::new(200, 0),
+            GdsPoint::new(0, 100),
+            i16::MAX,
+            i16::MAX,
+        )
+        .into(),
+    );
+
+    let mut lib = GdsLibrary::new("array_bbox_lib");
+    lib.structs = vec![child, top];
+    assert_eq!(
+        lib.bbox().unwrap(),
+        Some((GdsPoint::new(0, 0), GdsPoint::new(6_553_300, 3_276_650)))
+    );
+
+    // A cycle is an error even when no struct is top-level.
+    let cyclic = |name: &str, child: &str| {
+        let mut strukt = GdsStruct::new(name);
+        strukt.elems.push(
+            GdsStructRef {
+                name: child.into(),
+                ..Default::default()
+            }
+            .into(),
+        );
+        strukt
+    };
+    let mut lib = GdsLibrary::new("cycle_lib");
+    lib.structs = vec![cyclic("a", "b"), cyclic("b", "a")];
+    assert!(lib.bbox().is_err());
+}
+
+#[test]
+fn validate_geometry() {
+    let boundary = |pts: &[(i32, i32)]| {
+        GdsElement::GdsBoundary(GdsBoundary {
+            layer: 1,
+            datatype: 0,
+            xy: GdsPoint::vec(pts),
+            ..Default::default()
+        })
+    };
+    let path = |width| {
+        GdsElement::GdsPath(GdsPath {
+            layer: 2,
+            datatype: 0,
+            xy: GdsPoint::vec(&[(0, 0), (100, 0)]),
+            width: Some(width),
+            ..Default::default()
+        })
+    };
+
+    let mut good = GdsStruct::new("good");
+    good.elems = vec![boundary(&[(0, 0), (10, 0), (10, 10), (0, 0)]), path(20)];
+    let mut bad = GdsStruct::new("bad");
+    bad.elems = vec![
+        boundary(&[(0, 0), (10, 0), (10, 10), (0, 10), (0, 0)]),
+        // Missing closing point.
+        boundary(&[(0, 0), (10, 0), (10, 10), (0, 10)]),
+        // Closed, but degenerate.
+        boundary(&[(0, 0), (10, 0), (0, 0)]),
+        path(-20),
+    ];
+    let mut lib = GdsLibrary::new("validate_geometry");
+    lib.structs = vec![good, bad];
+    let before = lib.clone();
+
+    let issues = lib.validate_geometry();
+    assert_eq!(lib, before);
+    assert_eq!(
+        issues,
+        vec![
+            GdsGeometryIssue::UnclosedBoundary {
+                struct_name: "bad".into(),
+                index: 1,
+            },
+            GdsGeometryIssue::TooFewPoints {
+                struct_name: "bad".into(),
+                index: 2,
+                points: 3,
+            },
+            GdsGeometryIssue::NegativePathWidth {
+                struct_name: "bad".into(),
+                index: 3,
+                width: -20,
+            },
+        ]
+    );
+    assert!(issues.iter().all(|issue| issue.struct_name() == "bad"));
+    assert_eq!(issues[1].index(), 2);
+    assert_eq!(
+        issues[0].to_string(),
+        "boundary 1 in struct `bad` is not closed"
+    );
+
+    lib.structs.remove(1);
+    assert!(lib.validate_geometry().is_empty());
+}
+
+#[test]
+fn merge_renames_collisions() {
+    let rect = |layer| {
+        GdsElement::GdsBoundary(GdsBoundary {
+            layer,
+            datatype: 0,
+            xy: GdsPoint::vec(&[(0, 0), (10, 0), (10, 10), (0, 10), (0, 0)]),
+            ..Default::default()
+        })
+    };
+    let sref = |name: &str| {
+        GdsElement::GdsStructRef(GdsStructRef {
+            name: name.into(),
+            ..Default::default()
+        })
+    };
+    let lib = |name: &str, layer, top: &str| {
+        let mut inv = GdsStruct::new("inv");
+        inv.elems.push(rect(layer));
+        let mut top = GdsStruct::new(top);
+        top.elems.push(sref("inv"));
+        top.elems.push(GdsElement::GdsArrayRef(GdsArrayRef {
+            name: "inv".into(),
+            cols: 2,
+            rows: 1,
+            ..Default::default()
+        }));
+        let mut lib = GdsLibrary::new(name);
+        lib.structs = vec![inv, top];
+        lib
+    };
+
+    let mut merged = lib("a", 1, "top_a");
+    let mut other = lib("b", 2, "top_b");
+    // Renaming `inv` once collides with this incoming struct, so `rename` is applied again.
+    other.structs.push(GdsStruct::new("inv_b"));
+    merged.merge(other, |name| arcstr::format!("{name}_b"));
+
+    let names: Vec<_> = merged.structs.iter().map(|s| s.name.as_str()).collect();
+    assert_eq!(names, ["inv", "top_a", "inv_b_b", "top_b", "inv_b"]);
+    assert_eq!(merged.structs[0].elems, vec![rect(1)]);
+    assert_eq!(merged.structs[2].elems, vec![rect(2)]);
+
+    let refs = |strukt: &GdsStruct| -> Vec<ArcStr> {
+        strukt
+            .elems
+            .iter()
+            .filter_map(|elem| match elem {
+                GdsElement::GdsStructRef(r) => Some(r.name.clone()),
+                GdsElement::GdsArrayRef(r) => Some(r.name.clone()),
+                _ => None,
+            })
+            .collect()
+    };
+    assert_eq!(refs(&merged.structs[1]), ["inv", "inv"]);
+    assert_eq!(refs(&merged.structs[3]), ["inv_b_b", "inv_b_b"]);
+}
+
+#[test]
+fn sort_elements() {
+    let rect = |layer, x, y| {
+        GdsElement::GdsBoundary(GdsBoundary {
+            layer,
+            datatype: 0,
+            xy: GdsPoint::vec(&[(x, y), (x + 10, y), (x + 10, y + 10), (x, y + 10), (x, y)]),
+            ..Default::default()
+        })
+    };
+    let text = GdsElement::GdsTextElem(GdsTextElem {
+        string: "label".into(),
+        layer: 1,
+        texttype: 5,
+        xy: GdsPoint::new(0, 0),
+        ..Default::default()
+    });
+    let sref = GdsElement::GdsStructRef(GdsStructRef {
+        name: "child".into(),
+        xy: GdsPoint::new(50, 50),
+        ..Default::default()
+    });
+    let elems = vec![
+        rect(2, 0, 0),
+        text,
+        rect(1, 20, 0),
+        sref,
+        rect(1, 0, 20),
+        rect(1, 0, 0),
+    ];
+
+    let lib_with = |elems: Vec<GdsElement>| {
+        let mut lib = GdsLibrary::new("lib");
+        let mut strukt = GdsStruct::new("cell");
+        strukt.elems = elems;
+        lib.structs.push(strukt);
+        lib
+    };
+    let mut lib1 = lib_with(elems.clone());
+    let mut lib2 = lib_with(elems.iter().rev().cloned().collect());
+    assert_ne!(lib1.structs[0].elems, lib2.structs[0].elems);
+
+    lib1.sort_elements();
+    lib2.sort_elements();
+    assert_eq!(lib1.structs[0].elems, lib2.structs[0].elems);
+    assert_eq!(
+        lib1.structs[0].elems,
+        vec![
+            elems[3].clone(),
+            elems[5].clone(),
+            elems[4].clone(),
+            elems[2].clone(),
+            elems[1].clone(),
+            elems[0].clone(),
+        ]
+    );
+}
+
+#[test]
+fn load_lossy_truncated() -> GdsResult<()> {
+    let strukt = |name: &str| GdsStruct {
+        name: name.into(),
+        dates: test_dates(),
+        elems: vec![GdsBoundary {
+            layer: 1,
+            xy: GdsPoint::vec(&[(0, 0), (10, 0), (10, 20), (0, 20), (0, 0)]),
+            ..GdsBoundary::default()
+        }
+        .into()],
+    };
+    let mut lib = GdsLibrary::with_units("lossy_lib", GdsUnits::new(1e-3, 1e-9));
+    lib.dates = test_dates();
+    lib.structs = vec![strukt("first"), strukt("second"), strukt("third")];
+
+    let mut bytes = Vec::new();
+    lib.write(&mut bytes)?;
+    // Drop the ENDLIB, ENDSTR, and part of the last element of the final struct.
+    bytes.truncate(bytes.len() - 12);
+
+    let file = tempfile::NamedTempFile::new()?;
+    std::fs::write(file.path(), &bytes)?;
+    assert!(GdsLibrary::load(file.path()).is_err());
+
+    let (recovered, errors) = GdsLibrary::load_lossy(file.path());
+    assert_eq!(errors.len(), 1);
+    assert_eq!(recovered.name, lib.name);
+    assert_eq!(recovered.units, lib.units);
+    assert_eq!(recovered.structs, lib.structs[..2]);
+
+    let (recovered, errors) = GdsLibrary::load_lossy(file.path().with_extension("missing"));
+    assert!(recovered.structs.is_empty());
+    assert_eq!(errors.len(), 1);
+    Ok(())
+}
+
+#[test]
+fn stream_structs() -> GdsResult<()> {
+    let strukt = |name: &str| GdsStruct {
+        name: name.into(),
+        dates: test_dates(),
+        elems: vec![GdsBoundary {
+            layer: 1,
+            xy: GdsPoint::vec(&[(0, 0), (10, 0), (10, 20), (0, 20), (0, 0)]),
+            ..GdsBoundary::default()
+        }
+        .into()],
+    };
+    let mut lib = GdsLibrary::with_units("stream_lib", GdsUnits::new(1e-3, 1e-9));
+    lib.dates = test_dates();
+    lib.structs = vec![strukt("first"), strukt("second"), strukt("third")];
+
+    let mut bytes = Vec::new();
+    lib.write(&mut bytes)?;
+    let records = GdsParser::from_bytes(bytes.clone())?
+        .records()
+        .collect::<GdsResult<Vec<_>>>()?;
+    assert!(matches!(records.first(), Some(GdsRecord::Header { .. })));
+    assert!(matches!(records.last(), Some(GdsRecord::EndLib)));
+
+    let file = tempfile::NamedTempFile::new()?;
+    std::fs::write(file.path(), &bytes)?;
+    let streamed = GdsLibrary::stream_structs(file.path()).collect::<GdsResult<Vec<_>>>()?;
+    assert_eq!(streamed, lib.structs);
+
+    // A truncated file yields the complete structs, then a single error.
+    bytes.truncate(bytes.len() - 12);
+    std::fs::write(file.path(), &bytes)?;
+    let streamed = GdsLibrary::stream_structs(file.path()).collect::<Vec<_>>();
+    assert_eq!(streamed.len(), 3);
+    assert_eq!(streamed[0].as_ref().ok(), Some(&lib.structs[0]));
+    assert_eq!(streamed[1].as_ref().ok(), Some(&lib.structs[1]));
+    assert!(streamed[2].is_err());
+
+    let mut missing = GdsLibrary::stream_structs(file.path().with_extension("missing"));
+    assert!(matches!(missing.next(), Some(Err(_))));
+    assert!(missing.next().is_none());
+    Ok(())
+}
+
+#[test]
+fn remap_layers() {
+    let mut lib = GdsLibrary::new("remap_lib");
+    lib.structs.push(GdsStruct {
+        name: "remap".into(),
+        dates: test_dates(),
+        elems: vec![
+            GdsBoundary {
+                layer: 1,
+                datatype: 0,
+                ..Default::default()
+            }
+            .into(),
+            GdsPath {
+                layer: 1,
+                datatype: 0,
+                ..Default::default()
+            }
+            .into(),
+            GdsTextElem {
+                layer: 2,
+                texttype: 3,
+                ..Default::default()
+            }
+            .into(),
+            GdsNode {
+                layer: 4,
+                nodetype: 5,
+                ..Default::default()
+            }
+            .into(),
+            GdsBox {
+                layer: 6,
+                boxtype: 7,
+                ..Default::default()
+            }
+            .into(),
+            GdsBox {
+                layer: 9,
+                boxtype: 9,
+                ..Default::default()
+            }
+            .into(),
+            GdsStructRef::default().into(),
+            GdsArrayRef::default().into(),
+        ],
+    });
+    let refs = lib.structs[0].elems[6..].to_vec();
+    let map = HashMap::from([
+        (GdsLayerSpec::new(1, 0), GdsLayerSpec::new(10, 1)),
+        (GdsLayerSpec::new(2, 3), GdsLayerSpec::new(20, 30)),
+        (GdsLayerSpec::new(4, 5), GdsLayerSpec::new(40, 50)),
+        (GdsLayerSpec::new(6, 7), GdsLayerSpec::new(60, 70)),
+    ]);
+
+    assert_eq!(lib.remap_layers(&map), 5);
+    let elems = &lib.structs[0].elems;
+    let spec = |elem: &GdsElement| match elem {
+        GdsElement::GdsBoundary(b) => Some(b.layerspec()),
+        GdsElement::GdsPath(p) => Some(p.layerspec()),
+        GdsElement::GdsTextElem(t) => Some(t.layerspec()),
+        GdsElement::GdsNode(n) => Some(n.layerspec()),
+        GdsElement::GdsBox(b) => Some(b.layerspec()),
+        GdsElement::GdsStructRef(_) | GdsElement::GdsArrayRef(_) => None,
+    };
+    assert_eq!(spec(&elems[0]), Some(GdsLayerSpec::new(10, 1)));
+    assert_eq!(spec(&elems[1]), Some(GdsLayerSpec::new(10, 1)));
+    assert_eq!(spec(&elems[2]), Some(GdsLayerSpec::new(20, 30)));
+    assert_eq!(spec(&elems[3]), Some(GdsLayerSpec::new(40, 50)));
+    assert_eq!(spec(&elems[4]), Some(GdsLayerSpec::new(60, 70)));
+    // Unmapped elements and references are untouched.
+    assert_eq!(spec(&elems[5]), Some(GdsLayerSpec::new(9, 9)));
+    assert_eq!(elems[6..], refs);
+
+    // Mapped specs are not remapped again.
+    assert_eq!(lib.remap_layers(&map), 0);
+}
+
+#[test]
+fn json_versioned() -> GdsResult<()> {
+    let mut lib = GdsLibrary::with_units("json_lib", GdsUnits::new(1e-3, 1e-9));
+    lib.dates = test_dates();
+    lib.structs.push(GdsStruct {
+        name: "cell".into(),
+        dates: test_dates(),
+        elems: vec![GdsBoundary {
+            layer: 1,
+            xy: GdsPoint::vec(&[(0, 0), (10, 0), (10, 20), (0, 20), (0, 0)]),
+            ..GdsBoundary::default()
+        }
+        .into()],
+    });
+
+    let json = lib.to_json_versioned();
+    let value: serde_json::Value = serde_json::from_str(&json).unwrap();
+    assert_eq!(value["schema_version"], GdsLibrary::JSON_SCHEMA_VERSION);
+    assert_eq!(GdsLibrary::from_json_versioned(&json)?, lib);
+
+    let incompatible = json.replacen(
+        &format!("\"schema_version\":\"{}\"", GdsLibrary::JSON_SCHEMA_VERSION),
+        "\"schema_version\":\"0\"",
+        1,
+    );
+    assert_ne!(incompatible, json);
+    assert!(GdsLibrary::from_json_versioned(&incompatible).is_err());
+    assert!(GdsLibrary::from_json_versioned(&serde_json::to_string(&lib).unwrap()).is_err());
+    Ok(())
+}
+
+#[test]
+fn stream_writer() -> GdsResult<()> {
+    let strukt = |name: &str| GdsStruct {
+        name: name.into(),
+        dates: test_dates(),
+        elems: vec![GdsBoundary {
+            layer: 1,
+            xy: GdsPoint::vec(&[(0, 0), (10, 0), (10, 20), (0, 20), (0, 0)]),
+            ..GdsBoundary::default()
+        }
+        .into()],
+    };
+    let mut lib = GdsLibrary::with_units("stream_lib", GdsUnits::new(1e-3, 1e-9));
+    lib.dates = test_dates();
+    lib.structs = vec![strukt("first"), strukt("second"), strukt("third")];
+
+    let dir = tempfile::tempdir()?;
+    let saved = dir.path().join("saved.gds");
+    lib.save(&saved)?;
+
+    let streamed = dir.path().join("streamed.gds");
+    let mut wr = GdsWriter::open(&streamed)?;
+    assert!(wr.write_struct(&lib.structs[0]).is_err());
+    assert!(wr.end_library().is_err());
+    wr.begin_library(lib.name.clone(), lib.units.clone(), test_dates())?;
+    assert!(wr
+        .begin_library(lib.name.clone(), lib.units.clone(), test_dates())
+        .is_err());
+    for strukt in lib.structs.iter() {
+        wr.write_struct(strukt)?;
+    }
+    wr.end_library()?;
+    assert!(wr.write_struct(&lib.structs[0]).is_err());
+    assert!(wr.end_library().is_err());
+    drop(wr);
+
+    assert_eq!(std::fs::read(&saved)?, std::fs::read(&streamed)?);
+    Ok(())
+}
+
+#[test]
+fn save_incremental() -> GdsResult<()> {
+    let strukt = |name: &str| GdsStruct {
+        name: name.into(),
+        dates: test_dates(),
+        elems: vec![GdsBoundary {
+            layer: 1,
+            xy: GdsPoint::vec(&[(0, 0), (10, 0), (10, 20), (0, 20), (0, 0)]),
+            ..GdsBoundary::default()
+        }
+        .into()],
+    };
+    let mut lib = GdsLibrary::with_units("incremental_lib", GdsUnits::new(1e-3, 1e-9));
+    lib.dates = test_dates();
+    lib.structs = vec![strukt("first"), strukt("second"), strukt("third")];
+
+    let dir = tempfile::tempdir()?;
+    let (full, incremental) = (dir.path().join("full.gds"), dir.path().join("inc.gds"));
+    let mut dirty = StructDirtySet::new();
+    lib.save(&full)?;
+    lib.save_incremental(&incremental, &dirty)?;
+    assert_eq!(std::fs::read(&full)?, std::fs::read(&incremental)?);
+
+    lib.structs[1].elems.push(
+        GdsBoundary {
+            layer: 2,
+            xy: GdsPoint::vec(&[(0, 0), (5, 0), (5, 5), (0, 5), (0, 0)]),
+            ..GdsBoundary::default()
+        }
+        .into(),
+    );
+    dirty.mark_dirty("second");
+    lib.save(&full)?;
+    lib.save_incremental(&incremental, &dirty)?;
+    assert_eq!(std::fs::read(&full)?, std::fs::read(&incremental)?);
+    Ok(())
+}
+
+#[test]
+fn split_large_boundaries() -> GdsResult<()> {
+    // A staircase with 10,000 vertices.
+    let steps = 4_999;
+    let mut pts = vec![(0, 0)];
+    for i in 0..steps {
+        pts.push((10 * i, 10 * (i + 1)));
+        pts.push((10 * (i + 1), 10 * (i + 1)));
+    }
+    pts.push((10 * steps, 0));
+    pts.push((0, 0));
+    let boundary = GdsBoundary {
+        layer: 3,
+        datatype: 1,
+        xy: GdsPoint::vec(&pts),
+        ..GdsBoundary::default()
+    };
+    assert_eq!(boundary.num_vertices(), 10_000);
+
+    let mut strukt = GdsStruct::new("big");
+    strukt.elems.push(boundary.clone().into());
+    let mut lib = GdsLibrary::new("big_lib");
+    lib.structs.push(strukt);
+    assert!(lib.validate().is_err());
+
+    lib.split_large_boundaries(GdsBoundary::MAX_VERTICES)?;
+    lib.validate().unwrap();
+
+    let pieces: Vec<_> = lib.structs[0]
+        .elems
+        .iter()
+        .map(|elem| match elem {
+            GdsElement::GdsBoundary(b) => b,
+            _ => panic!("expected only boundaries"),
+        })
+        .collect();
+    assert!(pieces.len() > 1);
+    let area: i64 = pieces
+        .iter()
+        .map(|b| {
+            assert!(b.num_vertices() <= GdsBoundary::MAX_VERTICES);
+            assert_eq!(b.xy.first(), b.xy.last());
+            assert_eq!((b.layer, b.datatype), (3, 1));
+            b.signed_area2().abs()
+        })
+        .sum();
+    assert_eq!(area, boundary.signed_area2().abs());
+    Ok(())
+}
+
+#[test]
+fn split_large_boundaries_without_progress() {
+    // A self-intersecting boundary whose halves are no smaller than itself.
+    let boundary = GdsBoundary {
+        layer: 3,
+        datatype: 1,
+        xy: GdsPoint::vec(&[(4, 1), (2, 1), (1, 3), (2, 0), (3, 4), (0, 1), (4, 1)]),
+        ..GdsBoundary::default()
+    };
+    assert!(boundary.split(4).is_err());
+
+    let mut strukt = GdsStruct::new("bad");
+    strukt.elems.push(boundary.into());
+    let mut lib = GdsLibrary::new("bad_lib");
+    lib.structs.push(strukt);
+    let before = lib.clone();
+    assert!(lib.split_large_boundaries(4).is_err());
+    assert_eq!(lib, before);
+}
@@ -4,9 +4,26 @@
 use super::*;
 
 /// A GDS writer.
+///
+/// Writes entire libraries via [GdsWriter::write_lib], or streams a library one struct
+/// at a time via [GdsWriter::begin_library], [GdsWriter::write_struct],
+/// and [GdsWriter::end_library].
 pub struct GdsWriter<'wr> {
     /// Write destination.
     dest: Box<dyn Write + 'wr>,
+    /// Progress through a library being streamed.
+    state: StreamState,
+}
+
+/// Progress of a [GdsWriter] through a streamed library.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum StreamState {
+    /// No library has been begun.
+    Idle,
+    /// The library header has been written; structs may be written.
+    InLibrary,
+    /// The library has been ended.
+    Done,
 }
 
 impl<'wr> GdsWriter<'wr> {
@@ -20,9 +37,57 @@ impl<'wr> GdsWriter<'wr> {
     pub fn new(dest: impl Write + 'wr) -> Self {
         Self {
             dest: Box::new(dest),
+            state: StreamState::Idle,
         }
     }
 
+    /// Begins streaming a library, writing its header records.
+    ///
+    /// Must be called exactly once, before any calls to [GdsWriter::write_struct].
+    pub fn begin_library(
+        &mut self,
+        name: impl Into<ArcStr>,
+        units: GdsUnits,
+        dates: GdsDateTimes,
+    ) -> GdsResult<()> {
+        self.check_state(StreamState::Idle, "begin_library")?;
+        let lib = GdsLibrary {
+            dates,
+            ..GdsLibrary::with_units(name, units)
+        };
+        self.encode_lib_header(&lib)?;
+        self.state = StreamState::InLibrary;
+        Ok(())
+    }
+
+    /// Writes a struct to the library begun by [GdsWriter::begin_library].
+    pub fn write_struct(&mut self, strukt: &GdsStruct) -> GdsResult<()> {
+        self.check_state(StreamState::InLibrary, "write_struct")?;
+        self.encode_struct(strukt)
+    }
+
+    /// Ends the library begun by [GdsWriter::begin_library] and flushes the destination.
+    pub fn end_library(&mut self) -> GdsResult<()> {
+        self.check_state(StreamState::InLibrary, "end_library")?;
+        self.encode_record(GdsRecord::EndLib)?;
+        self.dest.flush()?;
+        self.state = StreamState::Done;
+        Ok(())
+    }
+
+    /// Returns an error if the writer is not in `expected` state when calling `method`.
+    fn check_state(&self, expected: StreamState, method: &str) -> GdsResult<()> {
+        if self.state == expected {
+            return Ok(());
+        }
+        let reason = match self.state {
+            StreamState::Idle => "no library has been begun",
+            StreamState::InLibrary => "a library has already been begun",
+            StreamState::Done => "the library has already been ended",
+        };
+        Err(format!("cannot call `GdsWriter::{method}`: {reason}").into())
+    }
+
     /// Writes [GdsLibrary] `lib` to our destination.
     pub fn write_lib(&mut self, lib: &GdsLibrary) -> GdsResult<()> {
         // `write_lib` is our typicaly entry point when writing to file.
@@ -30,6 +95,29 @@ impl<'wr> GdsWriter<'wr> {
         self.encode_lib(lib)
     }
 
+    /// Writes [GdsLibrary] `lib` to our destination, reusing the cached bytes of
+    /// structs that are not marked dirty in `dirty`.
+    ///
+    /// Produces the same bytes as [GdsWriter::write_lib].
+    pub fn write_lib_incremental(
+        &mut self,
+        lib: &GdsLibrary,
+        dirty: &StructDirtySet,
+    ) -> GdsResult<()> {
+        self.encode_lib_header(lib)?;
+        let mut cache = dirty.cache.lock().unwrap();
+        for strukt in lib.structs.iter() {
+            if dirty.is_dirty(&strukt.name) || !cache.contains_key(&strukt.name) {
+                let mut bytes = Vec::new();
+                GdsWriter::new(&mut bytes).encode_struct(strukt)?;
+                cache.insert(strukt.name.clone(), bytes);
+            }
+            self.dest.write_all(&cache[&strukt.name])?;
+        }
+        self.encode_record(GdsRecord::EndLib)?;
+        Ok(())
+    }
+
     /// Helper to write a sequence of [GdsRecord] references.
     fn write_records(&mut self, records: &[GdsRecord]) -> GdsResult<()> {
         for r in records {
@@ -166,12 +254,12 @@ impl<'wr> GdsWriter<'wr> {
             | GdsRecord::EndExtn(d) => self.dest.write_i32::<BigEndian>(*d)?,
             // Single F64s
             GdsRecord::Mag(d) | GdsRecord::Angle(d) => {
-                self.dest.write_u64::<BigEndian>(GdsFloat64::encode(*d))?
+                self.dest.write_u64::<BigEndian>(GdsFloat64::encode(*d)?)?
             }
             // "Structs"
             GdsRecord::Units(d0, d1) => {
-                self.dest.write_u64::<BigEndian>(GdsFloat64::encode(*d0))?;
-                self.dest.write_u64::<BigEndian>(GdsFloat64::encode(*d1))?;
+                self.dest.write_u64::<BigEndian>(GdsFloat64::encode(*d0)?)?;
+                self.dest.write_u64::<BigEndian>(GdsFloat64::encode(*d1)?)?;
             }
             GdsRecord::ColRow { cols, rows } => {
                 self.dest.write_i16::<BigEndian>(*cols)?;
@@ -250,6 +338,18 @@ trait Encode {
     /// Encodes a [GdsLibrary].
     fn encode_lib(&mut self, lib: &GdsLibrary) -> GdsResult<()> {
         // Write our header content
+        self.encode_lib_header(lib)?;
+        // Write all of our Structs/Cells
+        for strukt in lib.structs.iter() {
+            self.encode_struct(strukt)?;
+        }
+        // And finally, the library terminator
+        self.encode_record(GdsRecord::EndLib)?;
+        Ok(())
+    }
+
+    /// Encodes the header records of a [GdsLibrary].
+    fn encode_lib_header(&mut self, lib: &GdsLibrary) -> GdsResult<()> {
         self.encode_records(&[
             GdsRecord::Header {
                 version: lib.version,
@@ -259,14 +359,7 @@ trait Encode {
             },
             GdsRecord::LibName(lib.name.clone()),
             GdsRecord::Units(lib.units.0, lib.units.1),
-        ])?;
-        // Write all of our Structs/Cells
-        for strukt in lib.structs.iter() {
-            self.encode_struct(strukt)?;
-        }
-        // And finally, the library terminator
-        self.encode_record(GdsRecord::EndLib)?;
-        Ok(())
+        ])
     }
 
     /// Encodes a [GdsStruct].
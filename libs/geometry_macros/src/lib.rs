@@ -31,6 +31,7 @@ pub fn derive_translate_mut(input: TokenStream) -> TokenStream {
         method: quote!(translate_mut),
         extra_arg_idents: vec![quote!(__geometry_derive_point)],
         extra_arg_tys: vec![quote!(#geometry::point::Point)],
+        binary: None,
     };
 
     let expanded = derive_trait(&config, receiver);
@@ -48,6 +49,7 @@ pub fn derive_transform_mut(input: TokenStream) -> TokenStream {
         method: quote!(transform_mut),
         extra_arg_idents: vec![quote!(__geometry_derive_transformation)],
         extra_arg_tys: vec![quote!(#geometry::transform::Transformation)],
+        binary: None,
     };
 
     let expanded = derive_trait(&config, receiver);
@@ -0,0 +1,246 @@
+//! Structural comparison of SCIR libraries.
+//!
+//! Cells, ports, signals, and instances are matched by name, since IDs are arbitrary.
+
+use super::*;
+
+/// A change to a named item between two libraries.
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+pub enum Change<T> {
+    /// The item is only present in the second library.
+    Added(T),
+    /// The item is only present in the first library.
+    Removed(T),
+    /// The item is present in both libraries, but differs.
+    Changed {
+        /// The item in the first library.
+        old: T,
+        /// The item in the second library.
+        new: T,
+    },
+}
+
+/// The child of an instance, identified in a library-independent way.
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+pub enum DiffChild {
+    /// A cell with the given name.
+    Cell(ArcStr),
+    /// A primitive with the given ID in its respective library.
+    Primitive(PrimitiveId),
+}
+
+/// The differences between two instances with the same name.
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+pub struct InstanceDiff {
+    /// The name of the instance.
+    pub name: ArcStr,
+    /// The change to the instance's child, if any.
+    ///
+    /// Added or removed instances report their child as added or removed.
+    pub child: Option<Change<DiffChild>>,
+    /// Changed connections, keyed by port name.
+    ///
+    /// Connections are listed bit by bit, as signal names with an optional `[index]` suffix.
+    pub connections: Vec<(ArcStr, Change<Vec<ArcStr>>)>,
+}
+
+/// The differences between two cells with the same name.
+#[derive(Clone, Debug, Default, Eq, PartialEq, Serialize, Deserialize)]
+pub struct CellDiff {
+    /// The name of the cell.
+    pub name: ArcStr,
+    /// Changed port directions, keyed by port name.
+    pub ports: Vec<(ArcStr, Change<Direction>)>,
+    /// Changed signal widths, keyed by signal name.
+    pub signals: Vec<(ArcStr, Change<Option<usize>>)>,
+    /// Changed instances.
+    pub instances: Vec<InstanceDiff>,
+}
+
+impl CellDiff {
+    /// Returns `true` if the two cells are structurally identical.
+    pub fn is_empty(&self) -> bool {
+        self.ports.is_empty() && self.signals.is_empty() && self.instances.is_empty()
+    }
+}
+
+/// The structural differences between two SCIR libraries.
+#[derive(Clone, Debug, Default, Eq, PartialEq, Serialize, Deserialize)]
+pub struct LibraryDiff {
+    /// Names of cells only present in the second library.
+    pub added_cells: Vec<ArcStr>,
+    /// Names of cells only present in the first library.
+    pub removed_cells: Vec<ArcStr>,
+    /// Differences between cells present in both libraries.
+    pub cells: Vec<CellDiff>,
+}
+
+impl LibraryDiff {
+    /// Returns `true` if the two libraries are structurally identical.
+    pub fn is_empty(&self) -> bool {
+        self.added_cells.is_empty() && self.removed_cells.is_empty() && self.cells.is_empty()
+    }
+}
+
+/// Compares two libraries, reporting how `b` differs from `a`.
+///
+/// Primitive children are compared by value.
+pub fn diff_libraries<S: Schema + ?Sized>(a: &Library<S>, b: &Library<S>) -> LibraryDiff
+where
+    S::Primitive: PartialEq,
+{
+    let mut diff = LibraryDiff::default();
+    for (_, cell) in a.cells() {
+        match b.try_cell_named(cell.name()) {
+            Some(other) => {
+                let cell_diff = diff_cells(a, cell, b, other);
+                if !cell_diff.is_empty() {
+                    diff.cells.push(cell_diff);
+                }
+            }
+            None => diff.removed_cells.push(cell.name().clone()),
+        }
+    }
+    for (_, cell) in b.cells() {
+        if a.try_cell_named(cell.name()).is_none() {
+            diff.added_cells.push(cell.name().clone());
+        }
+    }
+    diff
+}
+
+fn diff_cells<S: Schema + ?Sized>(
+    a: &Library<S>,
+    a_cell: &Cell,
+    b: &Library<S>,
+    b_cell: &Cell,
+) -> CellDiff
+where
+    S::Primitive: PartialEq,
+{
+    let ports = |cell: &Cell| {
+        cell.ports()
+            .map(|port| (cell.signal(port.signal()).name.clone(), port.direction()))
+            .collect::<IndexMap<_, _>>()
+    };
+    let signals = |cell: &Cell| {
+        let mut signals = cell
+            .signals()
+            .map(|(_, info)| (info.name.clone(), info.width))
+            .collect::<Vec<_>>();
+        signals.sort();
+        signals.into_iter().collect::<IndexMap<_, _>>()
+    };
+    let children = |lib: &Library<S>, cell: &Cell| {
+        cell.instances()
+            .map(|(_, inst)| {
+                let child = match inst.child() {
+                    ChildId::Cell(id) => DiffChild::Cell(lib.cell(id).name().clone()),
+                    ChildId::Primitive(id) => DiffChild::Primitive(id),
+                };
+                (inst.name().clone(), child)
+            })
+            .collect::<IndexMap<_, _>>()
+    };
+    let same_child = |old: &DiffChild, new: &DiffChild| match (old, new) {
+        (DiffChild::Primitive(old), DiffChild::Primitive(new)) => {
+            a.primitive(*old) == b.primitive(*new)
+        }
+        _ => old == new,
+    };
+
+    let (a_children, b_children) = (children(a, a_cell), children(b, b_cell));
+    let mut instances = Vec::new();
+    let names = a_children.keys().chain(
+        b_children
+            .keys()
+            .filter(|name| !a_children.contains_key(*name)),
+    );
+    for name in names {
+        let child = match (a_children.get(name), b_children.get(name)) {
+            (Some(old), Some(new)) if same_child(old, new) => None,
+            (Some(old), Some(new)) => Some(Change::Changed {
+                old: old.clone(),
+                new: new.clone(),
+            }),
+            (Some(old), None) => Some(Change::Removed(old.clone())),
+            (None, Some(new)) => Some(Change::Added(new.clone())),
+            (None, None) => unreachable!(),
+        };
+        let connections = diff_named(
+            &connections(a_cell, a_cell.try_instance_named(name)),
+            &connections(b_cell, b_cell.try_instance_named(name)),
+            PartialEq::eq,
+        );
+        if child.is_some() || !connections.is_empty() {
+            instances.push(InstanceDiff {
+                name: name.clone(),
+                child,
+                connections,
+            });
+        }
+    }
+
+    CellDiff {
+        name: a_cell.name().clone(),
+        ports: diff_named(&ports(a_cell), &ports(b_cell), PartialEq::eq),
+        signals: diff_named(&signals(a_cell), &signals(b_cell), PartialEq::eq),
+        instances,
+    }
+}
+
+/// The connections of the given instance, if it exists, as lists of bit names.
+fn connections(cell: &Cell, inst: Option<&Instance>) -> IndexMap<ArcStr, Vec<ArcStr>> {
+    let Some(inst) = inst else {
+        return IndexMap::new();
+    };
+    let mut ports = inst.connections().iter().collect::<Vec<_>>();
+    ports.sort_by(|a, b| a.0.cmp(b.0));
+    ports
+        .into_iter()
+        .map(|(port, conn)| {
+            let bits = conn
+                .parts()
+                .flat_map(|part| {
+                    let name = &cell.signal(part.signal()).name;
+                    match part.range() {
+                        Some(range) => range
+                            .indices()
+                            .map(|i| arcstr::format!("{}[{}]", name, i))
+                            .collect(),
+                        None => vec![name.clone()],
+                    }
+                })
+                .collect();
+            (port.clone(), bits)
+        })
+        .collect()
+}
+
+/// Compares two name-keyed maps, listing changes in the order of `a` followed by `b`.
+fn diff_named<V: Clone>(
+    a: &IndexMap<ArcStr, V>,
+    b: &IndexMap<ArcStr, V>,
+    same: impl Fn(&V, &V) -> bool,
+) -> Vec<(ArcStr, Change<V>)> {
+    let mut changes = Vec::new();
+    for (name, old) in a {
+        match b.get(name) {
+            Some(new) if !same(old, new) => changes.push((
+                name.clone(),
+                Change::Changed {
+                    old: old.clone(),
+                    new: new.clone(),
+                },
+            )),
+            Some(_) => {}
+            None => changes.push((name.clone(), Change::Removed(old.clone()))),
+        }
+    }
+    for (name, new) in b {
+        if !a.contains_key(name) {
+            changes.push((name.clone(), Change::Added(new.clone())));
+        }
+    }
+    changes
+}
@@ -34,8 +34,11 @@ impl Display for Net {
 #[derive(Clone, Debug, Hash, Eq, PartialEq, Serialize, Deserialize)]
 pub struct DriverIssue {
     cause: Cause,
+    /// The cell in which this issue was found.
+    cell: CellId,
     severity: Severity,
-    net: Net,
+    /// The net associated with this issue, if any.
+    net: Option<Net>,
 }
 
 /// The state of a net.
@@ -79,35 +82,39 @@ impl NetState {
     }
 
     /// Validates the number of drivers, taps, and inouts on the net.
-    fn validate(&self, net: Net, output: &mut IssueSet<DriverIssue>) {
+    fn validate(&self, cell: CellId, net: Net, output: &mut IssueSet<DriverIssue>) {
         if self.drivers > 1 {
             output.add(DriverIssue::new_and_log(
+                cell,
                 Cause::MultipleDrivers,
-                net.clone(),
+                Some(net.clone()),
                 Severity::Info,
             ));
         }
 
         if self.taps > 0 && self.inouts + self.drivers == 0 {
             output.add(DriverIssue::new_and_log(
+                cell,
                 Cause::NoDrivers,
-                net.clone(),
+                Some(net.clone()),
                 Severity::Warning,
             ));
         }
 
         if self.degree() == 0 {
             output.add(DriverIssue::new_and_log(
+                cell,
                 Cause::Floating,
-                net.clone(),
+                Some(net.clone()),
                 Severity::Warning,
             ));
         }
 
         if self.taps == 0 && self.eff_drivers() == 1 {
             output.add(DriverIssue::new_and_log(
+                cell,
                 Cause::NotConnected,
-                net.clone(),
+                Some(net.clone()),
                 Severity::Info,
             ));
         }
@@ -133,6 +140,15 @@ pub enum Cause {
     ///
     /// Example: an inverter whose input port is not connected.
     NoDrivers,
+    /// An instance does not specify a connection to a port of its child cell.
+    UnconnectedPort {
+        /// The name of the cell containing the offending instance.
+        cell: ArcStr,
+        /// The name of the offending instance.
+        instance: ArcStr,
+        /// The name of the unconnected port.
+        port: ArcStr,
+    },
 }
 
 impl Diagnostic for DriverIssue {
@@ -143,9 +159,10 @@ impl Diagnostic for DriverIssue {
 
 impl DriverIssue {
     /// Creates a new validator issue from the given cause and severity.
-    pub(crate) fn new(cause: Cause, net: Net, severity: Severity) -> Self {
+    pub(crate) fn new(cell: CellId, cause: Cause, net: Option<Net>, severity: Severity) -> Self {
         Self {
             cause,
+            cell,
             net,
             severity,
         }
@@ -157,11 +174,22 @@ impl DriverIssue {
         &self.cause
     }
 
+    /// The ID of the cell in which this issue was found.
+    #[inline]
+    pub fn cell(&self) -> CellId {
+        self.cell
+    }
+
     /// Creates a new validator issue and logs it immediately.
     ///
     /// The log level will be selected according to the given severity.
-    pub(crate) fn new_and_log(cause: Cause, net: Net, severity: Severity) -> Self {
-        let result = Self::new(cause, net, severity);
+    pub(crate) fn new_and_log(
+        cell: CellId,
+        cause: Cause,
+        net: Option<Net>,
+        severity: Severity,
+    ) -> Self {
+        let result = Self::new(cell, cause, net, severity);
         match severity {
             Severity::Info => tracing::event!(Level::INFO, issue = ?result.cause, "{}", result),
             Severity::Warning => tracing::event!(Level::WARN, issue = ?result.cause, "{}", result),
@@ -173,7 +201,10 @@ impl DriverIssue {
 
 impl Display for DriverIssue {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "{}: {}", self.cause, self.net)
+        match &self.net {
+            Some(net) => write!(f, "{}: {}", self.cause, net),
+            None => write!(f, "{}", self.cause),
+        }
     }
 }
 
@@ -184,6 +215,15 @@ impl Display for Cause {
             Self::MultipleDrivers => write!(f, "multiple drivers on the same net"),
             Self::NoDrivers => write!(f, "net is used (i.e. read from), but has no drivers"),
             Self::NotConnected => write!(f, "net is driven but never used elsewhere"),
+            Self::UnconnectedPort {
+                cell,
+                instance,
+                port,
+            } => write!(
+                f,
+                "instance `{}` in cell `{}` does not connect port `{}`",
+                instance, cell, port
+            ),
         }
     }
 }
@@ -227,12 +267,14 @@ impl<S: Schema + ?Sized> LibraryBuilder<S> {
 
         for (_, instance) in cell.instances.iter() {
             analyze_instance(self, &mut net_states, instance);
+            check_instance_ports(self, id, cell, instance, issues);
         }
 
         for (sig, list) in net_states.iter() {
             for (i, state) in list.iter().enumerate() {
                 let info = cell.signal(*sig);
                 state.validate(
+                    id,
                     Net {
                         cell_name: cell.name().clone(),
                         signal_name: info.name.clone(),
@@ -269,6 +311,34 @@ fn analyze_instance<S: Schema + ?Sized>(
     }
 }
 
+fn check_instance_ports<S: Schema + ?Sized>(
+    lib: &LibraryBuilder<S>,
+    id: CellId,
+    cell: &Cell,
+    inst: &Instance,
+    issues: &mut IssueSet<DriverIssue>,
+) {
+    let ChildId::Cell(child) = inst.child() else {
+        return;
+    };
+    let child = lib.cell(child);
+    for port in child.ports() {
+        let name = &child.signal(port.signal()).name;
+        if !inst.connections().contains_key(name) {
+            issues.add(DriverIssue::new_and_log(
+                id,
+                Cause::UnconnectedPort {
+                    cell: cell.name().clone(),
+                    instance: inst.name().clone(),
+                    port: name.clone(),
+                },
+                None,
+                Severity::Warning,
+            ));
+        }
+    }
+}
+
 fn update_net_state(state: &mut NetState, dir: Direction) {
     match dir {
         Direction::Output => state.drivers += 1,
@@ -24,9 +24,11 @@
 //! Zero-width buses are not supported.
 #![warn(missing_docs)]
 
-use std::collections::{HashMap, HashSet, VecDeque};
+use std::collections::{BTreeSet, HashMap, HashSet, VecDeque};
 use std::fmt::{Display, Formatter};
+use std::hash::Hash;
 use std::ops::{Deref, DerefMut};
+use std::sync::Arc;
 
 use arcstr::ArcStr;
 use diagnostics::IssueSet;
@@ -37,15 +39,21 @@ use serde::{Deserialize, Serialize};
 use tracing::{span, Level};
 use uniquify::Names;
 
+pub mod diff;
 pub mod merge;
 pub mod schema;
 mod slice;
 
 use crate::schema::{FromSchema, NoSchema, NoSchemaError, Schema};
 use crate::validation::ValidatorIssue;
-pub use slice::{Concat, IndexOwned, NamedSlice, NamedSliceOne, Slice, SliceOne, SliceRange};
+pub use slice::{
+    Concat, ConcatBuilder, ConcatWidthMismatch, IndexOwned, NamedSlice, NamedSliceOne, Slice,
+    SliceOne, SliceRange,
+};
 
 pub mod drivers;
+#[cfg(any(test, feature = "selftest"))]
+pub mod selftest;
 pub mod validation;
 
 #[cfg(test)]
@@ -59,6 +67,10 @@ pub enum ParamValue {
     String(ArcStr),
     /// A numeric parameter value.
     Numeric(Decimal),
+    /// An expression, such as `w*2`, emitted verbatim by netlisters.
+    ///
+    /// Expressions are not parsed or evaluated during validation.
+    Expr(ArcStr),
 }
 
 impl From<ArcStr> for ParamValue {
@@ -78,6 +90,7 @@ impl Display for ParamValue {
         match self {
             ParamValue::String(s) => write!(f, "{}", s),
             ParamValue::Numeric(n) => write!(f, "{}", n),
+            ParamValue::Expr(e) => write!(f, "{}", e),
         }
     }
 }
@@ -463,6 +476,11 @@ impl Display for ChildId {
 }
 
 /// A library of SCIR cells with schema `S`.
+#[derive(Serialize, Deserialize)]
+#[serde(bound(
+    serialize = "S::Primitive: Serialize",
+    deserialize = "S::Primitive: Deserialize<'de>"
+))]
 pub struct LibraryBuilder<S: Schema + ?Sized = NoSchema> {
     /// The current cell ID counter.
     ///
@@ -588,6 +606,25 @@ impl<S: Schema + ?Sized> Library<S> {
     pub fn into_builder(self) -> LibraryBuilder<S> {
         self.0
     }
+
+    /// Returns the child instance ports of `cell` connected to any bit of `signal`.
+    ///
+    /// Instances are listed in insertion order; ports of the same instance are sorted by name.
+    pub fn connected_ports(&self, cell: CellId, signal: SignalId) -> Vec<(InstanceId, ArcStr)> {
+        let cell = self.cell(cell);
+        let mut ports = Vec::new();
+        for (id, inst) in cell.instances() {
+            let mut names = inst
+                .connections()
+                .iter()
+                .filter(|(_, conn)| conn.parts().any(|part| part.signal() == signal))
+                .map(|(name, _)| name.clone())
+                .collect::<Vec<_>>();
+            names.sort();
+            ports.extend(names.into_iter().map(|name| (id, name)));
+        }
+        ports
+    }
 }
 
 impl<S: Schema<Primitive = impl Clone> + ?Sized> Library<S> {
@@ -599,7 +636,7 @@ impl<S: Schema<Primitive = impl Clone> + ?Sized> Library<S> {
 }
 
 /// Issues encountered when validating a SCIR library.
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Default)]
 pub struct Issues {
     /// Correctness issues.
     pub correctness: IssueSet<ValidatorIssue>,
@@ -634,10 +671,61 @@ impl Issues {
     pub fn has_error(&self) -> bool {
         self.correctness.has_error() || self.drivers.has_error()
     }
+
+    /// Partitions these issues by the cell they reference.
+    ///
+    /// Issues concerning an instance are grouped under the parent cell containing it.
+    /// Issues referencing several cells, such as duplicate cell names,
+    /// appear under each of them.
+    pub fn by_cell(&self) -> HashMap<CellId, Issues> {
+        let mut by_cell: HashMap<CellId, Issues> = HashMap::new();
+        for issue in self.correctness.iter() {
+            for id in issue.cells() {
+                by_cell
+                    .entry(id)
+                    .or_default()
+                    .correctness
+                    .add(issue.clone());
+            }
+        }
+        for issue in self.drivers.iter() {
+            by_cell
+                .entry(issue.cell())
+                .or_default()
+                .drivers
+                .add(issue.clone());
+        }
+        by_cell
+    }
 }
 
+/// Statistics on the names used in a SCIR library.
+///
+/// Returned by [`LibraryBuilder::name_stats`].
+#[derive(Debug, Copy, Clone, Default, Eq, PartialEq, Hash, Serialize, Deserialize)]
+pub struct NameStats {
+    /// The number of distinct signal names across all cells.
+    pub unique_signal_names: usize,
+    /// The total number of signals across all cells.
+    pub total_signals: usize,
+    /// The number of distinct instance names across all cells.
+    pub unique_instance_names: usize,
+    /// The total number of instances across all cells.
+    pub total_instances: usize,
+}
+
+/// The error returned when an operation requires a top cell,
+/// but the library does not have one.
+#[derive(
+    Copy, Clone, Eq, PartialEq, Debug, Default, Hash, Serialize, Deserialize, thiserror::Error,
+)]
+#[error("library does not have a top cell")]
+pub struct NoTopCell;
+
 /// Port directions.
-#[derive(Debug, Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Default, Serialize, Deserialize)]
+#[derive(
+    Debug, Copy, Clone, Eq, PartialEq, Hash, Ord, PartialOrd, Default, Serialize, Deserialize,
+)]
 pub enum Direction {
     /// Input.
     Input,
@@ -712,6 +800,21 @@ pub struct Port {
     direction: Direction,
 }
 
+/// A port joined with information about its underlying signal.
+///
+/// Returned by [`Cell::port_details`].
+#[derive(Debug, Clone, Eq, PartialEq, Hash, Serialize, Deserialize)]
+pub struct PortDetail {
+    /// The name of the port.
+    pub name: ArcStr,
+    /// The direction of the port.
+    pub direction: Direction,
+    /// The width of the port, if the port is a bus.
+    pub width: Option<usize>,
+    /// The ID of the signal exposed by the port.
+    pub signal: SignalId,
+}
+
 /// Information about a signal in a cell.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SignalInfo {
@@ -744,6 +847,17 @@ impl SignalInfo {
     pub fn is_port(&self) -> bool {
         self.port.is_some()
     }
+
+    /// Iterates over the bits of this signal, yielding one [`SliceOne`] per bit.
+    ///
+    /// Single-wire signals yield exactly one unindexed [`SliceOne`].
+    pub fn bits(&self) -> impl Iterator<Item = SliceOne> {
+        let id = self.id;
+        (0..self.width.unwrap_or_default())
+            .map(Some)
+            .chain(self.width.is_none().then_some(None))
+            .map(move |index| SliceOne::new(id, index))
+    }
 }
 
 /// An instance of a child cell placed inside a parent cell.
@@ -760,6 +874,96 @@ pub struct Instance {
     /// The ports are the ports of the **child** cell.
     /// The connected signals are signals of the **parent** cell.
     connections: HashMap<ArcStr, Concat>,
+    /// Arbitrary key-value metadata attached to this instance.
+    ///
+    /// Ignored by validation and netlisting.
+    #[serde(default, skip_serializing_if = "IndexMap::is_empty")]
+    attributes: IndexMap<ArcStr, ArcStr>,
+}
+
+/// A builder for an [`Instance`] that allows connections to be chained.
+#[derive(Debug, Clone)]
+pub struct InstanceBuilder {
+    instance: Instance,
+    /// The first port that was connected more than once, if any.
+    duplicate: Option<ArcStr>,
+}
+
+/// The error returned when an [`InstanceBuilder`] connects the same port more than once.
+#[derive(Clone, Eq, PartialEq, Debug, Hash, Serialize, Deserialize, thiserror::Error)]
+#[error("port `{port}` of instance `{instance}` is connected more than once")]
+pub struct DuplicateConnection {
+    /// The name of the instance.
+    pub instance: ArcStr,
+    /// The name of the port that was connected more than once.
+    pub port: ArcStr,
+}
+
+/// The error returned when removing cells would leave a kept cell
+/// instantiating a cell that is no longer in the library.
+#[derive(Copy, Clone, Eq, PartialEq, Debug, Hash, Serialize, Deserialize, thiserror::Error)]
+#[error("cell {parent:?} instantiates removed cell {child:?}")]
+pub struct DanglingRefError {
+    /// The kept cell containing the dangling instance.
+    pub parent: CellId,
+    /// The removed cell that would be referenced.
+    pub child: CellId,
+}
+
+/// The error returned when a cell's instances form a loop
+/// that prevents computing an evaluation order.
+#[derive(Clone, Eq, PartialEq, Debug, Hash, Serialize, Deserialize, thiserror::Error)]
+#[error("instances {instances:?} form a loop")]
+pub struct CycleError {
+    /// The instances in the loop.
+    ///
+    /// Each instance drives the next, and the last instance drives the first.
+    pub instances: Vec<InstanceId>,
+}
+
+/// The error returned when a textual node path cannot be resolved
+/// by [`LibraryBuilder::parse_node_path`].
+#[derive(Clone, Eq, PartialEq, Debug, Hash, Serialize, Deserialize, thiserror::Error)]
+pub enum PathParseError {
+    /// No instance with the given name exists in the cell.
+    #[error("cell `{cell}` has no instance named `{instance}`")]
+    MissingInstance {
+        /// The name of the cell that was searched.
+        cell: ArcStr,
+        /// The name of the missing instance.
+        instance: ArcStr,
+    },
+    /// The path continues past an instance of a primitive.
+    #[error("instance `{instance}` is a primitive and cannot contain other instances")]
+    PrimitiveInstance {
+        /// The name of the primitive instance.
+        instance: ArcStr,
+    },
+    /// No signal with the given name exists in the cell.
+    #[error("cell `{cell}` has no signal named `{signal}`")]
+    MissingSignal {
+        /// The name of the cell that was searched.
+        cell: ArcStr,
+        /// The name of the missing signal.
+        signal: ArcStr,
+    },
+    /// The tail of the path is not of the form `name` or `name[index]`.
+    #[error("invalid signal reference `{tail}`")]
+    InvalidTail {
+        /// The unparsed tail.
+        tail: ArcStr,
+    },
+    /// The tail indexes a single-bit wire, omits the index of a bus,
+    /// or indexes a bus out of bounds.
+    #[error("invalid index {index:?} into signal `{signal}` of width {width:?}")]
+    InvalidIndex {
+        /// The name of the signal.
+        signal: ArcStr,
+        /// The width of the signal, or [`None`] for a single-bit wire.
+        width: Option<usize>,
+        /// The index given in the path, if any.
+        index: Option<usize>,
+    },
 }
 
 /// The ID of an instance's child.
@@ -810,6 +1014,11 @@ pub struct Cell {
     ///
     /// Instance names are only guaranteed to be unique in a validated [`Library`].
     instance_name_map: HashMap<ArcStr, InstanceId>,
+    /// Arbitrary key-value metadata attached to this cell.
+    ///
+    /// Ignored by validation and netlisting.
+    #[serde(default, skip_serializing_if = "IndexMap::is_empty")]
+    attributes: IndexMap<ArcStr, ArcStr>,
 }
 
 /// Metadata associated with the conversion from a SCIR library to a netlist.
@@ -1071,6 +1280,215 @@ impl<S: Schema + ?Sized> LibraryBuilder<S> {
         visited.drain().collect()
     }
 
+    /// Counts the instances of each primitive directly instantiated by the given cell.
+    ///
+    /// Instances of child cells are not counted, nor are primitives instantiated within them.
+    pub fn primitive_usage(&self, cell: CellId) -> HashMap<PrimitiveId, usize> {
+        let mut usage = HashMap::new();
+        for (_, inst) in self.cell(cell).instances() {
+            if let ChildId::Primitive(id) = inst.child {
+                *usage.entry(id).or_insert(0) += 1;
+            }
+        }
+        usage
+    }
+
+    /// Exports the cell hierarchy as a Graphviz DOT digraph.
+    ///
+    /// Each cell is a node. Each parent cell has one edge to each child cell it
+    /// instantiates, labeled with the names of the corresponding instances.
+    /// Primitive instances are omitted.
+    pub fn to_dot(&self) -> String {
+        fn quote(s: &str) -> String {
+            format!("\"{}\"", s.replace('\\', "\\\\").replace('"', "\\\""))
+        }
+
+        let mut out = String::from("digraph {\n");
+        for (_, cell) in self.cells() {
+            out.push_str(&format!("    {};\n", quote(cell.name())));
+        }
+        for (_, cell) in self.cells() {
+            let mut edges: IndexMap<CellId, Vec<&str>> = IndexMap::new();
+            for (_, inst) in cell.instances() {
+                if let ChildId::Cell(child) = inst.child {
+                    edges.entry(child).or_default().push(inst.name());
+                }
+            }
+            for (child, names) in edges {
+                out.push_str(&format!(
+                    "    {} -> {} [label={}];\n",
+                    quote(cell.name()),
+                    quote(self.cell(child).name()),
+                    quote(&names.join(", ")),
+                ));
+            }
+        }
+        out.push_str("}\n");
+        out
+    }
+
+    /// Removes all cells for which `keep` returns `false`.
+    ///
+    /// Returns an error and leaves the library unchanged if a kept cell
+    /// instantiates a cell that would be removed.
+    /// The top cell designation is cleared if the top cell is removed.
+    pub fn retain_cells(
+        &mut self,
+        keep: impl Fn(CellId, &Cell) -> bool,
+    ) -> Result<(), DanglingRefError> {
+        let kept: HashSet<CellId> = self
+            .cells()
+            .filter(|(id, cell)| keep(*id, cell))
+            .map(|(id, _)| id)
+            .collect();
+
+        for &parent in kept.iter() {
+            for (_, inst) in self.cell(parent).instances() {
+                if let ChildId::Cell(child) = inst.child {
+                    if !kept.contains(&child) {
+                        return Err(DanglingRefError { parent, child });
+                    }
+                }
+            }
+        }
+
+        self.cells.retain(|id, _| kept.contains(id));
+        self.name_map.retain(|_, id| kept.contains(id));
+        self.names = Names::new();
+        for (id, cell) in self.cells.iter() {
+            self.names.reserve_name(*id, cell.name.clone());
+        }
+        if self.top.is_some_and(|top| !kept.contains(&top)) {
+            self.top = None;
+        }
+        Ok(())
+    }
+
+    /// Moves the given instances of `parent` into a new cell, replacing them with
+    /// a single instance of that cell.
+    ///
+    /// The `boundary` signals of `parent` become ports of the new cell, in order.
+    /// Other signals used by both the extracted instances and the rest of `parent`
+    /// also become ports, with direction [`Direction::InOut`].
+    /// Signals used only by the extracted instances move into the new cell.
+    ///
+    /// The new cell and its instance in `parent` are named after `parent`,
+    /// with a suffix added if needed to keep the cell name unique.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `parent` or any of the given instances or signals do not exist.
+    pub fn extract_subcircuit(
+        &mut self,
+        parent: CellId,
+        instances: &[InstanceId],
+        boundary: &[(SignalId, Direction)],
+    ) -> CellId {
+        let cell = self.cells.get_mut(&parent).unwrap();
+        let extracted = instances
+            .iter()
+            .map(|id| {
+                cell.remove_instance(*id)
+                    .unwrap_or_else(|| panic!("instance {id} does not exist in cell {parent}"))
+            })
+            .collect::<Vec<_>>();
+
+        let signals_of = |inst: &Instance| {
+            inst.connections
+                .values()
+                .flat_map(|conn| conn.parts().map(|part| part.signal()))
+                .collect::<Vec<_>>()
+        };
+        let inside = extracted
+            .iter()
+            .flat_map(signals_of)
+            .collect::<BTreeSet<_>>();
+        let outside = cell
+            .instances
+            .values()
+            .flat_map(signals_of)
+            .chain(cell.ports.values().map(|port| port.signal))
+            .collect::<BTreeSet<_>>();
+
+        let mut ports = boundary.to_vec();
+        ports.extend(
+            inside
+                .intersection(&outside)
+                .filter(|id| !boundary.iter().any(|(signal, _)| signal == *id))
+                .map(|id| (*id, Direction::InOut)),
+        );
+        let internal = inside
+            .difference(&outside)
+            .filter(|id| !boundary.iter().any(|(signal, _)| signal == *id))
+            .copied()
+            .collect::<Vec<_>>();
+
+        let mut sub = Cell::new(arcstr::format!("{}_sub", cell.name));
+        let mut mapping = HashMap::new();
+        for (id, direction) in ports.iter() {
+            let info = cell.signal(*id);
+            let new = sub.add_signal(info.name.clone(), info.width);
+            sub.expose_port(new, *direction);
+            mapping.insert(*id, new);
+        }
+        for id in internal {
+            let info = cell.remove_signal(id);
+            mapping.insert(id, sub.add_signal(info.name, info.width));
+        }
+        for mut inst in extracted {
+            for conn in inst.connections.values_mut() {
+                *conn = conn
+                    .parts()
+                    .map(|part| Slice::new(mapping[&part.signal()], part.range()))
+                    .collect();
+            }
+            sub.add_instance(inst);
+        }
+
+        let connections = ports
+            .iter()
+            .map(|(id, _)| (cell.signal(*id).name.clone(), cell.signal(*id).slice()))
+            .collect::<Vec<_>>();
+        let id = self.merge_cell(sub);
+        let mut inst = Instance::new(self.cell(id).name.clone(), id);
+        for (port, slice) in connections {
+            inst.connect(port, slice);
+        }
+        self.cells.get_mut(&parent).unwrap().add_instance(inst);
+        id
+    }
+
+    /// Renames the cell with the given ID.
+    ///
+    /// Instances refer to cells by ID, so they need no updates.
+    /// Returns the conflicting name and leaves the library unchanged
+    /// if another cell already has the new name.
+    ///
+    /// # Panics
+    ///
+    /// Panics if no cell has the given ID.
+    pub fn rename_cell(&mut self, id: CellId, new_name: impl Into<ArcStr>) -> Result<(), ArcStr> {
+        let new_name = new_name.into();
+        if self
+            .name_map
+            .get(&new_name)
+            .is_some_and(|other| *other != id)
+        {
+            return Err(new_name);
+        }
+        let cell = self.cells.get_mut(&id).unwrap();
+        let old_name = std::mem::replace(&mut cell.name, new_name.clone());
+        if self.name_map.get(&old_name) == Some(&id) {
+            self.name_map.remove(&old_name);
+        }
+        self.name_map.insert(new_name, id);
+        self.names = Names::new();
+        for (id, cell) in self.cells.iter() {
+            self.names.reserve_name(*id, cell.name.clone());
+        }
+        Ok(())
+    }
+
     /// Gets the primitive with the given ID.
     ///
     /// # Panics
@@ -1094,6 +1512,210 @@ impl<S: Schema + ?Sized> LibraryBuilder<S> {
             .map(|(id, primitive)| (*id, primitive))
     }
 
+    /// Returns the IDs of primitives that are not instantiated by any cell.
+    ///
+    /// Primitives are listed in the order in which they were added.
+    pub fn unused_primitives(&self) -> Vec<PrimitiveId> {
+        let used: HashSet<PrimitiveId> = self
+            .cells()
+            .flat_map(|(_, cell)| cell.instances())
+            .filter_map(|(_, inst)| match inst.child {
+                ChildId::Primitive(id) => Some(id),
+                ChildId::Cell(_) => None,
+            })
+            .collect();
+        self.primitives
+            .keys()
+            .copied()
+            .filter(|id| !used.contains(id))
+            .collect()
+    }
+
+    /// Counts unique and total names used in this library.
+    ///
+    /// Useful for diagnosing libraries with many duplicated name strings.
+    pub fn name_stats(&self) -> NameStats {
+        let mut signal_names = HashSet::new();
+        let mut instance_names = HashSet::new();
+        let mut stats = NameStats::default();
+        for cell in self.cells.values() {
+            for (_, info) in cell.signals() {
+                signal_names.insert(&info.name);
+                stats.total_signals += 1;
+            }
+            for (_, inst) in cell.instances() {
+                instance_names.insert(&inst.name);
+                stats.total_instances += 1;
+            }
+        }
+        stats.unique_signal_names = signal_names.len();
+        stats.unique_instance_names = instance_names.len();
+        stats
+    }
+
+    /// Returns the port signals of the top cell, along with their directions.
+    ///
+    /// These are the external nets of the design, in port order.
+    pub fn top_io_signals(&self) -> Result<Vec<(SignalId, Direction)>, NoTopCell> {
+        let top = self.top_cell().ok_or(NoTopCell)?;
+        Ok(self
+            .cell(top)
+            .ports()
+            .map(|port| (port.signal(), port.direction()))
+            .collect())
+    }
+
+    /// Splits every bus signal in the library into single-bit signals.
+    ///
+    /// Bit `i` of a bus named `x` becomes a signal named `x[i]`.
+    /// Bus ports are replaced by one port per bit, and instance connections
+    /// throughout the hierarchy are rewired accordingly.
+    /// Port names of primitives are left unchanged.
+    ///
+    /// # Panics
+    ///
+    /// Panics if a bit name `x[i]` is already used by another signal in the same cell,
+    /// or if a connection to an exploded bus port does not match the port's width.
+    pub fn explode_buses(&mut self) {
+        let exploded = self
+            .cells
+            .iter_mut()
+            .map(|(id, cell)| (*id, cell.explode_signals()))
+            .collect::<HashMap<_, _>>();
+
+        for (id, cell) in self.cells.iter_mut() {
+            let (bits, _) = &exploded[id];
+            for inst in cell.instances.values_mut() {
+                let ports = match inst.child {
+                    ChildId::Cell(child) => Some(&exploded[&child].1),
+                    ChildId::Primitive(_) => None,
+                };
+                inst.connections = inst
+                    .connections
+                    .drain()
+                    .flat_map(|(port, conn)| {
+                        let conn = conn
+                            .parts()
+                            .flat_map(|part| match (bits.get(&part.signal()), part.range()) {
+                                (Some(new), Some(range)) => {
+                                    range.indices().map(|i| Slice::new(new[i], None)).collect()
+                                }
+                                _ => vec![*part],
+                            })
+                            .collect::<Vec<_>>();
+                        match ports.and_then(|ports| ports.get(&port)) {
+                            Some(names) => {
+                                assert_eq!(
+                                    names.len(),
+                                    conn.len(),
+                                    "wrong width for port `{port}` of `{}`",
+                                    inst.name
+                                );
+                                names
+                                    .iter()
+                                    .cloned()
+                                    .zip(conn.into_iter().map(Concat::from))
+                                    .collect()
+                            }
+                            None => vec![(port, Concat::new(conn))],
+                        }
+                    })
+                    .collect();
+            }
+        }
+    }
+
+    /// Flattens the hierarchy below the given cell into a single new cell.
+    ///
+    /// Child cell instances are recursively inlined; primitive instances are kept.
+    /// Inlined signals and instances are named by their instance path, joined with `.`.
+    /// The signals and ports of `top` are preserved unchanged.
+    ///
+    /// # Panics
+    ///
+    /// Panics if no cell has the given ID, or if the hierarchy below it contains a cycle.
+    pub fn flatten_cell(&self, top: CellId) -> Cell {
+        let cell = self.cell(top);
+        let mut flat = cell.clone();
+        flat.instances.clear();
+        flat.instance_name_map.clear();
+        let bindings = cell
+            .signals
+            .values()
+            .flat_map(|info| info.bits())
+            .map(|bit| (bit, bit))
+            .collect();
+        self.inline_instances(&mut flat, cell, None, &bindings);
+        flat
+    }
+
+    /// Adds the instances of `cell` to `flat`, inlining child cells.
+    ///
+    /// `bindings` maps each bit of `cell` to the corresponding bit of `flat`.
+    fn inline_instances(
+        &self,
+        flat: &mut Cell,
+        cell: &Cell,
+        prefix: Option<&str>,
+        bindings: &HashMap<SliceOne, SliceOne>,
+    ) {
+        let path = |name: &ArcStr| match prefix {
+            Some(prefix) => arcstr::format!("{}.{}", prefix, name),
+            None => name.clone(),
+        };
+        let bind = |conn: &Concat| {
+            conn.parts()
+                .flat_map(|part| match part.range() {
+                    Some(range) => range
+                        .indices()
+                        .map(|i| SliceOne::new(part.signal(), Some(i)))
+                        .collect::<Vec<_>>(),
+                    None => vec![SliceOne::new(part.signal(), None)],
+                })
+                .map(|bit| bindings[&bit])
+                .collect::<Vec<_>>()
+        };
+
+        for inst in cell.instances.values() {
+            let name = path(&inst.name);
+            match inst.child {
+                ChildId::Primitive(_) => {
+                    let mut inst = inst.clone();
+                    inst.connections = inst
+                        .connections
+                        .iter()
+                        .map(|(port, conn)| (port.clone(), bind(conn).into_iter().collect()))
+                        .collect();
+                    inst.name = name;
+                    flat.add_instance(inst);
+                }
+                ChildId::Cell(id) => {
+                    let child = self.cell(id);
+                    let mut child_bindings = HashMap::new();
+                    for (port, conn) in inst.connections.iter() {
+                        let Some(port) = child.ports.get(port) else {
+                            continue;
+                        };
+                        child_bindings.extend(child.signal(port.signal).bits().zip(bind(conn)));
+                    }
+                    // Internal signals, and any ports left unconnected, become new signals.
+                    let mut internal = child
+                        .signals
+                        .values()
+                        .filter(|info| !child_bindings.contains_key(&info.bits().next().unwrap()))
+                        .collect::<Vec<_>>();
+                    internal.sort_by_key(|info| info.id);
+                    for info in internal {
+                        let id =
+                            flat.add_signal(arcstr::format!("{}.{}", name, info.name), info.width);
+                        child_bindings.extend(info.bits().zip(flat.signal(id).bits()));
+                    }
+                    self.inline_instances(flat, child, Some(name.as_str()), &child_bindings);
+                }
+            }
+        }
+    }
+
     fn convert_instance_path_cell(&self, top: &InstancePathCell) -> Option<(CellId, &Cell)> {
         Some(match top {
             InstancePathCell::Id(id) => (*id, self.cell(*id)),
@@ -1285,6 +1907,86 @@ impl<S: Schema + ?Sized> LibraryBuilder<S> {
         self.convert_slice_one_path_inner(Some(conv), path, index_fmt)
     }
 
+    /// Returns the hierarchical name of the net addressed by a [`SliceOnePath`].
+    ///
+    /// Path elements are joined with `.` and bus bits are formatted as `name[index]`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the path contains instance or cell IDs that do not exist.
+    pub fn net_name(&self, path: &SliceOnePath) -> String {
+        self.convert_slice_one_path(path.clone(), |name, index| match index {
+            Some(index) => arcstr::format!("{}[{}]", name, index),
+            None => name.clone(),
+        })
+        .join(".")
+    }
+
+    /// Parses a `.`-separated path of instance names ending in a signal,
+    /// such as `xbuf.xinv.din[2]`, relative to the cell `top`.
+    ///
+    /// Instances are resolved to IDs. If the last instance is a primitive,
+    /// the tail is kept as a named reference to one of the primitive's ports.
+    ///
+    /// This is the inverse of [`LibraryBuilder::net_name`].
+    ///
+    /// # Panics
+    ///
+    /// Panics if `top` does not exist in the library.
+    pub fn parse_node_path(&self, top: CellId, s: &str) -> Result<SliceOnePath, PathParseError> {
+        let mut elems: Vec<&str> = s.split('.').collect();
+        let tail = elems.pop().unwrap();
+        let (name, index) = parse_signal_tail(tail)
+            .ok_or_else(|| PathParseError::InvalidTail { tail: tail.into() })?;
+
+        let mut path = InstancePath::new(top);
+        let mut cell = self.cell(top);
+        let mut elems = elems.into_iter().peekable();
+        while let Some(elem) = elems.next() {
+            let id = *cell.instance_name_map.get(elem).ok_or_else(|| {
+                PathParseError::MissingInstance {
+                    cell: cell.name.clone(),
+                    instance: elem.into(),
+                }
+            })?;
+            path.push(id);
+            match cell.instance(id).child {
+                ChildId::Cell(child) => cell = self.cell(child),
+                ChildId::Primitive(_) => {
+                    if elems.peek().is_some() {
+                        return Err(PathParseError::PrimitiveInstance {
+                            instance: elem.into(),
+                        });
+                    }
+                    let tail = match index {
+                        Some(index) => NamedSliceOne::with_index(name.into(), index),
+                        None => NamedSliceOne::new(name),
+                    };
+                    return Ok(path.slice_one(tail));
+                }
+            }
+        }
+
+        let signal = cell
+            .try_signal_named(name)
+            .ok_or_else(|| PathParseError::MissingSignal {
+                cell: cell.name.clone(),
+                signal: name.into(),
+            })?;
+        match (signal.width, index) {
+            (None, None) => {}
+            (Some(width), Some(index)) if index < width => {}
+            (width, index) => {
+                return Err(PathParseError::InvalidIndex {
+                    signal: signal.name.clone(),
+                    width,
+                    index,
+                })
+            }
+        }
+        Ok(path.slice_one(SliceOne::new(signal.id, index)))
+    }
+
     /// Returns a simplified path to the provided node, bubbling up through IOs.
     ///
     /// # Panics
@@ -1452,7 +2154,40 @@ impl<S: Schema + ?Sized> LibraryBuilder<S> {
     }
 }
 
+/// Splits a signal reference of the form `name` or `name[index]`
+/// into its name and optional index.
+fn parse_signal_tail(tail: &str) -> Option<(&str, Option<usize>)> {
+    let Some(rest) = tail.strip_suffix(']') else {
+        return (!tail.is_empty() && !tail.contains('[')).then_some((tail, None));
+    };
+    let (name, index) = rest.split_once('[')?;
+    if name.is_empty() {
+        return None;
+    }
+    Some((name, Some(index.parse().ok()?)))
+}
+
 impl<S: Schema<Primitive = impl Clone> + ?Sized> LibraryBuilder<S> {
+    /// Validates a copy of the current state of this builder, returning an immutable
+    /// [`Library`] that can be shared across threads.
+    ///
+    /// The builder can continue to be modified; changes are not reflected in the snapshot.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the current state of the builder is not a valid library.
+    /// For a non-panicking alternative, clone the builder and call
+    /// [`build`](LibraryBuilder::build).
+    pub fn snapshot(&self) -> Arc<Library<S>> {
+        match self.clone().build() {
+            Ok(lib) => Arc::new(lib),
+            Err(issues) => {
+                tracing::error!("cannot snapshot an invalid library:\n{}", issues);
+                panic!("cannot snapshot an invalid library:\n{}", issues);
+            }
+        }
+    }
+
     /// Creates a new SCIR library builder containing only the named cell and its children
     /// from an existing library builder.
     pub fn from_cell_named(lib: &Self, cell: &str) -> Self {
@@ -1476,6 +2211,29 @@ impl<S: Schema<Primitive = impl Clone> + ?Sized> LibraryBuilder<S> {
     }
 }
 
+/// A 64-bit FNV-1a hasher, whose output does not depend on the platform or Rust release.
+struct Fnv1a(u64);
+
+impl Fnv1a {
+    const OFFSET_BASIS: u64 = 0xcbf29ce484222325;
+    const PRIME: u64 = 0x100000001b3;
+
+    fn new() -> Self {
+        Self(Self::OFFSET_BASIS)
+    }
+
+    fn write(&mut self, bytes: &[u8]) {
+        for byte in bytes {
+            self.0 ^= *byte as u64;
+            self.0 = self.0.wrapping_mul(Self::PRIME);
+        }
+    }
+
+    fn finish(&self) -> u64 {
+        self.0
+    }
+}
+
 impl Cell {
     /// Creates a new cell with the given name.
     pub fn new(name: impl Into<ArcStr>) -> Self {
@@ -1489,6 +2247,7 @@ impl Cell {
             instance_id: 0,
             instances: IndexMap::new(),
             instance_name_map: HashMap::new(),
+            attributes: IndexMap::new(),
         }
     }
 
@@ -1543,18 +2302,280 @@ impl Cell {
         }
     }
 
+    /// Removes signals that are neither ports nor connected to any instance.
+    ///
+    /// Returns the number of signals removed. IDs of the remaining signals are unchanged.
+    pub fn prune_unused_signals(&mut self) -> usize {
+        let used = self
+            .instances
+            .values()
+            .flat_map(|inst| inst.connections.values())
+            .flat_map(|conn| conn.parts())
+            .map(|part| part.signal())
+            .collect::<HashSet<_>>();
+        let unused = self
+            .signals
+            .values()
+            .filter(|info| !info.is_port() && !used.contains(&info.id))
+            .map(|info| info.id)
+            .collect::<Vec<_>>();
+        for id in unused.iter() {
+            self.remove_signal(*id);
+        }
+        unused.len()
+    }
+
+    /// Removes a non-port signal, keeping name lookups consistent.
+    ///
+    /// Does not check whether the signal is still in use.
+    fn remove_signal(&mut self, id: SignalId) -> SignalInfo {
+        let info = self.signals.remove(&id).unwrap();
+        if self.signal_name_map.get(&info.name) == Some(&id) {
+            self.signal_name_map.remove(&info.name);
+            // Later signals take precedence in name lookups.
+            if let Some(other) = self
+                .signals
+                .values()
+                .filter(|other| other.name == info.name)
+                .map(|other| other.id)
+                .max()
+            {
+                self.signal_name_map.insert(info.name.clone(), other);
+            }
+        }
+        info
+    }
+
+    /// Replaces each bus signal in this cell with single-bit signals named `name[i]`.
+    ///
+    /// Returns the new signals for each bit of every exploded bus, along with
+    /// the names of the ports replacing each exploded bus port.
+    /// Instance connections are not updated.
+    fn explode_signals(
+        &mut self,
+    ) -> (
+        HashMap<SignalId, Vec<SignalId>>,
+        HashMap<ArcStr, Vec<ArcStr>>,
+    ) {
+        let ports = std::mem::take(&mut self.ports);
+        self.port_idx = 0;
+
+        let mut buses = self
+            .signals
+            .values()
+            .filter(|info| info.width.is_some())
+            .map(|info| info.id)
+            .collect::<Vec<_>>();
+        buses.sort();
+
+        let mut bits = HashMap::new();
+        for id in buses {
+            let info = self.signals.remove(&id).unwrap();
+            self.signal_name_map.remove(&info.name);
+            let new = (0..info.width.unwrap())
+                .map(|i| {
+                    let name = arcstr::format!("{}[{}]", info.name, i);
+                    assert!(
+                        !self.signal_name_map.contains_key(&name),
+                        "exploded bit `{name}` collides with an existing signal in cell `{}`",
+                        self.name
+                    );
+                    self.add_signal(name, None)
+                })
+                .collect::<Vec<_>>();
+            bits.insert(id, new);
+        }
+        for info in self.signals.values_mut() {
+            info.port = None;
+        }
+
+        let mut port_names = HashMap::new();
+        for (name, port) in ports {
+            if let Some(new) = bits.get(&port.signal) {
+                let names = new
+                    .iter()
+                    .map(|&bit| {
+                        self.expose_port(bit, port.direction);
+                        self.signals[&bit].name.clone()
+                    })
+                    .collect();
+                port_names.insert(name, names);
+            } else {
+                self.expose_port(port.signal, port.direction);
+            }
+        }
+
+        (bits, port_names)
+    }
+
     /// The name of the cell.
     #[inline]
     pub fn name(&self) -> &ArcStr {
         &self.name
     }
 
+    /// Returns the value of the attribute `key` on this cell, if set.
+    #[inline]
+    pub fn attribute(&self, key: &str) -> Option<&ArcStr> {
+        self.attributes.get(key)
+    }
+
+    /// Returns all attributes attached to this cell, in insertion order.
+    #[inline]
+    pub fn attributes(&self) -> &IndexMap<ArcStr, ArcStr> {
+        &self.attributes
+    }
+
+    /// Sets the attribute `key` on this cell to `value`.
+    ///
+    /// Returns the previous value, if any.
+    pub fn set_attribute(
+        &mut self,
+        key: impl Into<ArcStr>,
+        value: impl Into<ArcStr>,
+    ) -> Option<ArcStr> {
+        self.attributes.insert(key.into(), value.into())
+    }
+
     /// Iterate over the ports of this cell.
     #[inline]
     pub fn ports(&self) -> impl Iterator<Item = &Port> {
         self.ports.iter().map(|(_, port)| port)
     }
 
+    /// Iterate over the ports of this cell along with the name and width of each port's signal.
+    ///
+    /// Ports are yielded in port order.
+    pub fn port_details(&self) -> impl Iterator<Item = PortDetail> + '_ {
+        self.ports().map(|port| {
+            let info = self.signal(port.signal());
+            PortDetail {
+                name: info.name.clone(),
+                direction: port.direction(),
+                width: info.width,
+                signal: port.signal(),
+            }
+        })
+    }
+
+    /// Hashes the external interface of this cell.
+    ///
+    /// Includes the name, direction, and width of each port, in port order,
+    /// but not the cell name or any internal signals and instances.
+    /// Cells with identical interfaces have equal interface hashes.
+    ///
+    /// The hash is stable across platforms and Rust releases, so it may be used as a cache key.
+    pub fn interface_hash(&self) -> u64 {
+        let mut hasher = Fnv1a::new();
+        for (name, port) in self.ports.iter() {
+            hasher.write(&(name.len() as u64).to_le_bytes());
+            hasher.write(name.as_bytes());
+            hasher.write(&[match port.direction {
+                Direction::Input => 0,
+                Direction::Output => 1,
+                Direction::InOut => 2,
+            }]);
+            match self.signals[&port.signal].width {
+                Some(width) => {
+                    hasher.write(&[1]);
+                    hasher.write(&(width as u64).to_le_bytes());
+                }
+                None => hasher.write(&[0]),
+            }
+        }
+        hasher.finish()
+    }
+
+    /// Returns a rough estimate of the memory used by this cell, in bytes.
+    ///
+    /// Accounts for signals, ports, instances, and instance connections, including
+    /// the lengths of their names. The estimate is not exact, but never decreases
+    /// as content is added to the cell.
+    pub fn estimated_size(&self) -> usize {
+        use std::mem::size_of;
+
+        let signals: usize = self
+            .signals
+            .values()
+            .map(|signal| {
+                size_of::<SignalId>()
+                    + size_of::<SignalInfo>()
+                    + size_of::<ArcStr>()
+                    + size_of::<SignalId>()
+                    + signal.name.len()
+            })
+            .sum();
+        let ports: usize = self
+            .ports
+            .keys()
+            .map(|name| size_of::<ArcStr>() + size_of::<Port>() + name.len())
+            .sum();
+        let instances: usize = self
+            .instances
+            .values()
+            .map(|inst| {
+                let connections: usize = inst
+                    .connections
+                    .iter()
+                    .map(|(port, concat)| {
+                        size_of::<ArcStr>()
+                            + port.len()
+                            + size_of::<Concat>()
+                            + concat.parts().count() * size_of::<Slice>()
+                    })
+                    .sum();
+                size_of::<InstanceId>()
+                    + size_of::<Instance>()
+                    + size_of::<ArcStr>()
+                    + size_of::<InstanceId>()
+                    + inst.name.len()
+                    + connections
+            })
+            .sum();
+        size_of::<Cell>() + self.name.len() + signals + ports + instances
+    }
+
+    /// Returns a copy of this cell with signal IDs renumbered densely starting from 1.
+    ///
+    /// Signals keep their relative order. Ports and instance connections are updated
+    /// to use the new IDs. Also returns a map from each old signal ID to its new ID.
+    pub fn renumbered(&self) -> (Cell, HashMap<SignalId, SignalId>) {
+        let mut ids = self.signals.keys().copied().collect::<Vec<_>>();
+        ids.sort();
+        let map = ids
+            .iter()
+            .enumerate()
+            .map(|(i, id)| (*id, SignalId(i as u64 + 1)))
+            .collect::<HashMap<_, _>>();
+
+        let mut cell = self.clone();
+        cell.signal_id = ids.len() as u64;
+        cell.signals = self
+            .signals
+            .values()
+            .map(|info| {
+                let id = map[&info.id];
+                (id, SignalInfo { id, ..info.clone() })
+            })
+            .collect();
+        for id in cell.signal_name_map.values_mut() {
+            *id = map[id];
+        }
+        for port in cell.ports.values_mut() {
+            port.signal = map[&port.signal];
+        }
+        for inst in cell.instances.values_mut() {
+            for conn in inst.connections.values_mut() {
+                *conn = conn
+                    .parts()
+                    .map(|part| Slice::new(map[&part.signal()], part.range()))
+                    .collect();
+            }
+        }
+
+        (cell, map)
+    }
+
     /// Get a port of this cell by name.
     ///
     /// # Panics
@@ -1642,20 +2663,135 @@ impl Cell {
     }
 
     /// Add the given instance to the cell.
+    ///
+    /// If an instance with the same name already exists, lookups by name continue
+    /// to return the existing instance. Duplicate names are reported as errors
+    /// during validation.
     #[inline]
     pub fn add_instance(&mut self, instance: Instance) -> InstanceId {
         self.instance_id += 1;
         let id = InstanceId(self.instance_id);
-        self.instance_name_map.insert(instance.name.clone(), id);
+        self.instance_name_map
+            .entry(instance.name.clone())
+            .or_insert(id);
         self.instances.insert(id, instance);
         id
     }
 
+    /// Removes the instance with the given ID, returning it if it existed.
+    ///
+    /// IDs of the remaining instances are unchanged.
+    pub fn remove_instance(&mut self, id: InstanceId) -> Option<Instance> {
+        let instance = self.instances.shift_remove(&id)?;
+        if self.instance_name_map.get(&instance.name) == Some(&id) {
+            self.instance_name_map.remove(&instance.name);
+            // Earlier instances take precedence in name lookups.
+            if let Some((other, _)) = self
+                .instances
+                .iter()
+                .find(|(_, other)| other.name == instance.name)
+            {
+                self.instance_name_map.insert(instance.name.clone(), *other);
+            }
+        }
+        Some(instance)
+    }
+
     /// Iterate over the instances of this cell.
     #[inline]
     pub fn instances(&self) -> impl Iterator<Item = (InstanceId, &Instance)> {
         self.instances.iter().map(|x| (*x.0, x.1))
     }
+
+    /// Returns the IDs of this cell's instances, ordered so that drivers precede their loads.
+    ///
+    /// `child_lookup` returns the direction of the given port of an instance's child.
+    /// An instance must precede another if an output of the first
+    /// is connected to an input of the second. Inout ports impose no ordering.
+    /// Instances without an ordering constraint between them keep their insertion order.
+    ///
+    /// Returns a [`CycleError`] if the instances form a loop.
+    pub fn evaluation_order(
+        &self,
+        child_lookup: impl Fn(ChildId, &str) -> Direction,
+    ) -> Result<Vec<InstanceId>, CycleError> {
+        let ids = self.instances.keys().copied().collect::<Vec<_>>();
+        let mut drivers: HashMap<(SignalId, usize), Vec<usize>> = HashMap::new();
+        let mut loads: HashMap<(SignalId, usize), Vec<usize>> = HashMap::new();
+        for (i, inst) in self.instances.values().enumerate() {
+            for (port, conn) in inst.connections() {
+                let map = match child_lookup(inst.child(), port) {
+                    Direction::Output => &mut drivers,
+                    Direction::Input => &mut loads,
+                    Direction::InOut => continue,
+                };
+                for part in conn.parts() {
+                    match part.range() {
+                        Some(range) => {
+                            for idx in range {
+                                map.entry((part.signal(), idx)).or_default().push(i);
+                            }
+                        }
+                        None => map.entry((part.signal(), 0)).or_default().push(i),
+                    }
+                }
+            }
+        }
+
+        let mut succs = vec![BTreeSet::new(); ids.len()];
+        for (bit, ds) in drivers.iter() {
+            if let Some(ls) = loads.get(bit) {
+                for &d in ds {
+                    succs[d].extend(ls.iter().copied());
+                }
+            }
+        }
+        let mut indegree = vec![0usize; ids.len()];
+        for &j in succs.iter().flatten() {
+            indegree[j] += 1;
+        }
+
+        let mut ready = (0..ids.len())
+            .filter(|&i| indegree[i] == 0)
+            .collect::<BTreeSet<_>>();
+        let mut order = Vec::with_capacity(ids.len());
+        while let Some(i) = ready.pop_first() {
+            order.push(ids[i]);
+            for &j in succs[i].iter() {
+                indegree[j] -= 1;
+                if indegree[j] == 0 {
+                    ready.insert(j);
+                }
+            }
+        }
+        if order.len() == ids.len() {
+            return Ok(order);
+        }
+
+        // Every unordered instance has an unordered driver,
+        // so walking backwards through unordered drivers must revisit an instance.
+        let mut preds = vec![Vec::new(); ids.len()];
+        for (i, js) in succs.iter().enumerate() {
+            for &j in js {
+                preds[j].push(i);
+            }
+        }
+        let mut cur = (0..ids.len()).find(|&i| indegree[i] > 0).unwrap();
+        let mut visited = HashMap::new();
+        let mut walk = Vec::new();
+        while !visited.contains_key(&cur) {
+            visited.insert(cur, walk.len());
+            walk.push(cur);
+            cur = *preds[cur].iter().find(|&&p| indegree[p] > 0).unwrap();
+        }
+        Err(CycleError {
+            instances: walk[visited[&cur]..]
+                .iter()
+                .rev()
+                .map(|&i| ids[i])
+                .collect(),
+        })
+    }
 }
 
 impl Instance {
@@ -1665,9 +2801,16 @@ impl Instance {
             child: child.into(),
             name: name.into(),
             connections: HashMap::new(),
+            attributes: IndexMap::new(),
         }
     }
 
+    /// Creates an [`InstanceBuilder`] for an instance of the given cell with the given name.
+    #[inline]
+    pub fn builder(name: impl Into<ArcStr>, child: impl Into<ChildId>) -> InstanceBuilder {
+        InstanceBuilder::new(name, child)
+    }
+
     /// Connect the given port of the child cell to the given node in the parent cell.
     #[inline]
     pub fn connect(&mut self, name: impl Into<ArcStr>, conn: impl Into<Concat>) {
@@ -1690,6 +2833,29 @@ impl Instance {
         &self.name
     }
 
+    /// Returns the value of the attribute `key` on this instance, if set.
+    #[inline]
+    pub fn attribute(&self, key: &str) -> Option<&ArcStr> {
+        self.attributes.get(key)
+    }
+
+    /// Returns all attributes attached to this instance, in insertion order.
+    #[inline]
+    pub fn attributes(&self) -> &IndexMap<ArcStr, ArcStr> {
+        &self.attributes
+    }
+
+    /// Sets the attribute `key` on this instance to `value`.
+    ///
+    /// Returns the previous value, if any.
+    pub fn set_attribute(
+        &mut self,
+        key: impl Into<ArcStr>,
+        value: impl Into<ArcStr>,
+    ) -> Option<ArcStr> {
+        self.attributes.insert(key.into(), value.into())
+    }
+
     /// Returns a reference to this instance's connection map.
     #[inline]
     pub fn connections(&self) -> &HashMap<ArcStr, Concat> {
@@ -1724,6 +2890,44 @@ impl Instance {
     }
 }
 
+impl InstanceBuilder {
+    /// Creates a builder for an instance of the given cell with the given name.
+    pub fn new(name: impl Into<ArcStr>, child: impl Into<ChildId>) -> Self {
+        Self {
+            instance: Instance::new(name, child),
+            duplicate: None,
+        }
+    }
+
+    /// Connects the given port of the child cell to the given node in the parent cell.
+    pub fn connect(mut self, name: impl Into<ArcStr>, conn: impl Into<Concat>) -> Self {
+        let name = name.into();
+        if self
+            .instance
+            .connections
+            .insert(name.clone(), conn.into())
+            .is_some()
+            && self.duplicate.is_none()
+        {
+            self.duplicate = Some(name);
+        }
+        self
+    }
+
+    /// Builds the instance.
+    ///
+    /// Returns an error if any port was connected more than once.
+    pub fn build(self) -> Result<Instance, DuplicateConnection> {
+        match self.duplicate {
+            Some(port) => Err(DuplicateConnection {
+                instance: self.instance.name,
+                port,
+            }),
+            None => Ok(self.instance),
+        }
+    }
+}
+
 impl Port {
     /// The ID of the signal this port exposes.
     #[inline]
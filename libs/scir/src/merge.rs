@@ -4,6 +4,14 @@ use uniquify::Names;
 
 use super::*;
 
+/// Options for merging SCIR libraries.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct MergeOptions {
+    /// Whether to reuse primitives in the destination library that are equal to
+    /// primitives in the source library instead of adding duplicates.
+    pub dedup_primitives: bool,
+}
+
 /// Keeps track of cell and primitive IDs after a library is merged.
 pub struct MergedMapping {
     cells: HashMap<CellId, CellId>,
@@ -23,6 +31,8 @@ struct Merger<'a, S: Schema + ?Sized> {
     ///
     /// If [`None`], all source cells will be merged.
     merge_cells: Option<Vec<CellId>>,
+    /// Source primitive IDs that were mapped to existing destination primitives.
+    reused_primitives: HashSet<PrimitiveId>,
 }
 
 impl<'a, S: Schema + ?Sized> Merger<'a, S> {
@@ -35,6 +45,7 @@ impl<'a, S: Schema + ?Sized> Merger<'a, S> {
             dst,
             src,
             merge_cells: None,
+            reused_primitives: HashSet::new(),
         }
     }
 
@@ -51,6 +62,7 @@ impl<'a, S: Schema + ?Sized> Merger<'a, S> {
             dst,
             src,
             merge_cells: Some(cells.into_iter().collect()),
+            reused_primitives: HashSet::new(),
         }
     }
 
@@ -78,7 +90,9 @@ impl<'a, S: Schema + ?Sized> Merger<'a, S> {
             self.merge_cell(id, cell);
         }
         for (id, primitive) in primitives {
-            self.merge_primitive(id, primitive);
+            if !self.reused_primitives.contains(&id) {
+                self.merge_primitive(id, primitive);
+            }
         }
 
         MergedMapping {
@@ -94,7 +108,23 @@ impl<'a, S: Schema + ?Sized> Merger<'a, S> {
         cell.name = n_name;
     }
 
+    /// Maps source primitives to equal existing destination primitives.
+    fn reuse_equal_primitives(&mut self)
+    where
+        S::Primitive: PartialEq,
+    {
+        for (id, primitive) in self.src.primitives() {
+            if let Some((n_id, _)) = self.dst.primitives().find(|(_, p)| *p == primitive) {
+                self.primitive_mapping.insert(id, n_id);
+                self.reused_primitives.insert(id);
+            }
+        }
+    }
+
     fn assign_primitive_identifiers(&mut self, id: PrimitiveId) {
+        if self.reused_primitives.contains(&id) {
+            return;
+        }
         let n_id = self.dst.alloc_primitive_id();
         self.primitive_mapping.insert(id, n_id);
     }
@@ -149,6 +179,18 @@ impl<S: Schema + ?Sized> LibraryBuilder<S> {
         Merger::new(self, other).merge()
     }
 
+    /// Merges another SCIR library into the current library using the given options.
+    pub fn merge_with_options(&mut self, other: Self, options: MergeOptions) -> MergedMapping
+    where
+        S::Primitive: PartialEq,
+    {
+        let mut merger = Merger::new(self, other);
+        if options.dedup_primitives {
+            merger.reuse_equal_primitives();
+        }
+        merger.merge()
+    }
+
     /// Merges the given cells from another SCIR library into the current library.
     pub fn merge_cells(
         &mut self,
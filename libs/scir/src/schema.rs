@@ -67,7 +67,7 @@ pub struct NoSchemaError;
 ///
 /// Cannot be instantiated as [`NoSchema`] cannot have
 /// primitives.
-#[derive(Clone, Serialize, Deserialize)]
+#[derive(Clone, Eq, PartialEq, Serialize, Deserialize)]
 pub struct NoPrimitive(());
 
 impl Schema for NoSchema {
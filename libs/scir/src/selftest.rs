@@ -0,0 +1,29 @@
+//! Self-test helpers for SCIR schemas.
+
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+
+use crate::schema::Schema;
+use crate::{Library, LibraryBuilder};
+
+/// Checks that `lib` is structurally unchanged across a serde round-trip.
+///
+/// Useful for verifying that a schema's primitive type serializes correctly.
+///
+/// # Panics
+///
+/// Panics if serialization or deserialization fails, if the deserialized
+/// library fails validation, or if the deserialized library differs from `lib`.
+pub fn roundtrip_schema<S: Schema + ?Sized>(lib: &Library<S>)
+where
+    S::Primitive: Serialize + DeserializeOwned,
+{
+    let before = serde_json::to_value(&**lib).expect("failed to serialize SCIR library");
+    let builder: LibraryBuilder<S> =
+        serde_json::from_value(before.clone()).expect("failed to deserialize SCIR library");
+    let lib2 = builder
+        .build()
+        .expect("round-tripped SCIR library failed validation");
+    let after = serde_json::to_value(&*lib2).expect("failed to serialize SCIR library");
+    assert_eq!(before, after);
+}
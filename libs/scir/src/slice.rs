@@ -348,6 +348,15 @@ impl Slice {
     pub fn slice_one(&self) -> Option<SliceOne> {
         SliceOne::try_from(self).ok()
     }
+
+    /// Splits this bus slice into its lowest `idx` bits and the remaining high bits.
+    ///
+    /// # Panics
+    ///
+    /// Panics if this slice is not a bus or if `idx` is not strictly between 0 and its width.
+    pub fn split_at(&self, idx: usize) -> (Slice, Slice) {
+        (self.index(..idx), self.index(idx..))
+    }
 }
 
 impl IndexOwned<usize> for Slice {
@@ -487,6 +496,72 @@ impl Concat {
     pub fn parts(&self) -> impl Iterator<Item = &Slice> {
         self.parts.iter()
     }
+
+    /// Returns a concatenation with the bit order reversed.
+    ///
+    /// Bus parts wider than one bit are expanded into single bits.
+    pub fn reversed(&self) -> Concat {
+        self.parts
+            .iter()
+            .rev()
+            .flat_map(|part| match part.range() {
+                Some(range) => (range.start()..range.end())
+                    .rev()
+                    .map(|i| SliceOne::new(part.signal(), Some(i)).into())
+                    .collect(),
+                None => vec![*part],
+            })
+            .collect()
+    }
+}
+
+/// A builder for a [`Concat`] connected to a port of known width.
+#[derive(Debug, Clone)]
+pub struct ConcatBuilder {
+    width: usize,
+    parts: Vec<Slice>,
+}
+
+/// The error returned when a [`ConcatBuilder`] does not match the width of its port.
+#[derive(Copy, Clone, Eq, PartialEq, Debug, Hash, Serialize, Deserialize, thiserror::Error)]
+#[error("concatenation has width {actual}, but the port has width {expected}")]
+pub struct ConcatWidthMismatch {
+    /// The width of the port.
+    pub expected: usize,
+    /// The width of the concatenation.
+    pub actual: usize,
+}
+
+impl ConcatBuilder {
+    /// Creates a builder for a concatenation connected to a port of the given width.
+    pub fn for_port(width: usize) -> Self {
+        Self {
+            width,
+            parts: Vec::new(),
+        }
+    }
+
+    /// Appends the given slice to the concatenation.
+    pub fn push(mut self, slice: impl Into<Slice>) -> Self {
+        self.parts.push(slice.into());
+        self
+    }
+
+    /// Builds the concatenation.
+    ///
+    /// Returns an error if its width does not equal the width of the port.
+    pub fn build(self) -> Result<Concat, ConcatWidthMismatch> {
+        let concat = Concat::new(self.parts);
+        let actual = concat.width();
+        if actual == self.width {
+            Ok(concat)
+        } else {
+            Err(ConcatWidthMismatch {
+                expected: self.width,
+                actual,
+            })
+        }
+    }
 }
 
 impl FromIterator<Slice> for Concat {
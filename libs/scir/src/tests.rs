@@ -212,6 +212,1037 @@ fn schema_conversion() {
     assert!(orig_lib.convert_schema::<PartiallyTypedSchema>().is_err());
 }
 
+#[test]
+fn unconnected_instance_port() {
+    let mut lib = <LibraryBuilder>::new();
+
+    let mut inv = Cell::new("inv");
+    let din = inv.add_node("din");
+    let dout = inv.add_node("dout");
+    inv.expose_port(din, Direction::Input);
+    inv.expose_port(dout, Direction::Output);
+    let inv = lib.add_cell(inv);
+
+    let mut top = Cell::new("top");
+    let a = top.add_node("a");
+    let mut inst = Instance::new("inv0", inv);
+    inst.connect("din", a);
+    top.add_instance(inst);
+    top.expose_port(a, Direction::Input);
+    lib.add_cell(top);
+
+    let issues = lib.validate_drivers();
+    let unconnected: Vec<_> = issues
+        .iter()
+        .filter_map(|issue| match issue.cause() {
+            drivers::Cause::UnconnectedPort {
+                cell,
+                instance,
+                port,
+            } => Some((cell.as_str(), instance.as_str(), port.as_str())),
+            _ => None,
+        })
+        .collect();
+    assert_eq!(unconnected, vec![("top", "inv0", "dout")]);
+}
+
+#[test]
+fn name_stats() {
+    let mut lib = LibraryBuilder::<StringSchema>::new();
+    let res = lib.add_primitive("res".into());
+
+    for i in 0..3 {
+        let mut cell = Cell::new(format!("cell_{i}"));
+        let vdd = cell.add_node("vdd");
+        let vss = cell.add_node("vss");
+        cell.add_node(format!("int_{i}"));
+
+        let mut r = Instance::new("r", res);
+        r.connect("1", vdd);
+        r.connect("2", vss);
+        cell.add_instance(r);
+
+        lib.add_cell(cell);
+    }
+
+    let stats = lib.name_stats();
+    assert_eq!(
+        stats,
+        NameStats {
+            unique_signal_names: 5,
+            total_signals: 9,
+            unique_instance_names: 1,
+            total_instances: 3,
+        }
+    );
+}
+
+#[test]
+fn reversed_bus_connection() {
+    let mut lib = LibraryBuilder::<NoSchema>::new();
+
+    let mut buf = Cell::new("buf");
+    let din = buf.add_bus("din", 4);
+    buf.expose_port(din, Direction::Input);
+    let buf = lib.add_cell(buf);
+
+    let mut top = Cell::new("top");
+    let a = top.add_bus("a", 4);
+    let b = top.add_bus("b", 4);
+
+    let mut reversed = Instance::new("reversed", buf);
+    reversed.connect("din", (0..4).rev().map(|i| a.index(i)).collect::<Concat>());
+    top.add_instance(reversed);
+
+    let mut forward = Instance::new("forward", buf);
+    forward.connect("din", (0..4).map(|i| b.index(i)).collect::<Concat>());
+    top.add_instance(forward);
+
+    lib.add_cell(top);
+
+    let issues = lib.validate();
+    assert!(!issues.has_error());
+    let reversed: Vec<_> = issues
+        .iter()
+        .filter_map(|issue| match issue.cause() {
+            validation::Cause::ReversedBusConnection {
+                signal_name,
+                instance_name,
+                port,
+                ..
+            } => Some((signal_name.as_str(), instance_name.as_str(), port.as_str())),
+            _ => None,
+        })
+        .collect();
+    assert_eq!(reversed, vec![("a", "reversed", "din")]);
+    assert!(issues.has_warning());
+}
+
+#[test]
+fn duplicate_instance_names_keeps_first() {
+    let mut lib = <LibraryBuilder>::new();
+
+    let mut inv = Cell::new("inv");
+    let din = inv.add_node("din");
+    inv.expose_port(din, Direction::Input);
+    let inv = lib.add_cell(inv);
+
+    let mut top = Cell::new("top");
+    let a = top.add_node("a");
+    let b = top.add_node("b");
+    let mut first = Instance::new("inv0", inv);
+    first.connect("din", a);
+    let first = top.add_instance(first);
+    let mut second = Instance::new("inv0", inv);
+    second.connect("din", b);
+    top.add_instance(second);
+    assert!(std::ptr::eq(
+        top.instance_named("inv0"),
+        top.instance(first)
+    ));
+    assert_eq!(top.instances().count(), 2);
+    let top = lib.add_cell(top);
+
+    let issues = lib.validate();
+    assert!(issues.has_error());
+    let duplicates: Vec<_> = issues
+        .iter()
+        .filter_map(|issue| match issue.cause() {
+            validation::Cause::DuplicateInstanceNames {
+                inst_name, cell_id, ..
+            } => Some((inst_name.as_str(), *cell_id)),
+            _ => None,
+        })
+        .collect();
+    assert_eq!(duplicates, vec![("inv0", top)]);
+}
+
+#[test]
+fn recursive_instantiation() {
+    let mut lib = LibraryBuilder::<NoSchema>::new();
+
+    let ping = lib.add_cell(Cell::new("ping"));
+    let mut pong = Cell::new("pong");
+    pong.add_instance(Instance::new("ping0", ping));
+    let pong = lib.add_cell(pong);
+    let mut ping_cell = Cell::new("ping");
+    ping_cell.add_instance(Instance::new("pong0", pong));
+    lib.overwrite_cell_with_id(ping, ping_cell);
+
+    let Err(issues) = lib.build() else {
+        panic!("expected recursive instantiation to fail validation");
+    };
+    let cycles: Vec<_> = issues
+        .correctness
+        .iter()
+        .filter_map(|issue| match issue.cause() {
+            validation::Cause::RecursiveInstantiation { cells, cell_names } => {
+                Some((cells.clone(), cell_names.clone()))
+            }
+            _ => None,
+        })
+        .collect();
+    assert_eq!(
+        cycles,
+        vec![(
+            vec![ping, pong],
+            vec![arcstr::literal!("ping"), arcstr::literal!("pong")]
+        )]
+    );
+    assert!(issues
+        .to_string()
+        .contains("cells `ping` -> `pong` -> `ping` instantiate each other in a cycle"));
+}
+
+#[test]
+fn top_io_signals() {
+    let mut lib = LibraryBuilder::<NoSchema>::new();
+    assert_eq!(lib.top_io_signals(), Err(NoTopCell));
+
+    let mut cell = Cell::new("top");
+    let a = cell.add_node("a");
+    let b = cell.add_bus("b", 4);
+    cell.add_node("int");
+    let y = cell.add_node("y");
+    cell.expose_port(a, Direction::Input);
+    cell.expose_port(b, Direction::InOut);
+    cell.expose_port(y, Direction::Output);
+    let top = lib.add_cell(cell);
+    lib.set_top(top);
+
+    assert_eq!(
+        lib.top_io_signals().unwrap(),
+        vec![
+            (a.signal(), Direction::Input),
+            (b.signal(), Direction::InOut),
+            (y.signal(), Direction::Output),
+        ]
+    );
+}
+
+#[test]
+fn explode_buses() {
+    let mut lib = LibraryBuilder::<NoSchema>::new();
+
+    let mut buf = Cell::new("buf4");
+    let din = buf.add_bus("din", 4);
+    let dout = buf.add_node("dout");
+    buf.expose_port(din, Direction::Input);
+    buf.expose_port(dout, Direction::Output);
+    let buf = lib.add_cell(buf);
+
+    let mut top = Cell::new("top");
+    let a = top.add_bus("a", 4);
+    let y = top.add_node("y");
+    top.expose_port(a, Direction::Input);
+    top.expose_port(y, Direction::Output);
+    let mut inst = Instance::new("buf", buf);
+    inst.connect("din", (0..4).rev().map(|i| a.index(i)).collect::<Concat>());
+    inst.connect("dout", y);
+    top.add_instance(inst);
+    let top = lib.add_cell(top);
+
+    lib.explode_buses();
+    let issues = lib.validate();
+    assert!(!issues.has_error());
+
+    let buf = lib.cell(buf);
+    let names = buf
+        .ports()
+        .map(|port| buf.signal(port.signal()).name.as_str())
+        .collect::<Vec<_>>();
+    assert_eq!(names, vec!["din[0]", "din[1]", "din[2]", "din[3]", "dout"]);
+    assert!(buf.signals().all(|(_, info)| info.width.is_none()));
+
+    let top = lib.cell(top);
+    assert!(top.try_signal_named("a").is_none());
+    assert_eq!(top.ports().count(), 5);
+    let inst = top.instance_named("buf");
+    assert_eq!(inst.connections().len(), 5);
+    for i in 0..4 {
+        let parts = inst
+            .connection(&format!("din[{i}]"))
+            .parts()
+            .copied()
+            .collect::<Vec<_>>();
+        assert_eq!(
+            parts,
+            vec![top.signal_named(&format!("a[{}]", 3 - i)).slice()]
+        );
+    }
+    let parts = inst.connection("dout").parts().copied().collect::<Vec<_>>();
+    assert_eq!(parts, vec![y.into()]);
+}
+
+#[test]
+#[should_panic(expected = "collides with an existing signal")]
+fn explode_buses_name_collision() {
+    let mut lib = LibraryBuilder::<NoSchema>::new();
+    let mut cell = Cell::new("cell");
+    cell.add_bus("a", 2);
+    cell.add_node("a[1]");
+    lib.add_cell(cell);
+    lib.explode_buses();
+}
+
+#[test]
+#[should_panic(expected = "wrong width for port")]
+fn explode_buses_width_mismatch() {
+    let mut lib = LibraryBuilder::<NoSchema>::new();
+    let mut buf = Cell::new("buf4");
+    let din = buf.add_bus("din", 4);
+    buf.expose_port(din, Direction::Input);
+    let buf = lib.add_cell(buf);
+
+    let mut top = Cell::new("top");
+    let a = top.add_bus("a", 2);
+    let mut inst = Instance::new("buf", buf);
+    inst.connect("din", a);
+    top.add_instance(inst);
+    lib.add_cell(top);
+    lib.explode_buses();
+}
+
+#[test]
+fn instance_builder() {
+    let mut lib = LibraryBuilder::<NoSchema>::new();
+
+    let mut and2 = Cell::new("and2");
+    let a = and2.add_node("a");
+    let b = and2.add_node("b");
+    let y = and2.add_node("y");
+    and2.expose_port(a, Direction::Input);
+    and2.expose_port(b, Direction::Input);
+    and2.expose_port(y, Direction::Output);
+    let and2 = lib.add_cell(and2);
+
+    let mut top = Cell::new("top");
+    let x = top.add_bus("x", 2);
+    let out = top.add_node("out");
+
+    let inst = Instance::builder("and", and2)
+        .connect("a", x.index(0))
+        .connect("b", x.index(1))
+        .connect("y", out)
+        .build()
+        .unwrap();
+    assert_eq!(inst.connections().len(), 3);
+    for (port, conn) in [("a", x.index(0)), ("b", x.index(1)), ("y", out)] {
+        let parts = inst.connection(port).parts().copied().collect::<Vec<_>>();
+        assert_eq!(parts, vec![Slice::from(conn)]);
+    }
+    top.add_instance(inst);
+    lib.add_cell(top);
+    assert!(!lib.validate().has_error());
+
+    let err = Instance::builder("dup", and2)
+        .connect("a", x.index(0))
+        .connect("a", x.index(1))
+        .build()
+        .unwrap_err();
+    assert_eq!(
+        err,
+        DuplicateConnection {
+            instance: arcstr::literal!("dup"),
+            port: arcstr::literal!("a"),
+        }
+    );
+}
+
+#[test]
+fn interface_hash() {
+    let mut lib = LibraryBuilder::<NoSchema>::new();
+    let mut leaf = Cell::new("leaf");
+    let x = leaf.add_node("x");
+    leaf.expose_port(x, Direction::InOut);
+    let leaf = lib.add_cell(leaf);
+
+    let interface = |name: &str| {
+        let mut cell = Cell::new(name);
+        let a = cell.add_bus("a", 4);
+        let y = cell.add_node("y");
+        cell.expose_port(a, Direction::Input);
+        cell.expose_port(y, Direction::Output);
+        (cell, a, y)
+    };
+
+    let (empty, _, _) = interface("empty");
+    // The hash must not change between platforms or Rust releases.
+    assert_eq!(empty.interface_hash(), 0xfb97432477eef851);
+
+    let (mut detailed, a, y) = interface("detailed");
+    let int = detailed.add_node("int");
+    let mut inst = Instance::new("leaf0", leaf);
+    inst.connect("x", int);
+    detailed.add_instance(inst);
+    let mut inst = Instance::new("leaf1", leaf);
+    inst.connect("x", a.index(0));
+    detailed.add_instance(inst);
+    let mut inst = Instance::new("leaf2", leaf);
+    inst.connect("x", y);
+    detailed.add_instance(inst);
+
+    assert_eq!(empty.interface_hash(), detailed.interface_hash());
+
+    let (mut extra, _, _) = interface("extra");
+    let en = extra.add_node("en");
+    extra.expose_port(en, Direction::Input);
+    assert_ne!(empty.interface_hash(), extra.interface_hash());
+
+    let mut wider = Cell::new("wider");
+    let a = wider.add_bus("a", 5);
+    let y = wider.add_node("y");
+    wider.expose_port(a, Direction::Input);
+    wider.expose_port(y, Direction::Output);
+    assert_ne!(empty.interface_hash(), wider.interface_hash());
+}
+
+#[test]
+fn issues_by_cell() {
+    let mut lib = LibraryBuilder::<NoSchema>::new();
+
+    let mut leaf = Cell::new("leaf");
+    let din = leaf.add_node("din");
+    let dout = leaf.add_node("dout");
+    leaf.add_node("float");
+    leaf.expose_port(din, Direction::Input);
+    leaf.expose_port(dout, Direction::Output);
+    let leaf = lib.add_cell(leaf);
+
+    let mut top = Cell::new("top");
+    let a = top.add_node("a");
+    top.expose_port(a, Direction::Input);
+    let mut inst = Instance::new("leaf0", leaf);
+    inst.connect("din", a);
+    top.add_instance(inst);
+    let top = lib.add_cell(top);
+
+    let Err(issues) = lib.try_build() else {
+        panic!("expected validation to fail");
+    };
+    let by_cell = issues.by_cell();
+    assert_eq!(by_cell.len(), 2);
+
+    let leaf_issues = &by_cell[&leaf];
+    assert!(leaf_issues.correctness.is_empty());
+    assert!(leaf_issues
+        .drivers
+        .iter()
+        .any(|issue| matches!(issue.cause(), drivers::Cause::Floating)));
+    assert!(leaf_issues.drivers.iter().all(|issue| issue.cell() == leaf));
+
+    let top_issues = &by_cell[&top];
+    assert!(top_issues.has_error());
+    assert!(top_issues.correctness.iter().all(|issue| matches!(
+        issue.cause(),
+        validation::Cause::UnconnectedPort { parent_cell_id, .. } if *parent_cell_id == top
+    )));
+    assert!(top_issues
+        .drivers
+        .iter()
+        .any(|issue| matches!(issue.cause(), drivers::Cause::UnconnectedPort { .. })));
+
+    assert_eq!(
+        leaf_issues.drivers.len() + top_issues.drivers.len(),
+        issues.drivers.len()
+    );
+    assert_eq!(top_issues.correctness.len(), issues.correctness.len());
+}
+
+fn retain_lib() -> (LibraryBuilder<NoSchema>, CellId, CellId, CellId) {
+    let mut lib = LibraryBuilder::<NoSchema>::new();
+
+    let mut inv = Cell::new("inv");
+    let a = inv.add_node("a");
+    let y = inv.add_node("y");
+    inv.expose_port(a, Direction::Input);
+    inv.expose_port(y, Direction::Output);
+    let inv = lib.add_cell(inv);
+
+    let mut buf = Cell::new("buf");
+    let a = buf.add_node("a");
+    let x = buf.add_node("x");
+    let y = buf.add_node("y");
+    buf.expose_port(a, Direction::Input);
+    buf.expose_port(y, Direction::Output);
+    let mut inv0 = Instance::new("inv0", inv);
+    inv0.connect("a", a);
+    inv0.connect("y", x);
+    buf.add_instance(inv0);
+    let mut inv1 = Instance::new("inv1", inv);
+    inv1.connect("a", x);
+    inv1.connect("y", y);
+    buf.add_instance(inv1);
+    let buf = lib.add_cell(buf);
+
+    let unused = lib.add_cell(Cell::new("tmp_unused"));
+    lib.set_top(buf);
+
+    (lib, inv, buf, unused)
+}
+
+#[test]
+fn retain_cells_subset() {
+    let (mut lib, inv, buf, unused) = retain_lib();
+
+    lib.retain_cells(|_, cell| !cell.name().starts_with("tmp_"))
+        .unwrap();
+
+    assert_eq!(
+        lib.cells().map(|(id, _)| id).collect::<Vec<_>>(),
+        vec![inv, buf]
+    );
+    assert!(lib.try_cell(unused).is_none());
+    assert!(lib.try_cell_named("tmp_unused").is_none());
+    assert_eq!(lib.top_cell(), Some(buf));
+    assert!(!lib.validate().has_error());
+
+    // The removed cell's name may be reused.
+    let reused = lib.merge_cell(Cell::new("tmp_unused"));
+    assert_eq!(lib.cell(reused).name(), "tmp_unused");
+}
+
+#[test]
+fn retain_cells_dangling() {
+    let (mut lib, inv, buf, _) = retain_lib();
+
+    let err = lib.retain_cells(|id, _| id != inv).unwrap_err();
+    assert_eq!(
+        err,
+        DanglingRefError {
+            parent: buf,
+            child: inv,
+        }
+    );
+    // The library is left unchanged.
+    assert_eq!(lib.cells().count(), 3);
+    assert_eq!(lib.cell_id_named("inv"), inv);
+}
+
+#[test]
+fn primitive_usage() {
+    let mut lib = LibraryBuilder::<StringSchema>::new();
+    let res = lib.add_primitive("res".into());
+    let cap = lib.add_primitive("cap".into());
+
+    let mut ladder = Cell::new("ladder");
+    let nodes = ladder.add_bus("n", 4);
+    for i in 0..3 {
+        let mut r = Instance::new(arcstr::format!("r{i}"), res);
+        r.connect("1", nodes.index(i));
+        r.connect("2", nodes.index(i + 1));
+        ladder.add_instance(r);
+    }
+    let mut c = Instance::new("c0", cap);
+    c.connect("1", nodes.index(3));
+    c.connect("2", nodes.index(0));
+    ladder.add_instance(c);
+    let ladder = lib.add_cell(ladder);
+
+    let mut top = Cell::new("top");
+    top.add_instance(Instance::new("ladder", ladder));
+    let top = lib.add_cell(top);
+
+    assert_eq!(
+        lib.primitive_usage(ladder),
+        HashMap::from_iter([(res, 3), (cap, 1)])
+    );
+    assert!(lib.primitive_usage(top).is_empty());
+}
+
+#[test]
+fn unused_primitives() {
+    let mut lib = LibraryBuilder::<StringSchema>::new();
+    let res = lib.add_primitive("res".into());
+    let orphan = lib.add_primitive("orphan".into());
+    assert_eq!(lib.unused_primitives(), vec![res, orphan]);
+
+    let mut top = Cell::new("top");
+    top.add_instance(Instance::new("r0", res));
+    lib.add_cell(top);
+
+    assert_eq!(lib.unused_primitives(), vec![orphan]);
+}
+
+#[test]
+fn cell_and_instance_attributes() {
+    let mut lib = LibraryBuilder::<StringSchema>::new();
+    let res = lib.add_primitive("res".into());
+
+    let mut top = Cell::new("top");
+    assert!(top.attributes().is_empty());
+    assert_eq!(top.set_attribute("dont_touch", "true"), None);
+    top.set_attribute("synthesized_from", "yosys");
+    assert_eq!(
+        top.set_attribute("dont_touch", "false"),
+        Some(arcstr::literal!("true"))
+    );
+    let mut r0 = Instance::new("r0", res);
+    r0.set_attribute("dont_touch", "true");
+    top.add_instance(r0);
+    let top = lib.add_cell(top);
+
+    let json = serde_json::to_string(&lib).unwrap();
+    let lib: LibraryBuilder<StringSchema> = serde_json::from_str(&json).unwrap();
+    let cell = lib.cell(top);
+    assert_eq!(
+        cell.attributes().iter().collect::<Vec<_>>(),
+        vec![
+            (&arcstr::literal!("dont_touch"), &arcstr::literal!("false")),
+            (
+                &arcstr::literal!("synthesized_from"),
+                &arcstr::literal!("yosys")
+            ),
+        ]
+    );
+    assert_eq!(
+        cell.instance_named("r0").attribute("dont_touch"),
+        Some(&arcstr::literal!("true"))
+    );
+    assert_eq!(cell.instance_named("r0").attribute("missing"), None);
+
+    // Attributes do not affect validation.
+    lib.build().unwrap();
+}
+
+#[test]
+fn connected_ports_fanout() {
+    let mut lib = LibraryBuilder::<NoSchema>::new();
+
+    let mut inv = Cell::new("inv");
+    let din = inv.add_node("din");
+    let dout = inv.add_node("dout");
+    inv.expose_port(din, Direction::Input);
+    inv.expose_port(dout, Direction::Output);
+    let inv = lib.add_cell(inv);
+
+    let mut buf = Cell::new("buf");
+    let bin = buf.add_bus("din", 2);
+    buf.expose_port(bin, Direction::Input);
+    let buf = lib.add_cell(buf);
+
+    let mut top = Cell::new("top");
+    let a = top.add_bus("a", 4);
+    let y = top.add_bus("y", 4);
+    let z = top.add_node("z");
+
+    // a[1] fans out to three instances.
+    let mut inv0 = Instance::new("inv0", inv);
+    inv0.connect("din", a.index(1));
+    inv0.connect("dout", y.index(0));
+    let inv0 = top.add_instance(inv0);
+    let mut inv1 = Instance::new("inv1", inv);
+    inv1.connect("din", a.index(1));
+    inv1.connect("dout", a.index(2));
+    let inv1 = top.add_instance(inv1);
+    let mut inv2 = Instance::new("inv2", inv);
+    inv2.connect("din", z);
+    inv2.connect("dout", y.index(1));
+    let inv2 = top.add_instance(inv2);
+    let mut buf0 = Instance::new("buf0", buf);
+    buf0.connect("din", a.index(0..2));
+    let buf0 = top.add_instance(buf0);
+    let top = lib.add_cell(top);
+    let lib = lib.build().unwrap();
+
+    assert_eq!(
+        lib.connected_ports(top, a.signal()),
+        vec![
+            (inv0, arcstr::literal!("din")),
+            (inv1, arcstr::literal!("din")),
+            (inv1, arcstr::literal!("dout")),
+            (buf0, arcstr::literal!("din")),
+        ]
+    );
+    assert_eq!(
+        lib.connected_ports(top, y.signal()),
+        vec![
+            (inv0, arcstr::literal!("dout")),
+            (inv2, arcstr::literal!("dout")),
+        ]
+    );
+    assert_eq!(
+        lib.connected_ports(top, z.signal()),
+        vec![(inv2, arcstr::literal!("din"))]
+    );
+}
+
+#[test]
+fn prune_unused_signals_and_remove_instance() {
+    let mut lib = LibraryBuilder::<NoSchema>::new();
+
+    let mut inv = Cell::new("inv");
+    let din = inv.add_node("din");
+    let dout = inv.add_node("dout");
+    inv.expose_port(din, Direction::Input);
+    inv.expose_port(dout, Direction::Output);
+    let inv = lib.add_cell(inv);
+
+    let mut top = Cell::new("top");
+    let a = top.add_node("a");
+    let y = top.add_bus("y", 2);
+    let x = top.add_node("x");
+    let spare = top.add_bus("spare", 4);
+    top.add_node("floating");
+    top.expose_port(a, Direction::Input);
+    top.expose_port(y, Direction::Output);
+
+    let mut inv0 = Instance::new("inv0", inv);
+    inv0.connect("din", a);
+    inv0.connect("dout", x);
+    let inv0 = top.add_instance(inv0);
+    let mut inv1 = Instance::new("inv1", inv);
+    inv1.connect("din", x);
+    inv1.connect("dout", y.index(1));
+    let inv1 = top.add_instance(inv1);
+    let mut inv2 = Instance::new("inv2", inv);
+    inv2.connect("din", spare.index(3));
+    inv2.connect("dout", y.index(0));
+    let inv2 = top.add_instance(inv2);
+
+    assert_eq!(top.prune_unused_signals(), 1);
+    assert!(top.try_signal_named("floating").is_none());
+    assert_eq!(top.prune_unused_signals(), 0);
+
+    let removed = top.remove_instance(inv2).unwrap();
+    assert_eq!(removed.name(), "inv2");
+    assert!(top.remove_instance(inv2).is_none());
+    assert!(top.try_instance_named("inv2").is_none());
+    assert_eq!(top.instance_named("inv1").name(), "inv1");
+    assert_eq!(
+        top.instances().map(|(id, _)| id).collect::<Vec<_>>(),
+        vec![inv0, inv1]
+    );
+
+    // `spare` is now unused, but port `y` is kept despite losing a connection.
+    assert_eq!(top.prune_unused_signals(), 1);
+    assert!(top.try_signal(spare.signal()).is_none());
+    assert_eq!(top.signal_named("a").id, a.signal());
+    assert_eq!(top.signal_named("y").id, y.signal());
+    assert_eq!(top.signal_named("x").id, x.signal());
+
+    // Port signals are never pruned, even with no connections at all.
+    top.remove_instance(inv0);
+    top.remove_instance(inv1);
+    assert_eq!(top.prune_unused_signals(), 1);
+    assert!(top.try_signal(x.signal()).is_none());
+    assert!(top.signal(a.signal()).is_port());
+    assert!(top.signal(y.signal()).is_port());
+
+    lib.add_cell(top);
+    lib.build().unwrap();
+}
+
+#[test]
+fn extract_subcircuit_hides_internal_net() {
+    let mut lib = LibraryBuilder::<NoSchema>::new();
+
+    let mut inv = Cell::new("inv");
+    let din = inv.add_node("din");
+    let dout = inv.add_node("dout");
+    inv.expose_port(din, Direction::Input);
+    inv.expose_port(dout, Direction::Output);
+    let inv = lib.add_cell(inv);
+
+    let mut top = Cell::new("top");
+    let a = top.add_node("a");
+    let mid = top.add_node("mid");
+    let y = top.add_node("y");
+    let z = top.add_node("z");
+    top.expose_port(a, Direction::Input);
+    top.expose_port(z, Direction::Output);
+    let mut inv0 = Instance::new("inv0", inv);
+    inv0.connect("din", a);
+    inv0.connect("dout", mid);
+    let inv0 = top.add_instance(inv0);
+    let mut inv1 = Instance::new("inv1", inv);
+    inv1.connect("din", mid);
+    inv1.connect("dout", y);
+    let inv1 = top.add_instance(inv1);
+    let mut inv2 = Instance::new("inv2", inv);
+    inv2.connect("din", y);
+    inv2.connect("dout", z);
+    top.add_instance(inv2);
+    let top = lib.add_cell(top);
+
+    let sub = lib.extract_subcircuit(
+        top,
+        &[inv0, inv1],
+        &[
+            (a.signal(), Direction::Input),
+            (y.signal(), Direction::Output),
+        ],
+    );
+
+    let sub_cell = lib.cell(sub);
+    assert_eq!(sub_cell.name(), "top_sub");
+    assert_eq!(
+        sub_cell
+            .ports()
+            .map(|port| (
+                sub_cell.signal(port.signal()).name.as_str(),
+                port.direction()
+            ))
+            .collect::<Vec<_>>(),
+        vec![("a", Direction::Input), ("y", Direction::Output)]
+    );
+    assert!(!sub_cell.signal_named("mid").is_port());
+    assert_eq!(sub_cell.instances().count(), 2);
+    let mid_sub = sub_cell.signal_named("mid").slice();
+    assert_eq!(
+        sub_cell
+            .instance_named("inv0")
+            .connection("dout")
+            .parts()
+            .copied()
+            .collect::<Vec<_>>(),
+        vec![mid_sub]
+    );
+
+    let top_cell = lib.cell(top);
+    assert!(top_cell.try_signal(mid.signal()).is_none());
+    assert!(top_cell.try_signal_named("mid").is_none());
+    assert!(top_cell.try_instance(inv0).is_none());
+    assert!(top_cell.try_instance(inv1).is_none());
+    let inst = top_cell.instance_named("top_sub");
+    assert_eq!(inst.child(), ChildId::Cell(sub));
+    assert_eq!(
+        inst.connection("a").parts().copied().collect::<Vec<_>>(),
+        vec![Slice::from(a)]
+    );
+    assert_eq!(
+        inst.connection("y").parts().copied().collect::<Vec<_>>(),
+        vec![Slice::from(y)]
+    );
+
+    lib.build().unwrap();
+}
+
+#[test]
+fn rename_cell_instantiated_three_times() {
+    let mut lib = LibraryBuilder::<NoSchema>::new();
+
+    let mut inv = Cell::new("inv");
+    let din = inv.add_node("din");
+    inv.expose_port(din, Direction::Input);
+    let inv = lib.add_cell(inv);
+
+    let mut top = Cell::new("top");
+    let a = top.add_node("a");
+    for i in 0..3 {
+        let mut inst = Instance::new(arcstr::format!("inv{i}"), inv);
+        inst.connect("din", a);
+        top.add_instance(inst);
+    }
+    let top = lib.add_cell(top);
+    lib.set_top(top);
+
+    assert_eq!(lib.rename_cell(inv, "top"), Err(arcstr::literal!("top")));
+    assert_eq!(lib.cell(inv).name(), "inv");
+    assert_eq!(lib.rename_cell(inv, "inv"), Ok(()));
+
+    lib.rename_cell(inv, "inverter").unwrap();
+    assert_eq!(lib.cell(inv).name(), "inverter");
+    assert_eq!(lib.cell_id_named("inverter"), inv);
+    assert!(lib.try_cell_named("inv").is_none());
+
+    let lib = lib.build().unwrap();
+    let cell = lib.cell(top);
+    assert_eq!(cell.instances().count(), 3);
+    for (_, inst) in cell.instances() {
+        assert_eq!(inst.child(), ChildId::Cell(inv));
+    }
+    assert_eq!(lib.cell_named("inverter").name(), "inverter");
+}
+
+#[test]
+fn flatten_cell_two_levels() {
+    let mut lib = LibraryBuilder::<StringSchema>::new();
+    let res = lib.add_primitive("res".into());
+
+    let mut pair = Cell::new("pair");
+    let a = pair.add_bus("a", 2);
+    let b = pair.add_node("b");
+    let mid = pair.add_node("mid");
+    pair.expose_port(a, Direction::Input);
+    pair.expose_port(b, Direction::Output);
+    pair.add_instance(
+        InstanceBuilder::new("r0", res)
+            .connect("p", a.index(0))
+            .connect("n", mid)
+            .build()
+            .unwrap(),
+    );
+    pair.add_instance(
+        InstanceBuilder::new("r1", res)
+            .connect("p", mid)
+            .connect("n", b)
+            .build()
+            .unwrap(),
+    );
+    pair.add_instance(
+        InstanceBuilder::new("r2", res)
+            .connect("p", a.index(1))
+            .connect("n", b)
+            .build()
+            .unwrap(),
+    );
+    let pair = lib.add_cell(pair);
+
+    let mut stage = Cell::new("stage");
+    let x = stage.add_bus("x", 2);
+    let y = stage.add_node("y");
+    stage.expose_port(x, Direction::Input);
+    stage.expose_port(y, Direction::Output);
+    // Connect the bus reversed to check bit ordering.
+    stage.add_instance(
+        InstanceBuilder::new("p0", pair)
+            .connect("a", Concat::new(vec![x.index(1).into(), x.index(0).into()]))
+            .connect("b", y)
+            .build()
+            .unwrap(),
+    );
+    stage.add_instance(
+        InstanceBuilder::new("rm", res)
+            .connect("p", x.index(0))
+            .connect("n", y)
+            .build()
+            .unwrap(),
+    );
+    let stage = lib.add_cell(stage);
+
+    let mut top = Cell::new("top");
+    let din = top.add_bus("din", 2);
+    let out = top.add_node("out");
+    top.expose_port(din, Direction::Input);
+    top.expose_port(out, Direction::Output);
+    top.add_instance(
+        InstanceBuilder::new("m0", stage)
+            .connect("x", din)
+            .connect("y", out)
+            .build()
+            .unwrap(),
+    );
+    let top = lib.add_cell(top);
+    let lib = lib.build().unwrap();
+
+    let flat = lib.flatten_cell(top);
+    assert_eq!(flat.name(), "top");
+    assert_eq!(
+        flat.ports()
+            .map(|port| (flat.signal(port.signal()).name.clone(), port.direction()))
+            .collect::<Vec<_>>(),
+        vec![
+            (arcstr::literal!("din"), Direction::Input),
+            (arcstr::literal!("out"), Direction::Output),
+        ]
+    );
+    assert_eq!(flat.signal_named("din").id, din.signal());
+    assert_eq!(flat.signal_named("out").id, out.signal());
+    let inner = flat.signal_named("m0.p0.mid").slice();
+
+    let mut names = flat
+        .instances()
+        .map(|(_, inst)| {
+            assert_eq!(inst.child(), ChildId::Primitive(res));
+            inst.name().clone()
+        })
+        .collect::<Vec<_>>();
+    names.sort();
+    assert_eq!(names, vec!["m0.p0.r0", "m0.p0.r1", "m0.p0.r2", "m0.rm"]);
+
+    let conn = |inst: &str, port: &str| {
+        flat.instance_named(inst)
+            .connection(port)
+            .parts()
+            .copied()
+            .collect::<Vec<_>>()
+    };
+    assert_eq!(conn("m0.p0.r0", "p"), vec![Slice::from(din.index(1))]);
+    assert_eq!(conn("m0.p0.r0", "n"), vec![inner]);
+    assert_eq!(conn("m0.p0.r1", "p"), vec![inner]);
+    assert_eq!(conn("m0.p0.r1", "n"), vec![Slice::from(out)]);
+    assert_eq!(conn("m0.p0.r2", "p"), vec![Slice::from(din.index(0))]);
+    assert_eq!(conn("m0.p0.r2", "n"), vec![Slice::from(out)]);
+    assert_eq!(conn("m0.rm", "p"), vec![Slice::from(din.index(0))]);
+    assert_eq!(conn("m0.rm", "n"), vec![Slice::from(out)]);
+}
+
+#[test]
+fn hierarchy_to_dot() {
+    let mut lib = LibraryBuilder::<StringSchema>::new();
+    let res = lib.add_primitive("res".into());
+
+    let mut unit = Cell::new("unit");
+    unit.add_instance(Instance::new("r0", res));
+    let unit = lib.add_cell(unit);
+
+    let mut pair = Cell::new("pair");
+    pair.add_instance(Instance::new("u0", unit));
+    pair.add_instance(Instance::new("u1", unit));
+    let pair = lib.add_cell(pair);
+
+    let mut top = Cell::new("top");
+    top.add_instance(Instance::new("p0", pair));
+    top.add_instance(Instance::new("u2", unit));
+    lib.add_cell(top);
+
+    let dot = lib.to_dot();
+    assert!(dot.starts_with("digraph {\n"));
+    for node in ["unit", "pair", "top"] {
+        assert!(dot.contains(&format!("    \"{node}\";\n")));
+    }
+    assert!(dot.contains("    \"pair\" -> \"unit\" [label=\"u0, u1\"];\n"));
+    assert!(dot.contains("    \"top\" -> \"pair\" [label=\"p0\"];\n"));
+    assert!(dot.contains("    \"top\" -> \"unit\" [label=\"u2\"];\n"));
+    assert_eq!(dot.matches("->").count(), 3);
+}
+
+#[test]
+fn schema_roundtrip() {
+    pub struct ResistorSchema;
+
+    #[derive(Debug, Clone, Serialize, Deserialize)]
+    pub struct Resistor {
+        value: Decimal,
+        model: Option<ArcStr>,
+    }
+
+    impl Schema for ResistorSchema {
+        type Primitive = Resistor;
+    }
+
+    let mut lib = LibraryBuilder::<ResistorSchema>::new();
+    let res = lib.add_primitive(Resistor {
+        value: Decimal::new(1_000, 0),
+        model: Some(arcstr::literal!("rpoly")),
+    });
+
+    let mut vdivider = Cell::new("vdivider");
+    let vdd = vdivider.add_node("vdd");
+    let out = vdivider.add_node("out");
+    let vss = vdivider.add_node("vss");
+
+    let mut r1 = Instance::new("r1", res);
+    r1.connect("1", vdd);
+    r1.connect("2", out);
+    vdivider.add_instance(r1);
+
+    let mut r2 = Instance::new("r2", res);
+    r2.connect("1", out);
+    r2.connect("2", vss);
+    vdivider.add_instance(r2);
+
+    vdivider.expose_port(vdd, Direction::InOut);
+    vdivider.expose_port(vss, Direction::InOut);
+    vdivider.expose_port(out, Direction::Output);
+
+    lib.add_cell(vdivider);
+
+    let lib = lib.build().unwrap();
+    crate::selftest::roundtrip_schema(&lib);
+}
+
 /// Returns a SCIR library with nested cells and 3 varieties of [`SliceOnePath`]s that
 /// address the VDD node of the innermost instance for testing purposes.
 fn nested_lib(n: usize) -> (Library<StringSchema>, Vec<SliceOnePath>) {
@@ -291,6 +1322,98 @@ fn path_simplification() {
     }
 }
 
+#[test]
+fn net_name_for_nested_bus_bit() {
+    let mut lib = LibraryBuilder::<NoSchema>::new();
+
+    let mut leaf = Cell::new("leaf");
+    let data = leaf.add_bus("data", 4);
+    leaf.expose_port(data, Direction::InOut);
+    let leaf = lib.add_cell(leaf);
+
+    let mut mid = Cell::new("mid");
+    let bus = mid.add_bus("bus", 4);
+    let mut inst = Instance::new("inner", leaf);
+    inst.connect("data", bus);
+    let inner = mid.add_instance(inst);
+    mid.expose_port(bus, Direction::InOut);
+    let mid = lib.add_cell(mid);
+
+    let mut top = Cell::new("top");
+    let bus = top.add_bus("bus", 4);
+    let mut inst = Instance::new("outer", mid);
+    inst.connect("bus", bus);
+    let outer = top.add_instance(inst);
+    let top = lib.add_cell(top);
+
+    let mut path = InstancePath::new(top);
+    path.push_iter([outer, inner]);
+    let path = path.slice_one(data.index(2));
+
+    assert_eq!(lib.net_name(&path), "outer.inner.data[2]");
+}
+
+#[test]
+fn parse_node_path() {
+    let mut lib = LibraryBuilder::<NoSchema>::new();
+
+    let mut leaf = Cell::new("leaf");
+    let data = leaf.add_bus("data", 4);
+    leaf.expose_port(data, Direction::InOut);
+    let leaf = lib.add_cell(leaf);
+
+    let mut mid = Cell::new("mid");
+    let bus = mid.add_bus("bus", 4);
+    let mut inst = Instance::new("inner", leaf);
+    inst.connect("data", bus);
+    let inner = mid.add_instance(inst);
+    mid.expose_port(bus, Direction::InOut);
+    let mid = lib.add_cell(mid);
+
+    let mut top = Cell::new("top");
+    let bus = top.add_bus("bus", 4);
+    let mut inst = Instance::new("outer", mid);
+    inst.connect("bus", bus);
+    let outer = top.add_instance(inst);
+    let top = lib.add_cell(top);
+
+    let path = lib.parse_node_path(top, "outer.inner.data[2]").unwrap();
+    let mut expected = InstancePath::new(top);
+    expected.push_iter([outer, inner]);
+    assert_eq!(path, expected.slice_one(data.index(2)));
+    assert_eq!(lib.net_name(&path), "outer.inner.data[2]");
+
+    assert_eq!(
+        lib.parse_node_path(top, "outer.missing.data[2]"),
+        Err(PathParseError::MissingInstance {
+            cell: "mid".into(),
+            instance: "missing".into(),
+        })
+    );
+    assert_eq!(
+        lib.parse_node_path(top, "outer.inner.data"),
+        Err(PathParseError::InvalidIndex {
+            signal: "data".into(),
+            width: Some(4),
+            index: None,
+        })
+    );
+    assert_eq!(
+        lib.parse_node_path(top, "outer.inner.data[4]"),
+        Err(PathParseError::InvalidIndex {
+            signal: "data".into(),
+            width: Some(4),
+            index: Some(4),
+        })
+    );
+    assert_eq!(
+        lib.parse_node_path(top, "outer.inner.data[x]"),
+        Err(PathParseError::InvalidTail {
+            tail: "data[x]".into(),
+        })
+    );
+}
+
 #[test]
 fn name_path_conversion() {
     const N: usize = 5;
@@ -316,3 +1439,424 @@ fn name_path_conversion() {
         );
     }
 }
+
+fn inv_lib() -> (LibraryBuilder<NoSchema>, CellId) {
+    let mut lib = LibraryBuilder::<NoSchema>::new();
+    let mut inv = Cell::new("inv");
+    let a = inv.add_node("a");
+    let y = inv.add_node("y");
+    inv.expose_port(a, Direction::Input);
+    inv.expose_port(y, Direction::Output);
+    let inv = lib.add_cell(inv);
+    (lib, inv)
+}
+
+#[test]
+fn evaluation_order_feed_forward() {
+    let (mut lib, inv) = inv_lib();
+
+    let mut top = Cell::new("top");
+    let a = top.add_node("a");
+    let n1 = top.add_node("n1");
+    let n2 = top.add_node("n2");
+    let y = top.add_node("y");
+    top.expose_port(a, Direction::Input);
+    top.expose_port(y, Direction::Output);
+
+    let mut inst = Instance::new("inv3", inv);
+    inst.connect("a", n2);
+    inst.connect("y", y);
+    let inv3 = top.add_instance(inst);
+    let mut inst = Instance::new("inv2", inv);
+    inst.connect("a", n1);
+    inst.connect("y", n2);
+    let inv2 = top.add_instance(inst);
+    let mut inst = Instance::new("inv1", inv);
+    inst.connect("a", a);
+    inst.connect("y", n1);
+    let inv1 = top.add_instance(inst);
+    let top = lib.add_cell(top);
+
+    let order = lib
+        .cell(top)
+        .evaluation_order(|child, port| lib.cell(child.unwrap_cell()).port(port).direction())
+        .expect("feed-forward chain should have an evaluation order");
+    assert_eq!(order, vec![inv1, inv2, inv3]);
+}
+
+#[test]
+fn evaluation_order_loop() {
+    let (mut lib, inv) = inv_lib();
+
+    let mut top = Cell::new("top");
+    let x = top.add_node("x");
+    let z = top.add_node("z");
+
+    let mut inst = Instance::new("inv1", inv);
+    inst.connect("a", x);
+    inst.connect("y", z);
+    let inv1 = top.add_instance(inst);
+    let mut inst = Instance::new("inv2", inv);
+    inst.connect("a", z);
+    inst.connect("y", x);
+    let inv2 = top.add_instance(inst);
+    let top = lib.add_cell(top);
+
+    let err = lib
+        .cell(top)
+        .evaluation_order(|child, port| lib.cell(child.unwrap_cell()).port(port).direction())
+        .expect_err("ring of inverters should form a loop");
+    assert_eq!(err.instances.len(), 2);
+    assert!(err.instances.contains(&inv1));
+    assert!(err.instances.contains(&inv2));
+}
+
+#[test]
+fn port_details() {
+    let mut cell = Cell::new("cell");
+    let a = cell.add_bus("a", 4);
+    let en = cell.add_node("en");
+    let y = cell.add_node("y");
+    cell.add_node("internal");
+    cell.expose_port(y, Direction::Output);
+    cell.expose_port(a, Direction::Input);
+    cell.expose_port(en, Direction::InOut);
+
+    let details = cell.port_details().collect::<Vec<_>>();
+    assert_eq!(
+        details,
+        vec![
+            PortDetail {
+                name: "y".into(),
+                direction: Direction::Output,
+                width: None,
+                signal: y.signal(),
+            },
+            PortDetail {
+                name: "a".into(),
+                direction: Direction::Input,
+                width: Some(4),
+                signal: a.signal(),
+            },
+            PortDetail {
+                name: "en".into(),
+                direction: Direction::InOut,
+                width: None,
+                signal: en.signal(),
+            },
+        ]
+    );
+    for (detail, port) in details.iter().zip(cell.ports()) {
+        let info = cell.signal(port.signal());
+        assert_eq!(detail.signal, port.signal());
+        assert_eq!(detail.direction, port.direction());
+        assert_eq!(detail.name, info.name);
+        assert_eq!(detail.width, info.width);
+    }
+}
+
+#[test]
+fn merge_dedup_primitives() {
+    let lib_with_nch = |cell_name: &str| {
+        let mut lib = LibraryBuilder::<StringSchema>::new();
+        let nch = lib.add_primitive("nch".into());
+        let mut cell = Cell::new(cell_name);
+        let d = cell.add_node("d");
+        let mut mn = Instance::new("mn", nch);
+        mn.connect("d", d);
+        cell.add_instance(mn);
+        cell.expose_port(d, Direction::InOut);
+        let id = lib.add_cell(cell);
+        (lib, id)
+    };
+
+    let (mut lib, a) = lib_with_nch("a");
+    let (other, b) = lib_with_nch("b");
+    let mapping = lib.merge_with_options(
+        other,
+        merge::MergeOptions {
+            dedup_primitives: true,
+        },
+    );
+    let b = mapping.new_cell_id(b);
+
+    assert_eq!(lib.primitives().count(), 1);
+    let child = |id| lib.cell(id).instance_named("mn").child();
+    assert_eq!(child(a), child(b));
+    assert!(!lib.validate().has_error());
+
+    let (mut lib, _) = lib_with_nch("a");
+    let (other, _) = lib_with_nch("b");
+    lib.merge(other);
+    assert_eq!(lib.primitives().count(), 2);
+}
+
+#[test]
+fn signal_bits() {
+    let mut cell = Cell::new("bits");
+    let bus = cell.add_bus("bus", 4);
+    let wire = cell.add_node("wire");
+
+    let bits: Vec<_> = cell.signal(bus.signal()).bits().collect();
+    assert_eq!(bits.len(), 4);
+    for (i, bit) in bits.iter().enumerate() {
+        assert_eq!(bit.signal(), bus.signal());
+        assert_eq!(bit.index(), Some(i));
+        assert_eq!(*bit, bus.index(i));
+    }
+
+    let bits: Vec<_> = cell.signal(wire.signal()).bits().collect();
+    assert_eq!(bits, vec![wire]);
+    assert_eq!(bits[0].index(), None);
+}
+
+#[test]
+fn concat_builder_checks_port_width() {
+    let mut cell = Cell::new("concat");
+    let bus = cell.add_bus("bus", 3);
+    let wire = cell.add_node("wire");
+
+    let concat = ConcatBuilder::for_port(4)
+        .push(bus)
+        .push(wire)
+        .build()
+        .unwrap();
+    assert_eq!(concat.width(), 4);
+
+    let err = ConcatBuilder::for_port(4).push(bus).build().unwrap_err();
+    assert_eq!(
+        err,
+        ConcatWidthMismatch {
+            expected: 4,
+            actual: 3
+        }
+    );
+}
+
+#[test]
+fn slice_split_and_concat_reverse() {
+    let mut cell = Cell::new("split");
+    let bus = cell.add_bus("bus", 8);
+    let wire = cell.add_node("wire");
+
+    let (low, high) = bus.split_at(3);
+    assert_eq!(low.range(), Some(SliceRange::new(0, 3)));
+    assert_eq!(high.range(), Some(SliceRange::new(3, 8)));
+    assert_eq!(low.signal(), bus.signal());
+    assert_eq!(high.signal(), bus.signal());
+
+    // Splits are relative to the start of the slice.
+    let (mid_low, mid_high) = high.split_at(2);
+    assert_eq!(mid_low.range(), Some(SliceRange::new(3, 5)));
+    assert_eq!(mid_high.range(), Some(SliceRange::new(5, 8)));
+
+    let concat = Concat::new(vec![low, wire.into()]);
+    let reversed = concat.reversed();
+    assert_eq!(reversed.width(), concat.width());
+    assert_eq!(
+        reversed.parts().copied().collect::<Vec<_>>(),
+        vec![
+            Slice::from(wire),
+            bus.index(2).into(),
+            bus.index(1).into(),
+            bus.index(0).into(),
+        ]
+    );
+    assert_eq!(
+        reversed.reversed().parts().copied().collect::<Vec<_>>(),
+        (0..3)
+            .map(|i| Slice::from(bus.index(i)))
+            .chain([Slice::from(wire)])
+            .collect::<Vec<_>>()
+    );
+}
+
+#[test]
+#[should_panic]
+fn slice_split_at_out_of_range() {
+    let mut cell = Cell::new("split");
+    let bus = cell.add_bus("bus", 8);
+    bus.split_at(8);
+}
+
+#[test]
+fn cell_estimated_size() {
+    let mut lib = <LibraryBuilder>::new();
+    let mut inv = Cell::new("inv");
+    let din = inv.add_node("din");
+    inv.expose_port(din, Direction::Input);
+    let inv = lib.add_cell(inv);
+
+    let mut cell = Cell::new("top");
+    let mut sizes = vec![cell.estimated_size()];
+
+    let a = cell.add_node("a");
+    sizes.push(cell.estimated_size());
+    cell.expose_port(a, Direction::Input);
+    sizes.push(cell.estimated_size());
+    let bus = cell.add_bus("bus", 8);
+    sizes.push(cell.estimated_size());
+    cell.add_instance(Instance::new("inv0", inv));
+    sizes.push(cell.estimated_size());
+    let mut inst = Instance::new("inv1", inv);
+    inst.connect("din", bus.index(3));
+    cell.add_instance(inst);
+    sizes.push(cell.estimated_size());
+
+    assert!(sizes.windows(2).all(|w| w[0] < w[1]), "{sizes:?}");
+}
+
+#[test]
+fn snapshot_across_threads() {
+    let mut lib = LibraryBuilder::<StringSchema>::new();
+    let res = lib.add_primitive("res".into());
+    let mut cell = Cell::new("divider");
+    let nodes = cell.add_bus("n", 3);
+    for i in 0..2 {
+        let mut r = Instance::new(arcstr::format!("r{i}"), res);
+        r.connect("1", nodes.index(i));
+        r.connect("2", nodes.index(i + 1));
+        cell.add_instance(r);
+    }
+    let divider = lib.add_cell(cell);
+
+    let snapshot = lib.snapshot();
+
+    // Changes to the builder are not reflected in the snapshot.
+    lib.add_cell(Cell::new("other"));
+
+    let reader = {
+        let snapshot = snapshot.clone();
+        std::thread::spawn(move || {
+            let cell = snapshot.cell(divider);
+            (
+                cell.name().clone(),
+                cell.instances().count(),
+                snapshot.cells().count(),
+            )
+        })
+    };
+    assert_eq!(reader.join().unwrap(), (arcstr::literal!("divider"), 2, 1));
+    assert_eq!(lib.cells().count(), 2);
+}
+
+#[test]
+fn renumbered_cell() {
+    let mut lib = LibraryBuilder::<StringSchema>::new();
+    let res = lib.add_primitive("res".into());
+
+    let mut cell = Cell::new("sparse");
+    let a = cell.add_bus("a", 2);
+    let b = cell.add_node("b");
+    let c = cell.add_node("c");
+    cell.expose_port(a, Direction::Input);
+    cell.expose_port(c, Direction::Output);
+    let mut rb = Instance::new("rb", res);
+    rb.connect("1", b);
+    rb.connect("2", c);
+    cell.add_instance(rb);
+
+    // Exploding the bus removes its signal, leaving a gap in the signal IDs.
+    let (bits, _) = cell.explode_signals();
+    let mut ra = Instance::new("ra", res);
+    ra.connect("1", SliceOne::new(bits[&a.signal()][1], None));
+    ra.connect("2", c);
+    cell.add_instance(ra);
+    let mut old_ids = cell.signals().map(|(id, _)| id).collect::<Vec<_>>();
+    old_ids.sort();
+    assert_ne!(old_ids.last(), Some(&SignalId(old_ids.len() as u64)));
+
+    let (renumbered, map) = cell.renumbered();
+    let mut ids = renumbered.signals().map(|(id, _)| id).collect::<Vec<_>>();
+    ids.sort();
+    assert_eq!(ids, (1..=4).map(SignalId).collect::<Vec<_>>());
+    assert_eq!(map.len(), 4);
+
+    for (old, new) in map.iter() {
+        assert_eq!(cell.signal(*old).name, renumbered.signal(*new).name);
+        assert_eq!(renumbered.signal(*new).id, *new);
+    }
+    for (old, new) in cell.ports().zip(renumbered.ports()) {
+        assert_eq!(map[&old.signal()], new.signal());
+    }
+    for ((_, old), (_, new)) in cell.instances().zip(renumbered.instances()) {
+        for (port, conn) in old.connections() {
+            let remapped = conn
+                .parts()
+                .map(|part| (map[&part.signal()], part.range()))
+                .collect::<Vec<_>>();
+            let actual = new.connections()[port]
+                .parts()
+                .map(|part| (part.signal(), part.range()))
+                .collect::<Vec<_>>();
+            assert_eq!(remapped, actual);
+        }
+    }
+
+    // New signals continue from the last renumbered ID.
+    let mut renumbered = renumbered;
+    assert_eq!(renumbered.add_node("d").signal(), SignalId(5));
+}
+
+#[test]
+fn diff_libraries_single_connection() {
+    fn build(swap: bool) -> Library {
+        let mut lib = LibraryBuilder::<NoSchema>::new();
+
+        let mut inv = Cell::new("inv");
+        let din = inv.add_node("din");
+        let dout = inv.add_node("dout");
+        inv.expose_port(din, Direction::Input);
+        inv.expose_port(dout, Direction::Output);
+        let inv = lib.add_cell(inv);
+
+        let mut top = Cell::new("top");
+        let a = top.add_bus("a", 2);
+        let y = top.add_node("y");
+        top.expose_port(a, Direction::Input);
+        top.expose_port(y, Direction::Output);
+        let mut inv0 = Instance::new("inv0", inv);
+        inv0.connect("din", a.index(if swap { 1 } else { 0 }));
+        inv0.connect("dout", y);
+        top.add_instance(inv0);
+        let mut inv1 = Instance::new("inv1", inv);
+        inv1.connect("din", a.index(1));
+        inv1.connect("dout", y);
+        top.add_instance(inv1);
+        lib.add_cell(top);
+        lib.build().unwrap()
+    }
+
+    let a = build(false);
+    let b = build(true);
+    assert!(diff::diff_libraries(&a, &a).is_empty());
+
+    let diff = diff::diff_libraries(&a, &b);
+    assert_eq!(
+        diff,
+        diff::LibraryDiff {
+            added_cells: vec![],
+            removed_cells: vec![],
+            cells: vec![diff::CellDiff {
+                name: arcstr::literal!("top"),
+                ports: vec![],
+                signals: vec![],
+                instances: vec![diff::InstanceDiff {
+                    name: arcstr::literal!("inv0"),
+                    child: None,
+                    connections: vec![(
+                        arcstr::literal!("din"),
+                        diff::Change::Changed {
+                            old: vec![arcstr::literal!("a[0]")],
+                            new: vec![arcstr::literal!("a[1]")],
+                        }
+                    )],
+                }],
+            }],
+        }
+    );
+
+    let json = serde_json::to_string(&diff).unwrap();
+    let parsed: diff::LibraryDiff = serde_json::from_str(&json).unwrap();
+    assert_eq!(parsed, diff);
+}
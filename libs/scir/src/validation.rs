@@ -3,7 +3,7 @@
 //! This module provides helpers for ensuring that SCIR libraries
 //! and cells are valid.
 
-use std::collections::{HashMap, HashSet};
+use std::collections::{BTreeSet, HashMap, HashSet};
 use std::fmt::Display;
 
 use diagnostics::{Diagnostic, IssueSet, Severity};
@@ -160,6 +160,32 @@ pub enum Cause {
         /// The name of the child cell.
         child_cell_name: ArcStr,
     },
+    /// An instance connects the bits of a bus to a port in reversed order.
+    ///
+    /// This may be intentional, so it is reported as a warning.
+    ReversedBusConnection {
+        /// The name of the bus signal.
+        signal_name: ArcStr,
+        /// The name of the instance.
+        instance_name: ArcStr,
+        /// The name of the port with the reversed connection.
+        port: ArcStr,
+        /// The ID of the parent cell.
+        parent_cell_id: CellId,
+        /// The name of the parent cell.
+        parent_cell_name: ArcStr,
+        /// The ID of the child cell.
+        child_cell_id: CellId,
+        /// The name of the child cell.
+        child_cell_name: ArcStr,
+    },
+    /// A cell instantiates itself, directly or through other cells.
+    RecursiveInstantiation {
+        /// The IDs of the cells forming the cycle, in instantiation order.
+        cells: Vec<CellId>,
+        /// The names of the cells forming the cycle.
+        cell_names: Vec<ArcStr>,
+    },
 }
 
 impl Diagnostic for ValidatorIssue {
@@ -180,6 +206,28 @@ impl ValidatorIssue {
         &self.cause
     }
 
+    /// The cells referenced by this issue.
+    ///
+    /// Issues concerning an instance reference the parent cell containing it.
+    pub(crate) fn cells(&self) -> Vec<CellId> {
+        match &self.cause {
+            Cause::DuplicateCellNames { id1, id2, .. } => vec![*id1, *id2],
+            Cause::DuplicateInstanceNames { cell_id, .. }
+            | Cause::DuplicateSignalNames { cell_id, .. }
+            | Cause::ShortedPorts { cell_id, .. }
+            | Cause::MissingSignal { cell_id, .. }
+            | Cause::IndexOutOfBounds { cell_id, .. }
+            | Cause::MissingIndex { cell_id, .. }
+            | Cause::IndexedWire { cell_id, .. } => vec![*cell_id],
+            Cause::MissingChild { parent_cell_id, .. }
+            | Cause::UnconnectedPort { parent_cell_id, .. }
+            | Cause::ExtraPort { parent_cell_id, .. }
+            | Cause::PortWidthMismatch { parent_cell_id, .. }
+            | Cause::ReversedBusConnection { parent_cell_id, .. } => vec![*parent_cell_id],
+            Cause::RecursiveInstantiation { cells, .. } => cells.clone(),
+        }
+    }
+
     /// Creates a new validator issue and logs it immediately.
     ///
     /// The log level will be selected according to the given severity.
@@ -302,6 +350,29 @@ impl Display for Cause {
                     expected_width
                 ),
 
+            Self::ReversedBusConnection { signal_name, instance_name, port, parent_cell_name, child_cell_name, .. } =>
+                write!(
+                    f,
+                    "reversed bus connection: instance `{}` in cell `{}` connects the bits of bus `{}` in descending order to port `{}` of cell `{}`",
+                    instance_name,
+                    parent_cell_name,
+                    signal_name,
+                    port,
+                    child_cell_name
+                ),
+
+            Self::RecursiveInstantiation { cell_names, .. } =>
+                write!(
+                    f,
+                    "recursive instantiation: cells {} instantiate each other in a cycle",
+                    cell_names
+                        .iter()
+                        .chain(cell_names.first())
+                        .map(|name| format!("`{}`", name))
+                        .collect::<Vec<_>>()
+                        .join(" -> ")
+                ),
+
         }
     }
 }
@@ -317,6 +388,12 @@ impl<S: Schema + ?Sized> LibraryBuilder<S> {
             return issues;
         }
 
+        self.validate_hierarchy(&mut issues);
+
+        if issues.has_error() {
+            return issues;
+        }
+
         self.validate2(&mut issues);
         issues
     }
@@ -345,6 +422,61 @@ impl<S: Schema + ?Sized> LibraryBuilder<S> {
         }
     }
 
+    fn validate_hierarchy(&self, issues: &mut IssueSet<ValidatorIssue>) {
+        let _guard = span!(
+            Level::INFO,
+            "validation pass 1.5 (checking for recursive instantiation)"
+        )
+        .entered();
+
+        let mut done = HashSet::new();
+        let mut path = Vec::new();
+        for id in self.cells.keys().copied() {
+            self.find_cycles(id, &mut done, &mut path, issues);
+        }
+    }
+
+    /// Searches for cycles reachable from the given cell, reporting each one found.
+    ///
+    /// `path` is the chain of cells currently being visited.
+    fn find_cycles(
+        &self,
+        id: CellId,
+        done: &mut HashSet<CellId>,
+        path: &mut Vec<CellId>,
+        issues: &mut IssueSet<ValidatorIssue>,
+    ) {
+        if done.contains(&id) {
+            return;
+        }
+        if let Some(start) = path.iter().position(|cell| *cell == id) {
+            let cells = path[start..].to_vec();
+            let issue = ValidatorIssue::new_and_log(
+                Cause::RecursiveInstantiation {
+                    cell_names: cells.iter().map(|id| self.cells[id].name.clone()).collect(),
+                    cells,
+                },
+                Severity::Error,
+            );
+            issues.add(issue);
+            return;
+        }
+
+        // Missing children are reported in pass 2.
+        let children = self.cells[&id]
+            .instances
+            .values()
+            .filter_map(|inst| inst.child.into_cell())
+            .filter(|child| self.cells.contains_key(child))
+            .collect::<BTreeSet<_>>();
+        path.push(id);
+        for child in children {
+            self.find_cycles(child, done, path, issues);
+        }
+        path.pop();
+        done.insert(id);
+    }
+
     fn validate2(&self, issues: &mut IssueSet<ValidatorIssue>) {
         let _guard = span!(
             Level::INFO,
@@ -525,6 +657,20 @@ impl<S: Schema + ?Sized> LibraryBuilder<S> {
                                         Severity::Error,
                                     );
                                     issues.add(issue);
+                                } else if let Some(signal) = reversed_bus(conn) {
+                                    let issue = ValidatorIssue::new_and_log(
+                                        Cause::ReversedBusConnection {
+                                            signal_name: cell.signals[&signal].name.clone(),
+                                            instance_name: instance.name.clone(),
+                                            port: name.clone(),
+                                            parent_cell_id: id,
+                                            parent_cell_name: cell.name.clone(),
+                                            child_cell_id: instance.child.unwrap_cell(),
+                                            child_cell_name: child.name.clone(),
+                                        },
+                                        Severity::Warning,
+                                    );
+                                    issues.add(issue);
                                 }
                             }
                             None => {
@@ -580,3 +726,24 @@ impl<S: Schema + ?Sized> LibraryBuilder<S> {
         }
     }
 }
+
+/// Returns the bus signal whose bits are connected in descending order by `conn`, if any.
+///
+/// A connection is considered reversed if it consists of two or more single-bit
+/// slices of the same bus with strictly decreasing indices.
+fn reversed_bus(conn: &Concat) -> Option<SignalId> {
+    let parts: Vec<_> = conn.parts().collect();
+    let (first, rest) = parts.split_first()?;
+    if rest.is_empty() {
+        return None;
+    }
+    let mut prev = first.range().filter(|r| r.width() == 1)?;
+    for part in rest {
+        let range = part.range().filter(|r| r.width() == 1)?;
+        if part.signal() != first.signal() || range.start >= prev.start {
+            return None;
+        }
+        prev = range;
+    }
+    Some(first.signal())
+}
@@ -10,7 +10,7 @@ use std::path::PathBuf;
 use crate::{BlackboxElement, Primitive, Spice};
 use scir::schema::Schema;
 use scir::{
-    Cell, ChildId, Library, NetlistCellConversion, NetlistLibConversion, SignalInfo, Slice,
+    Cell, CellId, ChildId, Library, NetlistCellConversion, NetlistLibConversion, SignalInfo, Slice,
 };
 
 use substrate::schematic::netlist::ConvertibleNetlister;
@@ -108,6 +108,12 @@ pub trait HasSpiceLikeNetlist: Schema {
         }
         Ok(())
     }
+    /// Writes a comment.
+    ///
+    /// A newline will be added afterward.
+    fn write_comment<W: Write>(&self, out: &mut W, comment: &str) -> Result<()> {
+        write!(out, "* {}", comment)
+    }
     /// Writes a postlude to the end of the output stream.
     #[allow(unused_variables)]
     fn write_postlude<W: Write>(&self, out: &mut W, lib: &Library<Self>) -> Result<()> {
@@ -141,12 +147,26 @@ pub enum NetlistKind {
 pub struct NetlistOptions<'a> {
     kind: NetlistKind,
     includes: &'a [Include],
+    annotate: bool,
 }
 
 impl<'a> NetlistOptions<'a> {
     /// Creates a new [`NetlistOptions`].
     pub fn new(kind: NetlistKind, includes: &'a [Include]) -> Self {
-        Self { kind, includes }
+        Self {
+            kind,
+            includes,
+            annotate: false,
+        }
+    }
+
+    /// Sets whether subcircuits and instances are preceded by comments
+    /// naming the SCIR cell or primitive they correspond to.
+    ///
+    /// Disabled by default.
+    pub fn annotate(mut self, enabled: bool) -> Self {
+        self.annotate = enabled;
+        self
     }
 }
 
@@ -195,14 +215,19 @@ impl<'a, S: HasSpiceLikeNetlist, W: Write> NetlisterInstance<'a, S, W> {
 
         for (id, cell) in self.lib.cells() {
             conv.cells
-                .insert(id, self.export_cell(cell, self.lib.is_top(id))?);
+                .insert(id, self.export_cell(id, cell, self.lib.is_top(id))?);
         }
 
         self.schema.write_postlude(self.out, self.lib)?;
         Ok(conv)
     }
 
-    fn export_cell(&mut self, cell: &Cell, is_top: bool) -> Result<NetlistCellConversion> {
+    fn export_cell(
+        &mut self,
+        id: CellId,
+        cell: &Cell,
+        is_top: bool,
+    ) -> Result<NetlistCellConversion> {
         let is_testbench_top = is_top && self.opts.kind.is_testbench();
 
         let indent = if is_testbench_top { "" } else { "  " };
@@ -219,6 +244,12 @@ impl<'a, S: HasSpiceLikeNetlist, W: Write> NetlisterInstance<'a, S, W> {
             _ => None,
         };
 
+        if self.opts.annotate {
+            self.schema
+                .write_comment(self.out, &format!("{}: {}", id, cell.name()))?;
+            writeln!(self.out)?;
+        }
+
         if !is_testbench_top {
             let ports: Vec<&SignalInfo> = cell
                 .ports()
@@ -231,6 +262,17 @@ impl<'a, S: HasSpiceLikeNetlist, W: Write> NetlisterInstance<'a, S, W> {
 
         let mut conv = NetlistCellConversion::new();
         for (id, inst) in cell.instances() {
+            if self.opts.annotate {
+                let comment = match inst.child() {
+                    ChildId::Cell(child_id) => {
+                        format!("{}: {}", child_id, self.lib.cell(child_id).name())
+                    }
+                    ChildId::Primitive(child_id) => child_id.to_string(),
+                };
+                write!(self.out, "{}", indent)?;
+                self.schema.write_comment(self.out, &comment)?;
+                writeln!(self.out)?;
+            }
             write!(self.out, "{}", indent)?;
             let mut connections: HashMap<_, _> = inst
                 .connections()
@@ -2,13 +2,16 @@ use crate::netlist::{
     HasSpiceLikeNetlist, Include, NetlistKind, NetlistOptions, NetlisterInstance, RenameGround,
 };
 
-use crate::{BlackboxContents, BlackboxElement, Primitive, Spice};
+use crate::{BlackboxContents, BlackboxElement, ComponentValue, Primitive, Spice};
 use arcstr::ArcStr;
 use itertools::Itertools;
 use scir::schema::Schema;
-use scir::{Cell, Concat, Direction, IndexOwned, Instance, LibraryBuilder, SignalInfo, Slice};
+use scir::{
+    Cell, Concat, Direction, IndexOwned, Instance, LibraryBuilder, ParamValue, SignalInfo, Slice,
+};
 use std::collections::HashMap;
 use std::io::Write;
+use unicase::UniCase;
 
 #[test]
 fn scir_netlists_correctly() {
@@ -56,6 +59,44 @@ fn scir_netlists_correctly() {
     assert_eq!(string.matches("Rblackbox vdd vss 3000").count(), 1);
 }
 
+#[test]
+fn expr_params_netlist_verbatim() {
+    let mut lib = LibraryBuilder::new();
+    let mut top = Cell::new("top");
+    let vdd = top.add_node("vdd");
+    let vss = top.add_node("vss");
+    top.expose_port(vdd, Direction::InOut);
+    top.expose_port(vss, Direction::InOut);
+
+    let res = lib.add_primitive(Primitive::Res2 {
+        value: ComponentValue::Model("rpoly".into()),
+        params: HashMap::from_iter([(
+            UniCase::new(arcstr::literal!("r")),
+            ParamValue::Expr(arcstr::literal!("r_sheet*squares")),
+        )]),
+    });
+    let mut inst = Instance::new("r0", res);
+    inst.connect("1", vdd);
+    inst.connect("2", vss);
+    top.add_instance(inst);
+
+    let top = lib.add_cell(top);
+    lib.set_top(top);
+    let lib = lib.build().unwrap();
+
+    let mut buf: Vec<u8> = Vec::new();
+    let netlister = NetlisterInstance::new(&Spice, &lib, &mut buf, Default::default());
+    netlister.export().unwrap();
+    let string = String::from_utf8(buf).unwrap();
+
+    assert_eq!(
+        string
+            .matches("Rr0 vdd vss rpoly r=r_sheet*squares")
+            .count(),
+        1
+    );
+}
+
 #[test]
 fn spice_like_netlist() {
     pub struct SpiceLikeSchema {
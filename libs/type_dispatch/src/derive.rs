@@ -101,6 +101,16 @@ pub fn field_tokens(
     field_tokens_with_referent(style, vis, attrs, idx, ident, syn::parse_quote!(self))
 }
 
+/// How a derived binary method handles enum arguments of different variants.
+pub enum MismatchedVariants {
+    /// Panic with a message naming both variants.
+    Panic,
+    /// Evaluate the given expression in place of the method body.
+    ///
+    /// The expression may refer to `self` and `other`.
+    Fallback(TokenStream),
+}
+
 /// Configuration for deriving a trait.
 pub struct DeriveTrait {
     /// The trait to be implemented.
@@ -111,6 +121,11 @@ pub struct DeriveTrait {
     pub extra_arg_idents: Vec<TokenStream>,
     /// Types for extra arguments to the trait's associated methods.
     pub extra_arg_tys: Vec<TokenStream>,
+    /// If set, the trait's method takes a second argument `other: &Self` before any extra
+    /// arguments, and is dispatched to each pair of corresponding fields of `self` and `other`.
+    ///
+    /// Determines how enum values of different variants are handled.
+    pub binary: Option<MismatchedVariants>,
 }
 
 /// Derives a trait using the given configuration and input.
@@ -123,19 +138,29 @@ pub fn derive_trait(
         ref method,
         ref extra_arg_idents,
         ref extra_arg_tys,
+        ref binary,
     } = *config;
 
     let mut generics = receiver.generics;
     add_trait_bounds(&mut generics, quote!(#trait_));
     let (imp, ty, wher) = generics.split_for_impl();
+    let ident = &receiver.ident;
+
+    let other_arg = binary.as_ref().map(|_| quote!(other: &Self,));
+    let other_field = |refer: TokenStream| binary.as_ref().map(|_| quote!(&other.#refer,));
+    let other_binding = |name: &syn::Ident| {
+        let other = format_ident!("__type_dispatch_other_{name}");
+        binary.as_ref().map(|_| quote!(#other,))
+    };
 
     let match_clause: TokenStream = match receiver.data {
         Data::Struct(ref fields) => match fields.style {
             Style::Tuple => {
                 let recurse = fields.iter().enumerate().map(|(i, f)| {
                     let idx = Index::from(i);
+                    let other = other_field(quote!(#idx));
                     quote_spanned! { f.span() =>
-                        #trait_::#method(&mut self.#idx, #(#extra_arg_idents),*);
+                        #trait_::#method(&mut self.#idx, #other #(#extra_arg_idents),*);
                     }
                 });
                 quote! { #(#recurse)* }
@@ -143,8 +168,9 @@ pub fn derive_trait(
             Style::Struct => {
                 let recurse = fields.iter().map(|f| {
                     let name = f.ident.as_ref().unwrap();
+                    let other = other_field(quote!(#name));
                     quote_spanned! { f.span() =>
-                        #trait_::#method(&mut self.#name, #(#extra_arg_idents),*);
+                        #trait_::#method(&mut self.#name, #other #(#extra_arg_idents),*);
                     }
                 });
                 quote! { #(#recurse)* }
@@ -153,59 +179,109 @@ pub fn derive_trait(
         },
         Data::Enum(ref data) => {
             let clauses = data.iter().map(|v| {
-                let inner = match v.fields {
+                let ident = &v.ident;
+                let (declare, other_declare, recurse) = match v.fields {
                     syn::Fields::Named(ref fields) => {
-                        let recurse = fields.named.iter().map(|f| {
-                            let name = f.ident.as_ref().unwrap();
-                            quote_spanned! { f.span() =>
-                                #trait_::#method(#name, #(#extra_arg_idents),*);
-                            }
-                        });
-                        let declare = fields.named.iter().map(|f| {
-                            let name = f.ident.as_ref().unwrap();
+                        let names: Vec<_> = fields
+                            .named
+                            .iter()
+                            .map(|f| f.ident.clone().unwrap())
+                            .collect();
+                        let recurse = fields.named.iter().zip(&names).map(|(f, name)| {
+                            let other = other_binding(name);
                             quote_spanned! { f.span() =>
-                                ref mut #name,
+                                #trait_::#method(#name, #other #(#extra_arg_idents),*);
                             }
                         });
-                        quote! {
-                            { #(#declare)* } => { #(#recurse)* },
-                        }
+                        let others = names
+                            .iter()
+                            .map(|name| format_ident!("__type_dispatch_other_{name}"));
+                        (
+                            quote! { { #(ref mut #names,)* } },
+                            quote! { { #(#names: ref #others,)* } },
+                            quote! { #(#recurse)* },
+                        )
                     }
                     syn::Fields::Unnamed(ref fields) => {
-                        let recurse = fields.unnamed.iter().enumerate().map(|(i, f)| {
-                            let ident = format_ident!("field{i}");
+                        let names: Vec<_> = (0..fields.unnamed.len())
+                            .map(|i| format_ident!("field{i}"))
+                            .collect();
+                        let recurse = fields.unnamed.iter().zip(&names).map(|(f, name)| {
+                            let other = other_binding(name);
                             quote_spanned! { f.span() =>
-                                #trait_::#method(#ident, #(#extra_arg_idents),*);
+                                #trait_::#method(#name, #other #(#extra_arg_idents),*);
                             }
                         });
-                        let declare = fields.unnamed.iter().enumerate().map(|(i, f)| {
-                            let ident = format_ident!("field{i}");
-                            quote_spanned! { f.span() =>
-                                ref mut #ident,
-                            }
-                        });
-                        quote! {
-                            ( #(#declare)* ) => { #(#recurse)* },
-                        }
+                        let others = names
+                            .iter()
+                            .map(|name| format_ident!("__type_dispatch_other_{name}"));
+                        (
+                            quote! { ( #(ref mut #names,)* ) },
+                            quote! { ( #(ref #others,)* ) },
+                            quote! { #(#recurse)* },
+                        )
                     }
-                    syn::Fields::Unit => quote! { => (), },
+                    syn::Fields::Unit => (quote!(), quote!(), quote!()),
                 };
 
-                let ident = &v.ident;
-                quote! {
-                    Self::#ident #inner
+                if binary.is_some() {
+                    quote! {
+                        (Self::#ident #declare, Self::#ident #other_declare) => { #recurse },
+                    }
+                } else {
+                    quote! {
+                        Self::#ident #declare => { #recurse },
+                    }
                 }
             });
-            quote! {
-                match self {
-                    #(#clauses)*
+
+            match binary {
+                None => quote! {
+                    match self {
+                        #(#clauses)*
+                    }
+                },
+                Some(mismatch) => {
+                    let on_mismatch = match mismatch {
+                        MismatchedVariants::Panic => {
+                            let names = data.iter().map(|v| {
+                                let ident = &v.ident;
+                                let name = ident.to_string();
+                                quote! { Self::#ident { .. } => #name, }
+                            });
+                            quote! {
+                                let variant = |value: &Self| match value {
+                                    #(#names)*
+                                };
+                                panic!(
+                                    "cannot apply `{}::{}` to different variants `{}` and `{}`",
+                                    stringify!(#ident),
+                                    stringify!(#method),
+                                    variant(self),
+                                    variant(other),
+                                );
+                            }
+                        }
+                        MismatchedVariants::Fallback(expr) => quote! {
+                            #expr;
+                            return;
+                        },
+                    };
+                    quote! {
+                        if ::std::mem::discriminant(self) != ::std::mem::discriminant(other) {
+                            #on_mismatch
+                        }
+                        match (self, other) {
+                            #(#clauses)*
+                            #[allow(unreachable_patterns)]
+                            _ => unreachable!(),
+                        }
+                    }
                 }
             }
         }
     };
 
-    let ident = &receiver.ident;
-
     let extra_args_sig = extra_arg_idents
         .iter()
         .zip(extra_arg_tys)
@@ -217,7 +293,7 @@ pub fn derive_trait(
 
     quote! {
         impl #imp #trait_ for #ident #ty #wher {
-            fn #method(&mut self, #(#extra_args_sig),*) {
+            fn #method(&mut self, #other_arg #(#extra_args_sig),*) {
                 #match_clause
             }
         }
@@ -130,3 +130,63 @@ fn impl_dispatch_works() {
     assert_eq!(photoshop.pixels, vec![vec![0, 1], vec![1, 2]]);
     assert_eq!(illustrator.vectors, vec![(24, 32), (40, 48)]);
 }
+
+trait AddAssign {
+    fn add_assign(&mut self, other: &Self);
+}
+
+impl AddAssign for u64 {
+    fn add_assign(&mut self, other: &Self) {
+        *self += *other;
+    }
+}
+
+#[derive(Debug, PartialEq, type_dispatch_test_macros::AddAssignOrPanic)]
+enum Shape {
+    Circle { radius: u64 },
+    Line(u64, u64),
+    Empty,
+}
+
+#[derive(Debug, Default, PartialEq, type_dispatch_test_macros::AddAssignOrDefault)]
+enum DefaultShape {
+    Circle {
+        radius: u64,
+    },
+    Line(u64, u64),
+    #[default]
+    Empty,
+}
+
+#[test]
+fn derive_binary_trait_dispatches_matching_variants() {
+    let mut circle = Shape::Circle { radius: 1 };
+    circle.add_assign(&Shape::Circle { radius: 2 });
+    assert_eq!(circle, Shape::Circle { radius: 3 });
+
+    let mut line = Shape::Line(1, 2);
+    line.add_assign(&Shape::Line(3, 4));
+    assert_eq!(line, Shape::Line(4, 6));
+
+    let mut empty = Shape::Empty;
+    empty.add_assign(&Shape::Empty);
+    assert_eq!(empty, Shape::Empty);
+}
+
+#[test]
+#[should_panic(expected = "to different variants `Circle` and `Empty`")]
+fn derive_binary_trait_panics_on_mismatched_variants() {
+    let mut circle = Shape::Circle { radius: 1 };
+    circle.add_assign(&Shape::Empty);
+}
+
+#[test]
+fn derive_binary_trait_falls_back_on_mismatched_variants() {
+    let mut line = DefaultShape::Line(1, 2);
+    line.add_assign(&DefaultShape::Line(3, 4));
+    assert_eq!(line, DefaultShape::Line(4, 6));
+
+    let mut circle = DefaultShape::Circle { radius: 1 };
+    circle.add_assign(&DefaultShape::Line(3, 4));
+    assert_eq!(circle, DefaultShape::Empty);
+}
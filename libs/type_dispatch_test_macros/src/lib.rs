@@ -0,0 +1,50 @@
+//! Derive macros used to test the `type_dispatch` crate.
+#![warn(missing_docs)]
+
+use darling::FromDeriveInput;
+use proc_macro::TokenStream;
+use quote::quote;
+use syn::{parse_macro_input, DeriveInput};
+use type_dispatch::derive::{derive_trait, DeriveInputReceiver, DeriveTrait, MismatchedVariants};
+
+macro_rules! handle_error {
+    ($expression:expr) => {
+        match $expression {
+            Ok(value) => value,
+            Err(err) => {
+                return err.write_errors().into();
+            }
+        }
+    };
+}
+
+fn derive_add_assign(input: TokenStream, mismatch: MismatchedVariants) -> TokenStream {
+    let parsed = parse_macro_input!(input as DeriveInput);
+    let receiver = handle_error!(DeriveInputReceiver::from_derive_input(&parsed));
+    let config = DeriveTrait {
+        trait_: quote!(AddAssign),
+        method: quote!(add_assign),
+        extra_arg_idents: Vec::new(),
+        extra_arg_tys: Vec::new(),
+        binary: Some(mismatch),
+    };
+
+    let expanded = derive_trait(&config, receiver);
+    proc_macro::TokenStream::from(expanded)
+}
+
+/// Derives an `AddAssign` trait in scope that panics on mismatched enum variants.
+#[proc_macro_derive(AddAssignOrPanic)]
+pub fn derive_add_assign_or_panic(input: TokenStream) -> TokenStream {
+    derive_add_assign(input, MismatchedVariants::Panic)
+}
+
+/// Derives an `AddAssign` trait in scope that resets `self` to its default value
+/// on mismatched enum variants.
+#[proc_macro_derive(AddAssignOrDefault)]
+pub fn derive_add_assign_or_default(input: TokenStream) -> TokenStream {
+    derive_add_assign(
+        input,
+        MismatchedVariants::Fallback(quote!(*self = ::std::default::Default::default())),
+    )
+}
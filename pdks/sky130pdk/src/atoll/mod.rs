@@ -43,6 +43,7 @@ impl Sky130Layers {
                         endcap: 85,
                         via_spacing: 1,
                         strap_via_spacing: 1,
+                        explicit_tracks: None,
                     },
                 },
                 PdkLayer {
@@ -55,6 +56,7 @@ impl Sky130Layers {
                         endcap: 85,
                         via_spacing: 1,
                         strap_via_spacing: 1,
+                        explicit_tracks: None,
                     },
                 },
                 PdkLayer {
@@ -67,6 +69,7 @@ impl Sky130Layers {
                         endcap: 130,
                         via_spacing: 1,
                         strap_via_spacing: 1,
+                        explicit_tracks: None,
                     },
                 },
                 PdkLayer {
@@ -79,6 +82,7 @@ impl Sky130Layers {
                         endcap: 200,
                         via_spacing: 1,
                         strap_via_spacing: 1,
+                        explicit_tracks: None,
                     },
                 },
                 PdkLayer {
@@ -91,6 +95,7 @@ impl Sky130Layers {
                         endcap: 200,
                         via_spacing: 1,
                         strap_via_spacing: 1,
+                        explicit_tracks: None,
                     },
                 },
                 PdkLayer {
@@ -103,6 +108,7 @@ impl Sky130Layers {
                         endcap: 600,
                         via_spacing: 1,
                         strap_via_spacing: 1,
+                        explicit_tracks: None,
                     },
                 },
             ],
@@ -20,7 +20,11 @@ use crate::mos::{MosKind, MosParams};
 use scir::schema::{FromSchema, Schema};
 use scir::{Instance, ParamValue};
 use spice::Spice;
+use substrate::block::Block;
 use substrate::context::{ContextBuilder, Installation};
+use substrate::io::schematic::HardwareType;
+use substrate::schematic::primitives::RawInstance;
+use substrate::schematic::{CellBuilder, PrimitiveBinding, Schematic};
 
 pub mod atoll;
 pub mod corner;
@@ -393,3 +397,22 @@ impl Pdk for Sky130Pdk {
     type Layers = Sky130Layers;
     const LAYOUT_DB_UNITS: Decimal = dec!(1e-9);
 }
+
+impl Schematic<Sky130Pdk> for RawInstance {
+    fn schematic(
+        &self,
+        io: &<<Self as Block>::Io as HardwareType>::Bundle,
+        cell: &mut CellBuilder<Sky130Pdk>,
+    ) -> substrate::error::Result<Self::NestedData> {
+        let mut prim = PrimitiveBinding::new(Primitive::RawInstance {
+            cell: self.cell.clone(),
+            ports: self.ports.clone(),
+            params: self.params.clone(),
+        });
+        for (i, port) in self.ports.iter().enumerate() {
+            prim.connect(port, io[i]);
+        }
+        cell.set_primitive(prim);
+        Ok(())
+    }
+}
@@ -1,20 +1,31 @@
 use crate::paths::get_path;
 use crate::shared::pdk::sky130_open_ctx;
-use atoll::abs::{Abstract, DebugAbstract};
-use atoll::grid::{LayerStack, PdkLayer};
-use atoll::route::GreedyRouter;
-use atoll::{DrawnInstance, IoBuilder, Tile, TileBuilder, TileWrapper};
+use atoll::abs::{Abstract, DebugAbstract, TrackCoord};
+use atoll::grid::{LayerStack, PdkLayer, RoutingGrid};
+use atoll::route::{GreedyRouter, ViaMaker};
+use atoll::straps::{LayerStrappingParams, StrappingParams};
+use atoll::{DrawnInstance, FrozenTile, IoBuilder, PointState, Tile, TileBuilder, TileWrapper};
+use geometry::bbox::Bbox;
+use geometry::contains::Contains;
 use geometry::point::Point;
+use geometry::rect::Rect;
+use geometry::transform::Translate;
 
 use serde::{Deserialize, Serialize};
 use sky130pdk::atoll::{MosLength, NmosTile, Sky130ViaMaker};
 use sky130pdk::{Sky130CommercialSchema, Sky130Pdk};
 use spice::netlist::NetlistOptions;
 use spice::Spice;
+use std::collections::{HashMap, HashSet};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Mutex;
 use substrate::block::Block;
+use substrate::context::PdkContext;
 use substrate::io::layout::HardwareType;
 use substrate::io::{FlatLen, InOut, Io, Signal};
 
+use substrate::layout::element::{Element, RawCell, Shape};
+use substrate::layout::tracks::RoundingMode;
 use substrate::layout::{CellBuilder, ExportsLayoutData, Layout};
 use substrate::schematic;
 use substrate::schematic::netlist::ConvertibleNetlister;
@@ -123,6 +134,37 @@ pub struct Sky130NmosTileAutorouteIo {
 #[substrate(io = "Sky130NmosTileAutorouteIo")]
 pub struct Sky130NmosTileAutoroute;
 
+/// Draws three NMOS tiles side by side, connecting their terminals to the tile's IO.
+fn draw_three_nmos<B: Block<Io = Sky130NmosTileAutorouteIo>>(
+    io: &mut IoBuilder<'_, B>,
+    cell: &mut TileBuilder<'_, Sky130Pdk>,
+) -> substrate::error::Result<Vec<schematic::Instance<NmosTile>>> {
+    let block = NmosTile::new(1_680, MosLength::L150, 3);
+
+    let mut instances = Vec::new();
+
+    for i in 0..3 {
+        let mut inst = cell.generate_primitive(block);
+        inst.translate_mut(Point::new(5 * i, 0));
+        let DrawnInstance { schematic, layout } = cell.draw(inst)?;
+
+        for i in 0..4 {
+            cell.connect(io.schematic.sd, schematic.io().sd[i]);
+            io.layout.sd.merge(layout.io().sd[i].clone());
+        }
+        for j in 0..schematic.io().g.len() {
+            cell.connect(io.schematic.g, schematic.io().g[j]);
+            io.layout.g.merge(layout.io().g[j].clone());
+        }
+        cell.connect(io.schematic.b, schematic.io().b);
+        io.layout.b.merge(layout.io().b.clone());
+
+        instances.push(schematic);
+    }
+
+    Ok(instances)
+}
+
 impl ExportsNestedData for Sky130NmosTileAutoroute {
     type NestedData = Vec<schematic::Instance<NmosTile>>;
 }
@@ -134,34 +176,13 @@ impl ExportsLayoutData for Sky130NmosTileAutoroute {
 impl Tile<Sky130Pdk> for Sky130NmosTileAutoroute {
     fn tile<'a>(
         &self,
-        io: IoBuilder<'a, Self>,
+        mut io: IoBuilder<'a, Self>,
         cell: &mut TileBuilder<'a, Sky130Pdk>,
     ) -> substrate::error::Result<(
         <Self as ExportsNestedData>::NestedData,
         <Self as ExportsLayoutData>::LayoutData,
     )> {
-        let block = sky130pdk::atoll::NmosTile::new(1_680, MosLength::L150, 3);
-
-        let mut instances = Vec::new();
-
-        for i in 0..3 {
-            let mut inst = cell.generate_primitive(block);
-            inst.translate_mut(Point::new(5 * i, 0));
-            let DrawnInstance { schematic, layout } = cell.draw(inst)?;
-
-            for i in 0..4 {
-                cell.connect(io.schematic.sd, schematic.io().sd[i]);
-                io.layout.sd.merge(layout.io().sd[i].clone());
-            }
-            for j in 0..schematic.io().g.len() {
-                cell.connect(io.schematic.g, schematic.io().g[j]);
-                io.layout.g.merge(layout.io().g[j].clone());
-            }
-            cell.connect(io.schematic.b, schematic.io().b);
-            io.layout.b.merge(layout.io().b.clone());
-
-            instances.push(schematic);
-        }
+        let instances = draw_three_nmos(&mut io, cell)?;
 
         cell.set_top_layer(2);
         cell.set_router(GreedyRouter::new());
@@ -211,6 +232,717 @@ fn sky130_atoll_nmos_tile_autoroute() {
     .expect("failed to write abstract");
 }
 
+static LAYER1_VIAS: AtomicUsize = AtomicUsize::new(0);
+static LAYER2_VIAS: AtomicUsize = AtomicUsize::new(0);
+
+/// A via maker that only accepts vias from a single layer and counts the vias it draws.
+struct CountingViaMaker {
+    layer: usize,
+    count: &'static AtomicUsize,
+}
+
+impl ViaMaker<Sky130Pdk> for CountingViaMaker {
+    fn draw_via(&self, ctx: PdkContext<Sky130Pdk>, track_coord: TrackCoord) -> Vec<Shape> {
+        assert_eq!(track_coord.layer, self.layer);
+        self.count.fetch_add(1, Ordering::SeqCst);
+        Sky130ViaMaker.draw_via(ctx, track_coord)
+    }
+}
+
+/// A via maker that should never be used.
+struct UnusedViaMaker;
+
+impl ViaMaker<Sky130Pdk> for UnusedViaMaker {
+    fn draw_via(&self, _ctx: PdkContext<Sky130Pdk>, track_coord: TrackCoord) -> Vec<Shape> {
+        panic!("fallback via maker used for layer {}", track_coord.layer);
+    }
+}
+
+#[derive(Block, Copy, Clone, Eq, PartialEq, Hash, Serialize, Deserialize, Debug)]
+#[substrate(io = "Sky130NmosTileAutorouteIo")]
+pub struct Sky130NmosTileLayerViaMakers;
+
+impl ExportsNestedData for Sky130NmosTileLayerViaMakers {
+    type NestedData = ();
+}
+
+impl ExportsLayoutData for Sky130NmosTileLayerViaMakers {
+    type LayoutData = ();
+}
+
+impl Tile<Sky130Pdk> for Sky130NmosTileLayerViaMakers {
+    fn tile<'a>(
+        &self,
+        io: IoBuilder<'a, Self>,
+        cell: &mut TileBuilder<'a, Sky130Pdk>,
+    ) -> substrate::error::Result<(
+        <Self as ExportsNestedData>::NestedData,
+        <Self as ExportsLayoutData>::LayoutData,
+    )> {
+        let inst = cell.generate(Sky130NmosTileAutoroute);
+        let bounds = inst.physical_bounds();
+        let DrawnInstance { schematic, layout } = cell.draw(inst)?;
+        cell.connect(io.schematic.sd, schematic.io().sd);
+        io.layout.sd.merge(layout.io().sd.clone());
+        cell.connect(io.schematic.g, schematic.io().g);
+        io.layout.g.merge(layout.io().g.clone());
+        cell.connect(io.schematic.b, schematic.io().b);
+        io.layout.b.merge(layout.io().b.clone());
+
+        // Leave empty space to the right of the instance for one via per layer transition.
+        let virtual_layers = cell.layout.ctx.install_layers::<atoll::VirtualLayers>();
+        let outline = bounds.union(bounds.translate(Point::new(bounds.width(), 0)));
+        cell.layout
+            .draw(Shape::new(virtual_layers.outline, outline))?;
+
+        let grid = RoutingGrid::new((*cell.layer_stack).clone(), 0..3);
+        for layer in 0..2 {
+            let loc = grid.point_to_grid(
+                Point::new(
+                    bounds.right() + bounds.width() * (layer as i64 + 1) / 3,
+                    bounds.center().y,
+                ),
+                layer,
+                RoundingMode::Nearest,
+                RoundingMode::Nearest,
+            );
+            let net = cell.signal(format!("via_net_{layer}"), Signal);
+            cell.add_via(
+                net,
+                TrackCoord {
+                    layer,
+                    x: loc.x,
+                    y: loc.y,
+                },
+            );
+        }
+
+        cell.set_top_layer(2);
+        cell.set_router(GreedyRouter::new());
+        cell.set_via_maker(UnusedViaMaker);
+        cell.set_layer_via_maker(
+            1,
+            CountingViaMaker {
+                layer: 1,
+                count: &LAYER1_VIAS,
+            },
+        );
+        cell.set_layer_via_maker(
+            2,
+            CountingViaMaker {
+                layer: 2,
+                count: &LAYER2_VIAS,
+            },
+        );
+
+        Ok(((), ()))
+    }
+}
+
+#[test]
+fn sky130_atoll_layer_via_makers() {
+    let gds_path = get_path("sky130_atoll_layer_via_makers", "layout.gds");
+    let ctx = sky130_open_ctx();
+
+    ctx.write_layout(TileWrapper::new(Sky130NmosTileLayerViaMakers), gds_path)
+        .expect("failed to write layout");
+
+    // Each maker asserts that it only receives vias on its own layer.
+    let layer1_vias = LAYER1_VIAS.load(Ordering::SeqCst);
+    let layer2_vias = LAYER2_VIAS.load(Ordering::SeqCst);
+    assert!(layer1_vias > 0);
+    assert!(layer2_vias > 0);
+
+    // Each drawn via cut lands on the cut layer of its layer transition.
+    let handle = ctx.generate_layout(TileWrapper::new(Sky130NmosTileLayerViaMakers));
+    let count_on_layer = |layer| {
+        handle
+            .cell()
+            .raw()
+            .elements()
+            .filter(|elem| matches!(elem, Element::Shape(shape) if shape.layer() == layer))
+            .count()
+    };
+    assert_eq!(
+        count_on_layer(*ctx.layers.mcon.drawing.as_ref()),
+        layer1_vias
+    );
+    assert_eq!(
+        count_on_layer(*ctx.layers.via.drawing.as_ref()),
+        layer2_vias
+    );
+}
+
+#[test]
+fn sky130_atoll_nmos_tile_autoroute_congestion() {
+    let ctx = sky130_open_ctx();
+    let block = TileWrapper::new(Sky130NmosTileAutoroute);
+    let handle = ctx.generate_layout(block);
+
+    let abs = Abstract::generate(&ctx, handle.cell());
+    let report = abs.congestion();
+
+    assert!(!report.layers.is_empty());
+    for (i, layer) in report.layers.iter().enumerate() {
+        assert_eq!(layer.layer, i);
+        assert!(layer.used <= layer.total);
+        assert!((0.0..=1.0).contains(&layer.utilization()));
+        let peak = layer.peak.as_ref().unwrap();
+        assert!(peak.utilization() >= layer.utilization());
+    }
+    assert!(report.layers.iter().any(|layer| layer.used > 0));
+    assert!(report.peak().unwrap().utilization() > 0.);
+}
+
+/// Three NMOS tiles whose source/drain net is routed twice as wide as the line width.
+#[derive(Block, Copy, Clone, Eq, PartialEq, Hash, Serialize, Deserialize, Debug)]
+#[substrate(io = "Sky130NmosTileAutorouteIo")]
+pub struct Sky130NmosTileWideNet;
+
+impl ExportsNestedData for Sky130NmosTileWideNet {
+    type NestedData = ();
+}
+
+impl ExportsLayoutData for Sky130NmosTileWideNet {
+    type LayoutData = ();
+}
+
+impl Tile<Sky130Pdk> for Sky130NmosTileWideNet {
+    fn tile<'a>(
+        &self,
+        mut io: IoBuilder<'a, Self>,
+        cell: &mut TileBuilder<'a, Sky130Pdk>,
+    ) -> substrate::error::Result<(
+        <Self as ExportsNestedData>::NestedData,
+        <Self as ExportsLayoutData>::LayoutData,
+    )> {
+        draw_three_nmos(&mut io, cell)?;
+
+        cell.set_net_width(io.schematic.sd, 2);
+        cell.set_top_layer(2);
+        cell.set_router(GreedyRouter::new());
+        cell.set_via_maker(Sky130ViaMaker);
+
+        Ok(((), ()))
+    }
+}
+
+#[test]
+fn sky130_atoll_wide_net() {
+    let gds_path = get_path("sky130_atoll_wide_net", "layout.gds");
+    let ctx = sky130_open_ctx();
+
+    ctx.write_layout(TileWrapper::new(Sky130NmosTileWideNet), gds_path)
+        .expect("failed to write layout");
+
+    // Counts the routing segments drawn exactly twice as wide as the line width.
+    let stack = ctx.get_installation::<LayerStack<PdkLayer>>().unwrap();
+    let count_wide = |cell: &RawCell| {
+        cell.elements()
+            .filter(|elem| {
+                let Element::Shape(shape) = elem else {
+                    return false;
+                };
+                stack.layers.iter().any(|layer| {
+                    let rect = shape.shape().bbox().unwrap();
+                    shape.layer() == layer.id
+                        && std::cmp::min(rect.width(), rect.height()) == 2 * layer.inner.line
+                })
+            })
+            .count()
+    };
+
+    let wide = ctx.generate_layout(TileWrapper::new(Sky130NmosTileWideNet));
+    let narrow = ctx.generate_layout(TileWrapper::new(Sky130NmosTileAutoroute));
+    assert!(count_wide(wide.cell().raw()) > count_wide(narrow.cell().raw()));
+}
+
+/// [`Sky130NmosTileAutoroute`] with the tracks of layer 0 shifted back by one pitch.
+#[derive(Block, Copy, Clone, Eq, PartialEq, Hash, Serialize, Deserialize, Debug)]
+#[substrate(io = "Sky130NmosTileAutorouteIo")]
+pub struct Sky130NmosTileGridOffset;
+
+impl ExportsNestedData for Sky130NmosTileGridOffset {
+    type NestedData = ();
+}
+
+impl ExportsLayoutData for Sky130NmosTileGridOffset {
+    type LayoutData = ();
+}
+
+impl Tile<Sky130Pdk> for Sky130NmosTileGridOffset {
+    fn tile<'a>(
+        &self,
+        mut io: IoBuilder<'a, Self>,
+        cell: &mut TileBuilder<'a, Sky130Pdk>,
+    ) -> substrate::error::Result<(
+        <Self as ExportsNestedData>::NestedData,
+        <Self as ExportsLayoutData>::LayoutData,
+    )> {
+        draw_three_nmos(&mut io, cell)?;
+
+        let layer = &cell.layer_stack.layers[0].inner;
+        cell.set_grid_offset(0, -(layer.line + layer.space))
+            .expect("a whole pitch is a valid offset");
+        cell.set_top_layer(2);
+        cell.set_router(GreedyRouter::new());
+        cell.set_via_maker(Sky130ViaMaker);
+
+        Ok(((), ()))
+    }
+}
+
+#[test]
+fn sky130_atoll_grid_offset() {
+    let gds_path = get_path("sky130_atoll_grid_offset", "layout.gds");
+    let ctx = sky130_open_ctx();
+
+    ctx.write_layout(TileWrapper::new(Sky130NmosTileGridOffset), gds_path)
+        .expect("failed to write layout");
+
+    // Shifting the grid by a whole pitch moves the instances' blockages along with the
+    // tracks, so the routes land on the same physical tracks as without the offset.
+    // Only the column of grid points shifted past the edge of the tile is lost.
+    let shapes = |cell: &RawCell| {
+        cell.elements()
+            .filter_map(|elem| match elem {
+                Element::Shape(shape) => Some(shape.clone()),
+                _ => None,
+            })
+            .collect::<Vec<_>>()
+    };
+    let offset = shapes(
+        ctx.generate_layout(TileWrapper::new(Sky130NmosTileGridOffset))
+            .cell()
+            .raw(),
+    );
+    let unshifted = shapes(
+        ctx.generate_layout(TileWrapper::new(Sky130NmosTileAutoroute))
+            .cell()
+            .raw(),
+    );
+    assert!(!offset.is_empty());
+    for shape in offset {
+        assert!(
+            unshifted.contains(&shape),
+            "{shape:?} is not on an unshifted track"
+        );
+    }
+}
+
+/// Three NMOS tiles with every available routing track blocked.
+#[derive(Block, Copy, Clone, Eq, PartialEq, Hash, Serialize, Deserialize, Debug)]
+#[substrate(io = "Sky130NmosTileAutorouteIo")]
+pub struct Sky130NmosTileBlocked;
+
+impl ExportsNestedData for Sky130NmosTileBlocked {
+    type NestedData = ();
+}
+
+impl ExportsLayoutData for Sky130NmosTileBlocked {
+    type LayoutData = ();
+}
+
+impl Tile<Sky130Pdk> for Sky130NmosTileBlocked {
+    fn tile<'a>(
+        &self,
+        mut io: IoBuilder<'a, Self>,
+        cell: &mut TileBuilder<'a, Sky130Pdk>,
+    ) -> substrate::error::Result<(
+        <Self as ExportsNestedData>::NestedData,
+        <Self as ExportsLayoutData>::LayoutData,
+    )> {
+        draw_three_nmos(&mut io, cell)?;
+
+        for layer in 0..=2 {
+            cell.block_available_on_layer(layer);
+        }
+        cell.set_top_layer(2);
+        cell.set_router(GreedyRouter::new());
+
+        Ok(((), ()))
+    }
+}
+
+#[test]
+fn sky130_atoll_try_route() {
+    let ctx = sky130_open_ctx();
+
+    let result = atoll::try_route(&ctx, Sky130NmosTileAutoroute, 2).unwrap();
+    assert!(result.is_success());
+
+    let result = atoll::try_route(&ctx, Sky130NmosTileBlocked, 2).unwrap();
+    assert!(!result.is_success());
+    assert!(!result.unrouted.is_empty());
+}
+
+#[derive(Block, Copy, Clone, Eq, PartialEq, Hash, Serialize, Deserialize, Debug)]
+#[substrate(io = "Sky130NmosTileAutorouteIo")]
+pub struct Sky130StrappingWithoutStrapper;
+
+impl ExportsNestedData for Sky130StrappingWithoutStrapper {
+    type NestedData = ();
+}
+
+impl ExportsLayoutData for Sky130StrappingWithoutStrapper {
+    type LayoutData = ();
+}
+
+impl Tile<Sky130Pdk> for Sky130StrappingWithoutStrapper {
+    fn tile<'a>(
+        &self,
+        io: IoBuilder<'a, Self>,
+        cell: &mut TileBuilder<'a, Sky130Pdk>,
+    ) -> substrate::error::Result<(
+        <Self as ExportsNestedData>::NestedData,
+        <Self as ExportsLayoutData>::LayoutData,
+    )> {
+        let inst = cell.generate_primitive(NmosTile::new(1_680, MosLength::L150, 1));
+        let DrawnInstance { schematic, .. } = cell.draw(inst)?;
+        for i in 0..schematic.io().sd.len() {
+            cell.connect(io.schematic.sd, schematic.io().sd[i]);
+        }
+        for j in 0..schematic.io().g.len() {
+            cell.connect(io.schematic.g, schematic.io().g[j]);
+        }
+        cell.connect(io.schematic.b, schematic.io().b);
+
+        cell.set_strapping(
+            io.schematic.b,
+            StrappingParams::new(1, vec![LayerStrappingParams::ViaDown { min_period: 1 }]),
+        );
+        cell.set_top_layer(2);
+        cell.set_router(GreedyRouter::new());
+        cell.set_via_maker(Sky130ViaMaker);
+
+        Ok(((), ()))
+    }
+}
+
+#[test]
+fn sky130_atoll_strapping_without_strapper() {
+    let ctx = sky130_open_ctx();
+
+    let result = atoll::try_route(&ctx, Sky130NmosTileAutoroute, 2).unwrap();
+    assert!(result.unstrapped.is_empty());
+
+    let result = atoll::try_route(&ctx, Sky130StrappingWithoutStrapper, 2).unwrap();
+    assert!(result.is_success());
+    assert_eq!(result.unstrapped.len(), 1);
+}
+
+/// A single NMOS tile that asserts the connectivity of its nets.
+#[derive(Block, Copy, Clone, Eq, PartialEq, Hash, Serialize, Deserialize, Debug)]
+#[substrate(io = "Sky130NmosTileAutorouteIo")]
+pub struct Sky130AssertConnected {
+    /// Whether to assert that two unconnected nets are connected.
+    violate: bool,
+}
+
+impl ExportsNestedData for Sky130AssertConnected {
+    type NestedData = ();
+}
+
+impl ExportsLayoutData for Sky130AssertConnected {
+    type LayoutData = ();
+}
+
+impl Tile<Sky130Pdk> for Sky130AssertConnected {
+    fn tile<'a>(
+        &self,
+        io: IoBuilder<'a, Self>,
+        cell: &mut TileBuilder<'a, Sky130Pdk>,
+    ) -> substrate::error::Result<(
+        <Self as ExportsNestedData>::NestedData,
+        <Self as ExportsLayoutData>::LayoutData,
+    )> {
+        let inst = cell.generate_primitive(NmosTile::new(1_680, MosLength::L150, 1));
+        let DrawnInstance { schematic, .. } = cell.draw(inst)?;
+        for i in 0..schematic.io().sd.len() {
+            cell.connect(io.schematic.sd, schematic.io().sd[i]);
+        }
+        for j in 0..schematic.io().g.len() {
+            cell.connect(io.schematic.g, schematic.io().g[j]);
+        }
+        cell.connect(io.schematic.b, schematic.io().b);
+
+        let gate = cell.signal("gate", Signal);
+        cell.connect(gate, io.schematic.g);
+        cell.assert_connected(gate, io.schematic.g);
+        if self.violate {
+            cell.assert_connected(io.schematic.sd, io.schematic.g);
+        }
+
+        cell.set_top_layer(2);
+        cell.set_router(GreedyRouter::new());
+        cell.set_via_maker(Sky130ViaMaker);
+
+        Ok(((), ()))
+    }
+}
+
+#[test]
+fn sky130_atoll_assert_connected() {
+    let ctx = sky130_open_ctx();
+    let result = atoll::try_route(&ctx, Sky130AssertConnected { violate: false }, 2).unwrap();
+    assert!(result.is_success());
+}
+
+#[test]
+#[should_panic(expected = "are not connected")]
+fn sky130_atoll_assert_connected_violated() {
+    let ctx = sky130_open_ctx();
+    let _ = atoll::try_route(&ctx, Sky130AssertConnected { violate: true }, 2);
+}
+
+static PRE_ROUTE_HOOK_CALLS: AtomicUsize = AtomicUsize::new(0);
+
+/// Three NMOS tiles whose top routing layer is blocked by a pre-route hook.
+#[derive(Block, Copy, Clone, Eq, PartialEq, Hash, Serialize, Deserialize, Debug)]
+#[substrate(io = "Sky130NmosTileAutorouteIo")]
+pub struct Sky130PreRouteHook;
+
+impl ExportsNestedData for Sky130PreRouteHook {
+    type NestedData = ();
+}
+
+impl ExportsLayoutData for Sky130PreRouteHook {
+    type LayoutData = ();
+}
+
+impl Tile<Sky130Pdk> for Sky130PreRouteHook {
+    fn tile<'a>(
+        &self,
+        mut io: IoBuilder<'a, Self>,
+        cell: &mut TileBuilder<'a, Sky130Pdk>,
+    ) -> substrate::error::Result<(
+        <Self as ExportsNestedData>::NestedData,
+        <Self as ExportsLayoutData>::LayoutData,
+    )> {
+        draw_three_nmos(&mut io, cell)?;
+
+        cell.add_pre_route_hook(|state| {
+            PRE_ROUTE_HOOK_CALLS.fetch_add(1, Ordering::SeqCst);
+            for point in state.layer_mut(2).iter_mut() {
+                if *point == PointState::Available {
+                    *point = PointState::Blocked { has_via: false };
+                }
+            }
+        });
+        cell.set_top_layer(2);
+        cell.set_router(GreedyRouter::new());
+        cell.set_via_maker(Sky130ViaMaker);
+
+        Ok(((), ()))
+    }
+}
+
+#[test]
+fn sky130_atoll_pre_route_hook() {
+    let gds_path = get_path("sky130_atoll_pre_route_hook", "layout.gds");
+    let ctx = sky130_open_ctx();
+
+    ctx.write_layout(TileWrapper::new(Sky130PreRouteHook), gds_path)
+        .expect("failed to write layout");
+    assert!(PRE_ROUTE_HOOK_CALLS.load(Ordering::SeqCst) > 0);
+
+    // Routes never use the blocked layer, so no via lands on it.
+    let handle = ctx.generate_layout(TileWrapper::new(Sky130PreRouteHook));
+    let stack = ctx.get_installation::<LayerStack<PdkLayer>>().unwrap();
+    let met2 = stack.layer(2).id;
+    assert!(handle.cell().raw().elements().all(|elem| match elem {
+        Element::Shape(shape) => shape.layer() != met2,
+        _ => true,
+    }));
+}
+
+#[derive(Block, Copy, Clone, Eq, PartialEq, Hash, Serialize, Deserialize, Debug)]
+#[substrate(io = "Sky130NmosTileAutorouteIo")]
+pub struct Sky130GenerateAt;
+
+impl ExportsNestedData for Sky130GenerateAt {
+    type NestedData = ();
+}
+
+impl ExportsLayoutData for Sky130GenerateAt {
+    type LayoutData = Vec<Rect>;
+}
+
+impl Tile<Sky130Pdk> for Sky130GenerateAt {
+    fn tile<'a>(
+        &self,
+        io: IoBuilder<'a, Self>,
+        cell: &mut TileBuilder<'a, Sky130Pdk>,
+    ) -> substrate::error::Result<(
+        <Self as ExportsNestedData>::NestedData,
+        <Self as ExportsLayoutData>::LayoutData,
+    )> {
+        let reference = cell.generate(Sky130NmosTileAutoroute).lcm_bounds();
+        let mut bounds = vec![reference];
+
+        for loc in [Point::new(0, 0), Point::new(reference.width(), 0)] {
+            let inst = cell.generate_at(Sky130NmosTileAutoroute, loc);
+            bounds.push(inst.lcm_bounds());
+            let DrawnInstance { schematic, layout } = cell.draw(inst)?;
+
+            cell.connect(io.schematic.sd, schematic.io().sd);
+            io.layout.sd.merge(layout.io().sd.clone());
+            cell.connect(io.schematic.g, schematic.io().g);
+            io.layout.g.merge(layout.io().g.clone());
+            cell.connect(io.schematic.b, schematic.io().b);
+            io.layout.b.merge(layout.io().b.clone());
+        }
+
+        cell.set_top_layer(2);
+
+        Ok(((), bounds))
+    }
+}
+
+#[test]
+fn sky130_atoll_generate_at() {
+    let ctx = sky130_open_ctx();
+    let handle = ctx.generate_layout(TileWrapper::new(Sky130GenerateAt));
+    let bounds = handle.cell().data();
+
+    let reference = bounds[0];
+    assert_eq!(bounds[1], reference);
+    assert_eq!(
+        bounds[2],
+        reference.translate(Point::new(reference.width(), 0))
+    );
+}
+
+#[derive(Block, Copy, Clone, Eq, PartialEq, Hash, Serialize, Deserialize, Debug)]
+#[substrate(io = "Sky130NmosTileAutorouteIo")]
+pub struct Sky130FrozenPair;
+
+impl ExportsNestedData for Sky130FrozenPair {
+    type NestedData = ();
+}
+
+impl ExportsLayoutData for Sky130FrozenPair {
+    type LayoutData = ();
+}
+
+impl Tile<Sky130Pdk> for Sky130FrozenPair {
+    fn tile<'a>(
+        &self,
+        io: IoBuilder<'a, Self>,
+        cell: &mut TileBuilder<'a, Sky130Pdk>,
+    ) -> substrate::error::Result<(
+        <Self as ExportsNestedData>::NestedData,
+        <Self as ExportsLayoutData>::LayoutData,
+    )> {
+        let frozen = atoll::freeze_tile(&cell.generate(Sky130NmosTileAutoroute));
+        let a = cell.instantiate_frozen(&frozen);
+        let width = a.lcm_bounds().width();
+        let b = cell
+            .instantiate_frozen(&frozen)
+            .translate(Point::new(width, 0));
+
+        for inst in [a, b] {
+            cell.connect(io.schematic.sd, inst.io()[0]);
+            cell.connect(io.schematic.g, inst.io()[1]);
+            cell.connect(io.schematic.b, inst.io()[2]);
+            let ports = cell.draw_frozen(inst)?;
+            io.layout.sd.merge(ports[0].clone());
+            io.layout.g.merge(ports[1].clone());
+            io.layout.b.merge(ports[2].clone());
+        }
+
+        cell.set_top_layer(3);
+        cell.set_router(GreedyRouter::new());
+        cell.set_via_maker(Sky130ViaMaker);
+
+        Ok(((), ()))
+    }
+}
+
+/// The frozen [`Sky130FrozenPair`] and [`Sky130NmosTileAutoroute`] tiles,
+/// captured while generating [`Sky130FreezeFrozenPair`].
+static FROZEN_TILES: Mutex<Option<(FrozenTile, FrozenTile)>> = Mutex::new(None);
+
+#[derive(Block, Copy, Clone, Eq, PartialEq, Hash, Serialize, Deserialize, Debug)]
+#[substrate(io = "Sky130NmosTileAutorouteIo")]
+pub struct Sky130FreezeFrozenPair;
+
+impl ExportsNestedData for Sky130FreezeFrozenPair {
+    type NestedData = ();
+}
+
+impl ExportsLayoutData for Sky130FreezeFrozenPair {
+    type LayoutData = ();
+}
+
+impl Tile<Sky130Pdk> for Sky130FreezeFrozenPair {
+    fn tile<'a>(
+        &self,
+        io: IoBuilder<'a, Self>,
+        cell: &mut TileBuilder<'a, Sky130Pdk>,
+    ) -> substrate::error::Result<(
+        <Self as ExportsNestedData>::NestedData,
+        <Self as ExportsLayoutData>::LayoutData,
+    )> {
+        let child = atoll::freeze_tile(&cell.generate(Sky130NmosTileAutoroute));
+        let pair = cell.generate(Sky130FrozenPair);
+        *FROZEN_TILES.lock().unwrap() = Some((atoll::freeze_tile(&pair), child));
+
+        let DrawnInstance { schematic, layout } = cell.draw(pair)?;
+        cell.connect(io.schematic.sd, schematic.io().sd);
+        io.layout.sd.merge(layout.io().sd.clone());
+        cell.connect(io.schematic.g, schematic.io().g);
+        io.layout.g.merge(layout.io().g.clone());
+        cell.connect(io.schematic.b, schematic.io().b);
+        io.layout.b.merge(layout.io().b.clone());
+
+        Ok(((), ()))
+    }
+}
+
+#[test]
+fn sky130_atoll_frozen_tile() {
+    let gds_path = get_path("sky130_atoll_frozen_tile", "layout.gds");
+    let ctx = sky130_open_ctx();
+
+    ctx.write_layout(TileWrapper::new(Sky130FreezeFrozenPair), gds_path)
+        .expect("failed to write layout");
+
+    let (pair, child) = FROZEN_TILES.lock().unwrap().clone().unwrap();
+    assert_eq!(child.ports().len(), 3);
+
+    // The first frozen instance sits at the origin of the pair, so grid points
+    // coincide. Routing in the pair must not take over any point used by the
+    // frozen tile, and distinct nets of the frozen tile must remain distinct.
+    let child_state = child.abs().routing_state();
+    let pair_state = pair.abs().routing_state();
+    let mut nets = HashMap::new();
+    for layer in 0..=2 {
+        let grid = child_state.layer(layer);
+        for ((x, y), state) in grid.indexed_iter() {
+            let interior = x > 0 && y > 0 && x + 1 < grid.rows() && y + 1 < grid.cols();
+            if !interior || *state == PointState::Available {
+                continue;
+            }
+            let pair_point = pair_state.layer(layer).get(x, y).unwrap();
+            match (state, pair_point) {
+                (PointState::Routed { net, .. }, PointState::Routed { net: pair_net, .. }) => {
+                    assert_eq!(*nets.entry(*net).or_insert(*pair_net), *pair_net);
+                }
+                // Internal nets of the frozen tile become obstructions.
+                (
+                    PointState::Routed { .. } | PointState::Blocked { .. },
+                    PointState::Blocked { .. },
+                )
+                | (PointState::Reserved { .. }, _) => {}
+                _ => panic!("frozen tile point ({x}, {y}) on layer {layer} was routed over"),
+            }
+        }
+    }
+    let pair_nets: HashSet<_> = nets.values().collect();
+    assert_eq!(pair_nets.len(), nets.len());
+}
+
 #[derive(Block, Copy, Clone, Eq, PartialEq, Hash, Serialize, Deserialize, Debug)]
 #[substrate(io = "()")]
 pub struct Sky130DebugRoutingGrid;
@@ -314,3 +1046,228 @@ fn sky130_atoll_ptap_tile() {
     )
     .expect("failed to write abstract");
 }
+
+#[derive(Block, Copy, Clone, Eq, PartialEq, Hash, Serialize, Deserialize, Debug)]
+#[substrate(io = "Sky130NmosTileAutorouteIo")]
+pub struct Sky130StandaloneVia;
+
+impl ExportsNestedData for Sky130StandaloneVia {
+    type NestedData = ();
+}
+
+impl ExportsLayoutData for Sky130StandaloneVia {
+    /// The expected center of the standalone via.
+    type LayoutData = Point;
+}
+
+impl Tile<Sky130Pdk> for Sky130StandaloneVia {
+    fn tile<'a>(
+        &self,
+        io: IoBuilder<'a, Self>,
+        cell: &mut TileBuilder<'a, Sky130Pdk>,
+    ) -> substrate::error::Result<(
+        <Self as ExportsNestedData>::NestedData,
+        <Self as ExportsLayoutData>::LayoutData,
+    )> {
+        let inst = cell.generate(Sky130NmosTileAutoroute);
+        let bounds = inst.physical_bounds();
+        let DrawnInstance { schematic, layout } = cell.draw(inst)?;
+        cell.connect(io.schematic.sd, schematic.io().sd);
+        io.layout.sd.merge(layout.io().sd.clone());
+        cell.connect(io.schematic.g, schematic.io().g);
+        io.layout.g.merge(layout.io().g.clone());
+        cell.connect(io.schematic.b, schematic.io().b);
+        io.layout.b.merge(layout.io().b.clone());
+
+        // Leave empty space to the right of the instance for the via.
+        let virtual_layers = cell.layout.ctx.install_layers::<atoll::VirtualLayers>();
+        let outline = bounds.union(bounds.translate(Point::new(bounds.width(), 0)));
+        cell.layout
+            .draw(Shape::new(virtual_layers.outline, outline))?;
+
+        let grid = RoutingGrid::new((*cell.layer_stack).clone(), 0..3);
+        let loc = grid.point_to_grid(
+            Point::new(bounds.right() + bounds.width() / 2, bounds.center().y),
+            1,
+            RoundingMode::Nearest,
+            RoundingMode::Nearest,
+        );
+        let coord = TrackCoord {
+            layer: 1,
+            x: loc.x,
+            y: loc.y,
+        };
+        let net = cell.signal("via_net", Signal);
+        cell.add_via(net, coord);
+
+        cell.set_top_layer(2);
+        cell.set_via_maker(Sky130ViaMaker);
+
+        Ok(((), grid.xy_track_point(1, coord.x, coord.y)))
+    }
+}
+
+#[test]
+fn sky130_atoll_standalone_via() {
+    let gds_path = get_path("sky130_atoll_standalone_via", "layout.gds");
+    let ctx = sky130_open_ctx();
+
+    ctx.write_layout(TileWrapper::new(Sky130StandaloneVia), gds_path)
+        .expect("failed to write layout");
+
+    let handle = ctx.generate_layout(TileWrapper::new(Sky130StandaloneVia));
+    let center = *handle.cell().data();
+    let stack = ctx.get_installation::<LayerStack<PdkLayer>>().unwrap();
+
+    let shapes: Vec<_> = handle
+        .cell()
+        .raw()
+        .elements()
+        .filter_map(|elem| match elem {
+            Element::Shape(shape) => Some(shape),
+            _ => None,
+        })
+        .collect();
+    let on_layer = |layer| {
+        shapes
+            .iter()
+            .filter(|shape| shape.layer() == layer)
+            .collect::<Vec<_>>()
+    };
+
+    let vias = on_layer(*ctx.layers.via.drawing.as_ref());
+    assert_eq!(vias.len(), 1);
+    assert_eq!(vias[0].bbox_rect().center(), center);
+
+    // Only the via landing pads are drawn on the adjacent metal layers.
+    for layer in 1..=2 {
+        let metal = on_layer(stack.layer(layer).id);
+        assert_eq!(metal.len(), 1);
+        assert!(metal[0].bbox_rect().encloses(&center));
+    }
+}
+
+#[derive(Block, Copy, Clone, Eq, PartialEq, Hash, Serialize, Deserialize, Debug)]
+#[substrate(io = "Sky130NmosTileAutorouteIo")]
+pub struct Sky130TileMetrics;
+
+impl ExportsNestedData for Sky130TileMetrics {
+    type NestedData = ();
+}
+
+impl ExportsLayoutData for Sky130TileMetrics {
+    type LayoutData = ();
+}
+
+impl Tile<Sky130Pdk> for Sky130TileMetrics {
+    fn tile<'a>(
+        &self,
+        io: IoBuilder<'a, Self>,
+        cell: &mut TileBuilder<'a, Sky130Pdk>,
+    ) -> substrate::error::Result<(
+        <Self as ExportsNestedData>::NestedData,
+        <Self as ExportsLayoutData>::LayoutData,
+    )> {
+        let single = cell.generate(Sky130NmosTileAutoroute);
+        let metrics = single.metrics();
+        assert_eq!(metrics.device_count, 3);
+        assert_eq!(metrics.net_count, 3);
+        assert_eq!(metrics.area, single.physical_bounds().area());
+
+        let pair = cell.generate(Sky130GenerateAt);
+        let metrics = pair.metrics();
+        assert_eq!(metrics.device_count, 6);
+        assert_eq!(metrics.area, pair.physical_bounds().area());
+
+        let DrawnInstance { schematic, layout } = cell.draw(pair)?;
+        cell.connect(io.schematic.sd, schematic.io().sd);
+        io.layout.sd.merge(layout.io().sd.clone());
+        cell.connect(io.schematic.g, schematic.io().g);
+        io.layout.g.merge(layout.io().g.clone());
+        cell.connect(io.schematic.b, schematic.io().b);
+        io.layout.b.merge(layout.io().b.clone());
+
+        cell.set_top_layer(2);
+
+        Ok(((), ()))
+    }
+}
+
+#[test]
+fn sky130_atoll_tile_metrics() {
+    let ctx = sky130_open_ctx();
+    ctx.generate_layout(TileWrapper::new(Sky130TileMetrics))
+        .try_cell()
+        .expect("failed to generate tile");
+}
+
+/// Checks the net manifest of a generated tile's abstract.
+#[derive(Block, Copy, Clone, Eq, PartialEq, Hash, Serialize, Deserialize, Debug)]
+#[substrate(io = "Sky130NmosTileAutorouteIo")]
+pub struct Sky130NetManifest;
+
+impl ExportsNestedData for Sky130NetManifest {
+    type NestedData = ();
+}
+
+impl ExportsLayoutData for Sky130NetManifest {
+    type LayoutData = ();
+}
+
+impl Tile<Sky130Pdk> for Sky130NetManifest {
+    fn tile<'a>(
+        &self,
+        io: IoBuilder<'a, Self>,
+        cell: &mut TileBuilder<'a, Sky130Pdk>,
+    ) -> substrate::error::Result<(
+        <Self as ExportsNestedData>::NestedData,
+        <Self as ExportsLayoutData>::LayoutData,
+    )> {
+        let inst = cell.generate(Sky130NmosTileAutoroute);
+        let abs = atoll::freeze_tile(&inst).abs().clone();
+        let manifest = abs.net_manifest();
+
+        let state = abs.routing_state();
+        let mut routed = HashSet::new();
+        for layer in 0..=2 {
+            for point in state.layer(layer).iter() {
+                if let PointState::Routed { net, .. } = point {
+                    routed.insert(*net);
+                }
+            }
+        }
+        assert!(!routed.is_empty());
+        assert!(routed
+            .iter()
+            .all(|net| manifest.iter().any(|(id, _)| id == net)));
+
+        let names: HashSet<String> = manifest
+            .iter()
+            .filter_map(|(_, name)| name.as_ref().map(|name| name.to_string()))
+            .collect();
+        assert_eq!(
+            names,
+            HashSet::from(["sd".to_string(), "g".to_string(), "b".to_string()])
+        );
+
+        let DrawnInstance { schematic, layout } = cell.draw(inst)?;
+        cell.connect(io.schematic.sd, schematic.io().sd);
+        io.layout.sd.merge(layout.io().sd.clone());
+        cell.connect(io.schematic.g, schematic.io().g);
+        io.layout.g.merge(layout.io().g.clone());
+        cell.connect(io.schematic.b, schematic.io().b);
+        io.layout.b.merge(layout.io().b.clone());
+
+        cell.set_top_layer(2);
+
+        Ok(((), ()))
+    }
+}
+
+#[test]
+fn sky130_atoll_net_manifest() {
+    let ctx = sky130_open_ctx();
+    ctx.generate_layout(TileWrapper::new(Sky130NetManifest))
+        .try_cell()
+        .expect("failed to generate tile");
+}
@@ -168,8 +168,9 @@ fn export_hard_macro_to_spectre() {
         .build()
         .unwrap();
 
+    let spectre = Spectre::default();
     let mut buf: Vec<u8> = Vec::new();
-    let netlister = NetlisterInstance::new(&Spectre {}, &spectre_lib, &mut buf, Default::default());
+    let netlister = NetlisterInstance::new(&spectre, &spectre_lib, &mut buf, Default::default());
     netlister.export().unwrap();
     let string = String::from_utf8(buf).unwrap();
     println!("Netlist:\n{}", string);
@@ -1,8 +1,8 @@
 use arcstr::ArcStr;
 use rust_decimal::Decimal;
 use scir::*;
-use spectre::Spectre;
-use spice::netlist::{NetlistKind, NetlistOptions, NetlisterInstance};
+use spectre::{CasePolicy, Spectre};
+use spice::netlist::{NetlistKind, NetlistOptions, NetlisterInstance, RenameGround};
 use spice::{BlackboxContents, BlackboxElement, ComponentValue, Spice};
 use std::collections::HashMap;
 use substrate::schematic::netlist::ConvertibleNetlister;
@@ -215,7 +215,7 @@ fn netlist_spectre_vdivider() {
     let mut buf: Vec<u8> = Vec::new();
     let includes = Vec::new();
     NetlisterInstance::new(
-        &Spectre {},
+        &Spectre::default(),
         &lib,
         &mut buf,
         NetlistOptions::new(NetlistKind::Cells, &includes),
@@ -237,3 +237,117 @@ fn netlist_spectre_vdivider() {
     assert_eq!(string.matches("vdivider").count(), 2);
     assert_eq!(string.matches("resistor r=100").count(), 3);
 }
+
+#[test]
+fn netlist_spectre_custom_ground() {
+    let mut lib = LibraryBuilder::<Spectre>::new();
+    let res = lib.add_primitive(Spectre::resistor(100));
+
+    let mut tb = Cell::new("tb");
+    let vdd = tb.add_node("vdd");
+    let vss = tb.add_node("vss");
+
+    let mut r1 = Instance::new("r1", res);
+    r1.connect(Spectre::pos(), vdd);
+    r1.connect(Spectre::neg(), vss);
+    tb.add_instance(r1);
+
+    tb.expose_port(vss, Direction::InOut);
+    let tb = lib.add_cell(tb);
+    lib.set_top(tb);
+    let lib = lib.build().unwrap();
+
+    let spectre = Spectre::default().with_ground("gnd!");
+    assert_eq!(spectre.ground(), "gnd!");
+
+    let mut buf: Vec<u8> = Vec::new();
+    let includes = Vec::new();
+    NetlisterInstance::new(
+        &spectre,
+        &lib,
+        &mut buf,
+        NetlistOptions::new(
+            NetlistKind::Testbench(RenameGround::Yes(arcstr::literal!("gnd!"))),
+            &includes,
+        ),
+    )
+    .export()
+    .unwrap();
+    let string = String::from_utf8(buf).unwrap();
+    println!("{}", string);
+
+    assert!(string.contains("global gnd!\n"));
+    assert!(!string.contains("global 0"));
+    assert!(string.contains("xr1 ( vdd gnd! ) resistor r=100"));
+    assert!(!string.contains("vss"));
+}
+
+#[test]
+fn netlist_spectre_case_policy() {
+    let mut lib = LibraryBuilder::<Spectre>::new();
+    let res = lib.add_primitive(Spectre::resistor(100));
+
+    let mut top = Cell::new("Top");
+    let vdd = top.add_node("VddA");
+    let out = top.add_node("outB");
+    let data = top.add_bus("Data", 2);
+    let x0 = top.add_node("x0");
+
+    let mut r1 = Instance::new("R1", res);
+    r1.connect(Spectre::pos(), vdd);
+    r1.connect(Spectre::neg(), out);
+    top.add_instance(r1);
+
+    let mut r2 = Instance::new("R2", res);
+    r2.connect(Spectre::pos(), data.index(1));
+    r2.connect(Spectre::neg(), x0);
+    top.add_instance(r2);
+
+    top.expose_port(vdd, Direction::InOut);
+    top.expose_port(data, Direction::InOut);
+    let top = lib.add_cell(top);
+    lib.set_top(top);
+    let lib = lib.build().unwrap();
+
+    for (policy, ports, r1, r2) in [
+        (
+            CasePolicy::Preserve,
+            "subckt Top ( VddA Data\\[0\\] Data\\[1\\] )",
+            "xR1 ( VddA outB ) resistor r=100",
+            "xR2 ( Data\\[1\\] xx0 ) resistor r=100",
+        ),
+        (
+            CasePolicy::Lower,
+            "subckt Top ( vdda data\\[0\\] data\\[1\\] )",
+            "xr1 ( vdda outb ) resistor r=100",
+            "xr2 ( data\\[1\\] xx0 ) resistor r=100",
+        ),
+        (
+            CasePolicy::Upper,
+            "subckt Top ( VDDA DATA\\[0\\] DATA\\[1\\] )",
+            "XR1 ( VDDA OUTB ) resistor r=100",
+            "XR2 ( DATA\\[1\\] XX0 ) resistor r=100",
+        ),
+    ] {
+        let spectre = Spectre::default().with_case(policy);
+        assert_eq!(spectre.case(), policy);
+
+        let mut buf: Vec<u8> = Vec::new();
+        let includes = Vec::new();
+        NetlisterInstance::new(
+            &spectre,
+            &lib,
+            &mut buf,
+            NetlistOptions::new(NetlistKind::Cells, &includes),
+        )
+        .export()
+        .unwrap();
+        let string = String::from_utf8(buf).unwrap();
+        println!("{}", string);
+
+        assert!(string.contains(ports));
+        assert!(string.contains(r1));
+        assert!(string.contains(r2));
+        assert!(string.contains("ends Top"));
+    }
+}
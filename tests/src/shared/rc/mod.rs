@@ -101,6 +101,7 @@ impl Testbench<Spectre> for RcTb {
                         stop: dec!(2e6),
                         sweep: Sweep::Linear(10),
                         errpreset: Some(ErrPreset::Conservative),
+                        save: None,
                     },
                 ),
             )
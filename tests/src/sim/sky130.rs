@@ -191,3 +191,41 @@ fn sky130_and2_monte_carlo_spectre() {
         }
     }
 }
+
+#[cfg(feature = "spectre")]
+#[test]
+fn sky130_and2_monte_carlo_statistics() {
+    use crate::shared::pdk::sky130_commercial_ctx;
+
+    let test_name = "sky130_and2_monte_carlo_statistics";
+    let sim_dir = get_path(test_name, "sim/");
+    let ctx = sky130_commercial_ctx();
+
+    let mc_vout = ctx
+        .simulate::<spectre::Spectre, _>(
+            And2Tb {
+                vdd: dec!(1.8),
+                a: dec!(1.8),
+                b: dec!(1.8),
+            },
+            &sim_dir,
+        )
+        .unwrap();
+    let finals: Vec<f64> = mc_vout.iter().map(|v| *v.last().unwrap()).collect();
+    assert_eq!(finals.len(), 4);
+
+    let stats = mc_vout.statistics(|v| *v.last().unwrap());
+    let mean = finals.iter().sum::<f64>() / 4.;
+    let var = finals.iter().map(|v| (v - mean).powi(2)).sum::<f64>() / 3.;
+    assert_abs_diff_eq!(stats.mean, mean, epsilon = 1e-12);
+    assert_abs_diff_eq!(stats.std, var.sqrt(), epsilon = 1e-12);
+    assert_eq!(
+        stats.min,
+        finals.iter().copied().fold(f64::INFINITY, f64::min)
+    );
+    assert_eq!(
+        stats.max,
+        finals.iter().copied().fold(f64::NEG_INFINITY, f64::max)
+    );
+    assert_abs_diff_eq!(stats.mean, 1.8, epsilon = 1e-6);
+}
@@ -1,29 +1,42 @@
 use std::collections::HashMap;
+use std::path::Path;
 use std::process::Command;
+use std::sync::atomic::{AtomicUsize, Ordering};
 use std::sync::{Arc, Mutex};
+use std::time::Duration;
 
 use approx::{assert_relative_eq, relative_eq};
 use cache::multi::MultiCache;
 use rust_decimal::prelude::ToPrimitive;
 use rust_decimal_macros::dec;
+use scir::{InstancePath, NamedSliceOne, SliceOnePath};
 use serde::{Deserialize, Serialize};
 use sky130pdk::corner::Sky130Corner;
-use spectre::analysis::tran::Tran;
-use spectre::blocks::Vsource;
-use spectre::{Options, Primitive, Spectre};
+use spectre::analysis::ac::Sweep;
+use spectre::analysis::noise::Noise;
+use spectre::analysis::sp::Sp;
+use spectre::analysis::tran::{Edge, Tran};
+use spectre::blocks::{Port, PwlVsource, Vsource};
+use spectre::log::{parse_log, Severity, SpectreDiagnostic};
+use spectre::{
+    IntegrationMethod, Options, OutputParser, Primitive, RawOutput, SaveOption, SimSignal, Spectre,
+    SpectreLogOptions, SpectreNumericOptions,
+};
 use spice::{BlackboxContents, BlackboxElement, Spice};
 use substrate::block::Block;
 use substrate::cache::Cache;
 use substrate::context::Context;
 use substrate::execute::{ExecOpts, Executor, LocalExecutor};
-use substrate::io::schematic::HardwareType;
+use substrate::io::schematic::{HardwareType, Node};
 use substrate::io::{InOut, Signal, TestbenchIo};
 use substrate::io::{Io, TwoTerminalIo};
 use substrate::pdk::corner::Pvt;
+use substrate::schematic::netlist::ConvertibleNetlister;
 use substrate::schematic::{
     Cell, CellBuilder, ExportsNestedData, Instance, PrimitiveBinding, Schematic,
 };
 use substrate::simulation::data::{tran, FromSaved, Save, SaveTb};
+use substrate::simulation::options::ic::{self, InitialCondition};
 use substrate::simulation::{SimController, SimulationContext, Simulator, Testbench};
 use test_log::test;
 
@@ -33,7 +46,7 @@ use crate::shared::inverter::Inverter;
 use crate::shared::pdk::sky130_commercial_ctx;
 use crate::shared::vdivider::tb::{VdividerArrayTb, VdividerDuplicateSubcktTb};
 use crate::{paths::get_path, shared::vdivider::tb::VdividerTb};
-use substrate::schematic::primitives::{RawInstance, Resistor};
+use substrate::schematic::primitives::{Capacitor, RawInstance, Resistor};
 
 #[test]
 fn vdivider_tran() {
@@ -179,6 +192,237 @@ fn spectre_caches_simulations() {
     assert_eq!(*count.lock().unwrap(), 1);
 }
 
+#[test]
+fn spectre_simulate_batch_limits_concurrency() {
+    #[derive(Debug, Copy, Clone, Hash, Eq, PartialEq, Serialize, Deserialize, Block)]
+    #[substrate(io = "TestbenchIo")]
+    struct BatchTb;
+
+    impl ExportsNestedData for BatchTb {
+        type NestedData = ();
+    }
+
+    impl Schematic<Spectre> for BatchTb {
+        fn schematic(
+            &self,
+            io: &<<Self as Block>::Io as HardwareType>::Bundle,
+            cell: &mut CellBuilder<Spectre>,
+        ) -> substrate::error::Result<Self::NestedData> {
+            let r = cell.instantiate(Resistor::new(dec!(1000)));
+            cell.connect(r.io().p, io.vss);
+            cell.connect(r.io().n, io.vss);
+            Ok(())
+        }
+    }
+
+    /// Records the peak number of concurrent jobs, failing jobs whose working
+    /// directory is named `fail`.
+    #[derive(Debug, Default)]
+    struct PeakExecutor {
+        running: AtomicUsize,
+        peak: Arc<AtomicUsize>,
+    }
+
+    impl Executor for PeakExecutor {
+        fn execute(
+            &self,
+            command: Command,
+            _opts: ExecOpts,
+        ) -> Result<(), substrate::error::Error> {
+            let running = self.running.fetch_add(1, Ordering::SeqCst) + 1;
+            self.peak.fetch_max(running, Ordering::SeqCst);
+            std::thread::sleep(Duration::from_millis(50));
+            self.running.fetch_sub(1, Ordering::SeqCst);
+            if command
+                .get_current_dir()
+                .is_some_and(|dir| dir.ends_with("fail"))
+            {
+                return Err(substrate::error::Error::CommandFailed(Arc::new(command)));
+            }
+            Ok(())
+        }
+    }
+
+    #[derive(Debug)]
+    struct StubParser;
+
+    impl OutputParser for StubParser {
+        fn parse(
+            &self,
+            _output_dir: &Path,
+            _name: &str,
+            _analysis: &spectre::Input,
+        ) -> spectre::error::Result<RawOutput> {
+            Ok(RawOutput::Tran(HashMap::from([(
+                "time".to_string(),
+                vec![0., 1e-9],
+            )])))
+        }
+    }
+
+    let test_name = "spectre_simulate_batch_limits_concurrency";
+    let executor = PeakExecutor::default();
+    let peak = executor.peak.clone();
+    let ctx = Context::builder()
+        .install(Spectre::default())
+        .cache(Cache::new(MultiCache::builder().build()))
+        .executor(executor)
+        .build();
+    let lib = Arc::new(ctx.export_scir::<Spectre, _>(BatchTb).unwrap());
+
+    let mut configs = Vec::new();
+    for (i, dir) in ["sim0", "sim1", "fail", "sim3", "sim4", "sim5", "sim0"]
+        .into_iter()
+        .enumerate()
+    {
+        let sim_ctx = SimulationContext {
+            work_dir: get_path(test_name, dir),
+            lib: lib.clone(),
+            ctx: ctx.clone(),
+        };
+        let mut opts = Options::default();
+        opts.set_output_parser(StubParser);
+        // Distinct netlists keep each simulation from hitting the cache.
+        opts.set_temp(i.into());
+        let input = vec![Tran {
+            stop: dec!(1e-9),
+            ..Default::default()
+        }
+        .into()];
+        configs.push((sim_ctx, opts, input));
+    }
+
+    let results = Spectre::default().simulate_batch(configs, 2);
+
+    assert_eq!(results.len(), 7);
+    assert_eq!(peak.load(Ordering::SeqCst), 2);
+    for (i, result) in results.iter().enumerate() {
+        match i {
+            2 => assert!(matches!(result, Err(spectre::error::Error::Generator(_)))),
+            6 => assert!(matches!(
+                result,
+                Err(spectre::error::Error::DuplicateWorkDir(_))
+            )),
+            _ => assert_eq!(result.as_ref().unwrap().len(), 1),
+        }
+    }
+}
+
+#[test]
+fn spectre_parse_log_diagnostics() {
+    let log = r#"
+Warning from spectre during initial setup.
+    WARNING (SPECTRE-16707): Only tran supports psfxl format, result of other
+        analyses will be in psfbin format.
+
+ERROR (SFE-868): "netlist.scs" 12: Cannot open the input file 'models.scs'.
+Newton iteration did not converge at time = 1.2e-09 s.
+ERROR (SPECTRE-16080): No DC solution found (no convergence).
+
+Aggregate audit (5:05:14 PM, Mon Jan 1, 2024):
+"#;
+    let log_path = get_path("spectre_parse_log_diagnostics", "spectre.log");
+    std::fs::create_dir_all(log_path.parent().unwrap()).unwrap();
+    std::fs::write(&log_path, log).unwrap();
+
+    assert_eq!(
+        parse_log(&log_path),
+        vec![
+            SpectreDiagnostic {
+                severity: Severity::Warning,
+                code: Some(arcstr::literal!("SPECTRE-16707")),
+                message: "Only tran supports psfxl format, result of other\nanalyses will be in psfbin format.".to_string(),
+            },
+            SpectreDiagnostic {
+                severity: Severity::Error,
+                code: Some(arcstr::literal!("SFE-868")),
+                message: "\"netlist.scs\" 12: Cannot open the input file 'models.scs'.".to_string(),
+            },
+            SpectreDiagnostic {
+                severity: Severity::Convergence,
+                code: None,
+                message: "Newton iteration did not converge at time = 1.2e-09 s.".to_string(),
+            },
+            SpectreDiagnostic {
+                severity: Severity::Error,
+                code: Some(arcstr::literal!("SPECTRE-16080")),
+                message: "No DC solution found (no convergence).".to_string(),
+            },
+        ]
+    );
+    assert!(parse_log(&log_path.with_file_name("missing.log")).is_empty());
+}
+
+#[test]
+fn spectre_pwl_vsource_netlist() {
+    #[derive(Debug, Copy, Clone, Hash, Eq, PartialEq, Serialize, Deserialize, Block)]
+    #[substrate(io = "TestbenchIo")]
+    struct PwlTb;
+
+    impl ExportsNestedData for PwlTb {
+        type NestedData = ();
+    }
+
+    impl Schematic<Spectre> for PwlTb {
+        fn schematic(
+            &self,
+            io: &<<Self as Block>::Io as HardwareType>::Bundle,
+            cell: &mut CellBuilder<Spectre>,
+        ) -> substrate::error::Result<Self::NestedData> {
+            let vin = cell.signal("vin", Signal);
+            let source = cell.instantiate(
+                PwlVsource::new([
+                    (dec!(2e-9), dec!(0)),
+                    (dec!(0), dec!(0)),
+                    (dec!(1e-9), dec!(1.8)),
+                ])
+                .unwrap(),
+            );
+            cell.connect(source.io().p, vin);
+            cell.connect(source.io().n, io.vss);
+            Ok(())
+        }
+    }
+
+    let ctx = Context::builder().install(Spectre::default()).build();
+    let lib = ctx.export_scir::<Spectre, _>(PwlTb).unwrap();
+    let mut buf: Vec<u8> = Vec::new();
+    Spectre::default()
+        .write_scir_netlist(&lib.scir, &mut buf, Default::default())
+        .unwrap();
+    let netlist = String::from_utf8(buf).unwrap();
+
+    assert!(netlist.contains(&format!(
+        "vsource type=pwl wave=[{} {} {} {} {} {}]",
+        dec!(0),
+        dec!(0),
+        dec!(1e-9),
+        dec!(1.8),
+        dec!(2e-9),
+        dec!(0)
+    )));
+}
+
+#[test]
+fn spectre_pwl_vsource_rejects_duplicate_times() {
+    let points = [
+        (dec!(0), dec!(0)),
+        (dec!(1e-9), dec!(1.8)),
+        (dec!(1e-9), dec!(0)),
+    ];
+    assert!(matches!(
+        PwlVsource::new(points),
+        Err(spectre::error::Error::DuplicatePwlTime(t)) if t == dec!(1e-9)
+    ));
+
+    let json = serde_json::json!({ "points": points }).to_string();
+    assert!(serde_json::from_str::<PwlVsource>(&json).is_err());
+
+    let ctx = Context::builder().install(Spectre::default()).build();
+    let handle = ctx.generate_schematic::<Spectre, _>(Vsource::pwl(points.into_iter().collect()));
+    assert!(handle.try_cell().is_err());
+}
+
 #[test]
 fn spectre_can_include_sections() {
     #[derive(Default, Clone, Io)]
@@ -452,3 +696,1060 @@ fn spectre_rc_zin_ac() {
     assert_relative_eq!(z.re, -17.286407017773225);
     assert_relative_eq!(z.im, 130.3364383055986);
 }
+
+#[test]
+fn spectre_numeric_options() {
+    #[derive(Clone, Debug, Default)]
+    struct CountExecutor {
+        executor: LocalExecutor,
+        count: Arc<Mutex<u64>>,
+    }
+
+    impl Executor for CountExecutor {
+        fn execute(&self, command: Command, opts: ExecOpts) -> Result<(), substrate::error::Error> {
+            *self.count.lock().unwrap() += 1;
+            self.executor.execute(command, opts)
+        }
+    }
+
+    #[derive(Debug, Clone, Hash, Eq, PartialEq, Serialize, Deserialize, Block)]
+    #[substrate(io = "TestbenchIo")]
+    struct NumericOptionsTb(SpectreNumericOptions);
+
+    impl ExportsNestedData for NumericOptionsTb {
+        type NestedData = Instance<Resistor>;
+    }
+
+    impl Schematic<Spectre> for NumericOptionsTb {
+        fn schematic(
+            &self,
+            io: &<<Self as Block>::Io as HardwareType>::Bundle,
+            cell: &mut CellBuilder<Spectre>,
+        ) -> substrate::error::Result<Self::NestedData> {
+            let vdd = cell.signal("vdd", Signal);
+            let res = cell.instantiate(Resistor::new(1000));
+            cell.connect(res.io().p, vdd);
+            cell.connect(res.io().n, io.vss);
+
+            let vsource = cell.instantiate(Vsource::dc(dec!(1.8)));
+            cell.connect(vsource.io().p, vdd);
+            cell.connect(vsource.io().n, io.vss);
+
+            Ok(res)
+        }
+    }
+
+    impl SaveTb<Spectre, Tran, tran::Voltage> for NumericOptionsTb {
+        fn save_tb(
+            ctx: &SimulationContext<Spectre>,
+            cell: &Cell<Self>,
+            opts: &mut <Spectre as Simulator>::Options,
+        ) -> <tran::Voltage as FromSaved<Spectre, Tran>>::SavedKey {
+            tran::Voltage::save(ctx, cell.data().io().p, opts)
+        }
+    }
+
+    impl Testbench<Spectre> for NumericOptionsTb {
+        type Output = ();
+
+        fn run(&self, sim: SimController<Spectre, Self>) -> Self::Output {
+            let mut opts = Options::default();
+            opts.set_numeric(self.0.clone());
+            let _: tran::Voltage = sim
+                .simulate(
+                    opts,
+                    Tran {
+                        stop: dec!(1e-9),
+                        method: Some(IntegrationMethod::Gear2Only),
+                        ..Default::default()
+                    },
+                )
+                .expect("failed to run simulation");
+        }
+    }
+
+    let numeric = SpectreNumericOptions {
+        reltol: Some(dec!(1e-4)),
+        vabstol: Some(dec!(1e-7)),
+        iabstol: Some(dec!(1e-13)),
+    };
+    assert_eq!(
+        numeric.to_string(),
+        "reltol=0.0001 vabstol=0.0000001 iabstol=0.0000000000001"
+    );
+
+    let test_name = "spectre_numeric_options";
+    let sim_dir = get_path(test_name, "sim/");
+    let executor = CountExecutor::default();
+    let count = executor.count.clone();
+
+    let ctx = Context::builder()
+        .install(Spectre::default())
+        .cache(Cache::new(MultiCache::builder().build()))
+        .executor(executor)
+        .build();
+
+    ctx.simulate(NumericOptionsTb(numeric.clone()), &sim_dir)
+        .unwrap();
+    let netlist = std::fs::read_to_string(sim_dir.join("netlist.scs")).unwrap();
+    assert!(netlist
+        .contains("setnumeric1 options reltol=0.0001 vabstol=0.0000001 iabstol=0.0000000000001\n"));
+    // The integration method is a transient analysis parameter.
+    assert!(netlist.contains("tran stop=0.000000001 method=gear2only\n"));
+
+    ctx.simulate(NumericOptionsTb(numeric.clone()), &sim_dir)
+        .unwrap();
+    assert_eq!(*count.lock().unwrap(), 1);
+
+    // Changing numeric options changes the cache key.
+    ctx.simulate(
+        NumericOptionsTb(SpectreNumericOptions {
+            reltol: Some(dec!(1e-5)),
+            ..numeric
+        }),
+        &sim_dir,
+    )
+    .unwrap();
+    assert_eq!(*count.lock().unwrap(), 2);
+}
+
+#[test]
+fn spectre_log_options() {
+    #[derive(Debug, Clone, Hash, Eq, PartialEq, Serialize, Deserialize, Block)]
+    #[substrate(io = "TestbenchIo")]
+    struct LogOptionsTb(SpectreLogOptions);
+
+    impl ExportsNestedData for LogOptionsTb {
+        type NestedData = ();
+    }
+
+    impl Schematic<Spectre> for LogOptionsTb {
+        fn schematic(
+            &self,
+            io: &<<Self as Block>::Io as HardwareType>::Bundle,
+            cell: &mut CellBuilder<Spectre>,
+        ) -> substrate::error::Result<Self::NestedData> {
+            let vdd = cell.signal("vdd", Signal);
+            let res = cell.instantiate(Resistor::new(1000));
+            cell.connect(res.io().p, vdd);
+            cell.connect(res.io().n, io.vss);
+
+            let vsource = cell.instantiate(Vsource::dc(dec!(1.8)));
+            cell.connect(vsource.io().p, vdd);
+            cell.connect(vsource.io().n, io.vss);
+
+            Ok(())
+        }
+    }
+
+    impl SaveTb<Spectre, Tran, spectre::analysis::tran::Output> for LogOptionsTb {
+        fn save_tb(
+            _ctx: &SimulationContext<Spectre>,
+            _cell: &Cell<Self>,
+            _opts: &mut <Spectre as Simulator>::Options,
+        ) {
+        }
+    }
+
+    impl Testbench<Spectre> for LogOptionsTb {
+        type Output = ();
+
+        fn run(&self, sim: SimController<Spectre, Self>) -> Self::Output {
+            let mut opts = Options::default();
+            opts.set_log(self.0.clone());
+            let _: spectre::analysis::tran::Output = sim
+                .simulate(
+                    opts,
+                    Tran {
+                        stop: dec!(1e-9),
+                        ..Default::default()
+                    },
+                )
+                .expect("failed to run simulation");
+        }
+    }
+
+    let log = SpectreLogOptions {
+        maxnotes: Some(5),
+        maxwarns: Some(10),
+        ..SpectreLogOptions::quiet()
+    };
+    assert_eq!(log.to_string(), "maxnotes=5 maxwarns=10 narrate=no note=no");
+
+    let test_name = "spectre_log_options";
+    let sim_dir = get_path(test_name, "sim/");
+    let ctx = sky130_commercial_ctx();
+
+    ctx.simulate(LogOptionsTb(log), &sim_dir).unwrap();
+    let netlist = std::fs::read_to_string(sim_dir.join("netlist.scs")).unwrap();
+    assert!(netlist.contains("setlog1 options maxnotes=5 maxwarns=10 narrate=no note=no\n"));
+}
+
+#[test]
+fn spectre_netlist_annotations() {
+    #[derive(Debug, Copy, Clone, Hash, Eq, PartialEq, Serialize, Deserialize, Block)]
+    #[substrate(io = "TestbenchIo")]
+    struct AnnotatedTb(bool);
+
+    impl ExportsNestedData for AnnotatedTb {
+        type NestedData = ();
+    }
+
+    impl Schematic<Spectre> for AnnotatedTb {
+        fn schematic(
+            &self,
+            io: &<<Self as Block>::Io as HardwareType>::Bundle,
+            cell: &mut CellBuilder<Spectre>,
+        ) -> substrate::error::Result<Self::NestedData> {
+            let vdd = cell.signal("vdd", Signal);
+            let res = cell.instantiate(Resistor::new(1000));
+            cell.connect(res.io().p, vdd);
+            cell.connect(res.io().n, io.vss);
+
+            let vsource = cell.instantiate(Vsource::dc(dec!(1.8)));
+            cell.connect(vsource.io().p, vdd);
+            cell.connect(vsource.io().n, io.vss);
+
+            Ok(())
+        }
+    }
+
+    impl SaveTb<Spectre, Tran, spectre::analysis::tran::Output> for AnnotatedTb {
+        fn save_tb(
+            _ctx: &SimulationContext<Spectre>,
+            _cell: &Cell<Self>,
+            _opts: &mut <Spectre as Simulator>::Options,
+        ) {
+        }
+    }
+
+    impl Testbench<Spectre> for AnnotatedTb {
+        type Output = ();
+
+        fn run(&self, sim: SimController<Spectre, Self>) -> Self::Output {
+            let mut opts = Options::default();
+            opts.annotate(self.0);
+            let _: spectre::analysis::tran::Output = sim
+                .simulate(
+                    opts,
+                    Tran {
+                        stop: dec!(1e-9),
+                        ..Default::default()
+                    },
+                )
+                .expect("failed to run simulation");
+        }
+    }
+
+    let test_name = "spectre_netlist_annotations";
+    let ctx = sky130_commercial_ctx();
+
+    let sim_dir = get_path(test_name, "sim_annotated/");
+    ctx.simulate(AnnotatedTb(true), &sim_dir).unwrap();
+    let netlist = std::fs::read_to_string(sim_dir.join("netlist.scs")).unwrap();
+    assert!(netlist
+        .lines()
+        .any(|line| line.starts_with("// cell") && line.contains(": ")));
+    assert!(netlist.contains("// primitive"));
+
+    let sim_dir = get_path(test_name, "sim/");
+    ctx.simulate(AnnotatedTb(false), &sim_dir).unwrap();
+    let netlist = std::fs::read_to_string(sim_dir.join("netlist.scs")).unwrap();
+    assert!(!netlist.contains("// cell"));
+    assert!(!netlist.contains("// primitive"));
+}
+
+#[test]
+fn spectre_ics_from_final_state() {
+    #[derive(Debug, Copy, Clone, Hash, Eq, PartialEq, Serialize, Deserialize, Block)]
+    #[substrate(io = "TestbenchIo")]
+    struct RcDischargeTb;
+
+    impl ExportsNestedData for RcDischargeTb {
+        type NestedData = Node;
+    }
+
+    impl Schematic<Spectre> for RcDischargeTb {
+        fn schematic(
+            &self,
+            io: &<<Self as Block>::Io as HardwareType>::Bundle,
+            cell: &mut CellBuilder<Spectre>,
+        ) -> substrate::error::Result<Self::NestedData> {
+            let vout = cell.signal("vout", Signal);
+
+            let r = cell.instantiate(Resistor::new(dec!(1000)));
+            cell.connect(r.io().p, vout);
+            cell.connect(r.io().n, io.vss);
+
+            let c = cell.instantiate(Capacitor::new(dec!(1e-9)));
+            cell.connect(c.io().p, vout);
+            cell.connect(c.io().n, io.vss);
+
+            Ok(vout)
+        }
+    }
+
+    impl SaveTb<Spectre, Tran, tran::Voltage> for RcDischargeTb {
+        fn save_tb(
+            ctx: &SimulationContext<Spectre>,
+            cell: &Cell<Self>,
+            opts: &mut <Spectre as Simulator>::Options,
+        ) -> <tran::Voltage as FromSaved<Spectre, Tran>>::SavedKey {
+            tran::Voltage::save(ctx, cell.data(), opts)
+        }
+    }
+
+    impl SaveTb<Spectre, Tran, spectre::analysis::tran::Output> for RcDischargeTb {
+        fn save_tb(
+            ctx: &SimulationContext<Spectre>,
+            cell: &Cell<Self>,
+            opts: &mut <Spectre as Simulator>::Options,
+        ) {
+            tran::Voltage::save(ctx, cell.data(), opts);
+        }
+    }
+
+    impl Testbench<Spectre> for RcDischargeTb {
+        type Output = (f64, usize, f64);
+
+        fn run(&self, sim: SimController<Spectre, Self>) -> Self::Output {
+            let tran = Tran {
+                stop: dec!(1e-6),
+                ..Default::default()
+            };
+
+            let mut opts = Options::default();
+            sim.set_option(
+                InitialCondition {
+                    path: sim.tb.data(),
+                    value: ic::Voltage(dec!(1.8)),
+                },
+                &mut opts,
+            );
+            let first: spectre::analysis::tran::Output = sim.simulate(opts, tran.clone()).unwrap();
+            let vout = first.raw_values.values().next().unwrap();
+            let state = first.final_state();
+
+            let mut opts = Options::default();
+            opts.set_ics_from(&state);
+            let second: tran::Voltage = sim.simulate(opts, tran).unwrap();
+
+            (*vout.last().unwrap(), state.len(), *second.first().unwrap())
+        }
+    }
+
+    let test_name = "spectre_ics_from_final_state";
+    let sim_dir = get_path(test_name, "sim/");
+    let ctx = Context::builder().install(Spectre::default()).build();
+
+    let (end, num_ics, start) = ctx.simulate(RcDischargeTb, sim_dir).unwrap();
+    assert_eq!(num_ics, 1);
+    assert!(end < 1.8 && end > 0.);
+    assert_relative_eq!(start, end, max_relative = 1e-6);
+}
+
+#[test]
+fn spectre_sp_matched_resistor() {
+    #[derive(Debug, Copy, Clone, Hash, Eq, PartialEq, Serialize, Deserialize, Block)]
+    #[substrate(io = "TestbenchIo")]
+    struct MatchedResistorTb;
+
+    impl ExportsNestedData for MatchedResistorTb {
+        type NestedData = ();
+    }
+
+    impl Schematic<Spectre> for MatchedResistorTb {
+        fn schematic(
+            &self,
+            io: &<<Self as Block>::Io as HardwareType>::Bundle,
+            cell: &mut CellBuilder<Spectre>,
+        ) -> substrate::error::Result<Self::NestedData> {
+            let vin = cell.signal("vin", Signal);
+
+            let port = cell.instantiate(Port::new(1, dec!(50)));
+            cell.connect(port.io().p, vin);
+            cell.connect(port.io().n, io.vss);
+
+            let r = cell.instantiate(Resistor::new(dec!(50)));
+            cell.connect(r.io().p, vin);
+            cell.connect(r.io().n, io.vss);
+
+            Ok(())
+        }
+    }
+
+    impl SaveTb<Spectre, Sp, spectre::analysis::sp::Output> for MatchedResistorTb {
+        fn save_tb(
+            _ctx: &SimulationContext<Spectre>,
+            _cell: &Cell<Self>,
+            _opts: &mut <Spectre as Simulator>::Options,
+        ) {
+        }
+    }
+
+    impl Testbench<Spectre> for MatchedResistorTb {
+        type Output = (usize, Vec<f64>, Vec<f64>);
+
+        fn run(&self, sim: SimController<Spectre, Self>) -> Self::Output {
+            let output: spectre::analysis::sp::Output = sim
+                .simulate(
+                    Options::default(),
+                    Sp {
+                        start: dec!(1e6),
+                        stop: dec!(1e9),
+                        sweep: Sweep::Decade(5),
+                        ports: Vec::new(),
+                        errpreset: None,
+                    },
+                )
+                .unwrap();
+            (
+                output.num_ports(),
+                (*output.freq).clone(),
+                output.s(1, 1).iter().map(|s11| s11.norm()).collect(),
+            )
+        }
+    }
+
+    let test_name = "spectre_sp_matched_resistor";
+    let sim_dir = get_path(test_name, "sim/");
+    let ctx = Context::builder().install(Spectre::default()).build();
+
+    let (num_ports, freq, s11) = ctx.simulate(MatchedResistorTb, sim_dir).unwrap();
+    assert_eq!(num_ports, 1);
+    assert!(!freq.is_empty());
+    for s11 in s11 {
+        assert!(s11 < 1e-6);
+    }
+}
+
+#[test]
+fn spectre_invalid_save_path_returns_error() {
+    #[derive(Debug, Copy, Clone, Hash, Eq, PartialEq, Serialize, Deserialize, Block)]
+    #[substrate(io = "TestbenchIo")]
+    struct InvalidSaveTb;
+
+    impl ExportsNestedData for InvalidSaveTb {
+        type NestedData = ();
+    }
+
+    impl Schematic<Spectre> for InvalidSaveTb {
+        fn schematic(
+            &self,
+            io: &<<Self as Block>::Io as HardwareType>::Bundle,
+            cell: &mut CellBuilder<Spectre>,
+        ) -> substrate::error::Result<Self::NestedData> {
+            let r = cell.instantiate(Resistor::new(dec!(1000)));
+            cell.connect(r.io().p, io.vss);
+            cell.connect(r.io().n, io.vss);
+            Ok(())
+        }
+    }
+
+    impl SaveTb<Spectre, Tran, tran::Voltage> for InvalidSaveTb {
+        fn save_tb(
+            ctx: &SimulationContext<Spectre>,
+            _cell: &Cell<Self>,
+            opts: &mut <Spectre as Simulator>::Options,
+        ) -> <tran::Voltage as FromSaved<Spectre, Tran>>::SavedKey {
+            let top = ctx.lib.scir.top_cell().unwrap();
+            let path = SliceOnePath::new(InstancePath::new(top), NamedSliceOne::new("nonexistent"));
+            tran::Voltage::save(ctx, &path, opts)
+        }
+    }
+
+    impl Testbench<Spectre> for InvalidSaveTb {
+        type Output = Option<SliceOnePath>;
+
+        fn run(&self, sim: SimController<Spectre, Self>) -> Self::Output {
+            let tran = Tran {
+                stop: dec!(1e-9),
+                ..Default::default()
+            };
+            match sim.simulate::<_, tran::Voltage>(Options::default(), tran) {
+                Err(spectre::error::Error::InvalidSavePath { path }) => Some(path),
+                _ => None,
+            }
+        }
+    }
+
+    let test_name = "spectre_invalid_save_path_returns_error";
+    let sim_dir = get_path(test_name, "sim/");
+    let ctx = Context::builder().install(Spectre::default()).build();
+
+    let path = ctx.simulate(InvalidSaveTb, sim_dir).unwrap().unwrap();
+    assert_eq!(path.tail().clone().unwrap_name().signal(), "nonexistent");
+}
+
+#[test]
+fn spectre_custom_output_parser() {
+    #[derive(Debug, Copy, Clone, Hash, Eq, PartialEq, Serialize, Deserialize, Block)]
+    #[substrate(io = "TestbenchIo")]
+    struct StubParserTb;
+
+    impl ExportsNestedData for StubParserTb {
+        type NestedData = ();
+    }
+
+    impl Schematic<Spectre> for StubParserTb {
+        fn schematic(
+            &self,
+            io: &<<Self as Block>::Io as HardwareType>::Bundle,
+            cell: &mut CellBuilder<Spectre>,
+        ) -> substrate::error::Result<Self::NestedData> {
+            let r = cell.instantiate(Resistor::new(dec!(1000)));
+            cell.connect(r.io().p, io.vss);
+            cell.connect(r.io().n, io.vss);
+            Ok(())
+        }
+    }
+
+    impl SaveTb<Spectre, Tran, spectre::analysis::tran::Output> for StubParserTb {
+        fn save_tb(
+            _ctx: &SimulationContext<Spectre>,
+            _cell: &Cell<Self>,
+            _opts: &mut <Spectre as Simulator>::Options,
+        ) {
+        }
+    }
+
+    #[derive(Debug)]
+    struct StubParser;
+
+    impl OutputParser for StubParser {
+        fn parse(
+            &self,
+            _output_dir: &Path,
+            _name: &str,
+            analysis: &spectre::Input,
+        ) -> spectre::error::Result<RawOutput> {
+            assert!(matches!(analysis, spectre::Input::Tran(_)));
+            Ok(RawOutput::Tran(HashMap::from([
+                ("time".to_string(), vec![0., 1e-9]),
+                ("stub".to_string(), vec![1., 2.]),
+            ])))
+        }
+    }
+
+    impl Testbench<Spectre> for StubParserTb {
+        type Output = (Vec<f64>, Vec<f64>);
+
+        fn run(&self, sim: SimController<Spectre, Self>) -> Self::Output {
+            let mut opts = Options::default();
+            opts.set_output_parser(StubParser);
+            let output: spectre::analysis::tran::Output = sim
+                .simulate(
+                    opts,
+                    Tran {
+                        stop: dec!(1e-9),
+                        ..Default::default()
+                    },
+                )
+                .unwrap();
+            (
+                (*output.time).clone(),
+                (*output.raw_values[&arcstr::literal!("stub")]).clone(),
+            )
+        }
+    }
+
+    /// Skips running Spectre so that only the stub parser produces output.
+    struct NoopExecutor;
+
+    impl Executor for NoopExecutor {
+        fn execute(
+            &self,
+            _command: Command,
+            _opts: ExecOpts,
+        ) -> Result<(), substrate::error::Error> {
+            Ok(())
+        }
+    }
+
+    let test_name = "spectre_custom_output_parser";
+    let sim_dir = get_path(test_name, "sim/");
+    let ctx = Context::builder()
+        .install(Spectre::default())
+        .executor(NoopExecutor)
+        .build();
+
+    let (time, stub) = ctx.simulate(StubParserTb, sim_dir).unwrap();
+    assert_eq!(time, vec![0., 1e-9]);
+    assert_eq!(stub, vec![1., 2.]);
+}
+
+#[test]
+fn spectre_noise_netlist() {
+    #[derive(Debug, Clone, Hash, Eq, PartialEq, Serialize, Deserialize, Block)]
+    #[substrate(io = "TestbenchIo")]
+    struct NoiseTb(Sweep);
+
+    impl ExportsNestedData for NoiseTb {
+        type NestedData = ();
+    }
+
+    impl Schematic<Spectre> for NoiseTb {
+        fn schematic(
+            &self,
+            io: &<<Self as Block>::Io as HardwareType>::Bundle,
+            cell: &mut CellBuilder<Spectre>,
+        ) -> substrate::error::Result<Self::NestedData> {
+            let out = cell.signal("out", Signal);
+            let r = cell.instantiate(Resistor::new(dec!(1000)));
+            cell.connect(r.io().p, out);
+            cell.connect(r.io().n, io.vss);
+            Ok(())
+        }
+    }
+
+    #[derive(Debug)]
+    struct StubParser;
+
+    impl OutputParser for StubParser {
+        fn parse(
+            &self,
+            _output_dir: &Path,
+            _name: &str,
+            analysis: &spectre::Input,
+        ) -> spectre::error::Result<RawOutput> {
+            assert!(matches!(analysis, spectre::Input::Noise(_)));
+            Ok(RawOutput::Noise {
+                freq: vec![1e3, 1e6],
+                signals: HashMap::from([("out".to_string(), vec![4e-9, 4e-9])]),
+            })
+        }
+    }
+
+    impl Testbench<Spectre> for NoiseTb {
+        type Output = (Vec<f64>, Vec<f64>, bool);
+
+        fn run(&self, sim: SimController<Spectre, Self>) -> Self::Output {
+            let mut opts = Options::default();
+            opts.set_output_parser(StubParser);
+            let output = sim
+                .simulate_default(
+                    opts,
+                    Noise {
+                        output_p: arcstr::literal!("out"),
+                        output_n: None,
+                        start: dec!(1e3),
+                        stop: dec!(1e6),
+                        sweep: self.0.clone(),
+                        reference: None,
+                    },
+                )
+                .unwrap();
+            (
+                (*output.freq).clone(),
+                (*output.output_noise).clone(),
+                output.input_noise.is_some(),
+            )
+        }
+    }
+
+    struct NoopExecutor;
+
+    impl Executor for NoopExecutor {
+        fn execute(
+            &self,
+            _command: Command,
+            _opts: ExecOpts,
+        ) -> Result<(), substrate::error::Error> {
+            Ok(())
+        }
+    }
+
+    let ctx = Context::builder()
+        .install(Spectre::default())
+        .executor(NoopExecutor)
+        .build();
+
+    for (sweep, expected) in [
+        (Sweep::Linear(10), "lin=10"),
+        (Sweep::Logarithmic(20), "log=20"),
+        (Sweep::Decade(5), "dec=5"),
+    ] {
+        let sim_dir = get_path("spectre_noise_netlist", &format!("sim_{expected}/"));
+        let (freq, output_noise, has_input_noise) = ctx.simulate(NoiseTb(sweep), &sim_dir).unwrap();
+        let netlist = std::fs::read_to_string(sim_dir.join("netlist.scs")).unwrap();
+        assert!(netlist.contains(&format!(
+            "(out 0) noise start={} stop={} {expected}",
+            dec!(1e3),
+            dec!(1e6)
+        )));
+        assert_eq!(freq, vec![1e3, 1e6]);
+        assert_eq!(output_noise, vec![4e-9, 4e-9]);
+        assert!(!has_input_noise);
+    }
+}
+
+#[test]
+fn spectre_tran_measurements() {
+    #[derive(Debug, Copy, Clone, Hash, Eq, PartialEq, Serialize, Deserialize, Block)]
+    #[substrate(io = "TestbenchIo")]
+    struct MeasureTb;
+
+    impl ExportsNestedData for MeasureTb {
+        type NestedData = ();
+    }
+
+    impl Schematic<Spectre> for MeasureTb {
+        fn schematic(
+            &self,
+            io: &<<Self as Block>::Io as HardwareType>::Bundle,
+            cell: &mut CellBuilder<Spectre>,
+        ) -> substrate::error::Result<Self::NestedData> {
+            let r = cell.instantiate(Resistor::new(dec!(1000)));
+            cell.connect(r.io().p, io.vss);
+            cell.connect(r.io().n, io.vss);
+            Ok(())
+        }
+    }
+
+    /// Returns a ramp from 0 to 1 over the first half of the time points
+    /// and one period of a sine wave with amplitude 1.
+    #[derive(Debug)]
+    struct StubParser;
+
+    impl OutputParser for StubParser {
+        fn parse(
+            &self,
+            _output_dir: &Path,
+            _name: &str,
+            _analysis: &spectre::Input,
+        ) -> spectre::error::Result<RawOutput> {
+            let time: Vec<f64> = (0..=100).map(|i| i as f64 * 1e-11).collect();
+            let ramp = (0..=100).map(|i| (i as f64 / 50.).min(1.)).collect();
+            let sine = time
+                .iter()
+                .map(|t| (2. * std::f64::consts::PI * t / 1e-9).sin())
+                .collect();
+            Ok(RawOutput::Tran(HashMap::from([
+                ("time".to_string(), time),
+                ("ramp".to_string(), ramp),
+                ("sine".to_string(), sine),
+            ])))
+        }
+    }
+
+    #[derive(Debug, Clone, Serialize, Deserialize)]
+    struct Measurements {
+        ramp_rise: Option<f64>,
+        ramp_fall: Option<f64>,
+        ramp_max: Option<(f64, f64)>,
+        sine_fall: Option<f64>,
+        sine_max: Option<(f64, f64)>,
+        delay: Option<f64>,
+    }
+
+    impl Testbench<Spectre> for MeasureTb {
+        type Output = Measurements;
+
+        fn run(&self, sim: SimController<Spectre, Self>) -> Self::Output {
+            let mut opts = Options::default();
+            opts.set_output_parser(StubParser);
+            let ramp = opts.save_tran_voltage("ramp");
+            let sine = opts.save_tran_voltage("sine");
+            let output = sim
+                .simulate_default(
+                    opts,
+                    Tran {
+                        stop: dec!(1e-9),
+                        ..Default::default()
+                    },
+                )
+                .unwrap();
+            Measurements {
+                ramp_rise: output.cross(&ramp, 0.5, Edge::Rising),
+                ramp_fall: output.cross(&ramp, 0.5, Edge::Falling),
+                ramp_max: output.max(&ramp),
+                sine_fall: output.cross(&sine, 0., Edge::Falling),
+                sine_max: output.max(&sine),
+                delay: output.delay(&ramp, 0.5, &sine, -0.5),
+            }
+        }
+    }
+
+    struct NoopExecutor;
+
+    impl Executor for NoopExecutor {
+        fn execute(
+            &self,
+            _command: Command,
+            _opts: ExecOpts,
+        ) -> Result<(), substrate::error::Error> {
+            Ok(())
+        }
+    }
+
+    let sim_dir = get_path("spectre_tran_measurements", "sim/");
+    let ctx = Context::builder()
+        .install(Spectre::default())
+        .executor(NoopExecutor)
+        .build();
+    let m = ctx.simulate(MeasureTb, sim_dir).unwrap();
+
+    assert_relative_eq!(m.ramp_rise.unwrap(), 2.5e-10, max_relative = 1e-9);
+    assert!(m.ramp_fall.is_none());
+    let (t, v) = m.ramp_max.unwrap();
+    assert_relative_eq!(t, 5e-10, max_relative = 1e-9);
+    assert_relative_eq!(v, 1.);
+
+    assert_relative_eq!(m.sine_fall.unwrap(), 5e-10, max_relative = 1e-3);
+    let (t, v) = m.sine_max.unwrap();
+    assert_relative_eq!(t, 2.5e-10, max_relative = 1e-9);
+    assert_relative_eq!(v, 1., max_relative = 1e-9);
+
+    // The sine first falls through -0.5 at 7/12 of its period.
+    assert_relative_eq!(
+        m.delay.unwrap(),
+        7. / 12. * 1e-9 - 2.5e-10,
+        max_relative = 1e-2
+    );
+}
+
+#[test]
+fn spectre_strobed_tran() {
+    #[derive(Debug, Copy, Clone, Hash, Eq, PartialEq, Serialize, Deserialize, Block)]
+    #[substrate(io = "TestbenchIo")]
+    struct StrobedTranTb;
+
+    impl ExportsNestedData for StrobedTranTb {
+        type NestedData = ();
+    }
+
+    impl Schematic<Spectre> for StrobedTranTb {
+        fn schematic(
+            &self,
+            io: &<<Self as Block>::Io as HardwareType>::Bundle,
+            cell: &mut CellBuilder<Spectre>,
+        ) -> substrate::error::Result<Self::NestedData> {
+            let vdd = cell.signal("vdd", Signal);
+            let res = cell.instantiate(Resistor::new(1000));
+            cell.connect(res.io().p, vdd);
+            cell.connect(res.io().n, io.vss);
+
+            let vsource = cell.instantiate(Vsource::dc(dec!(1.8)));
+            cell.connect(vsource.io().p, vdd);
+            cell.connect(vsource.io().n, io.vss);
+
+            Ok(())
+        }
+    }
+
+    impl SaveTb<Spectre, Tran, spectre::analysis::tran::Output> for StrobedTranTb {
+        fn save_tb(
+            _ctx: &SimulationContext<Spectre>,
+            _cell: &Cell<Self>,
+            _opts: &mut <Spectre as Simulator>::Options,
+        ) {
+        }
+    }
+
+    impl Testbench<Spectre> for StrobedTranTb {
+        type Output = Vec<f64>;
+
+        fn run(&self, sim: SimController<Spectre, Self>) -> Self::Output {
+            let output: spectre::analysis::tran::Output = sim
+                .simulate(
+                    Options::default(),
+                    Tran {
+                        stop: dec!(1e-9),
+                        maxstep: Some(dec!(1e-12)),
+                        strobeperiod: Some(dec!(1e-11)),
+                        strobedelay: Some(dec!(0)),
+                        ..Default::default()
+                    },
+                )
+                .expect("failed to run simulation");
+            (*output.time).clone()
+        }
+    }
+
+    let test_name = "spectre_strobed_tran";
+    let sim_dir = get_path(test_name, "sim/");
+    let ctx = Context::builder().install(Spectre::default()).build();
+
+    let time = ctx.simulate(StrobedTranTb, &sim_dir).unwrap();
+    let netlist = std::fs::read_to_string(sim_dir.join("netlist.scs")).unwrap();
+    assert!(netlist.contains(&format!(
+        "tran stop={} maxstep={} strobeperiod={} strobedelay={}",
+        dec!(1e-9),
+        dec!(1e-12),
+        dec!(1e-11),
+        dec!(0)
+    )));
+
+    assert!(time.len() > 2);
+    for step in time.windows(2) {
+        assert_relative_eq!(step[1] - step[0], 1e-11, max_relative = 1e-6);
+    }
+}
+
+#[test]
+fn spectre_per_analysis_save() {
+    #[derive(Debug, Copy, Clone, Hash, Eq, PartialEq, Serialize, Deserialize, Block)]
+    #[substrate(io = "TestbenchIo")]
+    struct PerAnalysisSaveTb;
+
+    impl ExportsNestedData for PerAnalysisSaveTb {
+        type NestedData = ();
+    }
+
+    impl Schematic<Spectre> for PerAnalysisSaveTb {
+        fn schematic(
+            &self,
+            io: &<<Self as Block>::Io as HardwareType>::Bundle,
+            cell: &mut CellBuilder<Spectre>,
+        ) -> substrate::error::Result<Self::NestedData> {
+            let r = cell.instantiate(Resistor::new(dec!(1000)));
+            cell.connect(r.io().p, io.vss);
+            cell.connect(r.io().n, io.vss);
+            Ok(())
+        }
+    }
+
+    #[derive(Debug)]
+    struct StubParser;
+
+    impl OutputParser for StubParser {
+        fn parse(
+            &self,
+            _output_dir: &Path,
+            _name: &str,
+            _analysis: &spectre::Input,
+        ) -> spectre::error::Result<RawOutput> {
+            Ok(RawOutput::Tran(HashMap::from([(
+                "time".to_string(),
+                vec![0., 1e-9],
+            )])))
+        }
+    }
+
+    impl Testbench<Spectre> for PerAnalysisSaveTb {
+        type Output = ();
+
+        fn run(&self, sim: SimController<Spectre, Self>) -> Self::Output {
+            let mut opts = Options::default();
+            opts.set_output_parser(StubParser);
+            opts.save(SaveOption::None);
+            sim.simulate_default(
+                opts,
+                (
+                    Tran {
+                        stop: dec!(1e-9),
+                        save: Some(SaveOption::AllPub),
+                        ..Default::default()
+                    },
+                    Tran {
+                        stop: dec!(2e-9),
+                        save: Some(SaveOption::Selected),
+                        ..Default::default()
+                    },
+                ),
+            )
+            .unwrap();
+        }
+    }
+
+    struct NoopExecutor;
+
+    impl Executor for NoopExecutor {
+        fn execute(
+            &self,
+            _command: Command,
+            _opts: ExecOpts,
+        ) -> Result<(), substrate::error::Error> {
+            Ok(())
+        }
+    }
+
+    let sim_dir = get_path("spectre_per_analysis_save", "sim/");
+    let ctx = Context::builder()
+        .install(Spectre::default())
+        .executor(NoopExecutor)
+        .build();
+    ctx.simulate(PerAnalysisSaveTb, &sim_dir).unwrap();
+
+    let netlist = std::fs::read_to_string(sim_dir.join("netlist.scs")).unwrap();
+    assert!(netlist.contains("setsave1 options save=none"));
+    assert!(netlist.contains(&format!("analysis_0 tran stop={} save=allpub", dec!(1e-9))));
+    assert!(netlist.contains(&format!(
+        "analysis_1 tran stop={} save=selected",
+        dec!(2e-9)
+    )));
+}
+
+#[test]
+fn spectre_alias_save() {
+    #[derive(Debug, Copy, Clone, Hash, Eq, PartialEq, Serialize, Deserialize, Block)]
+    #[substrate(io = "TestbenchIo")]
+    struct AliasSaveTb;
+
+    impl ExportsNestedData for AliasSaveTb {
+        type NestedData = ();
+    }
+
+    impl Schematic<Spectre> for AliasSaveTb {
+        fn schematic(
+            &self,
+            io: &<<Self as Block>::Io as HardwareType>::Bundle,
+            cell: &mut CellBuilder<Spectre>,
+        ) -> substrate::error::Result<Self::NestedData> {
+            let vdd = cell.signal("vdd", Signal);
+            let res = cell.instantiate(Resistor::new(1000));
+            cell.connect(res.io().p, vdd);
+            cell.connect(res.io().n, io.vss);
+
+            let vsource = cell.instantiate(Vsource::dc(dec!(1.8)));
+            cell.connect(vsource.io().p, vdd);
+            cell.connect(vsource.io().n, io.vss);
+
+            Ok(())
+        }
+    }
+
+    impl SaveTb<Spectre, Tran, spectre::analysis::tran::Output> for AliasSaveTb {
+        fn save_tb(
+            _ctx: &SimulationContext<Spectre>,
+            _cell: &Cell<Self>,
+            opts: &mut <Spectre as Simulator>::Options,
+        ) {
+            opts.alias_save(SimSignal::Raw(arcstr::literal!("vdd")), "supply");
+        }
+    }
+
+    impl Testbench<Spectre> for AliasSaveTb {
+        type Output = (Vec<f64>, Vec<f64>, bool);
+
+        fn run(&self, sim: SimController<Spectre, Self>) -> Self::Output {
+            let output: spectre::analysis::tran::Output = sim
+                .simulate(
+                    Options::default(),
+                    Tran {
+                        stop: dec!(1e-9),
+                        ..Default::default()
+                    },
+                )
+                .expect("failed to run simulation");
+            (
+                (*output.raw_values[&arcstr::literal!("supply")]).clone(),
+                (*output.raw_values[&arcstr::literal!("vdd")]).clone(),
+                output
+                    .final_state()
+                    .contains_key(&SimSignal::Raw(arcstr::literal!("supply"))),
+            )
+        }
+    }
+
+    let test_name = "spectre_alias_save";
+    let sim_dir = get_path(test_name, "sim/");
+    let ctx = Context::builder().install(Spectre::default()).build();
+
+    let (supply, vdd, supply_in_final_state) = ctx.simulate(AliasSaveTb, sim_dir).unwrap();
+    assert_eq!(supply, vdd);
+    for value in supply.iter() {
+        assert_relative_eq!(*value, 1.8);
+    }
+    assert!(!supply_in_final_state);
+}
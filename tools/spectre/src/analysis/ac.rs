@@ -1,6 +1,6 @@
 //! Spectre AC small-signal analysis options and data structures.
 
-use crate::{ErrPreset, SimSignal, Spectre};
+use crate::{ErrPreset, SaveOption, SimSignal, Spectre};
 use arcstr::ArcStr;
 use num::complex::Complex64;
 use num::Zero;
@@ -16,7 +16,7 @@ use substrate::simulation::{Analysis, SimulationContext, Simulator, SupportedBy}
 use substrate::type_dispatch::impl_dispatch;
 
 /// Sweep kinds.
-#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+#[derive(Clone, Debug, Eq, PartialEq, Hash, Serialize, Deserialize)]
 pub enum Sweep {
     /// Linear sweep with the given number of points.
     Linear(usize),
@@ -40,6 +40,11 @@ pub struct Ac {
 
     /// The error preset.
     pub errpreset: Option<ErrPreset>,
+
+    /// The signals to save in this analysis.
+    ///
+    /// Defaults to the simulation-wide [`Options::save`](crate::Options::save) setting.
+    pub save: Option<SaveOption>,
 }
 
 /// The result of an AC analysis.
@@ -1,4 +1,6 @@
 //! Spectre analyses.
 pub mod ac;
 pub mod montecarlo;
+pub mod noise;
+pub mod sp;
 pub mod tran;
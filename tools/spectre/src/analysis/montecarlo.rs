@@ -66,6 +66,55 @@ impl<T> Output<T> {
     pub fn into_inner(self) -> Vec<T> {
         self.0
     }
+
+    /// Computes statistics of a scalar measurement across all Monte Carlo iterations.
+    ///
+    /// `measure` extracts the measurement from the output of a single iteration.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the simulation produced no iterations.
+    pub fn statistics(&self, measure: impl Fn(&T) -> f64) -> MeasStats {
+        MeasStats::new(self.0.iter().map(measure))
+    }
+}
+
+/// Statistics of a scalar measurement across Monte Carlo iterations.
+#[derive(Copy, Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct MeasStats {
+    /// The mean.
+    pub mean: f64,
+    /// The sample standard deviation.
+    ///
+    /// Zero if there is only one iteration.
+    pub std: f64,
+    /// The minimum value.
+    pub min: f64,
+    /// The maximum value.
+    pub max: f64,
+}
+
+impl MeasStats {
+    fn new(values: impl IntoIterator<Item = f64>) -> Self {
+        let values: Vec<f64> = values.into_iter().collect();
+        assert!(
+            !values.is_empty(),
+            "cannot compute statistics of an empty Monte Carlo output"
+        );
+        let n = values.len() as f64;
+        let mean = values.iter().sum::<f64>() / n;
+        let std = if values.len() > 1 {
+            (values.iter().map(|v| (v - mean).powi(2)).sum::<f64>() / (n - 1.)).sqrt()
+        } else {
+            0.
+        };
+        Self {
+            mean,
+            std,
+            min: values.iter().copied().fold(f64::INFINITY, f64::min),
+            max: values.iter().copied().fold(f64::NEG_INFINITY, f64::max),
+        }
+    }
 }
 
 impl<A: SupportedBy<Spectre>> From<MonteCarlo<A>> for MonteCarlo<Vec<Input>> {
@@ -131,3 +180,38 @@ impl<A: SupportedBy<Spectre>> SupportedBy<Spectre> for MonteCarlo<A> {
         )
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn meas_stats_of_known_values() {
+        let output = Output(vec![2., 4., 4., 4., 5., 5., 7., 9.]);
+        let stats = output.statistics(|v| *v);
+        assert_eq!(stats.mean, 5.);
+        assert!((stats.std - (32f64 / 7.).sqrt()).abs() < 1e-12);
+        assert_eq!(stats.min, 2.);
+        assert_eq!(stats.max, 9.);
+    }
+
+    #[test]
+    fn meas_stats_of_single_iteration() {
+        let stats = Output(vec![(1, -3.5)]).statistics(|(_, v)| *v);
+        assert_eq!(
+            stats,
+            MeasStats {
+                mean: -3.5,
+                std: 0.,
+                min: -3.5,
+                max: -3.5,
+            }
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "empty Monte Carlo output")]
+    fn meas_stats_of_no_iterations() {
+        Output(Vec::<f64>::new()).statistics(|v| *v);
+    }
+}
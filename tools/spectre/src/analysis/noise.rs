@@ -0,0 +1,100 @@
+//! Spectre noise analysis options and data structures.
+
+use crate::analysis::ac::Sweep;
+use crate::Spectre;
+use arcstr::ArcStr;
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::Arc;
+use substrate::simulation::data::{FromSaved, Save};
+use substrate::simulation::{Analysis, SimulationContext, Simulator, SupportedBy};
+
+/// A small-signal noise analysis.
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+pub struct Noise {
+    /// The raw name of the positive output node.
+    pub output_p: ArcStr,
+    /// The raw name of the negative output node.
+    ///
+    /// Defaults to ground if not specified.
+    pub output_n: Option<ArcStr>,
+    /// Start frequency (Hz).
+    pub start: Decimal,
+    /// Stop frequency (Hz).
+    pub stop: Decimal,
+    /// The sweep kind and number of points.
+    pub sweep: Sweep,
+    /// The raw name of the source or probe to which input-referred noise is referenced.
+    ///
+    /// If not specified, only output-referred noise is computed.
+    pub reference: Option<ArcStr>,
+}
+
+/// The result of a noise analysis.
+#[derive(Debug, Clone)]
+pub struct Output {
+    /// The frequency points of the noise simulation.
+    pub freq: Arc<Vec<f64>>,
+    /// The output-referred noise spectral density (V/sqrt(Hz)) at each frequency point.
+    pub output_noise: Arc<Vec<f64>>,
+    /// The input-referred noise spectral density at each frequency point.
+    ///
+    /// Only produced if a [`Noise::reference`] is specified.
+    pub input_noise: Option<Arc<Vec<f64>>>,
+    /// A map from signal name to values, including per-device noise contributions.
+    pub raw_values: HashMap<ArcStr, Arc<Vec<f64>>>,
+}
+
+impl Output {
+    /// Builds a noise output from raw PSF signals, where `out` and `in` hold the
+    /// output- and input-referred noise, respectively.
+    pub(crate) fn from_signals(freq: Vec<f64>, signals: HashMap<String, Vec<f64>>) -> Self {
+        let raw_values: HashMap<_, _> = signals
+            .into_iter()
+            .map(|(k, v)| (ArcStr::from(k), Arc::new(v)))
+            .collect();
+        Self {
+            output_noise: raw_values
+                .get("out")
+                .cloned()
+                .unwrap_or_else(|| Arc::new(Vec::new())),
+            input_noise: raw_values.get("in").cloned(),
+            freq: Arc::new(freq),
+            raw_values,
+        }
+    }
+}
+
+impl FromSaved<Spectre, Noise> for Output {
+    type SavedKey = ();
+
+    fn from_saved(output: &<Noise as Analysis>::Output, _key: &Self::SavedKey) -> Self {
+        (*output).clone()
+    }
+}
+
+impl Save<Spectre, Noise, ()> for Output {
+    fn save(
+        _ctx: &SimulationContext<Spectre>,
+        _to_save: (),
+        _opts: &mut <Spectre as Simulator>::Options,
+    ) -> Self::SavedKey {
+    }
+}
+
+impl Analysis for Noise {
+    type Output = Output;
+}
+
+impl SupportedBy<Spectre> for Noise {
+    fn into_input(self, inputs: &mut Vec<<Spectre as Simulator>::Input>) {
+        inputs.push(self.into());
+    }
+    fn from_output(
+        outputs: &mut impl Iterator<Item = <Spectre as Simulator>::Output>,
+    ) -> <Self as Analysis>::Output {
+        let item = outputs.next().unwrap();
+        item.try_into().unwrap()
+    }
+}
@@ -0,0 +1,132 @@
+//! Spectre S-parameter analysis options and data structures.
+
+use crate::analysis::ac::Sweep;
+use crate::{ErrPreset, Spectre};
+use arcstr::ArcStr;
+use num::complex::Complex64;
+use num::Zero;
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::Arc;
+use substrate::simulation::data::{FromSaved, Save};
+use substrate::simulation::{Analysis, SimulationContext, Simulator, SupportedBy};
+
+/// An S-parameter analysis.
+///
+/// Ports are defined by instantiating [`Port`](crate::blocks::Port)s in the testbench.
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+pub struct Sp {
+    /// Start frequency (Hz).
+    pub start: Decimal,
+    /// Stop frequency (Hz).
+    pub stop: Decimal,
+    /// The sweep kind and number of points.
+    pub sweep: Sweep,
+    /// The raw names of the port instances that define the S-parameters, in order.
+    ///
+    /// If empty, all ports in the circuit are used, ordered by their port number.
+    pub ports: Vec<ArcStr>,
+
+    /// The error preset.
+    pub errpreset: Option<ErrPreset>,
+}
+
+/// The result of an S-parameter analysis.
+#[derive(Debug, Clone)]
+pub struct Output {
+    /// The frequency points of the S-parameter simulation.
+    pub freq: Arc<Vec<f64>>,
+    /// The S-parameter matrix at each frequency point.
+    ///
+    /// `matrices[k][i][j]` is the S-parameter from port `j + 1` to port `i + 1`
+    /// at frequency `freq[k]`.
+    pub matrices: Arc<Vec<Vec<Vec<Complex64>>>>,
+}
+
+impl Output {
+    /// Returns the number of ports.
+    pub fn num_ports(&self) -> usize {
+        self.matrices.first().map(|m| m.len()).unwrap_or(0)
+    }
+
+    /// Returns the parameter `S{i}{j}` at each frequency point.
+    ///
+    /// Port numbers start at 1, so `s(1, 1)` returns S11.
+    pub fn s(&self, i: usize, j: usize) -> Vec<Complex64> {
+        self.matrices.iter().map(|m| m[i - 1][j - 1]).collect()
+    }
+
+    /// Builds an S-parameter matrix per frequency from raw PSF signals named `S{i}{j}`.
+    ///
+    /// Signals whose names cannot be interpreted as S-parameters are ignored.
+    pub(crate) fn from_signals(freq: Vec<f64>, signals: HashMap<String, Vec<Complex64>>) -> Self {
+        let params: Vec<_> = signals
+            .into_iter()
+            .filter_map(|(name, values)| Some((parse_sparam_name(&name)?, values)))
+            .collect();
+        let n = params
+            .iter()
+            .map(|((i, j), _)| std::cmp::max(*i, *j))
+            .max()
+            .unwrap_or(0);
+
+        let mut matrices = vec![vec![vec![Complex64::zero(); n]; n]; freq.len()];
+        for ((i, j), values) in params {
+            for (matrix, value) in matrices.iter_mut().zip(values) {
+                matrix[i - 1][j - 1] = value;
+            }
+        }
+
+        Self {
+            freq: Arc::new(freq),
+            matrices: Arc::new(matrices),
+        }
+    }
+}
+
+/// Parses a signal name of the form `S{i}{j}`, `S{i}_{j}`, or `S{i},{j}` into 1-indexed
+/// port numbers.
+fn parse_sparam_name(name: &str) -> Option<(usize, usize)> {
+    let rest = name.strip_prefix(['s', 'S'])?;
+    let (i, j) = match rest.split_once(['_', ',']) {
+        Some(split) => split,
+        None if rest.len() == 2 => rest.split_at(1),
+        None => return None,
+    };
+    let (i, j) = (i.parse().ok()?, j.parse().ok()?);
+    (i > 0 && j > 0).then_some((i, j))
+}
+
+impl FromSaved<Spectre, Sp> for Output {
+    type SavedKey = ();
+
+    fn from_saved(output: &<Sp as Analysis>::Output, _key: &Self::SavedKey) -> Self {
+        (*output).clone()
+    }
+}
+
+impl Save<Spectre, Sp, ()> for Output {
+    fn save(
+        _ctx: &SimulationContext<Spectre>,
+        _to_save: (),
+        _opts: &mut <Spectre as Simulator>::Options,
+    ) -> Self::SavedKey {
+    }
+}
+
+impl Analysis for Sp {
+    type Output = Output;
+}
+
+impl SupportedBy<Spectre> for Sp {
+    fn into_input(self, inputs: &mut Vec<<Spectre as Simulator>::Input>) {
+        inputs.push(self.into());
+    }
+    fn from_output(
+        outputs: &mut impl Iterator<Item = <Spectre as Simulator>::Output>,
+    ) -> <Self as Analysis>::Output {
+        let item = outputs.next().unwrap();
+        item.try_into().unwrap()
+    }
+}
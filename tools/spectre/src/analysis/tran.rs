@@ -1,7 +1,7 @@
 //! Spectre transient analysis options and data structures.
 
 use crate::dspf::DspfNode;
-use crate::{ErrPreset, InstanceTail, SimSignal, Spectre};
+use crate::{ErrPreset, InstanceTail, IntegrationMethod, SaveOption, SimSignal, Spectre};
 use arcstr::ArcStr;
 use rust_decimal::Decimal;
 use scir::{NamedSliceOne, SliceOnePath};
@@ -36,6 +36,27 @@ pub struct Tran {
 
     /// The minimum frequency for noise power spectral density.
     pub noise_fmin: Option<Decimal>,
+
+    /// The maximum time step (sec).
+    pub maxstep: Option<Decimal>,
+
+    /// The integration method.
+    pub method: Option<IntegrationMethod>,
+
+    /// The interval between output time points (sec).
+    ///
+    /// If set, output is only saved at uniformly spaced (strobed) time points.
+    pub strobeperiod: Option<Decimal>,
+
+    /// The delay before the first strobed time point (sec).
+    ///
+    /// Only used if [`Tran::strobeperiod`] is set.
+    pub strobedelay: Option<Decimal>,
+
+    /// The signals to save in this analysis.
+    ///
+    /// Defaults to the simulation-wide [`Options::save`](crate::Options::save) setting.
+    pub save: Option<SaveOption>,
 }
 
 /// The result of a transient analysis.
@@ -47,6 +68,98 @@ pub struct Output {
     pub raw_values: HashMap<ArcStr, Arc<Vec<f64>>>,
     /// A map from a save ID to a raw value identifier.
     pub(crate) saved_values: HashMap<u64, ArcStr>,
+    /// A map from an alias set with [`Options::alias_save`](crate::Options::alias_save)
+    /// to the raw value identifier it refers to.
+    pub(crate) aliases: HashMap<ArcStr, ArcStr>,
+}
+
+impl Output {
+    /// Returns the value of every saved node voltage at the final time point.
+    ///
+    /// Terminal currents are excluded. The result can be passed to
+    /// [`Options::set_ics_from`](crate::Options::set_ics_from) to seed a subsequent simulation.
+    pub fn final_state(&self) -> HashMap<SimSignal, f64> {
+        self.raw_values
+            .iter()
+            .filter(|(name, _)| !name.contains(':') && !self.aliases.contains_key(*name))
+            .filter_map(|(name, values)| Some((SimSignal::Raw(name.clone()), *values.last()?)))
+            .collect()
+    }
+
+    /// Returns the values of the saved voltage identified by `key`.
+    fn values(&self, key: &VoltageSavedKey) -> Option<&[f64]> {
+        let name = self.saved_values.get(&key.0)?;
+        Some(self.raw_values.get(name)?.as_slice())
+    }
+
+    /// Returns the first time at which the given signal crosses `level` with the given edge.
+    ///
+    /// Crossing times are linearly interpolated between samples.
+    pub fn cross(&self, signal: &VoltageSavedKey, level: f64, edge: Edge) -> Option<f64> {
+        cross_after(
+            &self.time,
+            self.values(signal)?,
+            level,
+            edge,
+            f64::NEG_INFINITY,
+        )
+    }
+
+    /// Returns the time from the first crossing of `from_level` by `from`
+    /// to the next crossing of `to_level` by `to`.
+    ///
+    /// Crossings in either direction are considered.
+    pub fn delay(
+        &self,
+        from: &VoltageSavedKey,
+        from_level: f64,
+        to: &VoltageSavedKey,
+        to_level: f64,
+    ) -> Option<f64> {
+        let start = self.cross(from, from_level, Edge::Either)?;
+        let end = cross_after(&self.time, self.values(to)?, to_level, Edge::Either, start)?;
+        Some(end - start)
+    }
+
+    /// Returns the time and value of the maximum sample of the given signal.
+    pub fn max(&self, signal: &VoltageSavedKey) -> Option<(f64, f64)> {
+        self.time
+            .iter()
+            .copied()
+            .zip(self.values(signal)?.iter().copied())
+            .fold(None, |max, (t, v)| match max {
+                Some((_, max_v)) if max_v >= v => max,
+                _ => Some((t, v)),
+            })
+    }
+}
+
+/// The direction of a signal crossing.
+#[derive(Debug, Clone, Copy, Hash, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Edge {
+    /// A crossing from below to above the level.
+    Rising,
+    /// A crossing from above to below the level.
+    Falling,
+    /// A crossing in either direction.
+    Either,
+}
+
+/// Returns the first time no earlier than `after` at which `values` crosses `level`.
+fn cross_after(time: &[f64], values: &[f64], level: f64, edge: Edge, after: f64) -> Option<f64> {
+    time.windows(2)
+        .zip(values.windows(2))
+        .filter_map(|(t, v)| {
+            let rising = v[0] < level && v[1] >= level;
+            let falling = v[0] > level && v[1] <= level;
+            let matches = match edge {
+                Edge::Rising => rising,
+                Edge::Falling => falling,
+                Edge::Either => rising || falling,
+            };
+            matches.then(|| t[0] + (level - v[0]) / (v[1] - v[0]) * (t[1] - t[0]))
+        })
+        .find(|t| *t >= after)
 }
 
 impl FromSaved<Spectre, Tran> for Output {
@@ -1,10 +1,12 @@
 //! Spectre-specific blocks for use in testbenches.
 
+use arcstr::ArcStr;
 use rust_decimal::Decimal;
 use scir::ParamValue;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::path::PathBuf;
+use std::sync::Arc;
 use substrate::block::Block;
 use substrate::io::schematic::HardwareType;
 use substrate::io::{Array, Io, TwoTerminalIo};
@@ -12,6 +14,7 @@ use substrate::schematic::primitives::DcVsource;
 use substrate::schematic::{CellBuilder, ExportsNestedData, PrimitiveBinding, Schematic};
 use substrate::simulation::waveform::{TimeWaveform, Waveform};
 
+use crate::error::Error;
 use crate::{Primitive, Spectre};
 
 /// Data associated with a pulse [`Vsource`].
@@ -42,7 +45,10 @@ pub enum Vsource {
     Ac(AcSource),
     /// A pulse voltage source.
     Pulse(Pulse),
-    /// A piecewise linear source
+    /// A piecewise linear source.
+    ///
+    /// The points are sorted by time when netlisting.
+    /// Generating the schematic fails if two points have the same time.
     Pwl(Waveform<Decimal>),
 }
 
@@ -120,18 +126,11 @@ impl Schematic<Spectre> for Vsource {
                 }
             }
             Vsource::Pwl(waveform) => {
-                let mut pwl = String::new();
-                pwl.push('[');
-                for (i, pt) in waveform.values().enumerate() {
-                    use std::fmt::Write;
-                    if i != 0 {
-                        pwl.push(' ');
-                    }
-                    write!(&mut pwl, "{} {}", pt.t(), pt.x()).unwrap();
-                }
-                pwl.push(']');
                 params.insert(literal!("type"), ParamValue::String(literal!("pwl")));
-                params.insert(literal!("wave"), ParamValue::String(pwl.into()));
+                params.insert(
+                    literal!("wave"),
+                    ParamValue::String(pwl_wave(waveform.values().map(|pt| (pt.t(), pt.x())))?),
+                );
             }
             Vsource::Ac(ac) => {
                 params.insert(literal!("type"), ParamValue::String(literal!("dc")));
@@ -165,6 +164,122 @@ impl Schematic<Spectre> for DcVsource {
     }
 }
 
+/// Sorts `(time, value)` pairs by time, rejecting duplicate times.
+fn sort_pwl_points(
+    points: impl IntoIterator<Item = (Decimal, Decimal)>,
+) -> crate::error::Result<Vec<(Decimal, Decimal)>> {
+    let mut points: Vec<_> = points.into_iter().collect();
+    points.sort_by_key(|(t, _)| *t);
+    if let Some(pair) = points.windows(2).find(|pair| pair[0].0 == pair[1].0) {
+        return Err(Error::DuplicatePwlTime(pair[0].0));
+    }
+    Ok(points)
+}
+
+/// Formats time/value pairs as a Spectre `wave` list, sorted by time.
+///
+/// Returns an error if two pairs have the same time.
+fn pwl_wave(
+    points: impl IntoIterator<Item = (Decimal, Decimal)>,
+) -> substrate::error::Result<ArcStr> {
+    let points =
+        sort_pwl_points(points).map_err(|e| substrate::error::Error::Boxed(Arc::new(e)))?;
+    let mut wave = String::new();
+    wave.push('[');
+    for (i, (t, x)) in points.into_iter().enumerate() {
+        use std::fmt::Write;
+        if i != 0 {
+            wave.push(' ');
+        }
+        write!(&mut wave, "{} {}", t, x).unwrap();
+    }
+    wave.push(']');
+    Ok(wave.into())
+}
+
+/// A piecewise linear voltage source.
+#[derive(Serialize, Deserialize, Debug, Clone, Hash, PartialEq, Eq)]
+#[serde(try_from = "RawPwlVsource")]
+pub struct PwlVsource {
+    points: Vec<(Decimal, Decimal)>,
+}
+
+/// The unvalidated serialized form of a [`PwlVsource`].
+#[derive(Deserialize)]
+struct RawPwlVsource {
+    points: Vec<(Decimal, Decimal)>,
+}
+
+impl TryFrom<RawPwlVsource> for PwlVsource {
+    type Error = Error;
+
+    fn try_from(value: RawPwlVsource) -> Result<Self, Self::Error> {
+        Self::new(value.points)
+    }
+}
+
+impl PwlVsource {
+    /// Creates a new piecewise linear voltage source from `(time, value)` pairs.
+    ///
+    /// The pairs are sorted by time.
+    /// Returns an error if two pairs have the same time.
+    pub fn new(points: impl IntoIterator<Item = (Decimal, Decimal)>) -> crate::error::Result<Self> {
+        Ok(Self {
+            points: sort_pwl_points(points)?,
+        })
+    }
+
+    /// The `(time, value)` pairs of this source, sorted by time.
+    pub fn points(&self) -> &[(Decimal, Decimal)] {
+        &self.points
+    }
+}
+
+impl Block for PwlVsource {
+    type Io = TwoTerminalIo;
+
+    fn id() -> arcstr::ArcStr {
+        arcstr::literal!("pwl_vsource")
+    }
+    fn name(&self) -> arcstr::ArcStr {
+        arcstr::literal!("pwl_vsource")
+    }
+    fn io(&self) -> Self::Io {
+        Default::default()
+    }
+}
+
+impl ExportsNestedData for PwlVsource {
+    type NestedData = ();
+}
+
+impl Schematic<Spectre> for PwlVsource {
+    fn schematic(
+        &self,
+        io: &<<Self as Block>::Io as HardwareType>::Bundle,
+        cell: &mut CellBuilder<Spectre>,
+    ) -> substrate::error::Result<Self::NestedData> {
+        let mut prim = PrimitiveBinding::new(Primitive::RawInstance {
+            cell: arcstr::literal!("vsource"),
+            ports: vec!["p".into(), "n".into()],
+            params: HashMap::from_iter([
+                (
+                    arcstr::literal!("type"),
+                    ParamValue::String(arcstr::literal!("pwl")),
+                ),
+                (
+                    arcstr::literal!("wave"),
+                    ParamValue::String(pwl_wave(self.points.iter().copied())?),
+                ),
+            ]),
+        });
+        prim.connect("p", io.p);
+        prim.connect("n", io.n);
+        cell.set_primitive(prim);
+        Ok(())
+    }
+}
+
 /// An AC source.
 #[derive(Serialize, Deserialize, Default, Debug, Copy, Clone, Hash, PartialEq, Eq)]
 pub struct AcSource {
@@ -324,6 +439,67 @@ impl Schematic<Spectre> for Iprobe {
     }
 }
 
+/// An S-parameter port with a reference resistance.
+#[derive(Serialize, Deserialize, Debug, Copy, Clone, Hash, PartialEq, Eq)]
+pub struct Port {
+    num: usize,
+    r: Decimal,
+}
+
+impl Port {
+    /// Creates a new port with the given port number and reference resistance.
+    ///
+    /// Port numbers start at 1.
+    pub fn new(num: usize, r: Decimal) -> Self {
+        assert!(num > 0, "port numbers start at 1");
+        Self { num, r }
+    }
+}
+
+impl Block for Port {
+    type Io = TwoTerminalIo;
+
+    fn id() -> arcstr::ArcStr {
+        arcstr::literal!("port")
+    }
+    fn name(&self) -> arcstr::ArcStr {
+        // `port` is a reserved Spectre keyword,
+        // so we call this block `userport`.
+        arcstr::format!("userport{}", self.num)
+    }
+    fn io(&self) -> Self::Io {
+        Default::default()
+    }
+}
+
+impl ExportsNestedData for Port {
+    type NestedData = ();
+}
+
+impl Schematic<Spectre> for Port {
+    fn schematic(
+        &self,
+        io: &<<Self as Block>::Io as HardwareType>::Bundle,
+        cell: &mut CellBuilder<Spectre>,
+    ) -> substrate::error::Result<Self::NestedData> {
+        let mut prim = PrimitiveBinding::new(Primitive::RawInstance {
+            cell: arcstr::literal!("port"),
+            ports: vec!["p".into(), "n".into()],
+            params: HashMap::from_iter([
+                (arcstr::literal!("r"), ParamValue::Numeric(self.r)),
+                (
+                    arcstr::literal!("num"),
+                    ParamValue::Numeric(Decimal::from(self.num)),
+                ),
+            ]),
+        });
+        prim.connect("p", io.p);
+        prim.connect("n", io.n);
+        cell.set_primitive(prim);
+        Ok(())
+    }
+}
+
 /// An n-port black box.
 #[derive(Serialize, Deserialize, Debug, Clone, Hash, PartialEq, Eq)]
 pub struct Nport {
@@ -1,9 +1,15 @@
 //! Spectre errors.
 
+use std::path::PathBuf;
 use std::sync::Arc;
 
+use itertools::Itertools;
+use rust_decimal::Decimal;
+use scir::SliceOnePath;
 use thiserror::Error as ThisError;
 
+use crate::log::SpectreDiagnostic;
+
 /// The result type returned by Spectre library functions.
 pub type Result<T> = std::result::Result<T, Error>;
 
@@ -19,6 +25,9 @@ pub enum Error {
     /// Error invoking Spectre.
     #[error("error running Spectre")]
     SpectreError,
+    /// Error invoking Spectre, with the errors reported in the Spectre log.
+    #[error("error running Spectre:\n{}", .0.iter().join("\n"))]
+    SpectreWithDiagnostics(Vec<SpectreDiagnostic>),
     /// Error parsing output files.
     #[error("error parsing Spectre output file")]
     Parse,
@@ -28,4 +37,16 @@ pub enum Error {
     /// Error caching results.
     #[error("error generating spectre results")]
     Caching(#[from] Arc<cache::error::Error>),
+    /// A saved signal or initial condition references a node that does not exist.
+    #[error("save path does not reference a valid node: {path:?}")]
+    InvalidSavePath {
+        /// The invalid path.
+        path: SliceOnePath,
+    },
+    /// A batched simulation shares its working directory with an earlier simulation in the batch.
+    #[error("working directory is used by another simulation in the batch: {0:?}")]
+    DuplicateWorkDir(PathBuf),
+    /// A piecewise linear waveform has two points with the same time.
+    #[error("duplicate time point in piecewise linear source: {0}")]
+    DuplicatePwlTime(Decimal),
 }
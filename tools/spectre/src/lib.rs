@@ -7,13 +7,17 @@ use std::io::Write;
 #[cfg(any(unix, target_os = "redox"))]
 use std::os::unix::prelude::PermissionsExt;
 use std::path::{Path, PathBuf};
-use std::sync::Arc;
+use std::sync::{Arc, Mutex};
 
 use crate::analysis::ac::{Ac, Sweep};
 use crate::analysis::montecarlo;
 use crate::analysis::montecarlo::MonteCarlo;
+use crate::analysis::noise::Noise;
+use crate::analysis::sp::Sp;
 
 use analysis::ac;
+use analysis::noise;
+use analysis::sp;
 use analysis::tran;
 use analysis::tran::Tran;
 use arcstr::ArcStr;
@@ -56,6 +60,7 @@ pub mod analysis;
 pub mod blocks;
 pub mod dspf;
 pub mod error;
+pub mod log;
 pub(crate) mod templates;
 
 /// Spectre primitives.
@@ -116,6 +121,113 @@ impl Display for ErrPreset {
     }
 }
 
+/// Spectre transient integration methods.
+#[derive(
+    Copy, Clone, Debug, Default, Eq, PartialEq, Hash, Ord, PartialOrd, Serialize, Deserialize,
+)]
+pub enum IntegrationMethod {
+    /// Backward Euler.
+    Euler,
+    /// Trapezoidal rule.
+    Trap,
+    /// Trapezoidal rule only, without switching to backward Euler.
+    TrapOnly,
+    /// Second-order backward difference formula.
+    Gear2,
+    /// Second-order backward difference formula only, without switching to backward Euler.
+    Gear2Only,
+    /// Trapezoidal rule and second-order backward difference formula.
+    #[default]
+    TrapGear2,
+}
+
+impl Display for IntegrationMethod {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match *self {
+            Self::Euler => write!(f, "euler"),
+            Self::Trap => write!(f, "trap"),
+            Self::TrapOnly => write!(f, "traponly"),
+            Self::Gear2 => write!(f, "gear2"),
+            Self::Gear2Only => write!(f, "gear2only"),
+            Self::TrapGear2 => write!(f, "trapgear2"),
+        }
+    }
+}
+
+/// Spectre numerical tolerance options.
+///
+/// Unset options are left at their Spectre defaults.
+#[derive(Clone, Debug, Default, Eq, PartialEq, Hash, Serialize, Deserialize)]
+pub struct SpectreNumericOptions {
+    /// Relative convergence tolerance.
+    pub reltol: Option<Decimal>,
+    /// Absolute convergence tolerance for voltages.
+    pub vabstol: Option<Decimal>,
+    /// Absolute convergence tolerance for currents.
+    pub iabstol: Option<Decimal>,
+}
+
+impl Display for SpectreNumericOptions {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let params = [
+            ("reltol", self.reltol.map(|v| v.to_string())),
+            ("vabstol", self.vabstol.map(|v| v.to_string())),
+            ("iabstol", self.iabstol.map(|v| v.to_string())),
+        ];
+        let mut first = true;
+        for (name, value) in params {
+            if let Some(value) = value {
+                if !first {
+                    write!(f, " ")?;
+                }
+                write!(f, "{name}={value}")?;
+                first = false;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Spectre log verbosity options.
+///
+/// Unset limits are left at their Spectre defaults.
+#[derive(Clone, Debug, Default, Eq, PartialEq, Hash, Serialize, Deserialize)]
+pub struct SpectreLogOptions {
+    /// The maximum number of times each note is printed.
+    pub maxnotes: Option<u64>,
+    /// The maximum number of times each warning is printed.
+    pub maxwarns: Option<u64>,
+    /// Disables narration and notes.
+    pub quiet: bool,
+}
+
+impl SpectreLogOptions {
+    /// Returns options that disable narration and notes.
+    pub fn quiet() -> Self {
+        Self {
+            quiet: true,
+            ..Default::default()
+        }
+    }
+}
+
+impl Display for SpectreLogOptions {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let mut params = Vec::new();
+        if let Some(maxnotes) = self.maxnotes {
+            params.push(format!("maxnotes={maxnotes}"));
+        }
+        if let Some(maxwarns) = self.maxwarns {
+            params.push(format!("maxwarns={maxwarns}"));
+        }
+        if self.quiet {
+            params.push("narrate=no".to_string());
+            params.push("note=no".to_string());
+        }
+        write!(f, "{}", params.join(" "))
+    }
+}
+
 /// A signal referenced by a save/ic Spectre statement.
 #[derive(Debug, Clone, Eq, PartialEq, Hash, Ord, PartialOrd)]
 pub enum SimSignal {
@@ -157,14 +269,19 @@ impl SimSignal {
         Self::from(path)
     }
 
-    pub(crate) fn to_string(&self, lib: &Library<Spectre>, conv: &NetlistLibConversion) -> ArcStr {
+    pub(crate) fn to_string(
+        &self,
+        spectre: &Spectre,
+        lib: &Library<Spectre>,
+        conv: &NetlistLibConversion,
+    ) -> ArcStr {
         match self {
             SimSignal::Raw(raw) => raw.clone(),
             SimSignal::ScirCurrent(scir) => {
-                ArcStr::from(Spectre::node_current_path(lib, conv, scir))
+                ArcStr::from(spectre.node_current_path(lib, conv, scir))
             }
             SimSignal::ScirVoltage(scir) => {
-                ArcStr::from(Spectre::node_voltage_path(lib, conv, scir))
+                ArcStr::from(spectre.node_voltage_path(lib, conv, scir))
             }
             SimSignal::InstanceTail(itail) => {
                 let ipath = Spectre::instance_path(lib, conv, &itail.instance);
@@ -172,11 +289,89 @@ impl SimSignal {
             }
         }
     }
+
+    /// Checks that SCIR paths referenced by this signal exist in `lib`.
+    ///
+    /// Raw and instance tail signals are passed through to Spectre unchecked.
+    pub(crate) fn validate(&self, lib: &Library<Spectre>) -> Result<()> {
+        match self {
+            SimSignal::ScirVoltage(path) | SimSignal::ScirCurrent(path) => {
+                if slice_one_path_exists(lib, path) {
+                    Ok(())
+                } else {
+                    Err(Error::InvalidSavePath { path: path.clone() })
+                }
+            }
+            SimSignal::Raw(_) | SimSignal::InstanceTail(_) => Ok(()),
+        }
+    }
+}
+
+/// Returns `true` if `path` can be converted to a Spectre node name without panicking.
+///
+/// Paths ending at a primitive instance are assumed to reference one of its ports by name.
+fn slice_one_path_exists(lib: &Library<Spectre>, path: &SliceOnePath) -> bool {
+    let annotated = lib.annotate_instance_path(path.instances().clone());
+    if annotated.instances.iter().any(|inst| inst.child.is_none()) {
+        return false;
+    }
+    let cell = match annotated.instances.last() {
+        Some(inst) => match inst.child.unwrap() {
+            scir::ChildId::Cell(id) => lib.try_cell(id),
+            scir::ChildId::Primitive(_) => return path.tail().is_name(),
+        },
+        None => annotated.top.get_id().and_then(|id| lib.try_cell(*id)),
+    };
+    let Some(cell) = cell else {
+        return false;
+    };
+    let (signal, index) = match path.tail() {
+        scir::SignalPathTail::Id(slice) => (cell.try_signal(slice.signal()), slice.index()),
+        scir::SignalPathTail::Name(slice) => (cell.try_signal_named(slice.signal()), slice.index()),
+    };
+    match (signal, index) {
+        (Some(info), Some(index)) => info.width.is_some_and(|width| index < width),
+        (Some(info), None) => info.width.is_none(),
+        (None, _) => false,
+    }
 }
 
 /// Spectre simulator global configuration.
 #[derive(Debug, Clone, Default)]
-pub struct Spectre {}
+pub struct Spectre {
+    /// The name of the global ground net.
+    ///
+    /// Defaults to `0` if not specified.
+    ground: Option<ArcStr>,
+    /// The casing applied to netlisted identifiers.
+    case: CasePolicy,
+}
+
+/// The casing applied to identifiers written to a Spectre netlist.
+///
+/// Names that differ only in case may collide under [`CasePolicy::Lower`]
+/// and [`CasePolicy::Upper`].
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq, Hash)]
+pub enum CasePolicy {
+    /// Leave identifiers unchanged.
+    #[default]
+    Preserve,
+    /// Convert identifiers to lowercase.
+    Lower,
+    /// Convert identifiers to uppercase.
+    Upper,
+}
+
+impl CasePolicy {
+    /// Applies this policy to the given identifier.
+    pub fn apply(&self, name: &str) -> String {
+        match self {
+            CasePolicy::Preserve => name.to_string(),
+            CasePolicy::Lower => name.to_lowercase(),
+            CasePolicy::Upper => name.to_uppercase(),
+        }
+    }
+}
 
 /// Spectre per-simulation options.
 ///
@@ -185,6 +380,7 @@ pub struct Spectre {}
 pub struct Options {
     includes: HashSet<Include>,
     saves: HashMap<SimSignal, u64>,
+    aliases: HashMap<SimSignal, ArcStr>,
     ics: HashMap<SimSignal, Decimal>,
     next_save_key: u64,
     /// The simulation temperature.
@@ -192,10 +388,22 @@ pub struct Options {
     save: Option<SaveOption>,
     /// Override the default Spectre flags.
     override_flags: Option<String>,
+    /// The name of the global ground net.
+    ground: Option<ArcStr>,
+    /// Numerical tolerance options.
+    numeric: Option<SpectreNumericOptions>,
+    /// Log verbosity options.
+    log: Option<SpectreLogOptions>,
+    /// The casing applied to netlisted identifiers, overriding that of the installed [`Spectre`].
+    case: Option<CasePolicy>,
+    /// Whether to annotate the netlist with the SCIR cells of subcircuits and instances.
+    annotate: bool,
+    /// A custom parser for raw simulation output.
+    parser: Option<Arc<dyn OutputParser>>,
 }
 
 /// The allowed values of the `save` option.
-#[derive(Copy, Clone, Debug, Default)]
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq, Hash, Serialize, Deserialize)]
 pub enum SaveOption {
     /// All signals.
     All,
@@ -265,6 +473,18 @@ impl Options {
         self.ics.insert(key.into(), value);
     }
 
+    /// Sets initial conditions from a previously solved state,
+    /// such as one returned by [`tran::Output::final_state`].
+    ///
+    /// Values that cannot be represented as a [`Decimal`] are ignored.
+    pub fn set_ics_from(&mut self, state: &HashMap<SimSignal, f64>) {
+        for (signal, value) in state {
+            if let Ok(value) = Decimal::try_from(*value) {
+                self.set_ic_inner(signal.clone(), value);
+            }
+        }
+    }
+
     /// Marks a transient voltage to be saved in all transient analyses.
     pub fn save_tran_voltage(&mut self, save: impl Into<SimSignal>) -> tran::VoltageSavedKey {
         tran::VoltageSavedKey(self.save_inner(save))
@@ -275,6 +495,13 @@ impl Options {
         tran::CurrentSavedKey(vec![self.save_inner(save)])
     }
 
+    /// Saves the given signal in all transient analyses, additionally exposing its
+    /// waveform in [`tran::Output::raw_values`] under the name `alias`.
+    pub fn alias_save(&mut self, save: SimSignal, alias: impl Into<ArcStr>) {
+        self.save_inner(save.clone());
+        self.aliases.insert(save, alias.into());
+    }
+
     /// Marks an AC voltage to be saved in all AC analyses.
     pub fn save_ac_voltage(&mut self, save: impl Into<SimSignal>) -> ac::VoltageSavedKey {
         ac::VoltageSavedKey(self.save_inner(save))
@@ -291,6 +518,8 @@ impl Options {
     }
 
     /// Set the `save` option.
+    ///
+    /// Analyses with their own `save` option override this setting.
     pub fn save(&mut self, save: SaveOption) {
         self.save = Some(save);
     }
@@ -301,6 +530,57 @@ impl Options {
     pub fn set_flags(&mut self, flags: impl Into<String>) {
         self.override_flags = Some(flags.into());
     }
+
+    /// Sets the name of the global ground net.
+    ///
+    /// The testbench ground node is renamed to `name` and declared global.
+    /// Defaults to the ground net of the installed [`Spectre`], which is `0`
+    /// unless set with [`Spectre::with_ground`].
+    pub fn set_ground(&mut self, name: impl Into<ArcStr>) {
+        self.ground = Some(name.into());
+    }
+
+    /// Sets numerical tolerance options.
+    ///
+    /// Emitted as a Spectre `options` statement.
+    pub fn set_numeric(&mut self, opts: SpectreNumericOptions) {
+        self.numeric = Some(opts);
+    }
+
+    /// Sets log verbosity options.
+    ///
+    /// Emitted as a Spectre `options` statement, so changing these options
+    /// also changes the simulation cache key.
+    pub fn set_log(&mut self, opts: SpectreLogOptions) {
+        self.log = Some(opts);
+    }
+
+    /// Sets the casing of identifiers in the netlist.
+    ///
+    /// Applies to escaped node and instance names, including those in the paths
+    /// of saved signals. Subcircuit and primitive names are left unchanged.
+    /// Defaults to the casing of the installed [`Spectre`], which is
+    /// [`CasePolicy::Preserve`] unless set with [`Spectre::with_case`].
+    pub fn set_case(&mut self, policy: CasePolicy) {
+        self.case = Some(policy);
+    }
+
+    /// Sets whether each subcircuit and instance in the netlist is preceded by
+    /// a comment naming its SCIR cell or primitive.
+    ///
+    /// Annotations are comments and do not affect simulation results.
+    /// Disabled by default.
+    pub fn annotate(&mut self, enabled: bool) {
+        self.annotate = enabled;
+    }
+
+    /// Sets the parser used to read raw simulation output.
+    ///
+    /// Defaults to [`PsfParser`]. The parser is not part of the simulation cache key,
+    /// so cached outputs are returned as previously parsed.
+    pub fn set_output_parser(&mut self, parser: impl OutputParser + 'static) {
+        self.parser = Some(Arc::new(parser));
+    }
 }
 
 impl SimOption<Spectre> for Temperature {
@@ -396,6 +676,7 @@ struct CachedSimState {
     executor: Arc<dyn Executor>,
     /// Override the default Spectre flags.
     override_flags: Option<String>,
+    parser: Arc<dyn OutputParser>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -405,31 +686,66 @@ enum CachedData {
         freq: Vec<f64>,
         signals: HashMap<String, Vec<Complex64>>,
     },
+    Sp {
+        freq: Vec<f64>,
+        signals: HashMap<String, Vec<Complex64>>,
+    },
+    Noise {
+        freq: Vec<f64>,
+        signals: HashMap<String, Vec<f64>>,
+    },
     // The outer vec has length `numruns`.
     // The inner vec length equals the length of the inner analysis.
     MonteCarlo(Vec<Vec<CachedData>>),
 }
 
+impl From<RawOutput> for CachedData {
+    fn from(value: RawOutput) -> Self {
+        match value {
+            RawOutput::Tran(signals) => CachedData::Tran(signals),
+            RawOutput::Ac { freq, signals } => CachedData::Ac { freq, signals },
+            RawOutput::Sp { freq, signals } => CachedData::Sp { freq, signals },
+            RawOutput::Noise { freq, signals } => CachedData::Noise { freq, signals },
+        }
+    }
+}
+
 impl CachedData {
     fn into_output(
         self,
         ctx: &SimulationContext<Spectre>,
+        spectre: &Spectre,
         conv: &NetlistLibConversion,
         saves: &HashMap<SimSignal, u64>,
+        aliases: &HashMap<SimSignal, ArcStr>,
     ) -> Output {
         match self {
-            CachedData::Tran(mut raw_values) => tran::Output {
-                time: Arc::new(raw_values.remove("time").unwrap()),
-                raw_values: raw_values
+            CachedData::Tran(mut raw_values) => {
+                let time = Arc::new(raw_values.remove("time").unwrap());
+                let mut raw_values: HashMap<_, _> = raw_values
                     .into_iter()
                     .map(|(k, v)| (ArcStr::from(k), Arc::new(v)))
-                    .collect(),
-                saved_values: saves
+                    .collect();
+                let aliases: HashMap<_, _> = aliases
                     .iter()
-                    .map(|(k, v)| (*v, k.to_string(&ctx.lib.scir, conv)))
-                    .collect(),
+                    .map(|(k, alias)| (alias.clone(), k.to_string(spectre, &ctx.lib.scir, conv)))
+                    .collect();
+                for (alias, name) in aliases.iter() {
+                    if let Some(values) = raw_values.get(name).cloned() {
+                        raw_values.insert(alias.clone(), values);
+                    }
+                }
+                tran::Output {
+                    time,
+                    raw_values,
+                    saved_values: saves
+                        .iter()
+                        .map(|(k, v)| (*v, k.to_string(spectre, &ctx.lib.scir, conv)))
+                        .collect(),
+                    aliases,
+                }
+                .into()
             }
-            .into(),
             CachedData::Ac { freq, signals } => ac::Output {
                 freq: Arc::new(freq),
                 raw_values: signals
@@ -438,15 +754,19 @@ impl CachedData {
                     .collect(),
                 saved_values: saves
                     .iter()
-                    .map(|(k, v)| (*v, k.to_string(&ctx.lib.scir, conv)))
+                    .map(|(k, v)| (*v, k.to_string(spectre, &ctx.lib.scir, conv)))
                     .collect(),
             }
             .into(),
+            CachedData::Sp { freq, signals } => sp::Output::from_signals(freq, signals).into(),
+            CachedData::Noise { freq, signals } => {
+                noise::Output::from_signals(freq, signals).into()
+            }
             CachedData::MonteCarlo(data) => Output::MonteCarlo(montecarlo::Output(
                 data.into_iter()
                     .map(|data| {
                         data.into_iter()
-                            .map(|d| d.into_output(ctx, conv, saves))
+                            .map(|d| d.into_output(ctx, spectre, conv, saves, aliases))
                             .collect()
                     })
                     .collect(),
@@ -473,6 +793,7 @@ impl CacheableWithState<CachedSimState> for CachedSim {
                 work_dir,
                 executor,
                 override_flags,
+                parser,
             } = state;
             write_run_script(
                 RunScriptContext {
@@ -491,16 +812,30 @@ impl CacheableWithState<CachedSimState> for CachedSim {
             perms.set_mode(0o744);
             std::fs::set_permissions(&run_script, perms)?;
 
+            // Remove any log left by a previous run so that its diagnostics are not reported.
+            if log.exists() {
+                std::fs::remove_file(&log)?;
+            }
+
             let mut command = std::process::Command::new("/bin/bash");
             command.arg(&run_script).current_dir(&work_dir);
-            executor
-                .execute(command, Default::default())
-                .map_err(|_| Error::SpectreError)?;
+            executor.execute(command, Default::default()).map_err(|_| {
+                let diagnostics: Vec<_> = crate::log::parse_log(&log)
+                    .into_iter()
+                    .filter(|d| d.severity != crate::log::Severity::Warning)
+                    .collect();
+                if diagnostics.is_empty() {
+                    Error::SpectreError
+                } else {
+                    Error::SpectreWithDiagnostics(diagnostics)
+                }
+            })?;
 
             let mut raw_outputs = Vec::with_capacity(input.len());
 
             for (i, input) in input.iter().enumerate() {
                 raw_outputs.push(parse_analysis(
+                    &*parser,
                     &output_path,
                     &subanalysis_name("analysis", i),
                     input,
@@ -538,6 +873,10 @@ impl Spectre {
         let mut f = std::fs::File::create(&netlist)?;
         let mut w = Vec::new();
 
+        // The ground name is written into the netlist, so changing it
+        // also changes the cache key of the simulation.
+        let spectre = self.with_options(&options);
+        let ground = ArcStr::from(spectre.ground());
         let mut includes = options.includes.into_iter().collect::<Vec<_>>();
         let mut saves = options.saves.keys().cloned().collect::<Vec<_>>();
         let mut ics = options
@@ -552,13 +891,15 @@ impl Spectre {
         saves.sort();
         ics.sort();
 
-        let conv = self.write_scir_netlist(
+        for signal in saves.iter().chain(ics.iter().map(|(k, _)| k)) {
+            signal.validate(&ctx.lib.scir)?;
+        }
+
+        let conv = spectre.write_scir_netlist(
             &ctx.lib.scir,
             &mut w,
-            NetlistOptions::new(
-                NetlistKind::Testbench(RenameGround::Yes("0".into())),
-                &includes,
-            ),
+            NetlistOptions::new(NetlistKind::Testbench(RenameGround::Yes(ground)), &includes)
+                .annotate(options.annotate),
         )?;
 
         writeln!(w)?;
@@ -566,13 +907,24 @@ impl Spectre {
             writeln!(w, "settemp1 options temp={}", temp)?;
         }
         for save in saves {
-            writeln!(w, "save {}", save.to_string(&ctx.lib.scir, &conv))?;
+            writeln!(w, "save {}", save.to_string(&spectre, &ctx.lib.scir, &conv))?;
         }
         if let Some(save) = options.save {
             writeln!(w, "setsave1 options save={}", save)?;
         }
+        if let Some(numeric) = &options.numeric {
+            writeln!(w, "setnumeric1 options {}", numeric)?;
+        }
+        if let Some(log) = &options.log {
+            writeln!(w, "setlog1 options {}", log)?;
+        }
         for (k, v) in ics {
-            writeln!(w, "ic {}={}", k.to_string(&ctx.lib.scir, &conv), v)?;
+            writeln!(
+                w,
+                "ic {}={}",
+                k.to_string(&spectre, &ctx.lib.scir, &conv),
+                v
+            )?;
         }
 
         writeln!(w)?;
@@ -605,6 +957,10 @@ impl Spectre {
                     work_dir,
                     executor,
                     override_flags: options.override_flags.clone(),
+                    parser: options
+                        .parser
+                        .clone()
+                        .unwrap_or_else(|| Arc::new(PsfParser)),
                 },
             )
             .try_inner()
@@ -617,23 +973,122 @@ impl Spectre {
         let conv = Arc::new(conv);
         let outputs = raw_outputs
             .into_iter()
-            .map(|raw_values| raw_values.into_output(ctx, &conv, &options.saves))
+            .map(|raw_values| {
+                raw_values.into_output(ctx, &spectre, &conv, &options.saves, &options.aliases)
+            })
             .collect();
 
         Ok(outputs)
     }
 
+    /// Runs independent simulations in parallel, with at most `max_parallel` running at once.
+    ///
+    /// Each simulation must have its own working directory. Errors are reported per simulation
+    /// and do not abort the rest of the batch. Results are returned in the order of `configs`.
+    pub fn simulate_batch(
+        &self,
+        configs: Vec<(SimulationContext<Self>, Options, Vec<Input>)>,
+        max_parallel: usize,
+    ) -> Vec<Result<Vec<Output>>> {
+        let mut results: Vec<Option<Result<Vec<Output>>>> = Vec::with_capacity(configs.len());
+        let mut work_dirs = HashSet::new();
+        let mut jobs = Vec::new();
+        for (i, (ctx, options, input)) in configs.into_iter().enumerate() {
+            if work_dirs.insert(ctx.work_dir.clone()) {
+                results.push(None);
+                jobs.push((i, ctx, options, input));
+            } else {
+                results.push(Some(Err(Error::DuplicateWorkDir(ctx.work_dir))));
+            }
+        }
+
+        let workers = max_parallel.clamp(1, std::cmp::max(jobs.len(), 1));
+        let jobs = Mutex::new(jobs.into_iter());
+        let results = Mutex::new(results);
+        std::thread::scope(|s| {
+            for _ in 0..workers {
+                s.spawn(|| loop {
+                    let Some((i, ctx, options, input)) = jobs.lock().unwrap().next() else {
+                        break;
+                    };
+                    let result = self.simulate(&ctx, options, input);
+                    results.lock().unwrap()[i] = Some(result);
+                });
+            }
+        });
+
+        results
+            .into_inner()
+            .unwrap()
+            .into_iter()
+            .map(|result| result.unwrap())
+            .collect()
+    }
+
+    /// Returns a copy of this configuration with the overrides set in `options` applied.
+    fn with_options(&self, options: &Options) -> Self {
+        let mut spectre = self.clone();
+        if let Some(ground) = &options.ground {
+            spectre.ground = Some(ground.clone());
+        }
+        if let Some(case) = options.case {
+            spectre.case = case;
+        }
+        spectre
+    }
+
+    /// Returns a copy of this configuration that declares `ground` as the global ground net.
+    pub fn with_ground(&self, ground: impl Into<ArcStr>) -> Self {
+        let mut spectre = self.clone();
+        spectre.ground = Some(ground.into());
+        spectre
+    }
+
+    /// The name of the global ground net.
+    pub fn ground(&self) -> &str {
+        self.ground.as_deref().unwrap_or("0")
+    }
+
+    /// Returns a copy of this configuration that netlists identifiers using the given casing.
+    pub fn with_case(&self, policy: CasePolicy) -> Self {
+        let mut spectre = self.clone();
+        spectre.case = policy;
+        spectre
+    }
+
+    /// The casing applied to netlisted identifiers.
+    pub fn case(&self) -> CasePolicy {
+        self.case
+    }
+
     /// Escapes the given identifier to be Spectre-compatible.
     pub fn escape_identifier(node_name: &str) -> String {
+        Self::escape_identifier_with_case(node_name, CasePolicy::Preserve)
+    }
+
+    /// Escapes the given identifier to be Spectre-compatible after converting it
+    /// according to this configuration's [`CasePolicy`].
+    pub fn escape_cased_identifier(&self, node_name: &str) -> String {
+        Self::escape_identifier_with_case(node_name, self.case)
+    }
+
+    fn escape_identifier_with_case(node_name: &str, case: CasePolicy) -> String {
         // The name "0" is reserved, as it represents global ground.
         // To prevent nodes from being accidentally connected to global ground,
         // we rename 0 to x0, x0 to xx0, xx0 to xxx0, etc.
+        // When converting to uppercase, the prefix is X rather than x.
         lazy_static! {
             static ref RE: Regex = Regex::new("^(x*)0$").unwrap();
+            static ref RE_UPPER: Regex = Regex::new("^(X*)0$").unwrap();
         }
-        if let Some(caps) = RE.captures(node_name) {
+        let node_name = case.apply(node_name);
+        let (re, x) = match case {
+            CasePolicy::Upper => (&*RE_UPPER, 'X'),
+            CasePolicy::Preserve | CasePolicy::Lower => (&*RE, 'x'),
+        };
+        if let Some(caps) = re.captures(&node_name) {
             let xs = caps.get(1).unwrap();
-            return format!("x{}0", xs.as_str());
+            return format!("{x}{}0", xs.as_str());
         }
 
         let mut escaped_name = String::new();
@@ -662,12 +1117,13 @@ impl Spectre {
     /// Converts a [`SliceOnePath`] to a Spectre path string corresponding to the associated
     /// node voltage.
     pub fn node_voltage_path(
+        &self,
         lib: &Library<Spectre>,
         conv: &NetlistLibConversion,
         path: &SliceOnePath,
     ) -> String {
         lib.convert_slice_one_path_with_conv(conv, path.clone(), |name, index| {
-            let name = Spectre::escape_identifier(name);
+            let name = self.escape_cased_identifier(name);
             if let Some(index) = index {
                 arcstr::format!("{}\\[{}\\]", name, index)
             } else {
@@ -680,13 +1136,14 @@ impl Spectre {
     /// Converts a [`SliceOnePath`] to a Spectre path string corresponding to the associated
     /// terminal current.
     pub fn node_current_path(
+        &self,
         lib: &Library<Spectre>,
         conv: &NetlistLibConversion,
         path: &SliceOnePath,
     ) -> String {
         let mut named_path =
             lib.convert_slice_one_path_with_conv(conv, path.clone(), |name, index| {
-                let name = Spectre::escape_identifier(name);
+                let name = self.escape_cased_identifier(name);
                 if let Some(index) = index {
                     arcstr::format!("{}\\[{}\\]", name, index)
                 } else {
@@ -841,6 +1298,10 @@ pub enum Input {
     Tran(Tran),
     /// AC simulation input.
     Ac(Ac),
+    /// S-parameter simulation input.
+    Sp(Sp),
+    /// Noise simulation input.
+    Noise(Noise),
     /// A Monte Carlo input.
     MonteCarlo(MonteCarlo<Vec<Input>>),
 }
@@ -857,6 +1318,18 @@ impl From<Ac> for Input {
     }
 }
 
+impl From<Sp> for Input {
+    fn from(value: Sp) -> Self {
+        Self::Sp(value)
+    }
+}
+
+impl From<Noise> for Input {
+    fn from(value: Noise) -> Self {
+        Self::Noise(value)
+    }
+}
+
 impl<A: SupportedBy<Spectre>> From<MonteCarlo<A>> for Input {
     fn from(value: MonteCarlo<A>) -> Self {
         Self::MonteCarlo(value.into())
@@ -870,6 +1343,10 @@ pub enum Output {
     Tran(tran::Output),
     /// AC simulation output.
     Ac(ac::Output),
+    /// S-parameter simulation output.
+    Sp(sp::Output),
+    /// Noise simulation output.
+    Noise(noise::Output),
     /// Monte Carlo simulation output.
     MonteCarlo(montecarlo::Output<Vec<Output>>),
 }
@@ -886,6 +1363,18 @@ impl From<ac::Output> for Output {
     }
 }
 
+impl From<sp::Output> for Output {
+    fn from(value: sp::Output) -> Self {
+        Self::Sp(value)
+    }
+}
+
+impl From<noise::Output> for Output {
+    fn from(value: noise::Output) -> Self {
+        Self::Noise(value)
+    }
+}
+
 impl TryFrom<Output> for tran::Output {
     type Error = Error;
     fn try_from(value: Output) -> Result<Self> {
@@ -906,6 +1395,26 @@ impl TryFrom<Output> for ac::Output {
     }
 }
 
+impl TryFrom<Output> for sp::Output {
+    type Error = Error;
+    fn try_from(value: Output) -> Result<Self> {
+        match value {
+            Output::Sp(sp) => Ok(sp),
+            _ => Err(Error::SpectreError),
+        }
+    }
+}
+
+impl TryFrom<Output> for noise::Output {
+    type Error = Error;
+    fn try_from(value: Output) -> Result<Self> {
+        match value {
+            Output::Noise(noise) => Ok(noise),
+            _ => Err(Error::SpectreError),
+        }
+    }
+}
+
 impl From<montecarlo::Output<Vec<Output>>> for Output {
     fn from(value: montecarlo::Output<Vec<Output>>) -> Self {
         Self::MonteCarlo(value)
@@ -928,6 +1437,8 @@ impl Input {
         match self {
             Self::Tran(t) => t.netlist(out),
             Input::Ac(ac) => ac.netlist(out),
+            Input::Sp(sp) => sp.netlist(out),
+            Input::Noise(noise) => noise.netlist(out),
             Self::MonteCarlo(mc) => mc.netlist(out, name),
         }
     }
@@ -948,6 +1459,21 @@ impl Tran {
         if let Some(noisefmin) = self.noise_fmin {
             write!(out, " noisefmin={noisefmin}")?;
         }
+        if let Some(maxstep) = self.maxstep {
+            write!(out, " maxstep={maxstep}")?;
+        }
+        if let Some(method) = self.method {
+            write!(out, " method={method}")?;
+        }
+        if let Some(strobeperiod) = self.strobeperiod {
+            write!(out, " strobeperiod={strobeperiod}")?;
+        }
+        if let Some(strobedelay) = self.strobedelay {
+            write!(out, " strobedelay={strobedelay}")?;
+        }
+        if let Some(save) = self.save {
+            write!(out, " save={save}")?;
+        }
         Ok(())
     }
 }
@@ -963,6 +1489,50 @@ impl Ac {
         if let Some(errpreset) = self.errpreset {
             write!(out, " errpreset={errpreset}")?;
         }
+        if let Some(save) = self.save {
+            write!(out, " save={save}")?;
+        }
+        Ok(())
+    }
+}
+
+impl Sp {
+    fn netlist<W: Write>(&self, out: &mut W) -> Result<()> {
+        write!(out, "sp")?;
+        if !self.ports.is_empty() {
+            write!(out, " ports=[{}]", self.ports.iter().join(" "))?;
+        }
+        write!(out, " start={} stop={}", self.start, self.stop)?;
+        match self.sweep {
+            Sweep::Linear(pts) => write!(out, " lin={pts}")?,
+            Sweep::Logarithmic(pts) => write!(out, " log={pts}")?,
+            Sweep::Decade(pts) => write!(out, " dec={pts}")?,
+        };
+        if let Some(errpreset) = self.errpreset {
+            write!(out, " errpreset={errpreset}")?;
+        }
+        Ok(())
+    }
+}
+
+impl Noise {
+    fn netlist<W: Write>(&self, out: &mut W) -> Result<()> {
+        write!(
+            out,
+            "({} {}) noise start={} stop={}",
+            self.output_p,
+            self.output_n.as_deref().unwrap_or("0"),
+            self.start,
+            self.stop
+        )?;
+        match self.sweep {
+            Sweep::Linear(pts) => write!(out, " lin={pts}")?,
+            Sweep::Logarithmic(pts) => write!(out, " log={pts}")?,
+            Sweep::Decade(pts) => write!(out, " dec={pts}")?,
+        };
+        if let Some(ref reference) = self.reference {
+            write!(out, " iprobe={reference}")?;
+        }
         Ok(())
     }
 }
@@ -971,51 +1541,118 @@ fn subanalysis_name(prefix: &str, idx: usize) -> String {
     format!("{prefix}_{idx}")
 }
 
-fn parse_analysis(output_dir: &Path, name: &str, analysis: &Input) -> Result<CachedData> {
-    Ok(if let Input::MonteCarlo(analysis) = analysis {
-        let mut data = Vec::new();
-        for iter in 1..analysis.numruns + 1 {
-            let mut mc_data = Vec::new();
-            for i in 0..analysis.analysis.len() {
-                // FIXME: loops should be swapped
-                let new_name = subanalysis_name(&format!("{}-{:0>3}_{}", name, iter, name), i);
-                mc_data.push(parse_analysis(
-                    output_dir,
-                    &new_name,
-                    &analysis.analysis[i],
-                )?)
-            }
-            data.push(mc_data);
-        }
-        CachedData::MonteCarlo(data)
-    } else {
+/// Raw data produced by a single Spectre analysis.
+#[derive(Debug, Clone)]
+pub enum RawOutput {
+    /// Transient signals by name, including the `time` sweep variable.
+    Tran(HashMap<String, Vec<f64>>),
+    /// AC frequency points and signals by name.
+    Ac {
+        /// The frequency points.
+        freq: Vec<f64>,
+        /// A map from signal name to values.
+        signals: HashMap<String, Vec<Complex64>>,
+    },
+    /// S-parameter frequency points and signals by name.
+    Sp {
+        /// The frequency points.
+        freq: Vec<f64>,
+        /// A map from signal name to values.
+        signals: HashMap<String, Vec<Complex64>>,
+    },
+    /// Noise frequency points and signals by name.
+    Noise {
+        /// The frequency points.
+        freq: Vec<f64>,
+        /// A map from signal name to noise spectral density values.
+        signals: HashMap<String, Vec<f64>>,
+    },
+}
+
+/// A parser for raw Spectre simulation output.
+pub trait OutputParser: std::fmt::Debug + Send + Sync {
+    /// Parses the output of the analysis named `name` from the files in `output_dir`.
+    ///
+    /// Monte Carlo analyses are parsed one inner analysis at a time,
+    /// so `analysis` is never an [`Input::MonteCarlo`].
+    fn parse(&self, output_dir: &Path, name: &str, analysis: &Input) -> Result<RawOutput>;
+}
+
+/// The default [`OutputParser`], which reads binary PSF files using `psfparser`.
+#[derive(Debug, Clone, Copy, Default, Eq, PartialEq)]
+pub struct PsfParser;
+
+impl OutputParser for PsfParser {
+    fn parse(&self, output_dir: &Path, name: &str, analysis: &Input) -> Result<RawOutput> {
         let file_name = match analysis {
             Input::Tran(_) => {
                 format!("{name}.tran.tran")
             }
             Input::Ac(_) => format!("{name}.ac"),
+            Input::Sp(_) => format!("{name}.sp"),
+            Input::Noise(_) => format!("{name}.noise"),
             Input::MonteCarlo(_) => unreachable!(),
         };
         let psf_path = output_dir.join(file_name);
         let psf = std::fs::read(psf_path)?;
         let ast = psfparser::binary::parse(&psf).map_err(|_| Error::Parse)?;
 
-        match analysis {
+        Ok(match analysis {
             Input::Tran(_) => {
                 let values = TransientData::from_binary(ast).signals;
-                CachedData::Tran(values)
+                RawOutput::Tran(values)
             }
             Input::Ac(_) => {
                 let values = AcData::from_binary(ast);
-                CachedData::Ac {
+                RawOutput::Ac {
+                    freq: values.freq,
+                    signals: values.signals,
+                }
+            }
+            Input::Sp(_) => {
+                let values = AcData::from_binary(ast);
+                RawOutput::Sp {
                     freq: values.freq,
                     signals: values.signals,
                 }
             }
+            Input::Noise(_) => {
+                let mut signals = TransientData::from_binary(ast).signals;
+                let freq = signals.remove("freq").ok_or(Error::Parse)?;
+                RawOutput::Noise { freq, signals }
+            }
             Input::MonteCarlo(_) => {
                 unreachable!()
             }
+        })
+    }
+}
+
+fn parse_analysis(
+    parser: &dyn OutputParser,
+    output_dir: &Path,
+    name: &str,
+    analysis: &Input,
+) -> Result<CachedData> {
+    Ok(if let Input::MonteCarlo(analysis) = analysis {
+        let mut data = Vec::new();
+        for iter in 1..analysis.numruns + 1 {
+            let mut mc_data = Vec::new();
+            for i in 0..analysis.analysis.len() {
+                // FIXME: loops should be swapped
+                let new_name = subanalysis_name(&format!("{}-{:0>3}_{}", name, iter, name), i);
+                mc_data.push(parse_analysis(
+                    parser,
+                    output_dir,
+                    &new_name,
+                    &analysis.analysis[i],
+                )?)
+            }
+            data.push(mc_data);
         }
+        CachedData::MonteCarlo(data)
+    } else {
+        parser.parse(output_dir, name, analysis)?.into()
     })
 }
 
@@ -1054,7 +1691,7 @@ impl HasSpiceLikeNetlist for Spectre {
             out,
             "// Be careful when editing manually: this file may be overwritten.\n"
         )?;
-        writeln!(out, "global 0\n")?;
+        writeln!(out, "global {}\n", self.ground())?;
 
         // find all unique spf netlists and include them
         let spfs = lib
@@ -1094,10 +1731,15 @@ impl HasSpiceLikeNetlist for Spectre {
         for sig in ports {
             if let Some(width) = sig.width {
                 for i in 0..width {
-                    write!(out, " {}\\[{}\\]", Spectre::escape_identifier(&sig.name), i)?;
+                    write!(
+                        out,
+                        " {}\\[{}\\]",
+                        self.escape_cased_identifier(&sig.name),
+                        i
+                    )?;
                 }
             } else {
-                write!(out, " {}", Spectre::escape_identifier(&sig.name))?;
+                write!(out, " {}", self.escape_cased_identifier(&sig.name))?;
             }
         }
         write!(out, " )")?;
@@ -1108,6 +1750,10 @@ impl HasSpiceLikeNetlist for Spectre {
         write!(out, "ends {}", name)
     }
 
+    fn write_comment<W: Write>(&self, out: &mut W, comment: &str) -> std::io::Result<()> {
+        write!(out, "// {}", comment)
+    }
+
     fn write_instance<W: Write>(
         &self,
         out: &mut W,
@@ -1115,7 +1761,7 @@ impl HasSpiceLikeNetlist for Spectre {
         connections: Vec<ArcStr>,
         child: &ArcStr,
     ) -> std::io::Result<ArcStr> {
-        let name = ArcStr::from(Spectre::escape_identifier(&format!("x{}", name)));
+        let name = ArcStr::from(self.escape_cased_identifier(&format!("x{}", name)));
         write!(out, "{} (", name)?;
 
         for connection in connections {
@@ -1189,7 +1835,7 @@ impl HasSpiceLikeNetlist for Spectre {
         slice: Slice,
         info: &SignalInfo,
     ) -> std::io::Result<()> {
-        let name = Spectre::escape_identifier(&info.name);
+        let name = self.escape_cased_identifier(&info.name);
         if let Some(range) = slice.range() {
             for i in range.indices() {
                 write!(out, "{}\\[{}\\]", &name, i)?;
@@ -1200,3 +1846,48 @@ impl HasSpiceLikeNetlist for Spectre {
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn simulation_ground_defaults_to_installed_ground() {
+        let spectre = Spectre::default().with_ground("gnd!");
+        assert_eq!(spectre.with_options(&Options::default()).ground(), "gnd!");
+
+        let mut options = Options::default();
+        options.set_ground("vss");
+        assert_eq!(spectre.with_options(&options).ground(), "vss");
+        assert_eq!(
+            Spectre::default()
+                .with_options(&Options::default())
+                .ground(),
+            "0"
+        );
+    }
+
+    #[test]
+    fn simulation_case_defaults_to_installed_case() {
+        let spectre = Spectre::default().with_case(CasePolicy::Upper);
+        assert_eq!(
+            spectre.with_options(&Options::default()).case(),
+            CasePolicy::Upper
+        );
+
+        let mut options = Options::default();
+        options.set_case(CasePolicy::Lower);
+        assert_eq!(spectre.with_options(&options).case(), CasePolicy::Lower);
+    }
+
+    #[test]
+    fn escape_identifiers() {
+        assert_eq!(Spectre::escape_identifier("Out0"), "Out0");
+        assert_eq!(Spectre::escape_identifier("x0"), "xx0");
+        assert_eq!(Spectre::escape_identifier("a.b"), "a\\.b");
+
+        let upper = Spectre::default().with_case(CasePolicy::Upper);
+        assert_eq!(upper.escape_cased_identifier("Out0"), "OUT0");
+        assert_eq!(upper.escape_cased_identifier("x0"), "XX0");
+    }
+}
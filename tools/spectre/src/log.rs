@@ -0,0 +1,102 @@
+//! Parsing of Spectre log files.
+
+use std::fmt::{Display, Formatter};
+use std::path::Path;
+
+use arcstr::ArcStr;
+use lazy_static::lazy_static;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+
+/// The severity of a [`SpectreDiagnostic`].
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash, Serialize, Deserialize)]
+pub enum Severity {
+    /// An `ERROR` message.
+    Error,
+    /// A `WARNING` message.
+    Warning,
+    /// A convergence failure reported outside of an `ERROR` or `WARNING` message.
+    Convergence,
+}
+
+/// A diagnostic message extracted from a Spectre log.
+#[derive(Clone, Debug, Eq, PartialEq, Hash, Serialize, Deserialize)]
+pub struct SpectreDiagnostic {
+    /// The severity of the message.
+    pub severity: Severity,
+    /// The Spectre message code (e.g. `SFE-868`), if any.
+    pub code: Option<ArcStr>,
+    /// The message text, including any indented continuation lines.
+    pub message: String,
+}
+
+impl Display for SpectreDiagnostic {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let severity = match self.severity {
+            Severity::Error => "ERROR",
+            Severity::Warning => "WARNING",
+            Severity::Convergence => "CONVERGENCE",
+        };
+        match self.code {
+            Some(ref code) => write!(f, "{severity} ({code}): {}", self.message),
+            None => write!(f, "{severity}: {}", self.message),
+        }
+    }
+}
+
+lazy_static! {
+    static ref HEADER: Regex =
+        Regex::new(r"^\s*(ERROR|WARNING)\s*(?:\(([^)]*)\))?\s*:\s*(.*)$").unwrap();
+    static ref CONVERGENCE: Regex =
+        Regex::new(r"(?i)(did not converge|no convergence|convergence failure|failed to converge)")
+            .unwrap();
+}
+
+/// Scans the Spectre log at `log` for errors, warnings, and convergence failures.
+///
+/// Returns an empty list if the log cannot be read.
+pub fn parse_log(log: &Path) -> Vec<SpectreDiagnostic> {
+    std::fs::read_to_string(log)
+        .map(|contents| parse_log_contents(&contents))
+        .unwrap_or_default()
+}
+
+/// Scans the contents of a Spectre log for diagnostics.
+///
+/// Indented lines following an `ERROR` or `WARNING` header, up to the next blank line,
+/// are treated as part of its message.
+fn parse_log_contents(contents: &str) -> Vec<SpectreDiagnostic> {
+    let mut diagnostics = Vec::new();
+    let mut current: Option<SpectreDiagnostic> = None;
+    for line in contents.lines() {
+        let continues = !line.trim().is_empty() && line.starts_with(char::is_whitespace);
+        if let Some(caps) = HEADER.captures(line) {
+            diagnostics.extend(current.take());
+            current = Some(SpectreDiagnostic {
+                severity: if &caps[1] == "ERROR" {
+                    Severity::Error
+                } else {
+                    Severity::Warning
+                },
+                code: caps.get(2).map(|code| ArcStr::from(code.as_str().trim())),
+                message: caps[3].trim().to_string(),
+            });
+        } else if let (true, Some(diagnostic)) = (continues, current.as_mut()) {
+            if !diagnostic.message.is_empty() {
+                diagnostic.message.push('\n');
+            }
+            diagnostic.message.push_str(line.trim());
+        } else {
+            diagnostics.extend(current.take());
+            if CONVERGENCE.is_match(line) {
+                diagnostics.push(SpectreDiagnostic {
+                    severity: Severity::Convergence,
+                    code: None,
+                    message: line.trim().to_string(),
+                });
+            }
+        }
+    }
+    diagnostics.extend(current);
+    diagnostics
+}